@@ -135,6 +135,7 @@ fn golem_def_with_response(
         draft: true,
         routes: vec![Route {
             method: MethodPattern::Get,
+            methods: None,
             path: "/{user-id}/get-cart-contents".to_string(),
             binding: GolemWorkerBinding {
                 component_id: VersionedComponentId {
@@ -258,6 +259,7 @@ pub fn to_definition(
 
                 RouteWithTypeInfo {
                     method: v.method,
+                    methods: v.methods,
                     path: v.path,
                     binding: GolemWorkerBindingWithTypeInfo {
                         component_id: v.binding.component_id,
@@ -13,22 +13,63 @@
 // limitations under the License.
 
 use crate::config::RetryConfig;
-use crate::retries::RetryState;
+use crate::metrics::grpc_client;
+use crate::retries::{RetryBudget, RetryState};
+use crate::shutdown::InFlight;
+use async_trait::async_trait;
 use dashmap::DashMap;
+use futures::future::join_all;
+use golem_api_grpc::proto::golem::worker::v1::{
+    worker_execution_error, Interrupted, InvalidRequest, RuntimeError, WorkerExecutionError,
+};
+use prost::Message;
+use std::any::Any;
+use std::collections::HashMap;
 use std::future::Future;
+use std::net::SocketAddr;
 use std::pin::Pin;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Mutex;
-use tonic::transport::{Channel, Endpoint};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+use tonic::codec::CompressionEncoding;
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
 use tonic::{Code, Status};
+use tower::Layer;
+use tracing::{info, warn};
 
 #[derive(Clone)]
 pub struct GrpcClient<T: Clone> {
     endpoint: http_02::Uri,
+    // Built once, so the calls don't allocate it for their metrics
+    endpoint_label: Arc<str>,
     config: GrpcClientConfig,
     client: Arc<Mutex<Option<GrpcClientConnection<T>>>>,
-    client_factory: Arc<dyn Fn(Channel) -> T + Send + Sync + 'static>,
+    tenant_clients: Arc<DashMap<String, GrpcClientConnection<T>>>,
+    client_factory: Arc<dyn Fn(Channel, &GrpcClientConfig) -> T + Send + Sync + 'static>,
+    in_flight: InFlight,
+    response_cache: Arc<DashMap<String, CachedResponse>>,
+}
+
+/// The statistics of a call made with `GrpcClient::call_with_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallStats {
+    /// Including the retries, and the waiting between them.
+    pub duration: Duration,
+    pub attempts: u32,
+    /// The number of times the channel was dropped to be connected again.
+    pub reconnects: u32,
+    /// Whether the channel of the last attempt was created by the call (instead of reusing
+    /// the one of a previous call).
+    pub fresh_connection: bool,
+}
+
+struct CachedResponse {
+    response: Arc<dyn Any + Send + Sync>,
+    expires_at: Instant,
 }
 
 impl<T: Clone> GrpcClient<T> {
@@ -37,31 +78,246 @@ impl<T: Clone> GrpcClient<T> {
         endpoint: http_02::Uri,
         config: GrpcClientConfig,
     ) -> Self {
+        Self::new_with_codec(move |channel, _| client_factory(channel), endpoint, config)
+    }
+
+    /// Creates a client whose factory also gets the config, so it can apply the
+    /// codec options (Ex: `send_compression`) to the generated client.
+    pub fn new_with_codec(
+        client_factory: impl Fn(Channel, &GrpcClientConfig) -> T + Send + Sync + 'static,
+        endpoint: http_02::Uri,
+        config: GrpcClientConfig,
+    ) -> Self {
+        info!("Created gRPC client for {}: {}", endpoint, config.summary());
         Self {
+            endpoint_label: endpoint_label(&endpoint).into(),
             endpoint,
             config,
             client: Arc::new(Mutex::new(None)),
+            tenant_clients: Arc::new(DashMap::new()),
             client_factory: Arc::new(client_factory),
+            in_flight: InFlight::new(),
+            response_cache: Arc::new(DashMap::new()),
         }
     }
 
-    pub async fn call<F, R>(&self, f: F) -> Result<R, Status>
+    /// Creates a client whose channel runs the interceptor on every outgoing request.
+    /// The interceptor is shared by the reconnected channels, so its state (Ex: a rotated
+    /// auth token) is read at call time.
+    pub fn new_with_interceptor(
+        client_factory: impl Fn(InterceptedChannel, &GrpcClientConfig) -> T + Send + Sync + 'static,
+        endpoint: http_02::Uri,
+        config: GrpcClientConfig,
+        interceptor: Arc<dyn GrpcInterceptor>,
+    ) -> Self {
+        let interceptor = SharedInterceptor(interceptor);
+        Self::new_with_codec(
+            move |channel, config| {
+                client_factory(
+                    InterceptedService::new(channel, interceptor.clone()),
+                    config,
+                )
+            },
+            endpoint,
+            config,
+        )
+    }
+
+    /// Creates a client whose channel is wrapped in a stack of tower layers (Ex: a
+    /// `tower::ServiceBuilder` with a concurrency limit and a load-shed), so standard tower
+    /// middleware can be reused. The stack sits below the reconnect logic: a new stack is
+    /// created for each (re)connected channel (and tenant), so the state of a layer (Ex: the
+    /// permits of a concurrency limit) is shared by the calls on the same channel, and every
+    /// retried attempt passes through the stack again. The errors of the layers reach the
+    /// call as statuses, and only the `Unavailable` ones are retried.
+    pub fn new_with_layer<L>(
+        client_factory: impl Fn(L::Service, &GrpcClientConfig) -> T + Send + Sync + 'static,
+        endpoint: http_02::Uri,
+        config: GrpcClientConfig,
+        layer: L,
+    ) -> Self
+    where
+        L: Layer<Channel> + Send + Sync + 'static,
+    {
+        Self::new_with_codec(
+            move |channel, config| client_factory(layer.layer(channel), config),
+            endpoint,
+            config,
+        )
+    }
+
+    /// Calls the service, retrying (and reconnecting) if it is unavailable.
+    /// `description` names the called method in the slow call warnings.
+    pub async fn call<F, R>(&self, description: impl AsRef<str>, f: F) -> Result<R, GrpcCallError>
     where
         F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
             + Send,
     {
-        let mut retries = RetryState::new(&self.config.retries_on_unavailable);
-        loop {
+        self.call_with_budget(description, &RetryBudget::unlimited(), f)
+            .await
+    }
+
+    /// Like `call`, but also returns the statistics of the call (Ex: to report the
+    /// diagnostics of a slow request).
+    pub async fn call_with_stats<F, R>(
+        &self,
+        description: impl AsRef<str>,
+        f: F,
+    ) -> Result<(R, CallStats), GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+    {
+        self.call_on_with_stats(None, description, &RetryBudget::unlimited(), f)
+            .await
+    }
+
+    /// Like `call`, but the retries are also limited by a budget shared with the other
+    /// calls made for the same logical request.
+    pub async fn call_with_budget<F, R>(
+        &self,
+        description: impl AsRef<str>,
+        budget: &RetryBudget,
+        f: F,
+    ) -> Result<R, GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+    {
+        self.call_on(None, description, budget, f).await
+    }
+
+    /// Like `call`, but on a channel of its own for the tenant, created on the first call
+    /// of the tenant (Ex: so a tenant with many slow calls doesn't hold up the calls
+    /// of the others on the same connection). The channels of the tenants connect to the
+    /// same endpoint, with the same config, and are reconnected the same way as the default one.
+    pub async fn call_for_tenant<F, R>(
+        &self,
+        tenant: &str,
+        description: impl AsRef<str>,
+        f: F,
+    ) -> Result<R, GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+    {
+        self.call_for_tenant_with_budget(tenant, description, &RetryBudget::unlimited(), f)
+            .await
+    }
+
+    /// Like `call`, but for idempotent reads (Ex: the metadata of a component version):
+    /// a successful response is cached under the key, which identifies the method and its
+    /// parameters, for `response_cache_ttl`. Until it expires, the calls with the same key
+    /// get the cached response without calling the service. Failures are not cached.
+    /// Without a `response_cache_ttl`, every call is made.
+    pub async fn call_cached<F, R>(
+        &self,
+        cache_key: impl AsRef<str>,
+        description: impl AsRef<str>,
+        f: F,
+    ) -> Result<R, GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+        R: Clone + Send + Sync + 'static,
+    {
+        let Some(ttl) = self.config.response_cache_ttl else {
+            return self.call(description, f).await;
+        };
+        let cache_key = cache_key.as_ref();
+
+        let now = Instant::now();
+        self.response_cache
+            .remove_if(cache_key, |_, cached| cached.expires_at <= now);
+        let cached = self
+            .response_cache
+            .get(cache_key)
+            .and_then(|cached| cached.response.downcast_ref::<R>().cloned());
+        if let Some(response) = cached {
+            return Ok(response);
+        }
+
+        let response = self.call(description, f).await?;
+
+        // The expired responses of the other keys are dropped as well
+        let now = Instant::now();
+        self.response_cache
+            .retain(|_, cached| cached.expires_at > now);
+        self.response_cache.insert(
+            cache_key.to_string(),
+            CachedResponse {
+                response: Arc::new(response.clone()),
+                expires_at: now + ttl,
+            },
+        );
+
+        Ok(response)
+    }
+
+    /// Like `call_for_tenant`, but the retries are also limited by a budget shared with
+    /// the other calls made for the same logical request.
+    pub async fn call_for_tenant_with_budget<F, R>(
+        &self,
+        tenant: &str,
+        description: impl AsRef<str>,
+        budget: &RetryBudget,
+        f: F,
+    ) -> Result<R, GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+    {
+        self.call_on(Some(tenant), description, budget, f).await
+    }
+
+    async fn call_on<F, R>(
+        &self,
+        tenant: Option<&str>,
+        description: impl AsRef<str>,
+        budget: &RetryBudget,
+        f: F,
+    ) -> Result<R, GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+    {
+        self.call_on_with_stats(tenant, description, budget, f)
+            .await
+            .map(|(result, _)| result)
+    }
+
+    async fn call_on_with_stats<F, R>(
+        &self,
+        tenant: Option<&str>,
+        description: impl AsRef<str>,
+        budget: &RetryBudget,
+        f: F,
+    ) -> Result<(R, CallStats), GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+    {
+        let _in_flight = self.in_flight.enter();
+        let start = Instant::now();
+        let endpoint_label = &*self.endpoint_label;
+        let mut retries = RetryState::new(&self.config.retries_on_unavailable).with_budget(budget);
+        let mut stats = CallStats::default();
+        let result = loop {
             retries.start_attempt();
-            let mut entry = self
-                .get()
-                .await
-                .map_err(|err| Status::from_error(Box::new(err)))?;
-            match f(&mut entry.client).await {
+            stats.attempts += 1;
+            let (mut entry, fresh_connection, _permit) =
+                self.acquire(tenant).await.map_err(GrpcCallError::Status)?;
+            stats.fresh_connection = fresh_connection;
+            match with_call_deadline(budget, f(&mut entry.client)).await {
                 Ok(result) => break Ok(result),
                 Err(e) => {
                     if requires_reconnect(&e) {
-                        let _ = self.client.lock().await.take();
+                        self.remove_client(tenant).await;
+                        stats.reconnects += 1;
+                        grpc_client::record_grpc_client_reconnect(
+                            endpoint_label,
+                            description.as_ref(),
+                        );
                         if !retries.failed_attempt().await {
                             break Err(e);
                         } else {
@@ -72,32 +328,148 @@ impl<T: Clone> GrpcClient<T> {
                     }
                 }
             }
+        };
+
+        let duration = start.elapsed();
+        stats.duration = duration;
+        grpc_client::record_grpc_client_call(endpoint_label, description.as_ref(), duration);
+        if let Err(status) = &result {
+            grpc_client::record_grpc_client_call_failure(
+                endpoint_label,
+                description.as_ref(),
+                &format!("{:?}", status.code()),
+            );
+        }
+
+        if let Some(threshold) = self.config.slow_call_threshold {
+            if duration > threshold {
+                warn!(
+                    endpoint = %self.endpoint,
+                    method = description.as_ref(),
+                    tenant = tenant,
+                    duration = ?duration,
+                    "Slow gRPC call"
+                );
+            }
+        }
+
+        result
+            .map(|result| (result, stats))
+            .map_err(|status| GrpcCallError::from_status(status, &self.config))
+    }
+
+    /// Waits for the in-flight calls to finish, up to the given timeout.
+    /// Returns the number of calls still in flight.
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        self.in_flight
+            .drain(&format!("gRPC client for {}", self.endpoint), timeout)
+            .await
+    }
+
+    /// Drops the channel, so the next call connects again (Ex: after a known restart of
+    /// the service), instead of waiting for a call to fail on the stale connection.
+    /// Calls already using the old channel are not affected.
+    /// The channels of the tenants are dropped as well.
+    pub async fn reset(&self) {
+        let _ = self.client.lock().await.take();
+        self.tenant_clients.clear();
+    }
+
+    /// Drops the channel of the tenant, so its next call connects again.
+    pub fn reset_tenant(&self, tenant: &str) {
+        self.tenant_clients.remove(tenant);
+    }
+
+    async fn remove_client(&self, tenant: Option<&str>) {
+        match tenant {
+            Some(tenant) => self.reset_tenant(tenant),
+            None => {
+                let _ = self.client.lock().await.take();
+            }
+        }
+    }
+
+    // The connection, whether it was created for this call, and the permit of its slow start.
+    // Both are waited for within the acquire timeout, for the channels of the tenants as well
+    async fn acquire(
+        &self,
+        tenant: Option<&str>,
+    ) -> Result<(GrpcClientConnection<T>, bool, Option<SlowStartPermit>), Status> {
+        let acquire = async {
+            let (entry, created) = self.get(tenant).await?;
+            let permit = entry.acquire().await;
+            Ok((entry, created, permit))
+        };
+
+        match self.config.acquire_timeout {
+            Some(acquire_timeout) => tokio::time::timeout(acquire_timeout, acquire)
+                .await
+                .map_err(|_| {
+                    Status::unavailable(format!(
+                        "Failed to acquire a connection to {} within {:?}",
+                        self.endpoint, acquire_timeout
+                    ))
+                })?,
+            None => acquire.await,
         }
     }
 
-    async fn get(&self) -> Result<GrpcClientConnection<T>, tonic::transport::Error> {
+    // The connection, and whether it was created for this call
+    async fn get(&self, tenant: Option<&str>) -> Result<(GrpcClientConnection<T>, bool), Status> {
+        if let Some(tenant) = tenant {
+            let mut created = false;
+            let entry = self
+                .tenant_clients
+                .entry(tenant.to_string())
+                .or_try_insert_with(|| {
+                    created = true;
+                    self.connect()
+                })
+                .map_err(|err| Status::from_error(Box::new(err)))?;
+            return Ok((entry.clone(), created));
+        }
+
         let mut entry = self.client.lock().await;
 
         match &*entry {
-            Some(client) => Ok(client.clone()),
+            Some(client) => Ok((client.clone(), false)),
             None => {
-                let endpoint = Endpoint::new(self.endpoint.clone())?
-                    .connect_timeout(self.config.connect_timeout);
-                let channel = endpoint.connect_lazy();
-                let client = (self.client_factory)(channel);
-                let connection = GrpcClientConnection { client };
+                let connection = self
+                    .connect()
+                    .map_err(|err| Status::from_error(Box::new(err)))?;
                 *entry = Some(connection.clone());
-                Ok(connection)
+                Ok((connection, true))
             }
         }
     }
+
+    fn connect(&self) -> Result<GrpcClientConnection<T>, EndpointBuildError> {
+        let endpoint = build_endpoint(self.endpoint.clone(), &self.config)?;
+        let channel = endpoint.connect_lazy();
+        let client = (self.client_factory)(channel, &self.config);
+        Ok(GrpcClientConnection::new(client, &self.config))
+    }
 }
 
 #[derive(Clone)]
 pub struct MultiTargetGrpcClient<T: Clone> {
     config: GrpcClientConfig,
+    endpoint_configs: Arc<HashMap<http_02::Uri, GrpcClientConfig>>,
     clients: Arc<DashMap<http_02::Uri, GrpcClientConnection<T>>>,
-    client_factory: Arc<dyn Fn(Channel) -> T + Send + Sync>,
+    client_factory: Arc<dyn Fn(Channel, &GrpcClientConfig) -> T + Send + Sync>,
+    in_flight: InFlight,
+    last_errors: Arc<DashMap<http_02::Uri, EndpointError>>,
+    // Built once per endpoint, so the calls don't allocate them for their metrics
+    endpoint_labels: Arc<DashMap<http_02::Uri, Arc<str>>>,
+    resolver: Arc<dyn EndpointResolver>,
+    resolved_addresses: Arc<DashMap<http_02::Uri, Vec<SocketAddr>>>,
+}
+
+/// The last failure of a call to an endpoint, kept for diagnostics.
+#[derive(Debug, Clone)]
+pub struct EndpointError {
+    pub status: Status,
+    pub at: SystemTime,
 }
 
 impl<T: Clone> MultiTargetGrpcClient<T> {
@@ -105,29 +477,139 @@ impl<T: Clone> MultiTargetGrpcClient<T> {
         client_factory: impl Fn(Channel) -> T + Send + Sync + 'static,
         config: GrpcClientConfig,
     ) -> Self {
+        Self::new_with_codec(move |channel, _| client_factory(channel), config)
+    }
+
+    /// Creates a client whose factory also gets the config of the endpoint, so it can apply
+    /// the codec options (Ex: `send_compression`) to the generated client.
+    pub fn new_with_codec(
+        client_factory: impl Fn(Channel, &GrpcClientConfig) -> T + Send + Sync + 'static,
+        config: GrpcClientConfig,
+    ) -> Self {
+        info!("Created multi-target gRPC client: {}", config.summary());
         Self {
             config,
+            endpoint_configs: Arc::new(HashMap::new()),
             clients: Arc::new(DashMap::new()),
             client_factory: Arc::new(client_factory),
+            in_flight: InFlight::new(),
+            last_errors: Arc::new(DashMap::new()),
+            endpoint_labels: Arc::new(DashMap::new()),
+            resolver: Arc::new(DnsEndpointResolver),
+            resolved_addresses: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Creates a client whose channels run the interceptor on every outgoing request.
+    pub fn new_with_interceptor(
+        client_factory: impl Fn(InterceptedChannel, &GrpcClientConfig) -> T + Send + Sync + 'static,
+        config: GrpcClientConfig,
+        interceptor: Arc<dyn GrpcInterceptor>,
+    ) -> Self {
+        let interceptor = SharedInterceptor(interceptor);
+        Self::new_with_codec(
+            move |channel, config| {
+                client_factory(
+                    InterceptedService::new(channel, interceptor.clone()),
+                    config,
+                )
+            },
+            config,
+        )
+    }
+
+    /// Creates a client whose channels are wrapped in a stack of tower layers, a new stack
+    /// for each channel. See `GrpcClient::new_with_layer`.
+    pub fn new_with_layer<L>(
+        client_factory: impl Fn(L::Service, &GrpcClientConfig) -> T + Send + Sync + 'static,
+        config: GrpcClientConfig,
+        layer: L,
+    ) -> Self
+    where
+        L: Layer<Channel> + Send + Sync + 'static,
+    {
+        Self::new_with_codec(
+            move |channel, config| client_factory(layer.layer(channel), config),
+            config,
+        )
+    }
+
+    /// Replaces the resolver used to detect the address changes of the endpoints.
+    pub fn with_resolver(mut self, resolver: Arc<dyn EndpointResolver>) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Overrides the config (retries, timeouts, authority) of individual endpoints.
+    /// Endpoints not in the map use the default config.
+    pub fn with_endpoint_configs(
+        mut self,
+        endpoint_configs: HashMap<http_02::Uri, GrpcClientConfig>,
+    ) -> Self {
+        for (endpoint, config) in &endpoint_configs {
+            info!("gRPC client config of {}: {}", endpoint, config.summary());
         }
+        self.endpoint_configs = Arc::new(endpoint_configs);
+        self
+    }
+
+    fn config_for(&self, endpoint: &http_02::Uri) -> &GrpcClientConfig {
+        self.endpoint_configs.get(endpoint).unwrap_or(&self.config)
     }
 
-    pub async fn call<F, R>(&self, endpoint: http_02::Uri, f: F) -> Result<R, Status>
+    /// Calls the endpoint, retrying (and reconnecting) if it is unavailable.
+    /// `description` names the called method in the metrics.
+    pub async fn call<F, R>(
+        &self,
+        endpoint: http_02::Uri,
+        description: impl AsRef<str>,
+        f: F,
+    ) -> Result<R, GrpcCallError>
     where
         F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
             + Send,
     {
-        let mut retries = RetryState::new(&self.config.retries_on_unavailable);
-        loop {
+        self.call_with_budget(endpoint, description, &RetryBudget::unlimited(), f)
+            .await
+    }
+
+    /// Like `call`, but the retries are also limited by a budget shared with the other
+    /// calls made for the same logical request (possibly to other endpoints).
+    pub async fn call_with_budget<F, R>(
+        &self,
+        endpoint: http_02::Uri,
+        description: impl AsRef<str>,
+        budget: &RetryBudget,
+        f: F,
+    ) -> Result<R, GrpcCallError>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send,
+    {
+        let _in_flight = self.in_flight.enter();
+        let start = Instant::now();
+        let endpoint_label = self.endpoint_label(&endpoint);
+        let mut retries =
+            RetryState::new(&self.config_for(&endpoint).retries_on_unavailable).with_budget(budget);
+        let result = loop {
             retries.start_attempt();
-            let mut entry = self
-                .get(endpoint.clone())
-                .map_err(|err| Status::from_error(Box::new(err)))?;
-            match f(&mut entry.client).await {
+            let (mut entry, _permit) = match self.acquire(&endpoint).await {
+                Ok(acquired) => acquired,
+                Err(status) => {
+                    self.record_error(&endpoint, &status);
+                    break Err(status);
+                }
+            };
+            match with_call_deadline(budget, f(&mut entry.client)).await {
                 Ok(result) => break Ok(result),
                 Err(e) => {
+                    self.record_error(&endpoint, &e);
                     if requires_reconnect(&e) {
-                        self.clients.remove(&endpoint);
+                        self.remove_client(&endpoint);
+                        grpc_client::record_grpc_client_reconnect(
+                            &endpoint_label,
+                            description.as_ref(),
+                        );
                         if !retries.failed_attempt().await {
                             break Err(e);
                         } else {
@@ -138,47 +620,2051 @@ impl<T: Clone> MultiTargetGrpcClient<T> {
                     }
                 }
             }
+        };
+
+        grpc_client::record_grpc_client_call(
+            &endpoint_label,
+            description.as_ref(),
+            start.elapsed(),
+        );
+        if let Err(status) = &result {
+            grpc_client::record_grpc_client_call_failure(
+                &endpoint_label,
+                description.as_ref(),
+                &format!("{:?}", status.code()),
+            );
         }
+
+        result.map_err(|status| GrpcCallError::from_status(status, self.config_for(&endpoint)))
     }
 
-    fn get(
+    /// Calls all the endpoints concurrently (Ex: to broadcast an invalidation to every executor).
+    /// Each call has the retries and reconnects of its endpoint, and a failed call is reported
+    /// in the result of its endpoint without affecting the others.
+    /// The results are in the order of the endpoints.
+    pub async fn call_all<F, R>(
         &self,
-        endpoint: http_02::Uri,
-    ) -> Result<GrpcClientConnection<T>, tonic::transport::Error> {
-        let connect_timeout = self.config.connect_timeout;
+        endpoints: impl IntoIterator<Item = http_02::Uri>,
+        description: impl AsRef<str>,
+        f: F,
+    ) -> Vec<(http_02::Uri, Result<R, GrpcCallError>)>
+    where
+        F: for<'a> Fn(&'a mut T) -> Pin<Box<dyn Future<Output = Result<R, Status>> + 'a + Send>>
+            + Send
+            + Sync,
+    {
+        let f = &f;
+        let description = description.as_ref();
+        let calls = endpoints.into_iter().map(|endpoint| async move {
+            let result = self.call(endpoint.clone(), description, f).await;
+            (endpoint, result)
+        });
+
+        join_all(calls).await
+    }
+
+    /// The last failed attempt of a call to the endpoint (including the retried ones),
+    /// to tell apart connection failures from application level errors.
+    pub fn last_error(&self, endpoint: &http_02::Uri) -> Option<EndpointError> {
+        self.last_errors.get(endpoint).map(|entry| entry.clone())
+    }
+
+    /// The last errors of all the endpoints which had a failed call.
+    pub fn last_errors(&self) -> HashMap<http_02::Uri, EndpointError> {
+        self.last_errors
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
+    fn endpoint_label(&self, endpoint: &http_02::Uri) -> Arc<str> {
+        match self.endpoint_labels.get(endpoint) {
+            Some(label) => label.clone(),
+            None => self
+                .endpoint_labels
+                .entry(endpoint.clone())
+                .or_insert_with(|| endpoint_label(endpoint).into())
+                .clone(),
+        }
+    }
+
+    fn record_error(&self, endpoint: &http_02::Uri, status: &Status) {
+        self.last_errors.insert(
+            endpoint.clone(),
+            EndpointError {
+                status: status.clone(),
+                at: SystemTime::now(),
+            },
+        );
+    }
+
+    /// Resolves the endpoints which have a channel, and drops the channels of the ones
+    /// whose addresses changed since the last refresh, so they are rebuilt on the next call.
+    /// Channels resolve their endpoint only once when connecting, so without refreshing they
+    /// keep using the old addresses of endpoints behind a DNS name (Ex: restarted executors).
+    /// Returns the number of dropped channels.
+    pub async fn refresh_endpoints(&self) -> usize {
+        let endpoints: Vec<http_02::Uri> = self
+            .clients
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        let mut refreshed = 0;
+
+        for endpoint in endpoints {
+            let mut addresses = match self.resolver.resolve(&endpoint).await {
+                Ok(addresses) => addresses,
+                Err(err) => {
+                    warn!(endpoint = %endpoint, "Failed to resolve the endpoint: {}", err);
+                    continue;
+                }
+            };
+            addresses.sort();
+
+            // The first resolved addresses of a channel are the ones it connected to
+            let previous = self
+                .resolved_addresses
+                .insert(endpoint.clone(), addresses.clone());
+
+            if previous.is_some_and(|previous| previous != addresses) {
+                info!(
+                    endpoint = %endpoint,
+                    addresses = ?addresses,
+                    "Endpoint addresses changed, reconnecting"
+                );
+                self.clients.remove(&endpoint);
+                refreshed += 1;
+            }
+        }
+
+        refreshed
+    }
+
+    /// Refreshes the endpoints periodically, until the returned task is aborted.
+    pub fn spawn_endpoint_refresh(&self, interval: Duration) -> JoinHandle<()>
+    where
+        T: Send + Sync + 'static,
+    {
+        let client = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+                client.refresh_endpoints().await;
+            }
+        })
+    }
+
+    /// Drops the channel of the endpoint, so the next call to it connects again.
+    pub fn reset(&self, endpoint: &http_02::Uri) {
+        self.remove_client(endpoint);
+    }
+
+    fn remove_client(&self, endpoint: &http_02::Uri) {
+        self.clients.remove(endpoint);
+        self.resolved_addresses.remove(endpoint);
+    }
+
+    /// Waits for the in-flight calls to any of the targets to finish, up to the given timeout.
+    /// Returns the number of calls still in flight.
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        self.in_flight.drain("gRPC clients", timeout).await
+    }
+
+    // The connection to the endpoint, and the permit of its slow start,
+    // which is waited for within the acquire timeout of the endpoint
+    async fn acquire(
+        &self,
+        endpoint: &http_02::Uri,
+    ) -> Result<(GrpcClientConnection<T>, Option<SlowStartPermit>), Status> {
+        let entry = self
+            .get(endpoint.clone())
+            .map_err(|err| Status::from_error(Box::new(err)))?;
+
+        let permit = match self.config_for(endpoint).acquire_timeout {
+            Some(acquire_timeout) => tokio::time::timeout(acquire_timeout, entry.acquire())
+                .await
+                .map_err(|_| {
+                    Status::unavailable(format!(
+                        "Failed to acquire a connection to {} within {:?}",
+                        endpoint, acquire_timeout
+                    ))
+                })?,
+            None => entry.acquire().await,
+        };
+
+        Ok((entry, permit))
+    }
+
+    fn get(&self, endpoint: http_02::Uri) -> Result<GrpcClientConnection<T>, EndpointBuildError> {
         let entry = self
             .clients
             .entry(endpoint.clone())
             .or_try_insert_with(move || {
-                let endpoint = Endpoint::new(endpoint)?.connect_timeout(connect_timeout);
+                let config = self.config_for(&endpoint);
+                let endpoint = build_endpoint(endpoint, config)?;
                 let channel = endpoint.connect_lazy();
-                let client = (self.client_factory)(channel);
-                Ok(GrpcClientConnection { client })
+                let client = (self.client_factory)(channel, config);
+                Ok(GrpcClientConnection::new(client, config))
             })?;
         Ok(entry.clone())
     }
 }
 
+/// The error of a call made through the gRPC clients.
+/// Responses exceeding the decoding limit are told apart from the other failures, as they
+/// are not transient: the callee has to return less data (or the limit has to be raised).
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum GrpcCallError {
+    #[error("Response too large, exceeding the limit of {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+    #[error("{} {}", .0.code(), .0.message())]
+    Status(Status),
+}
+
+impl GrpcCallError {
+    /// Detects the failure to decode an oversized response. Tonic reports it as a status
+    /// with the actual and the maximum size in the message, so the limit is read from there,
+    /// falling back to the configured one.
+    pub fn from_status(status: Status, config: &GrpcClientConfig) -> Self {
+        let is_too_large = matches!(status.code(), Code::ResourceExhausted | Code::OutOfRange)
+            && status.message().contains("message length too large");
+
+        if is_too_large {
+            let limit = status
+                .message()
+                .split("the limit is: ")
+                .nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                .and_then(|limit| limit.parse::<usize>().ok())
+                .or(config.max_decoding_message_size);
+
+            if let Some(limit) = limit {
+                return GrpcCallError::ResponseTooLarge { limit };
+            }
+        }
+
+        GrpcCallError::Status(status)
+    }
+}
+
+impl From<GrpcCallError> for Status {
+    fn from(value: GrpcCallError) -> Self {
+        match value {
+            GrpcCallError::ResponseTooLarge { .. } => Status::resource_exhausted(value.to_string()),
+            GrpcCallError::Status(status) => status,
+        }
+    }
+}
+
+impl GrpcCallError {
+    /// The failure of the invoked worker, if the status carries one in its details.
+    pub fn worker_error(&self) -> Option<WorkerError> {
+        match self {
+            GrpcCallError::ResponseTooLarge { .. } => None,
+            GrpcCallError::Status(status) => WorkerError::from_status(status),
+        }
+    }
+}
+
+/// The failure of a worker invocation, decoded from the `WorkerExecutionError` an executor
+/// attaches to the details of the failed status (sent in the `grpc-status-details-bin` trailer).
+/// The failures a caller responds to differently (Ex: with a specific HTTP status) are told apart,
+/// the rest keep the decoded error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerError {
+    /// The worker trapped while running the invoked function.
+    Trap {
+        details: String,
+    },
+    /// The worker trapped, as it consumed all of its fuel.
+    OutOfFuel {
+        details: String,
+    },
+    /// The invoked function is not exported by the component.
+    ExportNotFound {
+        details: String,
+    },
+    InvalidRequest {
+        details: String,
+    },
+    WorkerNotFound,
+    Interrupted {
+        recover_immediately: bool,
+    },
+    Other(WorkerExecutionError),
+}
+
+impl WorkerError {
+    /// Decodes the failure of the worker from the details of the status. Returns `None` if
+    /// the status carries no details, or they aren't a `WorkerExecutionError`.
+    pub fn from_status(status: &Status) -> Option<Self> {
+        if status.details().is_empty() {
+            return None;
+        }
+
+        let error = WorkerExecutionError::decode(status.details()).ok()?;
+
+        // The executor reports these as runtime errors and invalid requests, only their
+        // messages tell them apart: the trap of wasmtime, and the lookup of the function
+        match error.error.clone()? {
+            worker_execution_error::Error::RuntimeError(RuntimeError { details }) => {
+                if details.contains("all fuel consumed") {
+                    Some(WorkerError::OutOfFuel { details })
+                } else {
+                    Some(WorkerError::Trap { details })
+                }
+            }
+            worker_execution_error::Error::InvalidRequest(InvalidRequest { details }) => {
+                if details.starts_with("Function ") && details.contains(" not found") {
+                    Some(WorkerError::ExportNotFound { details })
+                } else {
+                    Some(WorkerError::InvalidRequest { details })
+                }
+            }
+            worker_execution_error::Error::WorkerNotFound(_) => Some(WorkerError::WorkerNotFound),
+            worker_execution_error::Error::Interrupted(Interrupted {
+                recover_immediately,
+            }) => Some(WorkerError::Interrupted {
+                recover_immediately,
+            }),
+            _ => Some(WorkerError::Other(error)),
+        }
+    }
+}
+
+tokio::task_local! {
+    // The deadline of the retry budget of the call in progress
+    static CALL_DEADLINE: Instant;
+}
+
+/// Creates the request of a call made through the gRPC clients. If the retry budget of the call
+/// has a deadline, the remaining time is sent as the `grpc-timeout` of the request, so the
+/// server (which enforces it) doesn't keep working on a call the caller already gave up on.
+/// Has to be called by the function passed to `call_with_budget`.
+pub fn request_with_deadline<M>(message: M) -> tonic::Request<M> {
+    let mut request = tonic::Request::new(message);
+    if let Ok(deadline) = CALL_DEADLINE.try_with(|deadline| *deadline) {
+        request.set_timeout(deadline.saturating_duration_since(Instant::now()));
+    }
+    request
+}
+
+async fn with_call_deadline<R>(budget: &RetryBudget, call: impl Future<Output = R>) -> R {
+    match budget.deadline() {
+        Some(deadline) => CALL_DEADLINE.scope(deadline, call).await,
+        None => call.await,
+    }
+}
+
+/// Runs on every outgoing request of a client (Ex: to add an auth header).
+pub trait GrpcInterceptor: Send + Sync {
+    fn intercept(&self, request: tonic::Request<()>) -> Result<tonic::Request<()>, Status>;
+}
+
+/// The channel of the clients created with an interceptor.
+pub type InterceptedChannel = InterceptedService<Channel, SharedInterceptor>;
+
 #[derive(Clone)]
-pub struct GrpcClientConnection<T: Clone> {
-    client: T,
+pub struct SharedInterceptor(Arc<dyn GrpcInterceptor>);
+
+impl Interceptor for SharedInterceptor {
+    fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        self.0.intercept(request)
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct GrpcClientConfig {
-    pub connect_timeout: Duration,
-    pub retries_on_unavailable: RetryConfig,
+/// Adds the current bearer token as the `authorization` header of the requests.
+/// The token can be replaced at any time, and is used by the calls started after that.
+pub struct BearerTokenInterceptor {
+    token: RwLock<String>,
 }
 
-impl Default for GrpcClientConfig {
-    fn default() -> Self {
+impl BearerTokenInterceptor {
+    pub fn new(token: impl Into<String>) -> Self {
         Self {
-            connect_timeout: Duration::from_secs(10),
-            retries_on_unavailable: RetryConfig::default(),
+            token: RwLock::new(token.into()),
         }
     }
+
+    pub fn set_token(&self, token: impl Into<String>) {
+        *self.token.write().unwrap() = token.into();
+    }
 }
 
-fn requires_reconnect(e: &Status) -> bool {
-    e.code() == Code::Unavailable
+impl GrpcInterceptor for BearerTokenInterceptor {
+    fn intercept(&self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        let header = format!("Bearer {}", self.token.read().unwrap());
+        let value = header
+            .parse()
+            .map_err(|_| Status::unauthenticated("Invalid bearer token"))?;
+        request.metadata_mut().insert("authorization", value);
+        Ok(request)
+    }
+}
+
+/// Resolves an endpoint to the addresses its channel connects to.
+#[async_trait]
+pub trait EndpointResolver: Send + Sync {
+    async fn resolve(&self, endpoint: &http_02::Uri) -> std::io::Result<Vec<SocketAddr>>;
+}
+
+/// Resolves the host of the endpoint with the system resolver.
+pub struct DnsEndpointResolver;
+
+#[async_trait]
+impl EndpointResolver for DnsEndpointResolver {
+    async fn resolve(&self, endpoint: &http_02::Uri) -> std::io::Result<Vec<SocketAddr>> {
+        let host = endpoint.host().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Endpoint {} has no host", endpoint),
+            )
+        })?;
+        let port = endpoint
+            .port_u16()
+            .unwrap_or(if endpoint.scheme_str() == Some("https") {
+                443
+            } else {
+                80
+            });
+
+        Ok(tokio::net::lookup_host((host, port)).await?.collect())
+    }
+}
+
+#[derive(Clone)]
+pub struct GrpcClientConnection<T: Clone> {
+    client: T,
+    slow_start: Option<Arc<SlowStartLimiter>>,
+}
+
+impl<T: Clone> GrpcClientConnection<T> {
+    fn new(client: T, config: &GrpcClientConfig) -> Self {
+        Self {
+            client,
+            slow_start: config
+                .slow_start
+                .clone()
+                .map(|slow_start| Arc::new(SlowStartLimiter::new(slow_start))),
+        }
+    }
+
+    async fn acquire(&self) -> Option<SlowStartPermit> {
+        match &self.slow_start {
+            Some(slow_start) => Some(slow_start.acquire().await),
+            None => None,
+        }
+    }
+}
+
+/// Limits the concurrent calls on a newly created channel, so that many channels created
+/// at once (Ex: to an executor which just restarted) don't overwhelm the service.
+/// The limit rises linearly from `initial_concurrency` to `max_concurrency` over `window`,
+/// and stays at `max_concurrency` afterwards.
+#[derive(Debug, Clone)]
+pub struct SlowStartConfig {
+    pub window: Duration,
+    pub initial_concurrency: usize,
+    pub max_concurrency: usize,
+}
+
+/// How often a call waiting for the slow start checks whether the limit has risen.
+const SLOW_START_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+struct SlowStartLimiter {
+    config: SlowStartConfig,
+    created_at: Instant,
+    in_flight: AtomicUsize,
+    released: Notify,
+}
+
+impl SlowStartLimiter {
+    fn new(config: SlowStartConfig) -> Self {
+        Self {
+            config,
+            created_at: Instant::now(),
+            in_flight: AtomicUsize::new(0),
+            released: Notify::new(),
+        }
+    }
+
+    /// The number of concurrent calls allowed at this time. At least one call is always
+    /// allowed, so the calls can't get stuck.
+    fn limit(&self) -> usize {
+        let max = self.config.max_concurrency.max(1);
+        let initial = self.config.initial_concurrency.clamp(1, max);
+        let elapsed = self.created_at.elapsed();
+
+        if elapsed >= self.config.window {
+            max
+        } else {
+            let ramp =
+                (max - initial) as f64 * elapsed.as_secs_f64() / self.config.window.as_secs_f64();
+            initial + ramp as usize
+        }
+    }
+
+    fn try_acquire(self: &Arc<Self>) -> Option<SlowStartPermit> {
+        let limit = self.limit();
+        self.in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |in_flight| {
+                (in_flight < limit).then_some(in_flight + 1)
+            })
+            .ok()
+            .map(|_| SlowStartPermit {
+                limiter: self.clone(),
+            })
+    }
+
+    async fn acquire(self: &Arc<Self>) -> SlowStartPermit {
+        loop {
+            let released = self.released.notified();
+            if let Some(permit) = self.try_acquire() {
+                return permit;
+            }
+            // The limit rises over time as well, not only when a call finishes
+            let _ = tokio::time::timeout(SLOW_START_POLL_INTERVAL, released).await;
+        }
+    }
+}
+
+struct SlowStartPermit {
+    limiter: Arc<SlowStartLimiter>,
+}
+
+impl Drop for SlowStartPermit {
+    fn drop(&mut self) {
+        self.limiter.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.limiter.released.notify_one();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GrpcClientConfig {
+    pub connect_timeout: Duration,
+    /// Limit of waiting for a connection: for the shared channel, which is locked while it
+    /// is being (re)created, and for a permit of its slow start (on the channels of the
+    /// tenants as well). A call that can't get the connection in time fails with
+    /// `Unavailable`, without being retried. If not set, the calls wait as long as it takes.
+    pub acquire_timeout: Option<Duration>,
+    pub retries_on_unavailable: RetryConfig,
+    /// Overrides the authority (`host:port`) sent in requests, instead of the one of the
+    /// dialed endpoint. Needed when connecting through a proxy or an ingress which routes
+    /// by authority. For `https` endpoints, its host is also the server name verified with TLS.
+    pub authority: Option<String>,
+    /// Calls taking longer than this (including retries) are logged as warnings.
+    pub slow_call_threshold: Option<Duration>,
+    /// Compression of the sent requests. Applied by the client factory, as the
+    /// codec is configured on the generated client and not on the channel.
+    pub send_compression: Option<CompressionEncoding>,
+    /// Compression accepted in the responses. Applied by the client factory.
+    pub accept_compression: Option<CompressionEncoding>,
+    /// Limit of the size of the decoded responses, if different from the default of tonic.
+    /// Applied by the client factory.
+    pub max_decoding_message_size: Option<usize>,
+    /// Ramps up the concurrent calls allowed on each newly created channel.
+    pub slow_start: Option<SlowStartConfig>,
+    /// How long the responses of `call_cached` are cached. If not set, nothing is cached.
+    pub response_cache_ttl: Option<Duration>,
+}
+
+impl GrpcClientConfig {
+    /// A human-readable description of the effective settings, logged when a client is
+    /// created, so an operator can confirm the retries and timeouts in use.
+    pub fn summary(&self) -> String {
+        let retries = &self.retries_on_unavailable;
+        let mut summary = format!(
+            "connect timeout {:?}, up to {} attempts with backoff from {:?} to {:?} (multiplier {}",
+            self.connect_timeout,
+            retries.max_attempts,
+            retries.min_delay,
+            retries.max_delay,
+            retries.multiplier
+        );
+        if let Some(acquire_timeout) = self.acquire_timeout {
+            summary.push_str(&format!(", acquire timeout {:?}", acquire_timeout));
+        }
+        if let Some(max_jitter_factor) = retries.max_jitter_factor {
+            summary.push_str(&format!(", max jitter factor {}", max_jitter_factor));
+        }
+        summary.push_str(&format!(
+            "), reconnect on {}",
+            RECONNECT_CODES
+                .iter()
+                .map(|code| format!("{:?}", code))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        if let Some(authority) = &self.authority {
+            summary.push_str(&format!(", authority {}", authority));
+        }
+        if let Some(slow_call_threshold) = self.slow_call_threshold {
+            summary.push_str(&format!(", slow call threshold {:?}", slow_call_threshold));
+        }
+        if let Some(slow_start) = &self.slow_start {
+            summary.push_str(&format!(
+                ", slow start from {} to {} concurrent calls over {:?}",
+                slow_start.initial_concurrency, slow_start.max_concurrency, slow_start.window
+            ));
+        }
+        if let Some(response_cache_ttl) = self.response_cache_ttl {
+            summary.push_str(&format!(", response cache TTL {:?}", response_cache_ttl));
+        }
+        summary
+    }
+}
+
+impl Default for GrpcClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            acquire_timeout: None,
+            retries_on_unavailable: RetryConfig::default(),
+            authority: None,
+            slow_call_threshold: None,
+            send_compression: None,
+            accept_compression: None,
+            max_decoding_message_size: None,
+            slow_start: None,
+            response_cache_ttl: None,
+        }
+    }
+}
+
+/// The error of creating the endpoint of a client from its URI and configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum EndpointBuildError {
+    #[error("Invalid authority override {authority}: {reason}")]
+    InvalidAuthority { authority: String, reason: String },
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+}
+
+fn build_endpoint(
+    uri: http_02::Uri,
+    config: &GrpcClientConfig,
+) -> Result<Endpoint, EndpointBuildError> {
+    let origin = endpoint_origin(&uri, config)?;
+    let domain_name = tls_domain_name(&uri, origin.as_ref());
+    let mut endpoint = Endpoint::new(uri)?.connect_timeout(config.connect_timeout);
+
+    if let Some(origin) = origin {
+        endpoint = endpoint.origin(origin);
+    }
+    if let Some(domain_name) = domain_name {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new().domain_name(domain_name))?;
+    }
+
+    Ok(endpoint)
+}
+
+/// The endpoint as a metric label: only the host and port of the URI, so the user info,
+/// path and query (Ex: a token) don't inflate the number of label values.
+fn endpoint_label(uri: &http_02::Uri) -> String {
+    match (uri.host(), uri.port_u16()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => "unknown".to_string(),
+    }
+}
+
+/// The origin of the requests, if the authority is overridden. An authority which is not
+/// a valid `host:port` is a configuration error, instead of being ignored.
+fn endpoint_origin(
+    uri: &http_02::Uri,
+    config: &GrpcClientConfig,
+) -> Result<Option<http_02::Uri>, EndpointBuildError> {
+    match &config.authority {
+        Some(authority) => http_02::Uri::builder()
+            .scheme(uri.scheme_str().unwrap_or("http"))
+            .authority(authority.as_str())
+            .path_and_query("/")
+            .build()
+            .map(Some)
+            .map_err(|err| EndpointBuildError::InvalidAuthority {
+                authority: authority.clone(),
+                reason: err.to_string(),
+            }),
+        None => Ok(None),
+    }
+}
+
+/// The server name verified with TLS (SNI) for `https` endpoints: the host of the overridden
+/// authority if any (Ex: the name in the certificate of the executor behind an ingress),
+/// and the host of the dialed endpoint otherwise.
+fn tls_domain_name(uri: &http_02::Uri, origin: Option<&http_02::Uri>) -> Option<String> {
+    if uri.scheme_str() != Some("https") {
+        return None;
+    }
+
+    origin
+        .and_then(|origin| origin.host())
+        .or(uri.host())
+        .map(|host| host.to_string())
+}
+
+/// The status codes after which the channel is dropped and the call is retried on a new one.
+const RECONNECT_CODES: [Code; 1] = [Code::Unavailable];
+
+fn requires_reconnect(e: &Status) -> bool {
+    RECONNECT_CODES.contains(&e.code())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{
+        build_endpoint, endpoint_label, endpoint_origin, request_with_deadline, tls_domain_name,
+        BearerTokenInterceptor, EndpointBuildError, EndpointResolver, GrpcCallError, GrpcClient,
+        GrpcClientConfig, GrpcInterceptor, MultiTargetGrpcClient, SlowStartConfig,
+        SlowStartLimiter, WorkerError,
+    };
+    use crate::config::RetryConfig;
+    use crate::retries::RetryBudget;
+    use async_trait::async_trait;
+    use futures::future::join_all;
+    use golem_api_grpc::proto::golem::worker::v1::worker_service_client::WorkerServiceClient;
+    use golem_api_grpc::proto::golem::worker::v1::{
+        worker_execution_error, InvalidRequest, InvokeAndAwaitRequest, RuntimeError,
+        WorkerExecutionError,
+    };
+    use prost::Message;
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use std::time::{Duration, Instant, SystemTime};
+    use tonic::body::BoxBody;
+    use tonic::codec::CompressionEncoding;
+    use tonic::service::interceptor::InterceptedService;
+    use tonic::service::Interceptor;
+    use tonic::transport::Channel;
+    use tonic::{Code, Status};
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+    use tracing_test::traced_test;
+
+    fn config_with_attempts(max_attempts: u32) -> GrpcClientConfig {
+        GrpcClientConfig {
+            retries_on_unavailable: RetryConfig {
+                max_attempts,
+                min_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(1),
+                multiplier: 1.0,
+                max_jitter_factor: None,
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn count_attempts(client: &MultiTargetGrpcClient<()>, endpoint: http_02::Uri) -> usize {
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        let result: Result<(), GrpcCallError> = client
+            .call(endpoint, "test", |_| {
+                let attempts = attempts.clone();
+                Box::pin(async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(Status::unavailable("unavailable"))
+                })
+            })
+            .await;
+
+        assert!(result.is_err());
+        attempts.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn multi_target_client_uses_endpoint_specific_retries() {
+        let flaky: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        let fail_fast: http_02::Uri = "http://10.0.0.2:9000".parse().unwrap();
+        let default: http_02::Uri = "http://10.0.0.3:9000".parse().unwrap();
+
+        let client = MultiTargetGrpcClient::new(|_| (), config_with_attempts(2))
+            .with_endpoint_configs(HashMap::from([
+                (flaky.clone(), config_with_attempts(5)),
+                (fail_fast.clone(), config_with_attempts(1)),
+            ]));
+
+        assert_eq!(count_attempts(&client, flaky).await, 5);
+        assert_eq!(count_attempts(&client, fail_fast).await, 1);
+        assert_eq!(count_attempts(&client, default).await, 2);
+    }
+
+    #[tokio::test]
+    async fn multi_target_client_records_last_error_per_endpoint() {
+        let failing: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        let healthy: http_02::Uri = "http://10.0.0.2:9000".parse().unwrap();
+
+        let client = MultiTargetGrpcClient::new(|_| (), config_with_attempts(2));
+        let before = SystemTime::now();
+
+        let result: Result<(), GrpcCallError> = client
+            .call(failing.clone(), "test", |_| {
+                Box::pin(async { Err(Status::permission_denied("no access to the worker")) })
+            })
+            .await;
+        let status = Status::from(result.unwrap_err());
+
+        client
+            .call(healthy.clone(), "test", |_| Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+
+        let last_error = client.last_error(&failing).unwrap();
+        assert_eq!(last_error.status.code(), status.code());
+        assert_eq!(last_error.status.message(), status.message());
+        assert!(last_error.at >= before);
+
+        assert!(client.last_error(&healthy).is_none());
+        assert_eq!(client.last_errors().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn multi_target_client_calls_all_endpoints() {
+        let first: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        let failing: http_02::Uri = "http://10.0.0.2:9000".parse().unwrap();
+        let third: http_02::Uri = "http://10.0.0.3:9000".parse().unwrap();
+
+        // The client of each endpoint is its config, so the calls can tell the failing one apart
+        let client = MultiTargetGrpcClient::new_with_codec(
+            |_, config| config.clone(),
+            config_with_attempts(2),
+        )
+        .with_endpoint_configs(HashMap::from([(
+            failing.clone(),
+            GrpcClientConfig {
+                authority: Some("failing".to_string()),
+                ..config_with_attempts(2)
+            },
+        )]));
+
+        let results = client
+            .call_all(
+                vec![first.clone(), failing.clone(), third.clone()],
+                "test",
+                |config| {
+                    let fails = config.authority.is_some();
+                    Box::pin(async move {
+                        if fails {
+                            Err(Status::unavailable("executor is down"))
+                        } else {
+                            Ok("invalidated")
+                        }
+                    })
+                },
+            )
+            .await;
+
+        let endpoints: Vec<_> = results
+            .iter()
+            .map(|(endpoint, _)| endpoint.clone())
+            .collect();
+        assert_eq!(endpoints, vec![first, failing.clone(), third]);
+
+        assert_eq!(results[0].1.as_ref().unwrap(), &"invalidated");
+        assert_eq!(results[2].1.as_ref().unwrap(), &"invalidated");
+
+        let status = Status::from(results[1].1.clone().unwrap_err());
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(
+            client.last_errors().keys().collect::<Vec<_>>(),
+            vec![&failing]
+        );
+    }
+
+    struct TestResolver {
+        addresses: std::sync::Mutex<Vec<SocketAddr>>,
+    }
+
+    #[async_trait]
+    impl EndpointResolver for TestResolver {
+        async fn resolve(&self, _endpoint: &http_02::Uri) -> std::io::Result<Vec<SocketAddr>> {
+            Ok(self.addresses.lock().unwrap().clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn multi_target_client_rebuilds_channel_when_addresses_change() {
+        let endpoint: http_02::Uri = "http://worker-executor.golem.svc:9000".parse().unwrap();
+
+        let resolver = Arc::new(TestResolver {
+            addresses: std::sync::Mutex::new(vec!["10.0.0.1:9000".parse().unwrap()]),
+        });
+
+        let channels = Arc::new(AtomicUsize::new(0));
+        let client = {
+            let channels = channels.clone();
+            MultiTargetGrpcClient::new(
+                move |_| {
+                    channels.fetch_add(1, Ordering::SeqCst);
+                },
+                GrpcClientConfig::default(),
+            )
+            .with_resolver(resolver.clone())
+        };
+
+        let call = || async {
+            client
+                .call(endpoint.clone(), "test", |_| Box::pin(async { Ok(()) }))
+                .await
+                .unwrap()
+        };
+
+        call().await;
+        assert_eq!(client.refresh_endpoints().await, 0);
+        call().await;
+        assert_eq!(channels.load(Ordering::SeqCst), 1);
+
+        *resolver.addresses.lock().unwrap() = vec!["10.0.0.2:9000".parse().unwrap()];
+
+        assert_eq!(client.refresh_endpoints().await, 1);
+        call().await;
+        assert_eq!(channels.load(Ordering::SeqCst), 2);
+
+        // Unchanged since the last refresh
+        assert_eq!(client.refresh_endpoints().await, 0);
+    }
+
+    #[tokio::test]
+    async fn client_reconnects_after_reset() {
+        let channels = Arc::new(AtomicUsize::new(0));
+        let client = {
+            let channels = channels.clone();
+            GrpcClient::new(
+                move |_| {
+                    channels.fetch_add(1, Ordering::SeqCst);
+                },
+                "http://10.0.0.1:9000".parse().unwrap(),
+                GrpcClientConfig::default(),
+            )
+        };
+
+        let call = || async {
+            client
+                .call("test", |_| Box::pin(async { Ok(()) }))
+                .await
+                .unwrap()
+        };
+
+        call().await;
+        call().await;
+        assert_eq!(channels.load(Ordering::SeqCst), 1);
+
+        client.reset().await;
+        call().await;
+        assert_eq!(channels.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn call_fails_if_the_connection_is_not_acquired_in_time() {
+        let client = GrpcClient::new(
+            |_| (),
+            "http://10.0.0.1:9000".parse().unwrap(),
+            GrpcClientConfig {
+                acquire_timeout: Some(Duration::from_millis(50)),
+                ..Default::default()
+            },
+        );
+
+        let call = || client.call("test", |_| Box::pin(async { Ok(()) }));
+
+        // Holding the lock, like a slow reconnect does
+        let lock = client.client.lock().await;
+        let start = Instant::now();
+        let result = call().await;
+        let elapsed = start.elapsed();
+
+        match result {
+            Err(GrpcCallError::Status(status)) => assert_eq!(status.code(), Code::Unavailable),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(1));
+
+        drop(lock);
+        call().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn call_fails_if_the_slow_start_permit_is_not_acquired_in_time() {
+        let client = GrpcClient::new(
+            |_| (),
+            "http://10.0.0.1:9000".parse().unwrap(),
+            GrpcClientConfig {
+                acquire_timeout: Some(Duration::from_millis(50)),
+                slow_start: Some(SlowStartConfig {
+                    window: Duration::from_secs(60),
+                    initial_concurrency: 1,
+                    max_concurrency: 10,
+                }),
+                ..Default::default()
+            },
+        );
+
+        for tenant in [None, Some("tenant")] {
+            // A call in flight, holding the only permit of the new channel
+            let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+            let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<()>();
+            let started_tx = Arc::new(std::sync::Mutex::new(Some(started_tx)));
+            let finish_rx = Arc::new(tokio::sync::Mutex::new(Some(finish_rx)));
+            let in_flight = {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .call_on(tenant, "test", &RetryBudget::unlimited(), move |_| {
+                            let started_tx = started_tx.clone();
+                            let finish_rx = finish_rx.clone();
+                            Box::pin(async move {
+                                if let Some(started_tx) = started_tx.lock().unwrap().take() {
+                                    let _ = started_tx.send(());
+                                }
+                                if let Some(finish_rx) = finish_rx.lock().await.take() {
+                                    let _ = finish_rx.await;
+                                }
+                                Ok(())
+                            })
+                        })
+                        .await
+                })
+            };
+            started_rx.await.unwrap();
+
+            let call = || {
+                client.call_on(tenant, "test", &RetryBudget::unlimited(), |_| {
+                    Box::pin(async { Ok(()) })
+                })
+            };
+
+            let start = Instant::now();
+            let result = call().await;
+            let elapsed = start.elapsed();
+
+            match result {
+                Err(GrpcCallError::Status(status)) => {
+                    assert_eq!(status.code(), Code::Unavailable, "{:?}", tenant)
+                }
+                other => panic!("Unexpected result for {:?}: {:?}", tenant, other),
+            }
+            assert!(elapsed >= Duration::from_millis(50));
+            assert!(elapsed < Duration::from_secs(1));
+
+            // Once the call in flight finishes, its permit is available again
+            finish_tx.send(()).unwrap();
+            in_flight.await.unwrap().unwrap();
+            call().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn multi_target_call_fails_if_the_slow_start_permit_is_not_acquired_in_time() {
+        let endpoint: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        let client = MultiTargetGrpcClient::new(
+            |_| (),
+            GrpcClientConfig {
+                acquire_timeout: Some(Duration::from_millis(50)),
+                slow_start: Some(SlowStartConfig {
+                    window: Duration::from_secs(60),
+                    initial_concurrency: 1,
+                    max_concurrency: 10,
+                }),
+                ..Default::default()
+            },
+        );
+
+        // A call in flight, holding the only permit of the new channel
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<()>();
+        let started_tx = Arc::new(std::sync::Mutex::new(Some(started_tx)));
+        let finish_rx = Arc::new(tokio::sync::Mutex::new(Some(finish_rx)));
+        let in_flight = {
+            let client = client.clone();
+            let endpoint = endpoint.clone();
+            tokio::spawn(async move {
+                client
+                    .call(endpoint, "test", move |_| {
+                        let started_tx = started_tx.clone();
+                        let finish_rx = finish_rx.clone();
+                        Box::pin(async move {
+                            if let Some(started_tx) = started_tx.lock().unwrap().take() {
+                                let _ = started_tx.send(());
+                            }
+                            if let Some(finish_rx) = finish_rx.lock().await.take() {
+                                let _ = finish_rx.await;
+                            }
+                            Ok(())
+                        })
+                    })
+                    .await
+            })
+        };
+        started_rx.await.unwrap();
+
+        let call = || client.call(endpoint.clone(), "test", |_| Box::pin(async { Ok(()) }));
+
+        let start = Instant::now();
+        let result = call().await;
+        let elapsed = start.elapsed();
+
+        match result {
+            Err(GrpcCallError::Status(status)) => assert_eq!(status.code(), Code::Unavailable),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(1));
+        assert_eq!(
+            client
+                .last_error(&endpoint)
+                .map(|error| error.status.code()),
+            Some(Code::Unavailable)
+        );
+
+        // Once the call in flight finishes, its permit is available again
+        finish_tx.send(()).unwrap();
+        in_flight.await.unwrap().unwrap();
+        call().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn call_with_stats_reports_the_attempts_and_reconnects() {
+        let client = GrpcClient::new(
+            |_| (),
+            "http://10.0.0.1:9000".parse().unwrap(),
+            config_with_attempts(5),
+        );
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let (_, stats) = client
+            .call_with_stats("test", |_| {
+                let calls = calls.clone();
+                Box::pin(async move {
+                    if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err(Status::unavailable("unavailable"))
+                    } else {
+                        Ok(())
+                    }
+                })
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(stats.attempts, 3);
+        assert_eq!(stats.reconnects, 2);
+        assert!(stats.fresh_connection);
+
+        // The connection of the last attempt is reused
+        let (_, stats) = client
+            .call_with_stats("test", |_| Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+
+        assert_eq!(stats.attempts, 1);
+        assert_eq!(stats.reconnects, 0);
+        assert!(!stats.fresh_connection);
+    }
+
+    #[tokio::test]
+    async fn cached_call_is_made_once_within_the_ttl() {
+        let client = GrpcClient::new(
+            |_| (),
+            "http://10.0.0.1:9000".parse().unwrap(),
+            GrpcClientConfig {
+                response_cache_ttl: Some(Duration::from_millis(100)),
+                ..Default::default()
+            },
+        );
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let call = |key: &'static str, fail: bool| {
+            let calls = calls.clone();
+            client.call_cached(key, "get_component_metadata", move |_| {
+                let calls = calls.clone();
+                Box::pin(async move {
+                    let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    if fail {
+                        Err(Status::not_found("not found"))
+                    } else {
+                        Ok(call)
+                    }
+                })
+            })
+        };
+
+        assert_eq!(call("component-1/0", false).await.unwrap(), 1);
+        assert_eq!(call("component-1/0", false).await.unwrap(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Other keys and failures are not cached
+        assert_eq!(call("component-2/0", false).await.unwrap(), 2);
+        assert!(call("component-3/0", true).await.is_err());
+        assert!(call("component-3/0", true).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(call("component-1/0", false).await.unwrap(), 5);
+    }
+
+    async fn failed_call(details: Vec<u8>) -> GrpcCallError {
+        let client = GrpcClient::new(
+            |_| (),
+            "http://10.0.0.1:9000".parse().unwrap(),
+            GrpcClientConfig::default(),
+        );
+
+        let result: Result<(), GrpcCallError> = client
+            .call("invoke_and_await_worker", move |_| {
+                let details = details.clone();
+                Box::pin(async move {
+                    Err(Status::with_details(
+                        Code::Internal,
+                        "Worker invocation failed",
+                        details.into(),
+                    ))
+                })
+            })
+            .await;
+
+        result.unwrap_err()
+    }
+
+    fn runtime_error(details: &str) -> Vec<u8> {
+        WorkerExecutionError {
+            error: Some(worker_execution_error::Error::RuntimeError(RuntimeError {
+                details: details.to_string(),
+            })),
+        }
+        .encode_to_vec()
+    }
+
+    #[tokio::test]
+    async fn worker_error_is_decoded_from_the_status_details() {
+        let error = failed_call(runtime_error("wasm trap: unreachable")).await;
+        assert_eq!(
+            error.worker_error(),
+            Some(WorkerError::Trap {
+                details: "wasm trap: unreachable".to_string()
+            })
+        );
+
+        let error = failed_call(runtime_error("all fuel consumed by WebAssembly")).await;
+        assert!(matches!(
+            error.worker_error(),
+            Some(WorkerError::OutOfFuel { .. })
+        ));
+
+        let not_found = WorkerExecutionError {
+            error: Some(worker_execution_error::Error::InvalidRequest(
+                InvalidRequest {
+                    details: "Function golem:it/api.{add-item} not found".to_string(),
+                },
+            )),
+        };
+        let error = failed_call(not_found.encode_to_vec()).await;
+        assert!(matches!(
+            error.worker_error(),
+            Some(WorkerError::ExportNotFound { .. })
+        ));
+
+        // A status without (or with other) details is not a worker error
+        assert_eq!(failed_call(vec![]).await.worker_error(), None);
+        assert_eq!(failed_call(vec![0xff, 0xff]).await.worker_error(), None);
+    }
+
+    #[tokio::test]
+    async fn multi_target_client_reconnects_after_reset() {
+        let reset: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        let other: http_02::Uri = "http://10.0.0.2:9000".parse().unwrap();
+
+        let channels = Arc::new(AtomicUsize::new(0));
+        let client = {
+            let channels = channels.clone();
+            MultiTargetGrpcClient::new(
+                move |_| {
+                    channels.fetch_add(1, Ordering::SeqCst);
+                },
+                GrpcClientConfig::default(),
+            )
+        };
+
+        let call = |endpoint: http_02::Uri| {
+            let client = client.clone();
+            async move {
+                client
+                    .call(endpoint, "test", |_| Box::pin(async { Ok(()) }))
+                    .await
+                    .unwrap()
+            }
+        };
+
+        call(reset.clone()).await;
+        call(other.clone()).await;
+        assert_eq!(channels.load(Ordering::SeqCst), 2);
+
+        client.reset(&reset);
+        call(reset).await;
+        call(other).await;
+        assert_eq!(channels.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn endpoint_origin_uses_overridden_authority() {
+        let uri: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        let config = GrpcClientConfig {
+            authority: Some("worker-executor.golem.svc:443".to_string()),
+            ..Default::default()
+        };
+
+        let origin = endpoint_origin(&uri, &config).unwrap().unwrap();
+
+        assert_eq!(origin.scheme_str(), Some("http"));
+        assert_eq!(
+            origin.authority().map(|a| a.as_str()),
+            Some("worker-executor.golem.svc:443")
+        );
+    }
+
+    #[test]
+    fn summary_reflects_the_config() {
+        let config = GrpcClientConfig {
+            connect_timeout: Duration::from_secs(3),
+            retries_on_unavailable: RetryConfig {
+                max_attempts: 7,
+                min_delay: Duration::from_millis(50),
+                max_delay: Duration::from_secs(2),
+                multiplier: 1.5,
+                max_jitter_factor: Some(0.2),
+            },
+            authority: Some("worker-executor.golem.svc:443".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.summary(),
+            "connect timeout 3s, up to 7 attempts with backoff from 50ms to 2s (multiplier 1.5, max jitter factor 0.2), reconnect on Unavailable, authority worker-executor.golem.svc:443"
+        );
+    }
+
+    #[test]
+    fn endpoint_origin_is_not_set_without_override() {
+        let uri: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+
+        assert_eq!(
+            endpoint_origin(&uri, &GrpcClientConfig::default()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_authority_is_a_config_error() {
+        let uri: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        let config = GrpcClientConfig {
+            authority: Some("worker executor/".to_string()),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            endpoint_origin(&uri, &config),
+            Err(EndpointBuildError::InvalidAuthority { authority, .. }) if authority == "worker executor/"
+        ));
+        assert!(matches!(
+            build_endpoint(uri, &config),
+            Err(EndpointBuildError::InvalidAuthority { .. })
+        ));
+    }
+
+    #[test]
+    fn tls_domain_name_is_the_host_of_the_overridden_authority() {
+        let uri: http_02::Uri = "https://10.0.0.1:9000".parse().unwrap();
+        let origin: http_02::Uri = "https://worker-executor.golem.svc:443/".parse().unwrap();
+
+        assert_eq!(
+            tls_domain_name(&uri, Some(&origin)).as_deref(),
+            Some("worker-executor.golem.svc")
+        );
+        assert_eq!(tls_domain_name(&uri, None).as_deref(), Some("10.0.0.1"));
+
+        // No TLS for plain HTTP endpoints
+        let uri: http_02::Uri = "http://10.0.0.1:9000".parse().unwrap();
+        assert_eq!(tls_domain_name(&uri, Some(&origin)), None);
+
+        let config = GrpcClientConfig {
+            authority: Some("worker-executor.golem.svc:443".to_string()),
+            ..Default::default()
+        };
+        assert!(build_endpoint("https://10.0.0.1:9000".parse().unwrap(), &config).is_ok());
+    }
+
+    // Records the authority of the requests received by the server
+    #[derive(Clone)]
+    struct CaptureAuthority<S> {
+        inner: S,
+        authorities: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl<S, B> tower::Service<http_02::Request<B>> for CaptureAuthority<S>
+    where
+        S: tower::Service<http_02::Request<B>>,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = S::Future;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, request: http_02::Request<B>) -> Self::Future {
+            if let Some(authority) = request.uri().authority() {
+                self.authorities
+                    .lock()
+                    .unwrap()
+                    .push(authority.as_str().to_string());
+            }
+            self.inner.call(request)
+        }
+    }
+
+    #[tokio::test]
+    async fn endpoint_is_built_with_the_overridden_authority() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let authorities = Arc::new(std::sync::Mutex::new(vec![]));
+        let captured = authorities.clone();
+        let (_health_reporter, health_service) = tonic_health::server::health_reporter();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .layer(tower::layer::layer_fn(move |inner| CaptureAuthority {
+                    inner,
+                    authorities: captured.clone(),
+                }))
+                .add_service(health_service)
+                .serve(addr),
+        );
+
+        // Dialed by its address, while the requests carry the overridden authority
+        let client = GrpcClient::new(
+            HealthClient::new,
+            format!("http://{addr}").parse().unwrap(),
+            GrpcClientConfig {
+                authority: Some("worker-executor.golem.svc:443".to_string()),
+                // Retries until the server is listening
+                retries_on_unavailable: RetryConfig {
+                    max_attempts: 20,
+                    min_delay: Duration::from_millis(50),
+                    max_delay: Duration::from_millis(50),
+                    multiplier: 1.0,
+                    max_jitter_factor: None,
+                },
+                ..Default::default()
+            },
+        );
+
+        client
+            .call("check", |client| {
+                Box::pin(async move {
+                    client
+                        .check(HealthCheckRequest {
+                            service: "".to_string(),
+                        })
+                        .await
+                })
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            authorities.lock().unwrap().last().map(|a| a.as_str()),
+            Some("worker-executor.golem.svc:443")
+        );
+    }
+
+    async fn count_attempts_with_budget(client: &GrpcClient<()>, budget: &RetryBudget) -> usize {
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        let result: Result<(), GrpcCallError> = client
+            .call_with_budget("unavailable_method", budget, |_| {
+                let attempts = attempts.clone();
+                Box::pin(async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(Status::unavailable("unavailable"))
+                })
+            })
+            .await;
+
+        assert!(result.is_err());
+        attempts.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn calls_share_retry_budget() {
+        let client = GrpcClient::new(
+            |_| (),
+            "http://10.0.0.1:9000".parse().unwrap(),
+            config_with_attempts(5),
+        );
+        let budget = RetryBudget::unlimited().with_max_retries(5);
+
+        // The first call makes all the 4 retries of its config, leaving 1 for the second call
+        assert_eq!(count_attempts_with_budget(&client, &budget).await, 5);
+        assert_eq!(count_attempts_with_budget(&client, &budget).await, 2);
+        assert_eq!(budget.remaining_retries(), Some(0));
+    }
+
+    async fn connection_id(client: &GrpcClient<usize>, tenant: Option<&str>) -> usize {
+        let result = match tenant {
+            Some(tenant) => {
+                client
+                    .call_for_tenant(tenant, "test", |id| {
+                        let id = *id;
+                        Box::pin(async move { Ok(id) })
+                    })
+                    .await
+            }
+            None => {
+                client
+                    .call("test", |id| {
+                        let id = *id;
+                        Box::pin(async move { Ok(id) })
+                    })
+                    .await
+            }
+        };
+
+        result.unwrap()
+    }
+
+    #[tokio::test]
+    async fn tenants_get_distinct_connections() {
+        // The client of each connection is its sequence number
+        let connections = Arc::new(AtomicUsize::new(0));
+        let client = {
+            let connections = connections.clone();
+            GrpcClient::new(
+                move |_| connections.fetch_add(1, Ordering::SeqCst),
+                "http://10.0.0.1:9000".parse().unwrap(),
+                config_with_attempts(1),
+            )
+        };
+
+        let first = connection_id(&client, Some("tenant-1")).await;
+        let second = connection_id(&client, Some("tenant-2")).await;
+        let default = connection_id(&client, None).await;
+
+        assert_ne!(first, second);
+        assert_ne!(first, default);
+        assert_ne!(second, default);
+
+        // The connections are reused by the later calls of the same tenant
+        assert_eq!(connection_id(&client, Some("tenant-1")).await, first);
+        assert_eq!(connection_id(&client, Some("tenant-2")).await, second);
+        assert_eq!(connection_id(&client, None).await, default);
+        assert_eq!(connections.load(Ordering::SeqCst), 3);
+
+        client.reset_tenant("tenant-1");
+
+        assert_ne!(connection_id(&client, Some("tenant-1")).await, first);
+        assert_eq!(connection_id(&client, Some("tenant-2")).await, second);
+    }
+
+    #[test]
+    fn endpoint_label_is_the_host_and_port() {
+        let uri: http_02::Uri = "http://user@10.0.0.1:9000/golem/v1?token=abc"
+            .parse()
+            .unwrap();
+        assert_eq!(endpoint_label(&uri), "10.0.0.1:9000");
+
+        let uri: http_02::Uri = "https://worker-executor.golem.svc/".parse().unwrap();
+        assert_eq!(endpoint_label(&uri), "worker-executor.golem.svc");
+    }
+
+    fn call_metric_labels(metric_name: &str, method: &str) -> Option<HashMap<String, String>> {
+        prometheus::gather()
+            .into_iter()
+            .filter(|family| family.get_name() == metric_name)
+            .flat_map(|family| family.get_metric().to_vec())
+            .map(|metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .map(|label| (label.get_name().to_string(), label.get_value().to_string()))
+                    .collect::<HashMap<_, _>>()
+            })
+            .find(|labels| labels.get("method").map(|m| m.as_str()) == Some(method))
+    }
+
+    #[tokio::test]
+    async fn call_metrics_are_labelled_by_endpoint_and_method() {
+        let client = GrpcClient::new(
+            |_| (),
+            "http://10.0.0.2:9000/golem?x=1".parse().unwrap(),
+            config_with_attempts(1),
+        );
+
+        client
+            .call("labelled_method", |_| Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+        let _ = client
+            .call("labelled_failing_method", |_| {
+                Box::pin(async { Err::<(), _>(Status::not_found("not found")) })
+            })
+            .await;
+
+        let labels = call_metric_labels("grpc_client_call_seconds", "labelled_method").unwrap();
+        assert_eq!(labels.get("endpoint").unwrap(), "10.0.0.2:9000");
+
+        let labels =
+            call_metric_labels("grpc_client_call_failure_total", "labelled_failing_method")
+                .unwrap();
+        assert_eq!(labels.get("endpoint").unwrap(), "10.0.0.2:9000");
+        assert_eq!(labels.get("code").unwrap(), "NotFound");
+        assert_eq!(
+            call_metric_labels("grpc_client_call_failure_total", "labelled_method"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn multi_target_call_metrics_are_labelled_by_endpoint_and_method() {
+        let endpoint: http_02::Uri = "http://10.0.0.3:9000/golem?x=1".parse().unwrap();
+        let client = MultiTargetGrpcClient::new(|_| (), config_with_attempts(1));
+
+        client
+            .call(endpoint.clone(), "multi_target_labelled_method", |_| {
+                Box::pin(async { Ok(()) })
+            })
+            .await
+            .unwrap();
+        let _ = client
+            .call(endpoint, "multi_target_labelled_failing_method", |_| {
+                Box::pin(async { Err::<(), _>(Status::not_found("not found")) })
+            })
+            .await;
+
+        let labels =
+            call_metric_labels("grpc_client_call_seconds", "multi_target_labelled_method").unwrap();
+        assert_eq!(labels.get("endpoint").unwrap(), "10.0.0.3:9000");
+
+        let labels = call_metric_labels(
+            "grpc_client_call_failure_total",
+            "multi_target_labelled_failing_method",
+        )
+        .unwrap();
+        assert_eq!(labels.get("endpoint").unwrap(), "10.0.0.3:9000");
+        assert_eq!(labels.get("code").unwrap(), "NotFound");
+        assert_eq!(
+            call_metric_labels(
+                "grpc_client_call_failure_total",
+                "multi_target_labelled_method"
+            ),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_start_ramps_up_the_concurrency() {
+        let limiter = Arc::new(SlowStartLimiter::new(SlowStartConfig {
+            window: Duration::from_millis(200),
+            initial_concurrency: 1,
+            max_concurrency: 4,
+        }));
+
+        // Right after the channel is created, only the initial concurrency is allowed
+        let first = limiter.try_acquire();
+        assert!(first.is_some());
+        assert!(limiter.try_acquire().is_none());
+
+        // A waiting call gets the permit of a finished one
+        let waiting = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { drop(limiter.acquire().await) })
+        };
+        drop(first);
+        tokio::time::timeout(Duration::from_millis(100), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        // After the window, the full concurrency is allowed, and no more
+        assert_eq!(limiter.limit(), 4);
+        let permits = (0..4)
+            .map(|_| limiter.try_acquire())
+            .collect::<Option<Vec<_>>>()
+            .unwrap();
+        assert_eq!(permits.len(), 4);
+        assert!(limiter.try_acquire().is_none());
+    }
+
+    fn client_with_slow_call_threshold() -> GrpcClient<()> {
+        GrpcClient::new(
+            |_| (),
+            "http://10.0.0.1:9000".parse().unwrap(),
+            GrpcClientConfig {
+                slow_call_threshold: Some(Duration::from_millis(50)),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn slow_call_is_logged() {
+        let client = client_with_slow_call_threshold();
+
+        client
+            .call("slow_method", |_| {
+                Box::pin(async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+
+        assert!(logs_contain("Slow gRPC call"));
+        assert!(logs_contain("slow_method"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn fast_call_is_not_logged() {
+        let client = client_with_slow_call_threshold();
+
+        client
+            .call("fast_method", |_| Box::pin(async { Ok(()) }))
+            .await
+            .unwrap();
+
+        assert!(!logs_contain("Slow gRPC call"));
+    }
+
+    // Records the authorization header set by the bearer token interceptor,
+    // without sending the request
+    struct CapturingInterceptor {
+        bearer: Arc<BearerTokenInterceptor>,
+        captured: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl GrpcInterceptor for CapturingInterceptor {
+        fn intercept(&self, request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+            let request = self.bearer.intercept(request)?;
+            let authorization = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            self.captured.lock().unwrap().push(authorization);
+            Err(Status::cancelled("captured"))
+        }
+    }
+
+    #[tokio::test]
+    async fn client_sends_the_current_bearer_token() {
+        let bearer = Arc::new(BearerTokenInterceptor::new("first"));
+        let interceptor = Arc::new(CapturingInterceptor {
+            bearer: bearer.clone(),
+            captured: std::sync::Mutex::new(vec![]),
+        });
+
+        let client = GrpcClient::new_with_interceptor(
+            |channel, _| WorkerServiceClient::new(channel),
+            "http://localhost:9000".parse().unwrap(),
+            GrpcClientConfig::default(),
+            interceptor.clone(),
+        );
+
+        let invoke = || {
+            client.call("invoke_and_await", |client| {
+                Box::pin(async move {
+                    client
+                        .invoke_and_await(InvokeAndAwaitRequest::default())
+                        .await
+                })
+            })
+        };
+
+        assert!(invoke().await.is_err());
+        bearer.set_token("second");
+        assert!(invoke().await.is_err());
+        assert!(invoke().await.is_err());
+
+        assert_eq!(
+            *interceptor.captured.lock().unwrap(),
+            vec![
+                "Bearer first".to_string(),
+                "Bearer second".to_string(),
+                "Bearer second".to_string()
+            ]
+        );
+    }
+
+    // Replaces the channel, counting the calls in flight, and failing them without sending
+    #[derive(Clone)]
+    struct InFlightCounter {
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    impl tower::Service<http_02::Request<BoxBody>> for InFlightCounter {
+        type Response = http_02::Response<BoxBody>;
+        type Error = tower::BoxError;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: http_02::Request<BoxBody>) -> Self::Future {
+            let in_flight = self.in_flight.clone();
+            let max_in_flight = self.max_in_flight.clone();
+            Box::pin(async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Err("not sent".into())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn layered_concurrency_limit_caps_the_calls_in_flight() {
+        let counter = InFlightCounter {
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            max_in_flight: Arc::new(AtomicUsize::new(0)),
+        };
+        let max_in_flight = counter.max_in_flight.clone();
+
+        let client = GrpcClient::new_with_layer(
+            |service, _| WorkerServiceClient::new(service),
+            "http://localhost:9000".parse().unwrap(),
+            GrpcClientConfig::default(),
+            tower::ServiceBuilder::new()
+                .concurrency_limit(2)
+                .layer(tower::layer::layer_fn(move |_: Channel| counter.clone())),
+        );
+
+        let results = join_all((0..6).map(|_| {
+            client.call("invoke_and_await", |client| {
+                Box::pin(async move {
+                    client
+                        .invoke_and_await(InvokeAndAwaitRequest::default())
+                        .await
+                })
+            })
+        }))
+        .await;
+
+        assert!(results.iter().all(|result| result.is_err()));
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    // Captures the `grpc-timeout` of the requests, without sending them
+    fn timeout_capturing_client(
+        timeouts: Arc<std::sync::Mutex<Vec<Option<Duration>>>>,
+    ) -> GrpcClient<WorkerServiceClient<InterceptedService<Channel, CaptureTimeout>>> {
+        GrpcClient::new(
+            move |channel| {
+                WorkerServiceClient::with_interceptor(channel, CaptureTimeout(timeouts.clone()))
+            },
+            "http://localhost:9000".parse().unwrap(),
+            GrpcClientConfig::default(),
+        )
+    }
+
+    #[derive(Clone)]
+    struct CaptureTimeout(Arc<std::sync::Mutex<Vec<Option<Duration>>>>);
+
+    impl Interceptor for CaptureTimeout {
+        fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+            let timeout = request
+                .metadata()
+                .get("grpc-timeout")
+                .and_then(|value| value.to_str().ok())
+                .map(parse_grpc_timeout);
+            self.0.lock().unwrap().push(timeout);
+            Err(Status::cancelled("captured"))
+        }
+    }
+
+    fn parse_grpc_timeout(value: &str) -> Duration {
+        let (amount, unit) = value.split_at(value.len() - 1);
+        let amount: u64 = amount.parse().unwrap();
+        match unit {
+            "H" => Duration::from_secs(amount * 3600),
+            "M" => Duration::from_secs(amount * 60),
+            "S" => Duration::from_secs(amount),
+            "m" => Duration::from_millis(amount),
+            "u" => Duration::from_micros(amount),
+            "n" => Duration::from_nanos(amount),
+            _ => panic!("Invalid grpc-timeout unit: {unit}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn grpc_timeout_is_the_remaining_time_of_the_budget() {
+        let timeouts = Arc::new(std::sync::Mutex::new(vec![]));
+        let client = timeout_capturing_client(timeouts.clone());
+
+        let invoke = |budget: RetryBudget| {
+            let client = client.clone();
+            async move {
+                client
+                    .call_with_budget("invoke_and_await", &budget, |client| {
+                        Box::pin(async move {
+                            client
+                                .invoke_and_await(request_with_deadline(
+                                    InvokeAndAwaitRequest::default(),
+                                ))
+                                .await
+                        })
+                    })
+                    .await
+            }
+        };
+
+        let budget = RetryBudget::unlimited().with_max_duration(Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(invoke(budget).await.is_err());
+
+        assert!(invoke(RetryBudget::unlimited()).await.is_err());
+
+        let timeouts = timeouts.lock().unwrap().clone();
+        let remaining = timeouts[0].unwrap();
+        assert!(remaining <= Duration::from_millis(4900));
+        assert!(remaining > Duration::from_secs(4));
+        assert_eq!(timeouts[1], None);
+    }
+
+    #[tokio::test]
+    async fn client_sends_compressed_requests() {
+        let encoding = Arc::new(std::sync::Mutex::new(None));
+
+        let captured = encoding.clone();
+        let client = GrpcClient::new_with_codec(
+            move |channel, config| {
+                let captured = captured.clone();
+                let mut client = WorkerServiceClient::with_interceptor(
+                    channel,
+                    move |request: tonic::Request<()>| {
+                        *captured.lock().unwrap() = request
+                            .metadata()
+                            .get("grpc-encoding")
+                            .and_then(|value| value.to_str().ok())
+                            .map(|value| value.to_string());
+                        // The request is not sent, only its headers are inspected
+                        Err::<tonic::Request<()>, _>(Status::cancelled("captured"))
+                    },
+                );
+                if let Some(encoding) = config.send_compression {
+                    client = client.send_compressed(encoding);
+                }
+                if let Some(encoding) = config.accept_compression {
+                    client = client.accept_compressed(encoding);
+                }
+                client
+            },
+            "http://localhost:9000".parse().unwrap(),
+            GrpcClientConfig {
+                send_compression: Some(CompressionEncoding::Gzip),
+                accept_compression: Some(CompressionEncoding::Gzip),
+                ..Default::default()
+            },
+        );
+
+        let request = InvokeAndAwaitRequest {
+            function: "compressible".repeat(100_000),
+            ..Default::default()
+        };
+
+        let result = client
+            .call("invoke_and_await", move |client| {
+                let request = request.clone();
+                Box::pin(async move { client.invoke_and_await(request).await })
+            })
+            .await;
+
+        assert_eq!(Status::from(result.unwrap_err()).code(), Code::Cancelled);
+        assert_eq!(encoding.lock().unwrap().as_deref(), Some("gzip"));
+    }
+
+    #[tokio::test]
+    async fn oversized_response_is_a_typed_error() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let (_health_reporter, health_service) = tonic_health::server::health_reporter();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(health_service)
+                .serve(addr),
+        );
+
+        // The health check response (2 bytes) exceeds the 1 byte limit
+        let client = GrpcClient::new_with_codec(
+            |channel, config| {
+                let mut client = HealthClient::new(channel);
+                if let Some(limit) = config.max_decoding_message_size {
+                    client = client.max_decoding_message_size(limit);
+                }
+                client
+            },
+            format!("http://{addr}").parse().unwrap(),
+            GrpcClientConfig {
+                // Retries until the server is listening
+                retries_on_unavailable: RetryConfig {
+                    max_attempts: 20,
+                    min_delay: Duration::from_millis(50),
+                    max_delay: Duration::from_millis(50),
+                    multiplier: 1.0,
+                    max_jitter_factor: None,
+                },
+                max_decoding_message_size: Some(1),
+                ..Default::default()
+            },
+        );
+
+        let result = client
+            .call("check", |client| {
+                Box::pin(async move {
+                    client
+                        .check(HealthCheckRequest {
+                            service: "".to_string(),
+                        })
+                        .await
+                })
+            })
+            .await;
+
+        assert!(matches!(
+            result.unwrap_err(),
+            GrpcCallError::ResponseTooLarge { limit: 1 }
+        ));
+    }
+
+    #[test]
+    fn other_statuses_are_not_typed_errors() {
+        let status = Status::resource_exhausted("too many requests");
+
+        let error = GrpcCallError::from_status(status, &GrpcClientConfig::default());
+
+        assert!(
+            matches!(error, GrpcCallError::Status(status) if status.code() == Code::ResourceExhausted)
+        );
+    }
 }
@@ -28,6 +28,7 @@ pub mod redis;
 pub mod retriable_error;
 pub mod retries;
 pub mod serialization;
+pub mod shutdown;
 pub mod tracing;
 pub mod uri;
 
@@ -182,6 +182,53 @@ pub mod redis {
     }
 }
 
+pub mod grpc_client {
+    use std::time::Duration;
+
+    use lazy_static::lazy_static;
+    use prometheus::*;
+
+    lazy_static! {
+        static ref GRPC_CLIENT_CALL_SECONDS: HistogramVec = register_histogram_vec!(
+            "grpc_client_call_seconds",
+            "Duration of gRPC client calls, including the retries",
+            &["endpoint", "method"],
+            crate::metrics::DEFAULT_TIME_BUCKETS.to_vec()
+        )
+        .unwrap();
+        static ref GRPC_CLIENT_CALL_FAILURE_TOTAL: CounterVec = register_counter_vec!(
+            "grpc_client_call_failure_total",
+            "Number of failed gRPC client calls",
+            &["endpoint", "method", "code"]
+        )
+        .unwrap();
+        static ref GRPC_CLIENT_RECONNECT_TOTAL: CounterVec = register_counter_vec!(
+            "grpc_client_reconnect_total",
+            "Number of gRPC client channels dropped to reconnect",
+            &["endpoint", "method"]
+        )
+        .unwrap();
+    }
+
+    pub fn record_grpc_client_call(endpoint: &str, method: &str, duration: Duration) {
+        GRPC_CLIENT_CALL_SECONDS
+            .with_label_values(&[endpoint, method])
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn record_grpc_client_call_failure(endpoint: &str, method: &str, code: &str) {
+        GRPC_CLIENT_CALL_FAILURE_TOTAL
+            .with_label_values(&[endpoint, method, code])
+            .inc();
+    }
+
+    pub fn record_grpc_client_reconnect(endpoint: &str, method: &str) {
+        GRPC_CLIENT_RECONNECT_TOTAL
+            .with_label_values(&[endpoint, method])
+            .inc();
+    }
+}
+
 pub mod caching {
     use lazy_static::lazy_static;
     use prometheus::*;
@@ -15,6 +15,8 @@
 use rand::{thread_rng, Rng};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn, Level};
 
@@ -64,6 +66,7 @@ pub fn get_delay(config: &RetryConfig, attempts: u64) -> Option<Duration> {
 pub struct RetryState<'a> {
     attempts: u64,
     retry_config: &'a RetryConfig,
+    budget: Option<&'a RetryBudget>,
 }
 
 impl<'a> RetryState<'a> {
@@ -72,9 +75,16 @@ impl<'a> RetryState<'a> {
         Self {
             attempts: 0,
             retry_config,
+            budget: None,
         }
     }
 
+    /// Also limits the retries by a budget shared with other retried actions.
+    pub fn with_budget(mut self, budget: &'a RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
     /// Indicates a new attempt has started
     pub fn start_attempt(&mut self) {
         self.attempts += 1;
@@ -84,11 +94,71 @@ impl<'a> RetryState<'a> {
     /// this function will sleep for the calculated delay and then return true. If there
     /// are no more retry attempts, it returns false
     pub async fn failed_attempt(&self) -> bool {
-        if let Some(delay) = get_delay(self.retry_config, self.attempts) {
-            tokio::time::sleep(delay).await;
-            true
-        } else {
-            false
+        match get_delay(self.retry_config, self.attempts) {
+            Some(delay) if self.budget.map_or(true, |budget| budget.try_retry(delay)) => {
+                tokio::time::sleep(delay).await;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A retry budget shared by the retried actions of a single logical request (Ex: the
+/// executor calls made for one inbound gateway request), bounding the total number of
+/// retries and the total time they can take. Clones share the same budget.
+#[derive(Debug, Clone, Default)]
+pub struct RetryBudget {
+    remaining_retries: Option<Arc<AtomicU64>>,
+    deadline: Option<Instant>,
+}
+
+impl RetryBudget {
+    /// A budget which never stops the retries, leaving it to the `RetryConfig` of each action.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Limits the total number of retries. The first attempts of the actions are not counted.
+    pub fn with_max_retries(mut self, max_retries: u64) -> Self {
+        self.remaining_retries = Some(Arc::new(AtomicU64::new(max_retries)));
+        self
+    }
+
+    /// No retry is started if waiting for it would end later than `max_duration` from now.
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.deadline = Some(Instant::now() + max_duration);
+        self
+    }
+
+    /// The number of retries left, if the number of retries is limited.
+    pub fn remaining_retries(&self) -> Option<u64> {
+        self.remaining_retries
+            .as_ref()
+            .map(|remaining| remaining.load(Ordering::Acquire))
+    }
+
+    /// The time by which the actions sharing the budget have to finish, if the duration is limited.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Takes a retry, started after `delay`, from the budget.
+    /// Returns false if the budget is exhausted.
+    pub fn try_retry(&self, delay: Duration) -> bool {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() + delay > deadline {
+                return false;
+            }
+        }
+
+        match &self.remaining_retries {
+            Some(remaining) => remaining
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                    remaining.checked_sub(1)
+                })
+                .is_ok(),
+            None => true,
         }
     }
 }
@@ -222,6 +292,24 @@ mod tests {
     use std::time::Duration;
 
     use crate::config::RetryConfig;
+    use crate::retries::RetryBudget;
+
+    #[test]
+    pub fn retry_budget_limits_retries_and_duration() {
+        let budget = RetryBudget::unlimited().with_max_retries(2);
+        let shared = budget.clone();
+
+        assert!(budget.try_retry(Duration::ZERO));
+        assert!(shared.try_retry(Duration::ZERO));
+        assert!(!budget.try_retry(Duration::ZERO));
+        assert_eq!(shared.remaining_retries(), Some(0));
+
+        let budget = RetryBudget::unlimited().with_max_duration(Duration::from_secs(1));
+
+        assert!(budget.try_retry(Duration::from_millis(10)));
+        assert!(!budget.try_retry(Duration::from_secs(2)));
+        assert_eq!(budget.remaining_retries(), None);
+    }
 
     #[test]
     pub fn get_delay_example_without_jitter() {
@@ -0,0 +1,240 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Counts the number of in-flight operations (requests, client calls) so that shutdown
+/// can wait for them to finish, bounded by a drain timeout.
+#[derive(Clone, Debug, Default)]
+pub struct InFlight {
+    count: Arc<AtomicUsize>,
+}
+
+impl InFlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the start of an operation. The operation is considered finished when the
+    /// returned guard is dropped.
+    pub fn enter(&self) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            count: self.count.clone(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Waits until there are no in-flight operations left, or the timeout elapses.
+    /// Returns the number of operations still in flight.
+    pub async fn drain(&self, name: &str, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = self.count();
+            if remaining == 0 {
+                info!("{name} drained");
+                break 0;
+            }
+            if Instant::now() >= deadline {
+                warn!("{name} did not drain within {timeout:?}, {remaining} still in flight");
+                break remaining;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+pub struct InFlightGuard {
+    count: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// The shutdown of a service, requested once (by a signal, or by one of its servers stopping
+/// on its own) and shared by all its servers and clients. They are all drained within the same
+/// deadline, set when the shutdown is requested.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    drain_timeout: Duration,
+    deadline: Arc<OnceLock<Instant>>,
+    requested: Arc<Notify>,
+}
+
+impl Shutdown {
+    pub fn new(drain_timeout: Duration) -> Self {
+        Self {
+            drain_timeout,
+            deadline: Arc::new(OnceLock::new()),
+            requested: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        self.drain_timeout
+    }
+
+    /// Requests the shutdown. Only the first request sets the deadline.
+    pub fn request(&self) {
+        if self
+            .deadline
+            .set(Instant::now() + self.drain_timeout)
+            .is_ok()
+        {
+            info!(
+                "Shutdown requested, draining within {:?}",
+                self.drain_timeout
+            );
+        }
+        self.requested.notify_waiters();
+    }
+
+    /// Requests the shutdown when the process receives Ctrl+C or, on unix, SIGTERM.
+    pub fn request_on_signal(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            shutdown.request();
+        });
+    }
+
+    /// Completes when the shutdown is requested.
+    pub async fn requested(&self) {
+        loop {
+            // Created before checking the deadline, so a request in between is not missed
+            let requested = self.requested.notified();
+            if self.deadline.get().is_some() {
+                break;
+            }
+            requested.await;
+        }
+    }
+
+    /// Completes when the deadline elapses, after the shutdown is requested.
+    pub async fn deadline_elapsed(&self) {
+        self.requested().await;
+        if let Some(deadline) = self.deadline.get() {
+            tokio::time::sleep_until(*deadline).await;
+        }
+    }
+
+    /// The time left to drain until the deadline, or the whole drain timeout if the shutdown
+    /// is not requested yet.
+    pub fn remaining(&self) -> Duration {
+        match self.deadline.get() {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => self.drain_timeout,
+        }
+    }
+}
+
+/// Completes when the process receives Ctrl+C or, on unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutdown signal received");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tracing_test::traced_test;
+
+    use crate::shutdown::{InFlight, Shutdown};
+
+    #[tokio::test]
+    #[traced_test]
+    async fn drain_completes_when_nothing_in_flight() {
+        let in_flight = InFlight::new();
+        let guard = in_flight.enter();
+        drop(guard);
+
+        let remaining = in_flight.drain("test", Duration::from_millis(100)).await;
+
+        assert_eq!(remaining, 0);
+        assert!(logs_contain("test drained"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn long_running_request_is_cut_off_by_drain_timeout() {
+        let in_flight = InFlight::new();
+        let guard = in_flight.enter();
+        let request = tokio::spawn(async move {
+            let _guard = guard;
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+
+        let remaining = in_flight.drain("test", Duration::from_millis(50)).await;
+        request.abort();
+
+        assert_eq!(remaining, 1);
+        assert!(logs_contain("1 still in flight"));
+    }
+
+    #[tokio::test]
+    async fn servers_and_clients_share_the_deadline() {
+        let shutdown = Shutdown::new(Duration::from_millis(100));
+        assert_eq!(shutdown.remaining(), Duration::from_millis(100));
+
+        let waiting = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.deadline_elapsed().await }
+        });
+
+        shutdown.request();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        // A later request does not move the deadline
+        shutdown.request();
+
+        assert!(shutdown.remaining() <= Duration::from_millis(40));
+        tokio::time::timeout(Duration::from_millis(100), waiting)
+            .await
+            .expect("deadline did not elapse")
+            .unwrap();
+        assert_eq!(shutdown.remaining(), Duration::ZERO);
+    }
+}
@@ -52,7 +52,7 @@ impl ComponentCompilationService for ComponentCompilationServiceDefault {
         let component_id_clone = component_id.clone();
         let result = self
             .client
-            .call(move |client| {
+            .call("enqueue_compilation", move |client| {
                 let component_id_clone = component_id_clone.clone();
                 Box::pin(async move {
                     let request = ComponentCompilationRequest {
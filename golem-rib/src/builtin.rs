@@ -0,0 +1,473 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Expr, InferredType};
+use bincode::{Decode, Encode};
+use std::fmt::Display;
+
+// Functions that are part of Rib itself, and are evaluated by the interpreter
+// instead of being invoked on a worker. A call is resolved to a builtin only if the name
+// is not shadowed by a function (or variant) in the component metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Encode, Decode)]
+pub enum BuiltinFunction {
+    // isPresent(option) returns true if the optional value is `some`
+    IsPresent,
+    // reject(status, message) short-circuits the evaluation with an HTTP status and a message
+    Reject,
+    // coalesce(a, b, ...) returns the first argument that is present.
+    // Arguments that are not optional (Ex: a literal) are always present
+    Coalesce,
+    // hasField(record, name) returns true if the record has a field with the name,
+    // without selecting the field (which fails if it doesn't exist)
+    HasField,
+    // round(number), floor(number) and ceil(number) return the number as an integer (s64).
+    // `round` rounds half-way values away from zero (Ex: 2.5 to 3, and -2.5 to -3)
+    Round,
+    Floor,
+    Ceil,
+    // split(string, separator) returns the list of the parts of the string between the separators.
+    // If the separator is not found, the list has the whole string as the only element
+    // (an empty string results in `[""]`). An empty separator is an error
+    Split,
+    // base64Encode(string) and base64Decode(string) convert between a string and its URL-safe
+    // base64 encoding (Ex: an opaque cursor). Encoding omits the padding, and decoding accepts
+    // it either way, as well as the standard alphabet (Ex: the base64 request.rawBody).
+    // Decoding fails if the input is not base64, or not an encoded string
+    Base64Encode,
+    Base64Decode,
+    // parseJson(string) parses a JSON string (Ex: a serialized query parameter) into a value whose
+    // fields can be selected. Its type is only known when it is evaluated: objects are records,
+    // numbers are f64, `null` is `none`, and the elements of an array must have the same type.
+    // Invalid JSON is an error
+    ParseJson,
+    // size(list) returns the number of elements of a list (u64). Request values read as
+    // a list (Ex: a repeated query parameter) are lists of strings, unless stated otherwise
+    Size,
+    // format(template, args...) fills the `{}` placeholders of the template with the arguments
+    // as strings, in order (Ex: `format("user-{}", request.path.id)`). `{{` and `}}` are
+    // literal braces. The number of placeholders must match the number of arguments, which
+    // is checked when compiling if the template is a literal, and when evaluating otherwise
+    Format,
+    // clamp(number, min, max) bounds the number to the range (Ex: `clamp(request.query.limit, 1, 100)`),
+    // returning `min` if it's below it, and `max` if it's above it. The bounds have the type of the number.
+    // A `min` greater than `max` is an error
+    Clamp,
+    // coalesceTo(value, default) returns the value as a number (f64), or the default if it's absent
+    // (Ex: `coalesceTo(request.query.limit, 20)`). Numeric strings (Ex: a query parameter) are parsed,
+    // and any other string is an error
+    CoalesceTo,
+    // range(start, end, inclusive) is the value of `start..end` (or `start..=end` if inclusive),
+    // a record of the bounds and whether the end is included. Like the bounds of `clamp`, the bounds
+    // have the type of a local variable bound, and are f64 otherwise. A `start` greater than `end`
+    // is an error, while `start..start` is an empty range
+    Range,
+    // inRange(value, range) is `value in range`, whether the number is within the bounds of the range.
+    // With a list instead of a range, it's whether the list has the value
+    // (Ex: `request.method in ["GET", "HEAD"]`), and the value has the type of the elements
+    InRange,
+    // isTruthy(value) is whether the value counts as true (see `RibInterpreterResult::is_truthy`).
+    // Conditions are compiled to it in lenient predicate mode, so `if request.query.debug then ..`
+    // reads the query parameter as an optional string, which is true if it's present
+    IsTruthy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuiltinArity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl BuiltinArity {
+    pub fn accepts(&self, arg_size: usize) -> bool {
+        match self {
+            BuiltinArity::Exactly(n) => arg_size == *n,
+            BuiltinArity::AtLeast(n) => arg_size >= *n,
+        }
+    }
+}
+
+impl Display for BuiltinArity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuiltinArity::Exactly(n) => write!(f, "{}", n),
+            BuiltinArity::AtLeast(n) => write!(f, "at least {}", n),
+        }
+    }
+}
+
+impl BuiltinFunction {
+    pub fn all() -> Vec<BuiltinFunction> {
+        vec![
+            BuiltinFunction::IsPresent,
+            BuiltinFunction::Reject,
+            BuiltinFunction::Coalesce,
+            BuiltinFunction::HasField,
+            BuiltinFunction::Round,
+            BuiltinFunction::Floor,
+            BuiltinFunction::Ceil,
+            BuiltinFunction::Split,
+            BuiltinFunction::Base64Encode,
+            BuiltinFunction::Base64Decode,
+            BuiltinFunction::ParseJson,
+            BuiltinFunction::Size,
+            BuiltinFunction::Format,
+            BuiltinFunction::Clamp,
+            BuiltinFunction::CoalesceTo,
+            BuiltinFunction::Range,
+            BuiltinFunction::InRange,
+            BuiltinFunction::IsTruthy,
+        ]
+    }
+
+    pub fn from_name(name: &str) -> Option<BuiltinFunction> {
+        BuiltinFunction::all()
+            .into_iter()
+            .find(|builtin| builtin.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinFunction::IsPresent => "isPresent",
+            BuiltinFunction::Reject => "reject",
+            BuiltinFunction::Coalesce => "coalesce",
+            BuiltinFunction::HasField => "hasField",
+            BuiltinFunction::Round => "round",
+            BuiltinFunction::Floor => "floor",
+            BuiltinFunction::Ceil => "ceil",
+            BuiltinFunction::Split => "split",
+            BuiltinFunction::Base64Encode => "base64Encode",
+            BuiltinFunction::Base64Decode => "base64Decode",
+            BuiltinFunction::ParseJson => "parseJson",
+            BuiltinFunction::Size => "size",
+            BuiltinFunction::Format => "format",
+            BuiltinFunction::Clamp => "clamp",
+            BuiltinFunction::CoalesceTo => "coalesceTo",
+            BuiltinFunction::Range => "range",
+            BuiltinFunction::InRange => "inRange",
+            BuiltinFunction::IsTruthy => "isTruthy",
+        }
+    }
+
+    pub fn arity(&self) -> BuiltinArity {
+        match self {
+            BuiltinFunction::IsPresent => BuiltinArity::Exactly(1),
+            BuiltinFunction::Reject => BuiltinArity::Exactly(2),
+            BuiltinFunction::Coalesce => BuiltinArity::AtLeast(2),
+            BuiltinFunction::HasField => BuiltinArity::Exactly(2),
+            BuiltinFunction::Split => BuiltinArity::Exactly(2),
+            BuiltinFunction::Format => BuiltinArity::AtLeast(1),
+            BuiltinFunction::Clamp => BuiltinArity::Exactly(3),
+            BuiltinFunction::CoalesceTo => BuiltinArity::Exactly(2),
+            BuiltinFunction::Range => BuiltinArity::Exactly(3),
+            BuiltinFunction::InRange => BuiltinArity::Exactly(2),
+            BuiltinFunction::Round
+            | BuiltinFunction::Floor
+            | BuiltinFunction::Ceil
+            | BuiltinFunction::Base64Encode
+            | BuiltinFunction::Base64Decode
+            | BuiltinFunction::ParseJson
+            | BuiltinFunction::Size
+            | BuiltinFunction::IsTruthy => BuiltinArity::Exactly(1),
+        }
+    }
+
+    // Tag the arguments with the types the builtin expects, and the call with its return type.
+    // The return type of `reject` is left to the context (Ex: the other branch of an if-else),
+    // as it never produces a value
+    pub(crate) fn infer_types(
+        &self,
+        args: &mut [Expr],
+        inferred_type: &mut InferredType,
+    ) -> Result<(), String> {
+        if !self.arity().accepts(args.len()) {
+            return Err(format!(
+                "Incorrect number of arguments for builtin function `{}`. Expected {}, but provided {}",
+                self,
+                self.arity(),
+                args.len()
+            ));
+        }
+
+        match self {
+            BuiltinFunction::IsPresent => {
+                // Absent request headers (or fields) are optional strings, unless stated otherwise
+                if args[0].inferred_type().is_unknown() {
+                    args[0].add_infer_type_mut(InferredType::Option(Box::new(InferredType::Str)));
+                }
+                *inferred_type = InferredType::Bool;
+            }
+            BuiltinFunction::Reject => {
+                args[0].add_infer_type_mut(InferredType::U16);
+                args[1].add_infer_type_mut(InferredType::Str);
+            }
+            BuiltinFunction::Coalesce => {
+                for arg in args.iter_mut() {
+                    if arg.inferred_type().is_unknown() {
+                        arg.add_infer_type_mut(InferredType::Option(Box::new(InferredType::Str)));
+                    }
+                }
+            }
+            BuiltinFunction::HasField => {
+                // The record is not given a type, as only its actual value is inspected
+                args[1].add_infer_type_mut(InferredType::Str);
+                *inferred_type = InferredType::Bool;
+            }
+            BuiltinFunction::Round | BuiltinFunction::Floor | BuiltinFunction::Ceil => {
+                // Number literals and request values are read as floating point numbers.
+                // Local variables keep the type they are declared with
+                let is_local_variable = matches!(&args[0], Expr::Identifier(variable_id, _) if !variable_id.is_global());
+
+                if matches!(&args[0], Expr::Number(..))
+                    || (args[0].inferred_type().is_unknown() && !is_local_variable)
+                {
+                    args[0].add_infer_type_mut(InferredType::F64);
+                }
+                *inferred_type = InferredType::S64;
+            }
+            BuiltinFunction::Split => {
+                args[0].add_infer_type_mut(InferredType::Str);
+                args[1].add_infer_type_mut(InferredType::Str);
+                *inferred_type = InferredType::List(Box::new(InferredType::Str));
+            }
+            BuiltinFunction::Base64Encode | BuiltinFunction::Base64Decode => {
+                args[0].add_infer_type_mut(InferredType::Str);
+                *inferred_type = InferredType::Str;
+            }
+            BuiltinFunction::ParseJson => {
+                // The type of the parsed value is left to the context (Ex: the selected fields)
+                args[0].add_infer_type_mut(InferredType::Str);
+            }
+            BuiltinFunction::Size => {
+                if args[0].inferred_type().is_unknown() {
+                    args[0].add_infer_type_mut(InferredType::List(Box::new(InferredType::Str)));
+                }
+                *inferred_type = InferredType::U64;
+            }
+            BuiltinFunction::Format => {
+                if let Expr::Literal(template, _) = &args[0] {
+                    let placeholders = format_placeholders(template)?;
+                    if placeholders != args.len() - 1 {
+                        return Err(format!(
+                            "The template of `format` has {} placeholders, but {} arguments are provided",
+                            placeholders,
+                            args.len() - 1
+                        ));
+                    }
+                }
+
+                args[0].add_infer_type_mut(InferredType::Str);
+                // Request values are read as strings, unless stated otherwise
+                for arg in args[1..].iter_mut() {
+                    if arg.inferred_type().is_unknown() {
+                        arg.add_infer_type_mut(InferredType::Str);
+                    }
+                }
+                *inferred_type = InferredType::Str;
+            }
+            BuiltinFunction::Clamp => {
+                // Like the argument of `round`, unless the number is a local variable,
+                // whose type the bounds (and the result) then have
+                let is_local_variable = matches!(&args[0], Expr::Identifier(variable_id, _) if !variable_id.is_global());
+
+                if matches!(&args[0], Expr::Number(..))
+                    || (args[0].inferred_type().is_unknown() && !is_local_variable)
+                {
+                    args[0].add_infer_type_mut(InferredType::F64);
+                }
+
+                let number_type = args[0].inferred_type();
+                if !number_type.is_unknown() {
+                    for bound in args[1..].iter_mut() {
+                        if matches!(bound, Expr::Number(..)) || bound.inferred_type().is_unknown() {
+                            bound.add_infer_type_mut(number_type.clone());
+                        }
+                    }
+                    *inferred_type = number_type;
+                }
+            }
+            BuiltinFunction::CoalesceTo => {
+                // Absent request values are optional strings, unless stated otherwise
+                if args[0].inferred_type().is_unknown() {
+                    args[0].add_infer_type_mut(InferredType::Option(Box::new(InferredType::Str)));
+                }
+                if matches!(&args[1], Expr::Number(..)) || args[1].inferred_type().is_unknown() {
+                    args[1].add_infer_type_mut(InferredType::F64);
+                }
+                *inferred_type = InferredType::F64;
+            }
+            BuiltinFunction::Range => {
+                let is_local_variable = |bound: &Expr| matches!(bound, Expr::Identifier(variable_id, _) if !variable_id.is_global());
+
+                let bound_type = args[..2]
+                    .iter()
+                    .filter(|bound| is_local_variable(bound))
+                    .map(|bound| bound.inferred_type())
+                    .find(|inferred_type| !inferred_type.is_unknown())
+                    .unwrap_or(InferredType::F64);
+
+                for bound in args[..2].iter_mut() {
+                    if matches!(bound, Expr::Number(..))
+                        || (bound.inferred_type().is_unknown() && !is_local_variable(bound))
+                    {
+                        bound.add_infer_type_mut(bound_type.clone());
+                    }
+                }
+                args[2].add_infer_type_mut(InferredType::Bool);
+                *inferred_type = range_type(bound_type);
+            }
+            BuiltinFunction::InRange => {
+                if let InferredType::List(element_type) = args[1].inferred_type() {
+                    if args[0].inferred_type().is_unknown() && !element_type.is_unknown() {
+                        args[0].add_infer_type_mut(*element_type);
+                    }
+                    *inferred_type = InferredType::Bool;
+                    return Ok(());
+                }
+
+                // Like the argument of `round`
+                let is_local_variable = matches!(&args[0], Expr::Identifier(variable_id, _) if !variable_id.is_global());
+
+                if matches!(&args[0], Expr::Number(..))
+                    || (args[0].inferred_type().is_unknown() && !is_local_variable)
+                {
+                    args[0].add_infer_type_mut(InferredType::F64);
+                }
+                *inferred_type = InferredType::Bool;
+            }
+            BuiltinFunction::IsTruthy => {
+                // Request values are optional strings, unless stated otherwise.
+                // Any other value keeps its type, as every value is either truthy or not
+                if args[0].inferred_type().is_unknown() && is_request_value(&args[0]) {
+                    args[0].add_infer_type_mut(InferredType::Option(Box::new(InferredType::Str)));
+                }
+                *inferred_type = InferredType::Bool;
+            }
+        }
+
+        Ok(())
+    }
+
+    // The return type of the builtin, given the (partially) inferred types of the arguments.
+    // Used when pulling the types up, for builtins whose return type depends on the arguments
+    pub(crate) fn return_type(&self, args: &[Expr]) -> Option<InferredType> {
+        match self {
+            BuiltinFunction::IsPresent => Some(InferredType::Bool),
+            BuiltinFunction::Reject | BuiltinFunction::ParseJson => None,
+            BuiltinFunction::HasField => Some(InferredType::Bool),
+            BuiltinFunction::Size => Some(InferredType::U64),
+            BuiltinFunction::CoalesceTo => Some(InferredType::F64),
+            BuiltinFunction::InRange | BuiltinFunction::IsTruthy => Some(InferredType::Bool),
+            BuiltinFunction::Range => args[..2]
+                .iter()
+                .map(|bound| bound.inferred_type())
+                .find(|inferred_type| !inferred_type.is_unknown())
+                .map(range_type),
+            BuiltinFunction::Clamp => {
+                Some(args[0].inferred_type()).filter(|inferred_type| !inferred_type.is_unknown())
+            }
+            BuiltinFunction::Round | BuiltinFunction::Floor | BuiltinFunction::Ceil => {
+                Some(InferredType::S64)
+            }
+            BuiltinFunction::Split => Some(InferredType::List(Box::new(InferredType::Str))),
+            BuiltinFunction::Base64Encode
+            | BuiltinFunction::Base64Decode
+            | BuiltinFunction::Format => Some(InferredType::Str),
+            BuiltinFunction::Coalesce => args
+                .iter()
+                .map(|arg| match arg.inferred_type() {
+                    InferredType::Option(inner) => *inner,
+                    other => other,
+                })
+                .filter(|inferred_type| !inferred_type.is_unknown())
+                .reduce(|acc, inferred_type| acc.merge(inferred_type)),
+        }
+    }
+}
+
+// Whether the expression selects a value of a global input (Ex: `request.query.debug`)
+fn is_request_value(expr: &Expr) -> bool {
+    match expr {
+        Expr::Identifier(variable_id, _) => variable_id.is_global(),
+        Expr::SelectField(inner, _, _) | Expr::SelectIndex(inner, _, _) => is_request_value(inner),
+        _ => false,
+    }
+}
+
+// The type of the value of a range, whose bounds have the given type
+fn range_type(bound_type: InferredType) -> InferredType {
+    InferredType::Record(vec![
+        ("start".to_string(), bound_type.clone()),
+        ("end".to_string(), bound_type),
+        ("inclusive".to_string(), InferredType::Bool),
+    ])
+}
+
+// The number of `{}` placeholders of a `format` template
+pub(crate) fn format_placeholders(template: &str) -> Result<usize, String> {
+    format_template(template, &[]).map(|(_, placeholders)| placeholders)
+}
+
+// Fills the placeholders of a `format` template with the arguments
+pub(crate) fn format_with(template: &str, args: &[String]) -> Result<String, String> {
+    let (formatted, placeholders) = format_template(template, args)?;
+
+    if placeholders != args.len() {
+        return Err(format!(
+            "The template of `format` has {} placeholders, but {} arguments are provided",
+            placeholders,
+            args.len()
+        ));
+    }
+
+    Ok(formatted)
+}
+
+// The template with its placeholders filled by the arguments (left empty if there are fewer
+// arguments than placeholders), and the number of its placeholders
+fn format_template(template: &str, args: &[String]) -> Result<(String, usize), String> {
+    let mut formatted = String::new();
+    let mut placeholders = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                formatted.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                if let Some(arg) = args.get(placeholders) {
+                    formatted.push_str(arg);
+                }
+                placeholders += 1;
+            }
+            ('{', _) | ('}', _) => {
+                return Err(format!(
+                    "Invalid template of `format`: {}. Placeholders are `{{}}`, and literal braces are `{{{{` and `}}}}`",
+                    template
+                ))
+            }
+            (c, _) => formatted.push(c),
+        }
+    }
+
+    Ok((formatted, placeholders))
+}
+
+impl Display for BuiltinFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{DynamicParsedFunctionName, ParsedFunctionName};
+use crate::{BuiltinFunction, DynamicParsedFunctionName, ParsedFunctionName};
 use bincode::{Decode, Encode};
 use std::convert::TryFrom;
 use std::fmt::Display;
@@ -22,6 +22,7 @@ pub enum CallType {
     Function(DynamicParsedFunctionName),
     VariantConstructor(String),
     EnumConstructor(String),
+    Builtin(BuiltinFunction),
 }
 
 impl Display for CallType {
@@ -30,6 +31,7 @@ impl Display for CallType {
             CallType::Function(parsed_fn_name) => write!(f, "{}", parsed_fn_name),
             CallType::VariantConstructor(name) => write!(f, "{}", name),
             CallType::EnumConstructor(name) => write!(f, "{}", name),
+            CallType::Builtin(builtin) => write!(f, "{}", builtin),
         }
     }
 }
@@ -48,6 +50,11 @@ impl TryFrom<golem_api_grpc::proto::golem::rib::CallType> for CallType {
             golem_api_grpc::proto::golem::rib::call_type::Name::EnumConstructor(name) => {
                 Ok(CallType::EnumConstructor(name))
             }
+            golem_api_grpc::proto::golem::rib::call_type::Name::Builtin(name) => {
+                BuiltinFunction::from_name(&name)
+                    .map(CallType::Builtin)
+                    .ok_or(format!("Unknown builtin function {}", name))
+            }
         }
     }
 }
@@ -70,6 +77,11 @@ impl From<CallType> for golem_api_grpc::proto::golem::rib::CallType {
                     golem_api_grpc::proto::golem::rib::call_type::Name::EnumConstructor(name),
                 ),
             },
+            CallType::Builtin(builtin) => golem_api_grpc::proto::golem::rib::CallType {
+                name: Some(golem_api_grpc::proto::golem::rib::call_type::Name::Builtin(
+                    builtin.name().to_string(),
+                )),
+            },
         }
     }
 }
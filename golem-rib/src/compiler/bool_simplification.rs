@@ -0,0 +1,106 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::VecDeque;
+
+use crate::Expr;
+
+// Removes the double negations (`!!x` is `x`) of the expression. With `de_morgan`, the negations
+// of `&&` and `||` are also pushed inward (`!(a && b)` is `!a || !b`, and `!(a || b)` is
+// `!a && !b`), so the negations only apply to the operands that are not `&&` or `||`.
+// The operands are evaluated in the same order and short-circuit in the same cases,
+// so the rewritten expression evaluates the same way. Unlike `fold_constants`,
+// nothing is evaluated here
+pub fn simplify_bool(expr: Expr, de_morgan: bool) -> Expr {
+    let mut expr = expr;
+    internal::simplify(&mut expr, de_morgan);
+    expr
+}
+
+mod internal {
+    use super::*;
+
+    pub(crate) fn simplify(expr: &mut Expr, de_morgan: bool) {
+        let mut children = VecDeque::new();
+        expr.visit_children_mut_bottom_up(&mut children);
+
+        for child in children {
+            simplify(child, de_morgan);
+        }
+
+        if let Expr::Not(inner, _) = expr {
+            *expr = negate(inner, de_morgan);
+        }
+    }
+
+    // The negation of the (already simplified) expression
+    fn negate(expr: &Expr, de_morgan: bool) -> Expr {
+        match expr {
+            Expr::Not(inner, _) => inner.as_ref().clone(),
+            Expr::And(lhs, rhs, _) if de_morgan => {
+                Expr::or(negate(lhs, de_morgan), negate(rhs, de_morgan))
+            }
+            Expr::Or(lhs, rhs, _) if de_morgan => {
+                Expr::and(negate(lhs, de_morgan), negate(rhs, de_morgan))
+            }
+            _ => Expr::not(expr.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{simplify_bool, Expr};
+
+    fn a() -> Expr {
+        Expr::identifier("a")
+    }
+
+    fn b() -> Expr {
+        Expr::identifier("b")
+    }
+
+    #[test]
+    fn test_double_negation() {
+        let expr = Expr::from_text("!!request.path.flag").unwrap();
+
+        assert_eq!(
+            simplify_bool(expr, false),
+            Expr::from_text("request.path.flag").unwrap()
+        );
+
+        // An odd number of negations keeps one
+        let expr = Expr::not(Expr::not(Expr::not(a())));
+        assert_eq!(simplify_bool(expr, false), Expr::not(a()));
+    }
+
+    #[test]
+    fn test_de_morgan() {
+        let expr = Expr::not(Expr::and(a(), Expr::not(b())));
+
+        assert_eq!(
+            simplify_bool(expr.clone(), true),
+            Expr::or(Expr::not(a()), b())
+        );
+
+        // Only if asked for
+        assert_eq!(simplify_bool(expr.clone(), false), expr);
+
+        let expr = Expr::not(Expr::or(a(), Expr::and(a(), b())));
+
+        assert_eq!(
+            simplify_bool(expr, true),
+            Expr::and(Expr::not(a()), Expr::or(Expr::not(a()), Expr::not(b())))
+        );
+    }
+}
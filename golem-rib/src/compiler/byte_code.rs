@@ -150,6 +150,9 @@ mod internal {
                 stack.push(ExprState::from_expr(lhs.deref()));
                 instructions.push(RibIR::LessThanOrEqualTo);
             }
+            // `&&` and `||` short-circuit: the right operand is evaluated only if the left one
+            // doesn't determine the result (Ex: `false && x` doesn't evaluate `x`),
+            // so it can depend on the left one (Ex: a value that is present only if the left one is true)
             Expr::And(lhs, rhs, _) => {
                 // This optimization isn't optional, it's required for the correct functioning of the interpreter
                 let optimised_expr = Expr::cond(
@@ -240,6 +243,11 @@ mod internal {
                 stack.push(ExprState::from_expr(sequence_expr.deref()));
                 instructions.push(RibIR::SelectIndex(*index));
             }
+            Expr::SelectIndexExpr(sequence_expr, index_expr, _) => {
+                stack.push(ExprState::from_expr(sequence_expr.deref()));
+                stack.push(ExprState::from_expr(index_expr.deref()));
+                instructions.push(RibIR::SelectDynamicIndex);
+            }
             Expr::Option(Some(inner_expr), inferred_type) => {
                 stack.push(ExprState::from_expr(inner_expr.deref()));
                 instructions.push(RibIR::PushSome(convert_to_analysed_type_for(
@@ -414,6 +422,9 @@ mod internal {
                             convert_to_analysed_type_for(expr, inferred_type)?,
                         ));
                     }
+                    CallType::Builtin(builtin) => {
+                        instructions.push(RibIR::CallBuiltin(builtin.clone(), arguments.len()));
+                    }
                 }
             }
 
@@ -0,0 +1,203 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::VecDeque;
+
+use crate::Expr;
+
+// Replaces the sub-expressions that only depend on constants (Ex: `"foo-${"bar"}"`,
+// `1 > 2`, `!true`) with their value, and the conditionals with a constant predicate
+// with the branch that is taken, so that the dead branch is never compiled.
+// Anything referring to a variable, a worker function or the request is kept as it is,
+// and so are the operands of `&&` and `||` unless both of them are constants
+pub fn fold_constants(expr: Expr) -> Expr {
+    let mut expr = expr;
+    internal::fold(&mut expr);
+    expr
+}
+
+mod internal {
+    use super::*;
+    use std::cmp::Ordering;
+
+    pub(crate) fn fold(expr: &mut Expr) {
+        let mut children = VecDeque::new();
+        expr.visit_children_mut_bottom_up(&mut children);
+
+        for child in children {
+            fold(child);
+        }
+
+        if let Some(folded) = fold_expr(expr) {
+            *expr = folded;
+        }
+    }
+
+    // Folds a single expression, given its children are already folded
+    fn fold_expr(expr: &Expr) -> Option<Expr> {
+        match expr {
+            Expr::Concat(exprs, _) => {
+                let mut result = String::new();
+                for expr in exprs {
+                    match expr {
+                        Expr::Literal(value, _) => result.push_str(value),
+                        _ => return None,
+                    }
+                }
+                Some(Expr::literal(result))
+            }
+            Expr::Not(inner, _) => as_bool(inner).map(|value| Expr::boolean(!value)),
+            Expr::And(lhs, rhs, _) => match (as_bool(lhs), as_bool(rhs)) {
+                (Some(lhs), Some(rhs)) => Some(Expr::boolean(lhs && rhs)),
+                _ => None,
+            },
+            Expr::Or(lhs, rhs, _) => match (as_bool(lhs), as_bool(rhs)) {
+                (Some(lhs), Some(rhs)) => Some(Expr::boolean(lhs || rhs)),
+                _ => None,
+            },
+            Expr::EqualTo(lhs, rhs, _) => {
+                compare(lhs, rhs).map(|ordering| Expr::boolean(ordering == Ordering::Equal))
+            }
+            Expr::GreaterThan(lhs, rhs, _) => {
+                compare(lhs, rhs).map(|ordering| Expr::boolean(ordering == Ordering::Greater))
+            }
+            Expr::GreaterThanOrEqualTo(lhs, rhs, _) => {
+                compare(lhs, rhs).map(|ordering| Expr::boolean(ordering != Ordering::Less))
+            }
+            Expr::LessThan(lhs, rhs, _) => {
+                compare(lhs, rhs).map(|ordering| Expr::boolean(ordering == Ordering::Less))
+            }
+            Expr::LessThanOrEqualTo(lhs, rhs, _) => {
+                compare(lhs, rhs).map(|ordering| Expr::boolean(ordering != Ordering::Greater))
+            }
+            Expr::Cond(pred, then, else_, _) => as_bool(pred).map(|value| {
+                if value {
+                    then.as_ref().clone()
+                } else {
+                    else_.as_ref().clone()
+                }
+            }),
+            _ => None,
+        }
+    }
+
+    fn as_bool(expr: &Expr) -> Option<bool> {
+        match expr {
+            Expr::Boolean(value, _) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // Only the constants of the same kind are compared. Numbers with different
+    // type annotations (Ex: `1u8` and `1u64`) are left to the type checker
+    fn compare(lhs: &Expr, rhs: &Expr) -> Option<Ordering> {
+        match (lhs, rhs) {
+            (Expr::Number(lhs, lhs_type, _), Expr::Number(rhs, rhs_type, _))
+                if lhs_type == rhs_type =>
+            {
+                lhs.value.partial_cmp(&rhs.value)
+            }
+            (Expr::Literal(lhs, _), Expr::Literal(rhs, _)) => Some(lhs.cmp(rhs)),
+            (Expr::Boolean(lhs, _), Expr::Boolean(rhs, _)) => Some(lhs.cmp(rhs)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compiler, fold_constants, Expr, Interpreter};
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+
+    #[test]
+    fn test_fold_constant_comparisons() {
+        let cases = [
+            ("1 > 2", false),
+            ("!1 > 2", true),
+            ("3u64 <= 3u64", true),
+            ("2.5 >= 3", false),
+            (r#""a" == "b""#, false),
+            (r#""a" < "b""#, true),
+            ("true && false || true", true),
+        ];
+
+        for (text, expected) in cases {
+            let expr = Expr::from_text(text).unwrap();
+            assert_eq!(fold_constants(expr), Expr::boolean(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_fold_keeps_differently_typed_numbers() {
+        let expr = Expr::from_text("1u8 == 1u64").unwrap();
+
+        assert_eq!(fold_constants(expr.clone()), expr);
+    }
+
+    #[test]
+    fn test_fold_constant_interpolation() {
+        let expr = Expr::from_text(r#""foo-${"bar"}-${"baz"}""#).unwrap();
+
+        assert_eq!(fold_constants(expr), Expr::literal("foo-bar-baz"));
+    }
+
+    #[test]
+    fn test_fold_constant_predicate_cond() {
+        let expr = Expr::from_text(r#"if 1 > 2 then request.path.foo else "default""#).unwrap();
+
+        assert_eq!(fold_constants(expr), Expr::literal("default"));
+    }
+
+    #[test]
+    fn test_fold_keeps_non_constants() {
+        let expr = Expr::from_text(
+            r#"if request.path.id > 2 then "${request.path.name}-${"x"}" else "default""#,
+        )
+        .unwrap();
+
+        assert_eq!(fold_constants(expr.clone()), expr);
+    }
+
+    #[test]
+    fn test_fold_nested_constants() {
+        let expr = Expr::from_text(
+            r#"if request.path.id > 2 then if true then "yes" else "no" else "${"de"}${"fault"}""#,
+        )
+        .unwrap();
+
+        let expected =
+            Expr::from_text(r#"if request.path.id > 2 then "yes" else "default""#).unwrap();
+
+        assert_eq!(fold_constants(expr), expected);
+    }
+
+    #[tokio::test]
+    async fn test_folded_expression_evaluates_identically() {
+        let expr = Expr::from_text(
+            r#"
+              let x: u64 = 2;
+              if 1u64 < 2u64 then "${"a"}-${x}" else "unreachable"
+            "#,
+        )
+        .unwrap();
+
+        let compiled = compiler::compile(&expr, &vec![]).unwrap();
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("a-2".to_string())
+        );
+    }
+}
@@ -140,6 +140,23 @@ mod internal {
                 inferred_type_of_pred,
             ),
 
+            ArmPattern::Range(start, end) => {
+                let in_range = Expr::and(
+                    Expr::greater_than_or_equal_to(pred_expr.clone(), start.as_ref().clone()),
+                    Expr::less_than(pred_expr.clone(), end.as_ref().clone()),
+                );
+
+                let branch = IfThenBranch {
+                    condition: if let Some(t) = tag {
+                        Expr::and(t, in_range)
+                    } else {
+                        in_range
+                    },
+                    body: resolution.as_ref().clone(),
+                };
+                Some(branch)
+            }
+
             ArmPattern::WildCard => {
                 let branch = IfThenBranch {
                     condition: tag.unwrap_or(Expr::boolean(true)),
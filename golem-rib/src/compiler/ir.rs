@@ -12,13 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{AnalysedTypeWithUnit, ParsedFunctionSite, VariableId};
+use crate::{AnalysedTypeWithUnit, BuiltinFunction, ParsedFunctionSite, VariableId};
 use bincode::{Decode, Encode};
 use golem_api_grpc::proto::golem::rib::rib_ir::Instruction;
 use golem_api_grpc::proto::golem::rib::{
-    And, CallInstruction, ConcatInstruction, CreateFunctionNameInstruction, EqualTo, GetTag,
-    GreaterThan, GreaterThanOrEqualTo, JumpInstruction, LessThan, LessThanOrEqualTo, Negate, Or,
-    PushListInstruction, PushNoneInstruction, PushTupleInstruction, RibIr as ProtoRibIR,
+    And, CallBuiltinInstruction, CallInstruction, ConcatInstruction, CreateFunctionNameInstruction,
+    EqualTo, GetTag, GreaterThan, GreaterThanOrEqualTo, JumpInstruction, LessThan,
+    LessThanOrEqualTo, Negate, Or, PushListInstruction, PushNoneInstruction, PushTupleInstruction,
+    RibIr as ProtoRibIR, SelectDynamicIndex,
 };
 use golem_wasm_ast::analysis::{AnalysedType, TypeStr};
 use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
@@ -41,6 +42,7 @@ pub enum RibIR {
     PushFlag(TypeAnnotatedValue), // More or less like a literal, compiler can form the value directly
     SelectField(String),
     SelectIndex(usize),
+    SelectDynamicIndex, // The index is the top of the stack, followed by the sequence
     EqualTo,
     GreaterThan,
     And,
@@ -54,6 +56,7 @@ pub enum RibIR {
     Deconstruct,
     CreateFunctionName(ParsedFunctionSite, FunctionReferenceType),
     InvokeFunction(usize, AnalysedTypeWithUnit),
+    CallBuiltin(BuiltinFunction, usize),
     PushVariant(String, AnalysedType), // There is no arg size since the type of each variant case is only 1 from beginning
     PushEnum(String, AnalysedType),
     Throw(String),
@@ -313,6 +316,7 @@ impl TryFrom<ProtoRibIR> for RibIR {
             }
             Instruction::SelectField(value) => Ok(RibIR::SelectField(value)),
             Instruction::SelectIndex(value) => Ok(RibIR::SelectIndex(value as usize)),
+            Instruction::SelectDynamicIndex(_) => Ok(RibIR::SelectDynamicIndex),
             Instruction::EqualTo(_) => Ok(RibIR::EqualTo),
             Instruction::GreaterThan(_) => Ok(RibIR::GreaterThan),
             Instruction::LessThan(_) => Ok(RibIR::LessThan),
@@ -399,6 +403,15 @@ impl TryFrom<ProtoRibIR> for RibIR {
             Instruction::Concat(concat_instruction) => {
                 Ok(RibIR::Concat(concat_instruction.arg_size as usize))
             }
+            Instruction::CallBuiltin(call_builtin) => {
+                let builtin = BuiltinFunction::from_name(&call_builtin.name)
+                    .ok_or(format!("Unknown builtin function {}", call_builtin.name))?;
+
+                Ok(RibIR::CallBuiltin(
+                    builtin,
+                    call_builtin.argument_count as usize,
+                ))
+            }
             Instruction::CreateFunctionName(instruction) => {
                 let parsed_site = instruction.site.ok_or("Missing site".to_string())?;
                 let parsed_function_site = ParsedFunctionSite::try_from(parsed_site)?;
@@ -446,6 +459,7 @@ impl From<RibIR> for ProtoRibIR {
             RibIR::PushErrResult(value) => Instruction::CreateErrResult((&value).into()),
             RibIR::SelectField(value) => Instruction::SelectField(value),
             RibIR::SelectIndex(value) => Instruction::SelectIndex(value as u64),
+            RibIR::SelectDynamicIndex => Instruction::SelectDynamicIndex(SelectDynamicIndex {}),
             RibIR::EqualTo => Instruction::EqualTo(EqualTo {}),
             RibIR::GreaterThan => Instruction::GreaterThan(GreaterThan {}),
             RibIR::LessThan => Instruction::LessThan(LessThan {}),
@@ -477,6 +491,12 @@ impl From<RibIR> for ProtoRibIR {
                     return_type: typ,
                 })
             }
+            RibIR::CallBuiltin(builtin, arg_count) => {
+                Instruction::CallBuiltin(CallBuiltinInstruction {
+                    name: builtin.name().to_string(),
+                    argument_count: arg_count as u64,
+                })
+            }
             RibIR::PushVariant(name, return_type) => {
                 let typ = golem_wasm_ast::analysis::protobuf::Type::from(&return_type);
 
@@ -0,0 +1,97 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::VecDeque;
+
+use crate::{BuiltinFunction, CallType, Expr, InferredType};
+
+// Wraps the conditions (the predicate of an if-else, and the operands of `&&`, `||` and `!`)
+// that are not booleans already in `isTruthy`, so they can be of any type when compiled in
+// lenient predicate mode (Ex: `if request.query.debug then ..`). Comparisons, boolean literals,
+// `&&`, `||`, `!` and the builtins that return a boolean (Ex: `isPresent`) are left as they are.
+// Applied before the type inference, which would otherwise require the conditions to be booleans
+pub fn lenient_predicates(expr: Expr) -> Expr {
+    let mut expr = expr;
+    internal::wrap_conditions(&mut expr);
+    expr
+}
+
+mod internal {
+    use super::*;
+
+    pub(crate) fn wrap_conditions(expr: &mut Expr) {
+        let mut children = VecDeque::new();
+        expr.visit_children_mut_bottom_up(&mut children);
+
+        for child in children {
+            wrap_conditions(child);
+        }
+
+        match expr {
+            Expr::Cond(cond, _, _, _) | Expr::Not(cond, _) => wrap(cond),
+            Expr::And(lhs, rhs, _) | Expr::Or(lhs, rhs, _) => {
+                wrap(lhs);
+                wrap(rhs);
+            }
+            _ => {}
+        }
+    }
+
+    fn wrap(condition: &mut Expr) {
+        if !is_boolean(condition) {
+            let inner = std::mem::replace(condition, Expr::boolean(false));
+            *condition = Expr::call_builtin(BuiltinFunction::IsTruthy, vec![inner]);
+        }
+    }
+
+    fn is_boolean(expr: &Expr) -> bool {
+        match expr {
+            Expr::Call(CallType::Builtin(builtin), args, _) => {
+                builtin.return_type(args) == Some(InferredType::Bool)
+            }
+            other => other.inferred_type() == InferredType::Bool,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lenient_predicates, BuiltinFunction, Expr};
+
+    #[test]
+    fn test_conditions_are_wrapped_in_is_truthy() {
+        let expr = Expr::from_text(r#"if request.query.debug then "on" else "off""#).unwrap();
+
+        let expected = Expr::cond(
+            Expr::call_builtin(
+                BuiltinFunction::IsTruthy,
+                vec![Expr::from_text("request.query.debug").unwrap()],
+            ),
+            Expr::literal("on"),
+            Expr::literal("off"),
+        );
+
+        assert_eq!(lenient_predicates(expr), expected);
+    }
+
+    #[test]
+    fn test_boolean_conditions_are_not_wrapped() {
+        for input in [
+            r#"if request.query.page == "1" then "first" else "next""#,
+            r#"if true && !false then "yes" else "no""#,
+        ] {
+            let expr = Expr::from_text(input).unwrap();
+            assert_eq!(lenient_predicates(expr.clone()), expr, "{}", input);
+        }
+    }
+}
@@ -12,18 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use bool_simplification::*;
 pub use byte_code::*;
+pub use constant_folding::*;
 use golem_wasm_ast::analysis::AnalysedExport;
 pub use ir::*;
+pub use lenient_predicates::*;
 pub use type_with_unit::*;
 
 use crate::type_registry::FunctionTypeRegistry;
-use crate::{Expr, RibInputTypeInfo};
+use crate::{Expr, PredicateMode, RibInputTypeInfo};
 use golem_api_grpc::proto::golem::rib::CompilerOutput as ProtoCompilerOutput;
 
+mod bool_simplification;
 mod byte_code;
+mod constant_folding;
 mod desugar;
 mod ir;
+mod lenient_predicates;
 mod type_with_unit;
 
 pub fn compile(
@@ -40,13 +46,36 @@ pub fn compile_with_limited_globals(
     expr: &Expr,
     export_metadata: &Vec<AnalysedExport>,
     allowed_global_variables: Option<Vec<String>>,
+) -> Result<CompilerOutput, String> {
+    compile_with_predicate_mode(
+        expr,
+        export_metadata,
+        allowed_global_variables,
+        PredicateMode::Strict,
+    )
+}
+
+// In strict mode, the conditions must be booleans, which is type checked.
+// In lenient mode, they can be of any type, and are evaluated by their truthiness
+// (see `lenient_predicates`), so the byte code evaluates the same way in either mode of the `Interpreter`
+pub fn compile_with_predicate_mode(
+    expr: &Expr,
+    export_metadata: &Vec<AnalysedExport>,
+    allowed_global_variables: Option<Vec<String>>,
+    predicate_mode: PredicateMode,
 ) -> Result<CompilerOutput, String> {
     let type_registry = FunctionTypeRegistry::from_export_metadata(export_metadata);
-    let mut expr_cloned = expr.clone();
+    let mut expr_cloned = match predicate_mode {
+        PredicateMode::Strict => expr.clone(),
+        PredicateMode::Lenient => lenient_predicates(expr.clone()),
+    };
     expr_cloned
         .infer_types(&type_registry)
         .map_err(|e| e.join("\n"))?;
 
+    // Folded only after the type check, so the branches that are never taken are checked too
+    let mut expr_cloned = fold_constants(expr_cloned);
+
     let global_input_type_info =
         RibInputTypeInfo::from_expr(&mut expr_cloned).map_err(|e| format!("Error: {}", e))?;
 
@@ -110,3 +139,117 @@ impl From<CompilerOutput> for ProtoCompilerOutput {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{compiler, Expr, Interpreter, PredicateMode, RibByteCode};
+    use golem_api_grpc::proto::golem::rib::RibByteCode as ProtoRibByteCode;
+    use golem_wasm_ast::analysis::analysed_type::{bool, field, option, record, str};
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_restored_byte_code_evaluates_identically() {
+        let programs = [
+            r#"let x: u64 = 2 in if x > 1u64 then x else 0u64"#,
+            r#"
+              let x: option<option<u64>> = none;
+              match x {
+                some(some(x)) => x,
+                none => 0u64
+              }
+            "#,
+            r#"
+              let x: tuple<u64, option<str>, str> = (1, some("foo"), "bar");
+              match x {
+                (x, none, z) => "${x} ${z}",
+                (x, some(y), z) => "${x} ${y} ${z}"
+              }
+            "#,
+        ];
+
+        for program in programs {
+            let expr = Expr::from_text(program).unwrap();
+            let compiled = compiler::compile(&expr, &vec![]).unwrap();
+
+            let proto_byte_code = ProtoRibByteCode::from(compiled.byte_code.clone());
+            let restored = RibByteCode::try_from(proto_byte_code).unwrap();
+
+            let expected = Interpreter::default()
+                .run(compiled.byte_code.clone())
+                .await
+                .unwrap();
+
+            // The same compiled byte code is reused across evaluations
+            for _ in 0..3 {
+                let result = Interpreter::default().run(restored.clone()).await.unwrap();
+                assert_eq!(result, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_branches_never_taken_are_type_checked() {
+        let programs = [
+            r#"if true then "a" else 1"#,
+            r#"if false then foo(1) else "b""#,
+        ];
+
+        for program in programs {
+            let expr = Expr::from_text(program).unwrap();
+            assert!(compiler::compile(&expr, &vec![]).is_err(), "{}", program);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conditions_of_any_type_in_lenient_mode() {
+        let expr = Expr::from_text(r#"if request.query.debug then "on" else "off""#).unwrap();
+
+        let request_type = |debug_type| {
+            record(vec![field(
+                "query",
+                record(vec![field("debug", debug_type)]),
+            )])
+        };
+
+        // The query parameter must be a boolean in strict mode
+        let compiled = compiler::compile(&expr, &vec![]).unwrap();
+        assert_eq!(
+            compiled.global_input_type_info.types.get("request"),
+            Some(&request_type(bool()))
+        );
+
+        // and is an optional string in lenient mode, which is true if it's present
+        let compiled =
+            compiler::compile_with_predicate_mode(&expr, &vec![], None, PredicateMode::Lenient)
+                .unwrap();
+        let request_type = request_type(option(str()));
+        assert_eq!(
+            compiled.global_input_type_info.types.get("request"),
+            Some(&request_type)
+        );
+
+        for (debug, expected) in [
+            (r#"some("true")"#, "on"),
+            (r#"some("1")"#, "on"),
+            ("none", "off"),
+        ] {
+            let request = golem_wasm_rpc::type_annotated_value_from_str(
+                &request_type,
+                &format!("{{query: {{debug: {}}}}}", debug),
+            )
+            .unwrap();
+
+            let mut interpreter =
+                Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+            let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string()),
+                "debug: {}",
+                debug
+            );
+        }
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::{HashMap, VecDeque};
+
+use golem_wasm_ast::analysis::AnalysedExport;
+use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+
+use crate::{compile, interpret, interpret_pure, Expr, RibFunctionInvoke, RibInterpreterResult};
+
+// The phases in which the expressions of a route are evaluated. The same expression can be
+// evaluated in both of them, but before the worker is invoked (Ex: to compute the worker name)
+// it can neither call the worker functions nor refer to the worker response (`worker.response`)
+// or to the named responses of the workers of an aggregation route (`worker.responses.<name>`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvalPhase {
+    PreInvocation,
+    PostInvocation,
+}
+
+impl EvalPhase {
+    // Fails on the first part of the expression which is not available in the phase
+    pub fn check(&self, expr: &Expr) -> Result<(), String> {
+        if *self == EvalPhase::PostInvocation {
+            return Ok(());
+        }
+
+        let mut queue = VecDeque::new();
+        queue.push_back(expr);
+
+        while let Some(expr) = queue.pop_back() {
+            if let Some(usage) = internal::post_invocation_usage(expr) {
+                return Err(format!(
+                    "{} is not available before the worker is invoked",
+                    usage
+                ));
+            }
+
+            expr.visit_children_bottom_up(&mut queue);
+        }
+
+        Ok(())
+    }
+}
+
+// Compiles and evaluates the expression in the phase. The functions are invoked
+// (with `function_invoke`) only after the worker invocation
+pub async fn interpret_in_phase(
+    expr: &Expr,
+    phase: EvalPhase,
+    export_metadata: &Vec<AnalysedExport>,
+    rib_input: HashMap<String, TypeAnnotatedValue>,
+    function_invoke: RibFunctionInvoke,
+) -> Result<RibInterpreterResult, String> {
+    phase.check(expr)?;
+
+    let compiled = compile(expr, export_metadata)?;
+
+    match phase {
+        EvalPhase::PreInvocation => interpret_pure(&compiled.byte_code, &rib_input).await,
+        EvalPhase::PostInvocation => {
+            interpret(&compiled.byte_code, rib_input, function_invoke).await
+        }
+    }
+}
+
+mod internal {
+    use crate::call_type::CallType;
+    use crate::{BuiltinFunction, DynamicParsedFunctionReference, Expr, ParsedFunctionSite};
+
+    pub(crate) fn post_invocation_usage(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::SelectField(selected, field, _) => match selected.as_ref() {
+                selected
+                    if is_worker(selected) && (field == "response" || field == "responses") =>
+                {
+                    Some(format!("`worker.{}`", field))
+                }
+                Expr::SelectField(inner, responses, _)
+                    if responses == "responses" && is_worker(inner) =>
+                {
+                    Some(format!("`worker.responses.{}`", field))
+                }
+                _ => None,
+            },
+            Expr::Call(CallType::Function(function_name), _, _) => {
+                match (&function_name.site, &function_name.function) {
+                    (
+                        ParsedFunctionSite::Global,
+                        DynamicParsedFunctionReference::Function { function },
+                    ) if BuiltinFunction::from_name(function).is_some() => None,
+                    _ => Some(format!("The call of `{}`", function_name)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn is_worker(expr: &Expr) -> bool {
+        matches!(expr, Expr::Identifier(variable_id, _) if variable_id.is_global() && variable_id.name() == "worker")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use golem_wasm_ast::analysis::analysed_type::{field, list, record, str, u32};
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+
+    use crate::{interpret_in_phase, EvalPhase, Expr, RibFunctionInvoke};
+
+    fn no_function_invoke() -> RibFunctionInvoke {
+        Arc::new(|function_name, _| {
+            Box::pin(async move { Err(format!("Unexpected call of {}", function_name)) })
+        })
+    }
+
+    fn input(name: &str, value_type: &str, value: &str) -> HashMap<String, TypeAnnotatedValue> {
+        let value_type = record(vec![field(value_type, str())]);
+        let value = golem_wasm_rpc::type_annotated_value_from_str(&value_type, value).unwrap();
+        HashMap::from([(name.to_string(), value)])
+    }
+
+    #[tokio::test]
+    async fn expr_without_response_is_evaluated_in_both_phases() {
+        let expr = Expr::from_text(r#"let user: str = request.user; "user-${user}""#).unwrap();
+
+        for phase in [EvalPhase::PreInvocation, EvalPhase::PostInvocation] {
+            let result = interpret_in_phase(
+                &expr,
+                phase,
+                &vec![],
+                input("request", "user", r#"{user: "foo"}"#),
+                no_function_invoke(),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str("user-foo".to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn worker_response_is_evaluated_post_invocation() {
+        let expr = Expr::from_text(r#"let response: str = worker.response; response"#).unwrap();
+
+        let result = interpret_in_phase(
+            &expr,
+            EvalPhase::PostInvocation,
+            &vec![],
+            input("worker", "response", r#"{response: "done"}"#),
+            no_function_invoke(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("done".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn worker_response_is_rejected_pre_invocation() {
+        let expr = Expr::from_text(r#"let response: str = worker.response; response"#).unwrap();
+
+        let result = interpret_in_phase(
+            &expr,
+            EvalPhase::PreInvocation,
+            &vec![],
+            input("worker", "response", r#"{response: "done"}"#),
+            no_function_invoke(),
+        )
+        .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "`worker.response` is not available before the worker is invoked"
+        );
+    }
+
+    #[tokio::test]
+    async fn named_worker_responses_are_merged_post_invocation() {
+        let expr = Expr::from_text(
+            r#"
+              let profile: str = worker.responses.profile;
+              let orders: list<u32> = worker.responses.orders;
+              {profile: profile, orders: orders}
+            "#,
+        )
+        .unwrap();
+
+        let worker_type = record(vec![field(
+            "responses",
+            record(vec![field("profile", str()), field("orders", list(u32()))]),
+        )]);
+
+        let worker = golem_wasm_rpc::type_annotated_value_from_str(
+            &worker_type,
+            r#"{responses: {profile: "foo", orders: [1, 2]}}"#,
+        )
+        .unwrap();
+
+        let result = interpret_in_phase(
+            &expr,
+            EvalPhase::PostInvocation,
+            &vec![],
+            HashMap::from([("worker".to_string(), worker)]),
+            no_function_invoke(),
+        )
+        .await
+        .unwrap();
+
+        let expected = golem_wasm_rpc::type_annotated_value_from_str(
+            &record(vec![field("profile", str()), field("orders", list(u32()))]),
+            r#"{profile: "foo", orders: [1, 2]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(result.get_val().unwrap(), expected);
+    }
+
+    #[test]
+    fn named_worker_response_is_rejected_pre_invocation() {
+        let expr =
+            Expr::from_text(r#"let profile: str = worker.responses.profile; profile"#).unwrap();
+
+        assert_eq!(
+            EvalPhase::PreInvocation.check(&expr),
+            Err(
+                "`worker.responses.profile` is not available before the worker is invoked"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn function_call_is_rejected_pre_invocation() {
+        let expr = Expr::from_text(r#"let result = foo("bar"); isPresent(result)"#).unwrap();
+
+        assert_eq!(
+            EvalPhase::PreInvocation.check(&expr),
+            Err("The call of `foo` is not available before the worker is invoked".to_string())
+        );
+        assert_eq!(EvalPhase::PostInvocation.check(&expr), Ok(()));
+    }
+}
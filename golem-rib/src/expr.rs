@@ -13,12 +13,14 @@
 // limitations under the License.
 
 use crate::call_type::CallType;
+use crate::parser::config::ParserConfig;
 use crate::parser::rib_expr::rib_program;
+use crate::parser::source_map::SourceMap;
 use crate::parser::type_name::TypeName;
 use crate::type_registry::FunctionTypeRegistry;
 use crate::{
-    from_string, text, type_inference, DynamicParsedFunctionName, InferredType, ParsedFunctionName,
-    VariableId,
+    from_string, text, type_inference, BuiltinFunction, DynamicParsedFunctionName, InferredType,
+    ParsedFunctionName, VariableId,
 };
 use bincode::{Decode, Encode};
 use combine::stream::position;
@@ -37,6 +39,7 @@ pub enum Expr {
     Let(VariableId, Option<TypeName>, Box<Expr>, InferredType),
     SelectField(Box<Expr>, String, InferredType),
     SelectIndex(Box<Expr>, usize, InferredType),
+    SelectIndexExpr(Box<Expr>, Box<Expr>, InferredType),
     Sequence(Vec<Expr>, InferredType),
     Record(Vec<(String, Box<Expr>)>, InferredType),
     Tuple(Vec<Expr>, InferredType),
@@ -89,6 +92,17 @@ impl Expr {
             .map_err(|err| format!("{}", err))
     }
 
+    // Parse a text as Rib expression, with a non-default syntax (Ex: keyword aliases, features)
+    pub fn from_text_with_config(input: &str, config: &ParserConfig) -> Result<Expr, String> {
+        crate::parser::config::parse_with_config(input, config)
+    }
+
+    // Parse a text as Rib expression, along with the spans of its nodes (see `SourceMap`).
+    // The expression is the same as the one `from_text` returns
+    pub fn from_text_with_source_map(input: &str) -> Result<(Expr, SourceMap), String> {
+        crate::parser::source_map::parse_with_source_map(input)
+    }
+
     pub fn is_literal(&self) -> bool {
         matches!(self, Expr::Literal(_, _))
     }
@@ -142,7 +156,10 @@ impl Expr {
     }
 
     pub fn is_select_index(&self) -> bool {
-        matches!(self, Expr::SelectIndex(_, _, _))
+        matches!(
+            self,
+            Expr::SelectIndex(_, _, _) | Expr::SelectIndexExpr(_, _, _)
+        )
     }
 
     pub fn is_boolean(&self) -> bool {
@@ -208,6 +225,12 @@ impl Expr {
         )
     }
 
+    // A call that is always resolved to the builtin, even if the component exports a function
+    // with the same name. Used for the operators that are desugared to builtins (Ex: `??`)
+    pub fn call_builtin(builtin: BuiltinFunction, args: Vec<Expr>) -> Self {
+        Expr::Call(CallType::Builtin(builtin), args, InferredType::Unknown)
+    }
+
     pub fn concat(expressions: Vec<Expr>) -> Self {
         Expr::Concat(expressions, InferredType::Str)
     }
@@ -282,6 +305,13 @@ impl Expr {
         )
     }
 
+    /// `let name = value in body`. There is no separate node for it: it is encoded as the
+    /// block `{ let name = value; body }`, so the binding is scoped to the body, and is
+    /// type checked, compiled and printed like any other block.
+    pub fn let_in(name: impl AsRef<str>, value: Expr, body: Expr) -> Self {
+        Expr::multiple(vec![Expr::let_binding(name, value), body])
+    }
+
     pub fn literal(value: impl AsRef<str>) -> Self {
         Expr::Literal(value.as_ref().to_string(), InferredType::Str)
     }
@@ -303,6 +333,11 @@ impl Expr {
         Expr::Not(Box::new(expr), InferredType::Bool)
     }
 
+    // `a != b` (or `a <> b`) is sugar for `!(a == b)`
+    pub fn not_equal_to(left: Expr, right: Expr) -> Self {
+        Expr::not(Expr::equal_to(left, right))
+    }
+
     pub fn ok(expr: Expr) -> Self {
         let inferred_type = expr.inferred_type();
         Expr::Result(
@@ -363,6 +398,11 @@ impl Expr {
         Expr::SelectIndex(Box::new(expr), index, InferredType::Unknown)
     }
 
+    // Selects the element at the index that `index` evaluates to at runtime
+    pub fn select_index_expr(expr: Expr, index: Expr) -> Self {
+        Expr::SelectIndexExpr(Box::new(expr), Box::new(index), InferredType::Unknown)
+    }
+
     pub fn get_tag(expr: Expr) -> Self {
         Expr::GetTag(Box::new(expr), InferredType::Unknown)
     }
@@ -393,6 +433,7 @@ impl Expr {
             Expr::Let(_, _, _, inferred_type)
             | Expr::SelectField(_, _, inferred_type)
             | Expr::SelectIndex(_, _, inferred_type)
+            | Expr::SelectIndexExpr(_, _, inferred_type)
             | Expr::Sequence(_, inferred_type)
             | Expr::Record(_, inferred_type)
             | Expr::Tuple(_, inferred_type)
@@ -444,6 +485,7 @@ impl Expr {
         self.name_binding_local_variables();
         self.infer_variants(function_type_registry);
         self.infer_enums(function_type_registry);
+        self.infer_builtins(function_type_registry);
 
         Ok(())
     }
@@ -523,6 +565,7 @@ impl Expr {
             | Expr::Let(_, _, _, inferred_type)
             | Expr::SelectField(_, _, inferred_type)
             | Expr::SelectIndex(_, _, inferred_type)
+            | Expr::SelectIndexExpr(_, _, inferred_type)
             | Expr::Sequence(_, inferred_type)
             | Expr::Record(_, inferred_type)
             | Expr::Tuple(_, inferred_type)
@@ -565,6 +608,7 @@ impl Expr {
             | Expr::Let(_, _, _, inferred_type)
             | Expr::SelectField(_, _, inferred_type)
             | Expr::SelectIndex(_, _, inferred_type)
+            | Expr::SelectIndexExpr(_, _, inferred_type)
             | Expr::Sequence(_, inferred_type)
             | Expr::Record(_, inferred_type)
             | Expr::Tuple(_, inferred_type)
@@ -605,6 +649,10 @@ impl Expr {
         type_inference::infer_variants(self, function_type_registry);
     }
 
+    pub fn infer_builtins(&mut self, function_type_registry: &FunctionTypeRegistry) {
+        type_inference::infer_builtins(self, function_type_registry);
+    }
+
     pub fn visit_children_bottom_up<'a>(&'a self, queue: &mut VecDeque<&'a Expr>) {
         type_inference::visit_children_bottom_up(self, queue);
     }
@@ -711,6 +759,7 @@ pub enum ArmPattern {
     RecordConstructor(Vec<(String, ArmPattern)>),
     ListConstructor(Vec<ArmPattern>),
     Literal(Box<Expr>),
+    Range(Box<Expr>, Box<Expr>), // Half-open numeric range, `start..end`
 }
 
 impl ArmPattern {
@@ -722,9 +771,14 @@ impl ArmPattern {
         ArmPattern::Literal(Box::new(expr))
     }
 
+    pub fn range(start: Expr, end: Expr) -> ArmPattern {
+        ArmPattern::Range(Box::new(start), Box::new(end))
+    }
+
     pub fn get_expr_literals_mut(&mut self) -> Vec<&mut Box<Expr>> {
         match self {
             ArmPattern::Literal(expr) => vec![expr],
+            ArmPattern::Range(start, end) => vec![start, end],
             ArmPattern::As(_, pattern) => pattern.get_expr_literals_mut(),
             ArmPattern::Constructor(_, patterns) => {
                 let mut result = vec![];
@@ -761,6 +815,7 @@ impl ArmPattern {
     pub fn get_expr_literals(&self) -> Vec<&Expr> {
         match self {
             ArmPattern::Literal(expr) => vec![expr.as_ref()],
+            ArmPattern::Range(start, end) => vec![start.as_ref(), end.as_ref()],
             ArmPattern::As(_, pattern) => pattern.get_expr_literals(),
             ArmPattern::Constructor(_, patterns) => {
                 let mut result = vec![];
@@ -1031,6 +1086,12 @@ impl TryFrom<golem_api_grpc::proto::golem::rib::Expr> for Expr {
                 let expr = *expr.expr.ok_or("Missing expr")?;
                 Expr::select_index(expr.try_into()?, index)
             }
+            golem_api_grpc::proto::golem::rib::expr::Expr::SelectDynamicIndex(expr) => {
+                let expr = *expr;
+                let index = *expr.index.ok_or("Missing index")?;
+                let expr = *expr.expr.ok_or("Missing expr")?;
+                Expr::select_index_expr(expr.try_into()?, index.try_into()?)
+            }
             golem_api_grpc::proto::golem::rib::expr::Expr::Option(expr) => match expr.expr {
                 Some(expr) => Expr::option(Some((*expr).try_into()?)),
                 None => Expr::option(None),
@@ -1095,6 +1156,13 @@ impl TryFrom<golem_api_grpc::proto::golem::rib::Expr> for Expr {
                             golem_api_grpc::proto::golem::rib::call_type::Name::EnumConstructor(
                                 name,
                             ) => Expr::call(DynamicParsedFunctionName::parse(name)?, params),
+                            golem_api_grpc::proto::golem::rib::call_type::Name::Builtin(
+                                name,
+                            ) => Expr::call_builtin(
+                                BuiltinFunction::from_name(&name)
+                                    .ok_or(format!("Unknown builtin function {}", name))?,
+                                params,
+                            ),
                         }
                     }
                     (_, _) => Err("Missing both call type (and legacy invocation type)")?,
@@ -1133,6 +1201,14 @@ impl From<Expr> for golem_api_grpc::proto::golem::rib::Expr {
                     }),
                 ))
             }
+            Expr::SelectIndexExpr(expr, index, _) => Some(
+                golem_api_grpc::proto::golem::rib::expr::Expr::SelectDynamicIndex(Box::new(
+                    golem_api_grpc::proto::golem::rib::SelectDynamicIndexExpr {
+                        expr: Some(Box::new((*expr).into())),
+                        index: Some(Box::new((*index).into())),
+                    },
+                )),
+            ),
             Expr::Sequence(exprs, _) => {
                 Some(golem_api_grpc::proto::golem::rib::expr::Expr::Sequence(
                     golem_api_grpc::proto::golem::rib::SequenceExpr {
@@ -1360,6 +1436,16 @@ impl TryFrom<golem_api_grpc::proto::golem::rib::ArmPattern> for ArmPattern {
                 let inner = expr.ok_or("Missing expr")?;
                 Ok(ArmPattern::Literal(Box::new(inner.try_into()?)))
             }
+            golem_api_grpc::proto::golem::rib::arm_pattern::Pattern::Range(
+                golem_api_grpc::proto::golem::rib::RangeArmPattern { start, end },
+            ) => {
+                let start = start.ok_or("Missing start")?;
+                let end = end.ok_or("Missing end")?;
+                Ok(ArmPattern::Range(
+                    Box::new(start.try_into()?),
+                    Box::new(end.try_into()?),
+                ))
+            }
             golem_api_grpc::proto::golem::rib::arm_pattern::Pattern::RecordConstructor(
                 golem_api_grpc::proto::golem::rib::RecordConstructorArmPattern { fields },
             ) => {
@@ -1430,6 +1516,17 @@ impl From<ArmPattern> for golem_api_grpc::proto::golem::rib::ArmPattern {
                 ),
             },
 
+            ArmPattern::Range(start, end) => golem_api_grpc::proto::golem::rib::ArmPattern {
+                pattern: Some(
+                    golem_api_grpc::proto::golem::rib::arm_pattern::Pattern::Range(
+                        golem_api_grpc::proto::golem::rib::RangeArmPattern {
+                            start: Some((*start).into()),
+                            end: Some((*end).into()),
+                        },
+                    ),
+                ),
+            },
+
             ArmPattern::TupleConstructor(patterns) => {
                 golem_api_grpc::proto::golem::rib::ArmPattern {
                     pattern: Some(
@@ -1546,7 +1643,8 @@ impl Serialize for Expr {
 mod tests {
     use crate::ParsedFunctionSite::PackagedInterface;
     use crate::{
-        ArmPattern, DynamicParsedFunctionName, DynamicParsedFunctionReference, Expr, MatchArm,
+        ArmPattern, BuiltinFunction, DynamicParsedFunctionName, DynamicParsedFunctionReference,
+        Expr, MatchArm,
     };
 
     #[test]
@@ -1673,4 +1771,35 @@ mod tests {
         let result = Expr::from_text(sample_rib);
         assert_eq!(result, Ok(expected()));
     }
+
+    fn proto_round_trip(expr: Expr) -> Expr {
+        let proto: golem_api_grpc::proto::golem::rib::Expr = expr.into();
+        Expr::try_from(proto).unwrap()
+    }
+
+    #[test]
+    fn test_proto_round_trip_builtins() {
+        for builtin in BuiltinFunction::all() {
+            let expr = Expr::call_builtin(builtin, vec![Expr::identifier("foo")]);
+            assert_eq!(proto_round_trip(expr.clone()), expr);
+        }
+    }
+
+    #[test]
+    fn test_proto_round_trip_operators_desugared_to_builtins() {
+        let inputs = [
+            "request.query.limit ?? 10",
+            "request.body?.user",
+            "request.query.cursor == null",
+            "request.query.cursor != null",
+            r#"request.method in ["GET", "HEAD"]"#,
+            "request.path.page in 1..=10",
+            "1..10",
+        ];
+
+        for input in inputs {
+            let expr = Expr::from_text(input).unwrap();
+            assert_eq!(proto_round_trip(expr.clone()), expr, "{}", input);
+        }
+    }
 }
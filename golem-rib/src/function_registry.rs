@@ -0,0 +1,246 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use golem_wasm_ast::analysis::{
+    AnalysedExport, AnalysedFunction, AnalysedFunctionParameter, AnalysedFunctionResult,
+    AnalysedType,
+};
+use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+use golem_wasm_rpc::protobuf::TypedTuple;
+
+use crate::{compile, BuiltinFunction, CompilerOutput, Expr, RibFunctionInvoke};
+
+pub type CustomFunction =
+    Arc<dyn Fn(&[TypeAnnotatedValue]) -> Result<TypeAnnotatedValue, String> + Send + Sync>;
+
+// Functions registered by the host application (Ex: helpers specific to a deployment),
+// which Rib can call like the functions of the worker. A call is compiled against the
+// declared types, and evaluated by the registered function instead of invoking the worker.
+// Calling a function which is neither registered nor exported by the worker fails the compilation
+#[derive(Clone, Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, RegisteredFunction>,
+}
+
+#[derive(Clone)]
+struct RegisteredFunction {
+    parameter_types: Vec<AnalysedType>,
+    return_type: AnalysedType,
+    function: CustomFunction,
+}
+
+impl FunctionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The arity of the function is the number of the parameter types
+    pub fn register(
+        &mut self,
+        name: impl AsRef<str>,
+        parameter_types: Vec<AnalysedType>,
+        return_type: AnalysedType,
+        function: CustomFunction,
+    ) -> Result<(), String> {
+        let name = name.as_ref();
+
+        if BuiltinFunction::from_name(name).is_some() {
+            return Err(format!("`{}` is a built-in function", name));
+        }
+
+        if self.functions.contains_key(name) {
+            return Err(format!("Function `{}` is already registered", name));
+        }
+
+        self.functions.insert(
+            name.to_string(),
+            RegisteredFunction {
+                parameter_types,
+                return_type,
+                function,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn arity(&self, name: &str) -> Option<usize> {
+        self.functions
+            .get(name)
+            .map(|function| function.parameter_types.len())
+    }
+
+    // The registered functions in the form of exports, to compile Rib against
+    pub fn exports(&self) -> Vec<AnalysedExport> {
+        self.functions
+            .iter()
+            .map(|(name, function)| {
+                AnalysedExport::Function(AnalysedFunction {
+                    name: name.clone(),
+                    parameters: function
+                        .parameter_types
+                        .iter()
+                        .enumerate()
+                        .map(|(index, typ)| AnalysedFunctionParameter {
+                            name: format!("arg{}", index),
+                            typ: typ.clone(),
+                        })
+                        .collect(),
+                    results: vec![AnalysedFunctionResult {
+                        name: None,
+                        typ: function.return_type.clone(),
+                    }],
+                })
+            })
+            .collect()
+    }
+
+    // Compiles the expression against the exports of the worker and the registered functions
+    pub fn compile(
+        &self,
+        expr: &Expr,
+        export_metadata: &[AnalysedExport],
+    ) -> Result<CompilerOutput, String> {
+        let mut exports = export_metadata.to_vec();
+        exports.extend(self.exports());
+        compile(expr, &exports)
+    }
+
+    // Evaluates the calls of the registered functions,
+    // and passes the rest of the calls to `worker_function_invoke`
+    pub fn function_invoke(&self, worker_function_invoke: RibFunctionInvoke) -> RibFunctionInvoke {
+        let functions = Arc::new(self.functions.clone());
+
+        Arc::new(
+            move |function_name, arguments| match functions.get(&function_name) {
+                Some(registered) => {
+                    let result = internal::call(&function_name, registered, &arguments);
+                    Box::pin(async move { result })
+                }
+                None => worker_function_invoke(function_name, arguments),
+            },
+        )
+    }
+}
+
+mod internal {
+    use super::*;
+
+    pub(crate) fn call(
+        function_name: &str,
+        registered: &RegisteredFunction,
+        arguments: &[TypeAnnotatedValue],
+    ) -> Result<TypeAnnotatedValue, String> {
+        if arguments.len() != registered.parameter_types.len() {
+            return Err(format!(
+                "Function `{}` expects {} arguments, but got {}",
+                function_name,
+                registered.parameter_types.len(),
+                arguments.len()
+            ));
+        }
+
+        let result = (registered.function)(arguments)?;
+
+        // The same shape as the results of a worker function
+        Ok(TypeAnnotatedValue::Tuple(TypedTuple {
+            typ: vec![golem_wasm_ast::analysis::protobuf::Type::from(
+                &registered.return_type,
+            )],
+            value: vec![golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+                type_annotated_value: Some(result),
+            }],
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use golem_wasm_ast::analysis::analysed_type::{field, record, u64};
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+
+    use crate::{interpret, Expr, FunctionRegistry, RibFunctionInvoke};
+
+    fn registry() -> FunctionRegistry {
+        let mut registry = FunctionRegistry::new();
+        registry
+            .register(
+                "double",
+                vec![u64()],
+                u64(),
+                Arc::new(|arguments| match arguments {
+                    [TypeAnnotatedValue::U64(value)] => Ok(TypeAnnotatedValue::U64(value * 2)),
+                    _ => Err("double expects a u64".to_string()),
+                }),
+            )
+            .unwrap();
+        registry
+    }
+
+    fn no_worker_function_invoke() -> RibFunctionInvoke {
+        Arc::new(|function_name, _| {
+            Box::pin(async move { Err(format!("Unexpected call of {}", function_name)) })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_registered_function_is_evaluated() {
+        let registry = registry();
+        let expr = Expr::from_text("double(request.count)").unwrap();
+        let compiled = registry.compile(&expr, &[]).unwrap();
+
+        let request_type = record(vec![field("count", u64())]);
+        let request =
+            golem_wasm_rpc::type_annotated_value_from_str(&request_type, "{count: 21}").unwrap();
+
+        let result = interpret(
+            &compiled.byte_code,
+            HashMap::from([("request".to_string(), request)]),
+            registry.function_invoke(no_worker_function_invoke()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::U64(42));
+    }
+
+    #[test]
+    fn test_unknown_function_is_rejected() {
+        let expr = Expr::from_text("triple(request.count)").unwrap();
+        let result = registry().compile(&expr, &[]);
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Unknown function call: `triple`".to_string()
+        );
+    }
+
+    #[test]
+    fn test_builtin_function_cannot_be_registered() {
+        let result = FunctionRegistry::new().register(
+            "round",
+            vec![u64()],
+            u64(),
+            Arc::new(|arguments| Ok(arguments[0].clone())),
+        );
+
+        assert_eq!(result, Err("`round` is a built-in function".to_string()));
+        assert_eq!(registry().arity("double"), Some(1));
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fmt::Display;
+
+use golem_wasm_ast::analysis::AnalysedType;
+use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+
+// An error of coercing a value to a type, with the path of the offending
+// part of the value (Ex: `.items[2].price`), which is empty for the value itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoercionError {
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for CoercionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+// Validates a value (Ex: the result of a Rib expression) against the type it's going to be used as
+// (Ex: a parameter of a worker function), and converts it where it's lossless:
+// numbers to other numeric types within range, numeric strings to numbers, `"true"`/`"false"` to booleans,
+// and values to optionals. Missing optional fields of records become `none`, and extra fields are dropped
+pub fn coerce_to(
+    value: &TypeAnnotatedValue,
+    target: &AnalysedType,
+) -> Result<TypeAnnotatedValue, CoercionError> {
+    internal::coerce(value, target, "")
+}
+
+mod internal {
+    use crate::interpreter::coercion::CoercionError;
+    use crate::interpreter::literal::{CoercedNumericValue, GetLiteralValue, LiteralValue};
+    use golem_wasm_ast::analysis::protobuf::{NameTypePair as ProtoNameTypePair, Type};
+    use golem_wasm_ast::analysis::AnalysedType;
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+    use golem_wasm_rpc::protobuf::{
+        NameValuePair, TypeAnnotatedValue as RootTypeAnnotatedValue, TypedList, TypedOption,
+        TypedRecord,
+    };
+
+    pub(crate) fn coerce(
+        value: &TypeAnnotatedValue,
+        target: &AnalysedType,
+        path: &str,
+    ) -> Result<TypeAnnotatedValue, CoercionError> {
+        match target {
+            AnalysedType::Bool(_) => match value {
+                TypeAnnotatedValue::Bool(_) => Ok(value.clone()),
+                TypeAnnotatedValue::Str(str) => str
+                    .parse::<bool>()
+                    .map(TypeAnnotatedValue::Bool)
+                    .map_err(|_| mismatch(value, target, path)),
+                _ => Err(mismatch(value, target, path)),
+            },
+
+            AnalysedType::Str(_) => match value {
+                TypeAnnotatedValue::Str(_) => Ok(value.clone()),
+                _ => Err(mismatch(value, target, path)),
+            },
+
+            AnalysedType::Chr(_) => match value {
+                TypeAnnotatedValue::Char(_) => Ok(value.clone()),
+                _ => Err(mismatch(value, target, path)),
+            },
+
+            AnalysedType::U8(_)
+            | AnalysedType::U16(_)
+            | AnalysedType::U32(_)
+            | AnalysedType::U64(_)
+            | AnalysedType::S8(_)
+            | AnalysedType::S16(_)
+            | AnalysedType::S32(_)
+            | AnalysedType::S64(_)
+            | AnalysedType::F32(_)
+            | AnalysedType::F64(_) => {
+                let number = match value.get_literal() {
+                    Some(LiteralValue::Num(number)) => number,
+                    // Ex: a path or a query parameter
+                    Some(LiteralValue::String(str))
+                        if matches!(value, TypeAnnotatedValue::Str(_)) =>
+                    {
+                        match LiteralValue::from(str) {
+                            LiteralValue::Num(number) => number,
+                            _ => return Err(mismatch(value, target, path)),
+                        }
+                    }
+                    _ => return Err(mismatch(value, target, path)),
+                };
+
+                coerce_number(number, target).ok_or_else(|| {
+                    error(
+                        path,
+                        format!(
+                            "{} is out of the range of {}",
+                            value_description(value),
+                            type_name(target)
+                        ),
+                    )
+                })
+            }
+
+            AnalysedType::Option(option_type) => match value {
+                TypeAnnotatedValue::Option(option) => match option
+                    .value
+                    .as_ref()
+                    .and_then(|inner| inner.type_annotated_value.as_ref())
+                {
+                    Some(inner) => Ok(some(
+                        coerce(inner, &option_type.inner, path)?,
+                        &option_type.inner,
+                    )),
+                    None => Ok(none(&option_type.inner)),
+                },
+                other => Ok(some(
+                    coerce(other, &option_type.inner, path)?,
+                    &option_type.inner,
+                )),
+            },
+
+            AnalysedType::List(list_type) => match value {
+                TypeAnnotatedValue::List(list) => {
+                    let values = list
+                        .values
+                        .iter()
+                        .enumerate()
+                        .map(|(index, element)| {
+                            let element_path = format!("{}[{}]", path, index);
+                            let element = element
+                                .type_annotated_value
+                                .as_ref()
+                                .ok_or_else(|| error(&element_path, "missing value".to_string()))?;
+
+                            coerce(element, &list_type.inner, &element_path).map(wrap)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    Ok(TypeAnnotatedValue::List(TypedList {
+                        values,
+                        typ: Some(Type::from(&*list_type.inner)),
+                    }))
+                }
+                _ => Err(mismatch(value, target, path)),
+            },
+
+            AnalysedType::Record(record_type) => match value {
+                TypeAnnotatedValue::Record(record) => {
+                    let mut fields = vec![];
+
+                    for field in &record_type.fields {
+                        let field_path = format!("{}.{}", path, field.name);
+
+                        let field_value = record
+                            .value
+                            .iter()
+                            .find(|name_value| name_value.name == field.name)
+                            .and_then(|name_value| name_value.value.as_ref())
+                            .and_then(|value| value.type_annotated_value.as_ref());
+
+                        let coerced = match (field_value, &field.typ) {
+                            (Some(field_value), _) => coerce(field_value, &field.typ, &field_path)?,
+                            (None, AnalysedType::Option(option_type)) => none(&option_type.inner),
+                            (None, _) => {
+                                return Err(error(
+                                    path,
+                                    format!(
+                                        "missing field `{}` of type {}",
+                                        field.name,
+                                        type_name(&field.typ)
+                                    ),
+                                ))
+                            }
+                        };
+
+                        fields.push(NameValuePair {
+                            name: field.name.clone(),
+                            value: Some(wrap(coerced)),
+                        });
+                    }
+
+                    Ok(TypeAnnotatedValue::Record(TypedRecord {
+                        value: fields,
+                        typ: record_type
+                            .fields
+                            .iter()
+                            .map(|field| ProtoNameTypePair {
+                                name: field.name.clone(),
+                                typ: Some(Type::from(&field.typ)),
+                            })
+                            .collect(),
+                    }))
+                }
+                _ => Err(mismatch(value, target, path)),
+            },
+
+            // The rest of the types (Ex: variants) have to match exactly
+            _ => match AnalysedType::try_from(value) {
+                Ok(value_type) if &value_type == target => Ok(value.clone()),
+                _ => Err(mismatch(value, target, path)),
+            },
+        }
+    }
+
+    fn coerce_number(
+        number: CoercedNumericValue,
+        target: &AnalysedType,
+    ) -> Option<TypeAnnotatedValue> {
+        let integer = match number {
+            CoercedNumericValue::PosInt(value) => Some(value as i128),
+            CoercedNumericValue::NegInt(value) => Some(value as i128),
+            // Only whole numbers are integers, the fraction is never dropped
+            CoercedNumericValue::Float(value) if value.fract() == 0.0 && value.is_finite() => {
+                Some(value as i128)
+            }
+            CoercedNumericValue::Float(_) => None,
+        };
+
+        let float = match number {
+            CoercedNumericValue::PosInt(value) => value as f64,
+            CoercedNumericValue::NegInt(value) => value as f64,
+            CoercedNumericValue::Float(value) => value,
+        };
+
+        match target {
+            AnalysedType::U8(_) => u8::try_from(integer?)
+                .ok()
+                .map(|x| TypeAnnotatedValue::U8(x as u32)),
+            AnalysedType::U16(_) => u16::try_from(integer?)
+                .ok()
+                .map(|x| TypeAnnotatedValue::U16(x as u32)),
+            AnalysedType::U32(_) => u32::try_from(integer?).ok().map(TypeAnnotatedValue::U32),
+            AnalysedType::U64(_) => u64::try_from(integer?).ok().map(TypeAnnotatedValue::U64),
+            AnalysedType::S8(_) => i8::try_from(integer?)
+                .ok()
+                .map(|x| TypeAnnotatedValue::S8(x as i32)),
+            AnalysedType::S16(_) => i16::try_from(integer?)
+                .ok()
+                .map(|x| TypeAnnotatedValue::S16(x as i32)),
+            AnalysedType::S32(_) => i32::try_from(integer?).ok().map(TypeAnnotatedValue::S32),
+            AnalysedType::S64(_) => i64::try_from(integer?).ok().map(TypeAnnotatedValue::S64),
+            AnalysedType::F32(_) => Some(TypeAnnotatedValue::F32(float as f32)),
+            AnalysedType::F64(_) => Some(TypeAnnotatedValue::F64(float)),
+            _ => None,
+        }
+    }
+
+    fn some(value: TypeAnnotatedValue, inner_type: &AnalysedType) -> TypeAnnotatedValue {
+        TypeAnnotatedValue::Option(Box::new(TypedOption {
+            typ: Some(Type::from(inner_type)),
+            value: Some(Box::new(wrap(value))),
+        }))
+    }
+
+    fn none(inner_type: &AnalysedType) -> TypeAnnotatedValue {
+        TypeAnnotatedValue::Option(Box::new(TypedOption {
+            typ: Some(Type::from(inner_type)),
+            value: None,
+        }))
+    }
+
+    fn wrap(value: TypeAnnotatedValue) -> RootTypeAnnotatedValue {
+        RootTypeAnnotatedValue {
+            type_annotated_value: Some(value),
+        }
+    }
+
+    fn mismatch(value: &TypeAnnotatedValue, target: &AnalysedType, path: &str) -> CoercionError {
+        error(
+            path,
+            format!(
+                "expected {}, but found {}",
+                type_name(target),
+                value_description(value)
+            ),
+        )
+    }
+
+    fn error(path: &str, message: String) -> CoercionError {
+        CoercionError {
+            path: path.to_string(),
+            message,
+        }
+    }
+
+    fn value_description(value: &TypeAnnotatedValue) -> String {
+        match value {
+            TypeAnnotatedValue::Str(str) => format!("str {:?}", str),
+            other => match other.get_literal() {
+                Some(literal) => format!("{} {}", value_type_name(other), literal.as_string()),
+                None => value_type_name(other),
+            },
+        }
+    }
+
+    fn value_type_name(value: &TypeAnnotatedValue) -> String {
+        AnalysedType::try_from(value)
+            .map(|analysed_type| type_name(&analysed_type).to_string())
+            .unwrap_or_else(|_| "a value of unknown type".to_string())
+    }
+
+    fn type_name(analysed_type: &AnalysedType) -> &'static str {
+        match analysed_type {
+            AnalysedType::Bool(_) => "bool",
+            AnalysedType::U8(_) => "u8",
+            AnalysedType::U16(_) => "u16",
+            AnalysedType::U32(_) => "u32",
+            AnalysedType::U64(_) => "u64",
+            AnalysedType::S8(_) => "s8",
+            AnalysedType::S16(_) => "s16",
+            AnalysedType::S32(_) => "s32",
+            AnalysedType::S64(_) => "s64",
+            AnalysedType::F32(_) => "f32",
+            AnalysedType::F64(_) => "f64",
+            AnalysedType::Chr(_) => "char",
+            AnalysedType::Str(_) => "str",
+            AnalysedType::List(_) => "list",
+            AnalysedType::Tuple(_) => "tuple",
+            AnalysedType::Record(_) => "record",
+            AnalysedType::Flags(_) => "flags",
+            AnalysedType::Enum(_) => "enum",
+            AnalysedType::Option(_) => "option",
+            AnalysedType::Result(_) => "result",
+            AnalysedType::Variant(_) => "variant",
+            AnalysedType::Handle(_) => "handle",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::coercion::{coerce_to, CoercionError};
+    use golem_wasm_ast::analysis::analysed_type::{field, list, option, record, str, u32, u64};
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+    use golem_wasm_rpc::type_annotated_value_from_str;
+
+    #[test]
+    fn test_coerce_numeric_string_to_number() {
+        let value = TypeAnnotatedValue::Str("42".to_string());
+
+        assert_eq!(coerce_to(&value, &u64()), Ok(TypeAnnotatedValue::U64(42)));
+        assert_eq!(coerce_to(&value, &u32()), Ok(TypeAnnotatedValue::U32(42)));
+    }
+
+    #[test]
+    fn test_coerce_invalid_numbers() {
+        assert_eq!(
+            coerce_to(&TypeAnnotatedValue::Str("42abc".to_string()), &u64()),
+            Err(CoercionError {
+                path: "".to_string(),
+                message: "expected u64, but found str \"42abc\"".to_string()
+            })
+        );
+
+        assert_eq!(
+            coerce_to(&TypeAnnotatedValue::S64(-1), &u64())
+                .unwrap_err()
+                .to_string(),
+            "s64 -1 is out of the range of u64"
+        );
+    }
+
+    #[test]
+    fn test_coerce_record_with_missing_field() {
+        let source_type = record(vec![field("name", str())]);
+        let value = type_annotated_value_from_str(&source_type, r#"{name: "foo"}"#).unwrap();
+
+        let target = record(vec![field("name", str()), field("quantity", u32())]);
+
+        assert_eq!(
+            coerce_to(&value, &target),
+            Err(CoercionError {
+                path: "".to_string(),
+                message: "missing field `quantity` of type u32".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_coerce_nested_record() {
+        let source_type = record(vec![
+            field("name", str()),
+            field("items", list(record(vec![field("quantity", str())]))),
+            field("extra", str()),
+        ]);
+        let value = type_annotated_value_from_str(
+            &source_type,
+            r#"{name: "foo", items: [{quantity: "1"}, {quantity: "2"}], extra: "dropped"}"#,
+        )
+        .unwrap();
+
+        let target = record(vec![
+            field("name", str()),
+            field("items", list(record(vec![field("quantity", u32())]))),
+            field("coupon", option(str())),
+        ]);
+
+        let expected = type_annotated_value_from_str(
+            &target,
+            r#"{name: "foo", items: [{quantity: 1}, {quantity: 2}], coupon: none}"#,
+        )
+        .unwrap();
+
+        assert_eq!(coerce_to(&value, &target), Ok(expected));
+    }
+
+    #[test]
+    fn test_coerce_error_path() {
+        let source_type = record(vec![field(
+            "items",
+            list(record(vec![field("quantity", str())])),
+        )]);
+        let value = type_annotated_value_from_str(
+            &source_type,
+            r#"{items: [{quantity: "1"}, {quantity: "many"}]}"#,
+        )
+        .unwrap();
+
+        let target = record(vec![field(
+            "items",
+            list(record(vec![field("quantity", u32())])),
+        )]);
+
+        assert_eq!(
+            coerce_to(&value, &target).unwrap_err().to_string(),
+            ".items[1].quantity: expected u32, but found str \"many\""
+        );
+    }
+}
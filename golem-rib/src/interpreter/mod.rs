@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use coercion::*;
 pub use env::RibFunctionInvoke;
 pub use literal::*;
 pub use result::*;
@@ -20,6 +21,7 @@ pub use rib_interpreter::*;
 use crate::RibByteCode;
 use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
 use std::collections::HashMap;
+mod coercion;
 mod env;
 mod literal;
 mod result;
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::interpreter::literal::{GetLiteralValue, LiteralValue};
+use crate::interpreter::literal::{CoercedNumericValue, GetLiteralValue, LiteralValue};
 use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
 use golem_wasm_rpc::protobuf::typed_result::ResultValue;
 
@@ -20,6 +20,8 @@ use golem_wasm_rpc::protobuf::typed_result::ResultValue;
 pub enum RibInterpreterResult {
     Unit,
     Val(TypeAnnotatedValue),
+    // The evaluation was short-circuited by the builtin `reject(status, message)`
+    Reject { status: u16, message: String },
 }
 
 impl RibInterpreterResult {
@@ -44,17 +46,38 @@ impl RibInterpreterResult {
         }
     }
 
+    // Structural equality (`==`). Lists and tuples are equal if their items are equal, in the same
+    // order (Ex: `[1, 2]` is not `[2, 1]`). Records are equal if they have the same fields with
+    // equal values, in any order (Ex: `{a: 1, b: 2}` is `{b: 2, a: 1}`). The items and the fields
+    // are compared the same way, so nested lists and records are compared deeply.
+    // Other values are compared like `compare` does
+    pub fn equal(&self, right: &RibInterpreterResult) -> Result<RibInterpreterResult, String> {
+        if self.is_unit() && right.is_unit() {
+            Ok(RibInterpreterResult::Val(TypeAnnotatedValue::Bool(true)))
+        } else {
+            match (self.get_val(), right.get_val()) {
+                (Some(left), Some(right)) => {
+                    let result = internal::equal_typed_values(&left, &right)?;
+                    Ok(RibInterpreterResult::Val(TypeAnnotatedValue::Bool(result)))
+                }
+                _ => Err("Values are not literals and cannot be compared".to_string()),
+            }
+        }
+    }
+
     pub fn get_bool(&self) -> Option<bool> {
         match self {
             RibInterpreterResult::Val(TypeAnnotatedValue::Bool(bool)) => Some(*bool),
             RibInterpreterResult::Val(_) => None,
             RibInterpreterResult::Unit => None,
+            RibInterpreterResult::Reject { .. } => None,
         }
     }
     pub fn get_val(&self) -> Option<TypeAnnotatedValue> {
         match self {
             RibInterpreterResult::Val(val) => Some(val.clone()),
             RibInterpreterResult::Unit => None,
+            RibInterpreterResult::Reject { .. } => None,
         }
     }
 
@@ -62,6 +85,7 @@ impl RibInterpreterResult {
         match self {
             RibInterpreterResult::Val(val) => val.get_literal(),
             RibInterpreterResult::Unit => None,
+            RibInterpreterResult::Reject { .. } => None,
         }
     }
 
@@ -69,6 +93,33 @@ impl RibInterpreterResult {
         matches!(self, RibInterpreterResult::Unit)
     }
 
+    pub fn is_reject(&self) -> bool {
+        matches!(self, RibInterpreterResult::Reject { .. })
+    }
+
+    // Whether the value counts as true when it's used as a condition (in lenient mode).
+    // `false`, empty strings, zero, `none`, empty lists and unit are falsy, and everything else is truthy
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            RibInterpreterResult::Val(val) => match val {
+                TypeAnnotatedValue::Option(option) => option.value.is_some(),
+                TypeAnnotatedValue::List(list) => !list.values.is_empty(),
+                other => match other.get_literal() {
+                    Some(LiteralValue::Bool(bool)) => bool,
+                    Some(LiteralValue::String(string)) => !string.is_empty(),
+                    Some(LiteralValue::Num(number)) => match number {
+                        CoercedNumericValue::PosInt(value) => value != 0,
+                        CoercedNumericValue::NegInt(value) => value != 0,
+                        CoercedNumericValue::Float(value) => value != 0.0,
+                    },
+                    None => true,
+                },
+            },
+            RibInterpreterResult::Unit => false,
+            RibInterpreterResult::Reject { .. } => false,
+        }
+    }
+
     pub fn val(val: TypeAnnotatedValue) -> Self {
         RibInterpreterResult::Val(val)
     }
@@ -98,6 +149,7 @@ impl RibInterpreterResult {
                 _ => None,
             },
             RibInterpreterResult::Unit => None,
+            RibInterpreterResult::Reject { .. } => None,
         }
     }
 }
@@ -135,6 +187,83 @@ mod internal {
         }
     }
 
+    pub(crate) fn equal_typed_values(
+        left: &TypeAnnotatedValue,
+        right: &TypeAnnotatedValue,
+    ) -> Result<bool, String> {
+        match (left, right) {
+            (TypeAnnotatedValue::List(left), TypeAnnotatedValue::List(right)) => {
+                equal_items(&left.values, &right.values)
+            }
+            (TypeAnnotatedValue::Tuple(left), TypeAnnotatedValue::Tuple(right)) => {
+                equal_items(&left.value, &right.value)
+            }
+            (TypeAnnotatedValue::Record(left), TypeAnnotatedValue::Record(right)) => {
+                if left.value.len() != right.value.len() {
+                    return Ok(false);
+                }
+
+                for field in &left.value {
+                    let other = right.value.iter().find(|other| other.name == field.name);
+
+                    let equal = match other {
+                        Some(other) => equal_wrapped_values(
+                            field
+                                .value
+                                .as_ref()
+                                .and_then(|x| x.type_annotated_value.as_ref()),
+                            other
+                                .value
+                                .as_ref()
+                                .and_then(|x| x.type_annotated_value.as_ref()),
+                        )?,
+                        None => false,
+                    };
+
+                    if !equal {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+            _ => compare_typed_value(left, right, |left, right| left == right)
+                .map(|result| matches!(result, TypeAnnotatedValue::Bool(true))),
+        }
+    }
+
+    fn equal_items(
+        left: &[golem_wasm_rpc::protobuf::TypeAnnotatedValue],
+        right: &[golem_wasm_rpc::protobuf::TypeAnnotatedValue],
+    ) -> Result<bool, String> {
+        if left.len() != right.len() {
+            return Ok(false);
+        }
+
+        for (left, right) in left.iter().zip(right) {
+            if !equal_wrapped_values(
+                left.type_annotated_value.as_ref(),
+                right.type_annotated_value.as_ref(),
+            )? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    // GRPC wrapper
+    fn equal_wrapped_values(
+        left: Option<&TypeAnnotatedValue>,
+        right: Option<&TypeAnnotatedValue>,
+    ) -> Result<bool, String> {
+        match (left, right) {
+            (Some(left), Some(right)) => equal_typed_values(left, right),
+            (None, None) => Ok(true),
+            _ => Ok(false),
+        }
+    }
+
     fn compare_flags(left: &TypedFlags, right: &TypedFlags) -> Result<TypeAnnotatedValue, String> {
         if left.values == right.values {
             Ok(TypeAnnotatedValue::Bool(true))
@@ -189,3 +318,86 @@ mod internal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::RibInterpreterResult;
+    use golem_wasm_ast::analysis::analysed_type::{str, u32};
+    use golem_wasm_ast::analysis::protobuf::Type;
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+    use golem_wasm_rpc::protobuf::{TypedList, TypedOption};
+
+    fn is_truthy(value: TypeAnnotatedValue) -> bool {
+        RibInterpreterResult::Val(value).is_truthy()
+    }
+
+    fn wrap(value: TypeAnnotatedValue) -> golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+        golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+            type_annotated_value: Some(value),
+        }
+    }
+
+    #[test]
+    fn test_truthiness_of_bool() {
+        assert!(is_truthy(TypeAnnotatedValue::Bool(true)));
+        assert!(!is_truthy(TypeAnnotatedValue::Bool(false)));
+    }
+
+    #[test]
+    fn test_truthiness_of_string() {
+        assert!(is_truthy(TypeAnnotatedValue::Str("true".to_string())));
+        assert!(is_truthy(TypeAnnotatedValue::Str("false".to_string())));
+        assert!(!is_truthy(TypeAnnotatedValue::Str("".to_string())));
+    }
+
+    #[test]
+    fn test_truthiness_of_number() {
+        assert!(is_truthy(TypeAnnotatedValue::U32(1)));
+        assert!(is_truthy(TypeAnnotatedValue::S64(-1)));
+        assert!(is_truthy(TypeAnnotatedValue::F64(0.5)));
+        assert!(!is_truthy(TypeAnnotatedValue::U32(0)));
+        assert!(!is_truthy(TypeAnnotatedValue::S64(0)));
+        assert!(!is_truthy(TypeAnnotatedValue::F64(0.0)));
+    }
+
+    #[test]
+    fn test_truthiness_of_option() {
+        let some = TypeAnnotatedValue::Option(Box::new(TypedOption {
+            typ: Some(Type::from(&u32())),
+            value: Some(Box::new(wrap(TypeAnnotatedValue::U32(0)))),
+        }));
+        let none = TypeAnnotatedValue::Option(Box::new(TypedOption {
+            typ: Some(Type::from(&u32())),
+            value: None,
+        }));
+
+        // A present value is truthy, whatever the value is
+        assert!(is_truthy(some));
+        assert!(!is_truthy(none));
+    }
+
+    #[test]
+    fn test_truthiness_of_list() {
+        let non_empty = TypeAnnotatedValue::List(TypedList {
+            typ: Some(Type::from(&str())),
+            values: vec![wrap(TypeAnnotatedValue::Str("".to_string()))],
+        });
+        let empty = TypeAnnotatedValue::List(TypedList {
+            typ: Some(Type::from(&str())),
+            values: vec![],
+        });
+
+        assert!(is_truthy(non_empty));
+        assert!(!is_truthy(empty));
+    }
+
+    #[test]
+    fn test_truthiness_of_unit_and_reject() {
+        assert!(!RibInterpreterResult::Unit.is_truthy());
+        assert!(!RibInterpreterResult::Reject {
+            status: 403,
+            message: "forbidden".to_string()
+        }
+        .is_truthy());
+    }
+}
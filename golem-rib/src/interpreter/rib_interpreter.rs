@@ -17,12 +17,15 @@ use crate::interpreter::result::RibInterpreterResult;
 use crate::interpreter::stack::InterpreterStack;
 use crate::{RibByteCode, RibIR};
 use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 
 #[derive(Debug)]
 pub struct Interpreter {
     pub stack: InterpreterStack,
     pub env: InterpreterEnv,
+    pub trace: Option<Vec<TraceEntry>>,
+    pub predicate_mode: PredicateMode,
 }
 
 impl Default for Interpreter {
@@ -30,10 +33,35 @@ impl Default for Interpreter {
         Interpreter {
             stack: InterpreterStack::new(),
             env: InterpreterEnv::default(),
+            trace: None,
+            predicate_mode: PredicateMode::default(),
         }
     }
 }
 
+// An entry in the evaluation trace: the value left on top of the stack
+// by the instruction at `instruction_index` in the byte code.
+// Instruction indices are stable for a given byte code, and therefore identify the sub-expressions
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub instruction_index: usize,
+    pub value: RibInterpreterResult,
+}
+
+// How a condition (the predicate of an if-else, or an operand of `&&`, `||` and `!`)
+// is treated when it doesn't evaluate to a boolean. Compiled Rib is already type checked
+// to have boolean conditions, but the byte code can come from elsewhere (Ex: restored from an older version).
+// The same mode is given to `compile_with_predicate_mode`, to compile the conditions that are not booleans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PredicateMode {
+    // Fail the evaluation, as a non-boolean predicate is almost always a bug
+    #[default]
+    Strict,
+    // Apply the truthiness rules of `RibInterpreterResult::is_truthy`
+    Lenient,
+}
+
 impl Interpreter {
     pub fn new(
         input: HashMap<String, TypeAnnotatedValue>,
@@ -47,6 +75,8 @@ impl Interpreter {
         Interpreter {
             stack: InterpreterStack::new(),
             env: InterpreterEnv::new(input, function_invoke),
+            trace: None,
+            predicate_mode: PredicateMode::default(),
         }
     }
 
@@ -56,17 +86,36 @@ impl Interpreter {
         Interpreter {
             stack: InterpreterStack::new(),
             env: InterpreterEnv::from_input(env),
+            trace: None,
+            predicate_mode: PredicateMode::default(),
         }
     }
 
+    // Opt-in recording of the intermediate values, available in `trace` after `run`.
+    // Useful to debug why a Rib script produced an unexpected result
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    pub fn with_predicate_mode(mut self, predicate_mode: PredicateMode) -> Self {
+        self.predicate_mode = predicate_mode;
+        self
+    }
+
     pub async fn run(
         &mut self,
         instructions0: RibByteCode,
     ) -> Result<RibInterpreterResult, String> {
+        let total_instructions = instructions0.instructions.len();
+
         // O(1) to do this
         let mut instructions = VecDeque::from(instructions0.instructions);
 
         while let Some(instruction) = instructions.pop_front() {
+            let instruction_index = total_instructions - instructions.len() - 1;
+            let produces_value = internal::produces_value(&instruction);
+
             match instruction {
                 RibIR::PushLit(val) => {
                     self.stack.push_val(val);
@@ -89,9 +138,7 @@ impl Interpreter {
                 }
 
                 RibIR::EqualTo => {
-                    internal::run_compare_instruction(&mut self.stack, |left, right| {
-                        left == right
-                    })?;
+                    internal::run_equal_instruction(&mut self.stack)?;
                 }
 
                 RibIR::GreaterThan => {
@@ -127,6 +174,7 @@ impl Interpreter {
                         instruction_id,
                         &mut instructions,
                         &mut self.stack,
+                        self.predicate_mode,
                     )?;
                 }
 
@@ -138,6 +186,10 @@ impl Interpreter {
                     internal::run_select_index_instruction(&mut self.stack, index)?;
                 }
 
+                RibIR::SelectDynamicIndex => {
+                    internal::run_select_dynamic_index_instruction(&mut self.stack)?;
+                }
+
                 RibIR::CreateFunctionName(site, function_type) => {
                     internal::run_create_function_name_instruction(site, function_type, self)?;
                 }
@@ -146,6 +198,15 @@ impl Interpreter {
                     internal::run_call_instruction(arg_size, self).await?;
                 }
 
+                RibIR::CallBuiltin(builtin, arg_size) => {
+                    // A builtin such as `reject` ends the evaluation early
+                    if let Some(result) =
+                        internal::run_call_builtin_instruction(builtin, arg_size, &mut self.stack)?
+                    {
+                        return Ok(result);
+                    }
+                }
+
                 RibIR::PushVariant(variant_name, analysed_type) => {
                     internal::run_variant_construction_instruction(
                         variant_name,
@@ -194,17 +255,26 @@ impl Interpreter {
                     internal::run_push_tuple_instruction(arg_size, analysed_type, &mut self.stack)?;
                 }
                 RibIR::Negate => {
-                    internal::run_negate_instruction(&mut self.stack)?;
+                    internal::run_negate_instruction(&mut self.stack, self.predicate_mode)?;
                 }
 
                 RibIR::Label(_) => {}
 
                 RibIR::And => {
-                    internal::run_and_instruction(&mut self.stack)?;
+                    internal::run_and_instruction(&mut self.stack, self.predicate_mode)?;
                 }
 
                 RibIR::Or => {
-                    internal::run_or_instruction(&mut self.stack)?;
+                    internal::run_or_instruction(&mut self.stack, self.predicate_mode)?;
+                }
+            }
+
+            if produces_value {
+                if let (Some(trace), Some(value)) = (&mut self.trace, self.stack.stack.last()) {
+                    trace.push(TraceEntry {
+                        instruction_index,
+                        value: value.clone(),
+                    });
                 }
             }
         }
@@ -216,16 +286,24 @@ impl Interpreter {
 }
 
 mod internal {
+    use crate::builtin::format_with;
+    use crate::interpreter::coercion::{coerce_to, CoercionError};
     use crate::interpreter::env::EnvironmentKey;
-    use crate::interpreter::literal::LiteralValue;
+    use crate::interpreter::literal::{CoercedNumericValue, LiteralValue};
     use crate::interpreter::result::RibInterpreterResult;
     use crate::interpreter::stack::InterpreterStack;
     use crate::{
-        FunctionReferenceType, GetLiteralValue, InstructionId, Interpreter, ParsedFunctionName,
-        ParsedFunctionReference, ParsedFunctionSite, RibIR, VariableId,
+        BuiltinFunction, FunctionReferenceType, GetLiteralValue, InstructionId, Interpreter,
+        ParsedFunctionName, ParsedFunctionReference, ParsedFunctionSite, PredicateMode, RibIR,
+        VariableId,
     };
+    use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, URL_SAFE_NO_PAD};
+    use base64::engine::DecodePaddingMode;
+    use base64::{alphabet, Engine};
+    use golem_wasm_ast::analysis::analysed_type;
     use golem_wasm_ast::analysis::AnalysedType;
     use golem_wasm_ast::analysis::TypeResult;
+    use golem_wasm_rpc::json::TypeAnnotatedValueJsonExtensions;
     use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
     use golem_wasm_rpc::protobuf::typed_result::ResultValue;
     use golem_wasm_rpc::protobuf::{NameValuePair, TypedRecord, TypedTuple};
@@ -234,6 +312,18 @@ mod internal {
     use std::collections::VecDeque;
     use std::ops::Deref;
 
+    // Control flow instructions (and assignments) don't leave a new value on the stack
+    pub(crate) fn produces_value(instruction: &RibIR) -> bool {
+        !matches!(
+            instruction,
+            RibIR::Label(_)
+                | RibIR::Jump(_)
+                | RibIR::JumpIfFalse(_)
+                | RibIR::AssignVar(_)
+                | RibIR::Throw(_)
+        )
+    }
+
     pub(crate) fn run_assign_var_instruction(
         variable_id: VariableId,
         interpreter: &mut Interpreter,
@@ -336,12 +426,23 @@ mod internal {
                     .pop_n(list_size)
                     .ok_or(format!("Expected {} value on the stack", list_size))?;
 
+                // The elements are validated against (and converted to) the element type,
+                // as the list may be bound to a parameter of a worker function (Ex: `list<u32>`)
                 let type_annotated_values = last_list
                     .iter()
-                    .map(|interpreter_result| {
-                        interpreter_result
+                    .enumerate()
+                    .map(|(index, interpreter_result)| {
+                        let value = interpreter_result
                             .get_val()
-                            .ok_or("Internal Error: Failed to construct list".to_string())
+                            .ok_or("Internal Error: Failed to construct list".to_string())?;
+
+                        coerce_to(&value, inner_type.inner.deref()).map_err(|err| {
+                            let err = CoercionError {
+                                path: format!("[{}]{}", index, err.path),
+                                message: err.message,
+                            };
+                            format!("Invalid list: {}", err)
+                        })
                     })
                     .collect::<Result<Vec<TypeAnnotatedValue>, String>>()?;
 
@@ -389,15 +490,19 @@ mod internal {
 
     pub(crate) fn run_negate_instruction(
         interpreter_stack: &mut InterpreterStack,
+        predicate_mode: PredicateMode,
     ) -> Result<(), String> {
         let value = interpreter_stack
-            .pop_val()
+            .pop()
             .ok_or("Failed to get a value from the stack to negate".to_string())?;
 
-        let result = value
-            .get_literal()
-            .and_then(|literal| literal.get_bool())
-            .ok_or("Failed to get a boolean value from the stack to negate".to_string())?;
+        let result = match (value.get_bool(), predicate_mode) {
+            (Some(bool), _) => bool,
+            (None, PredicateMode::Lenient) => value.is_truthy(),
+            (None, PredicateMode::Strict) => {
+                return Err("Failed to get a boolean value from the stack to negate".to_string())
+            }
+        };
 
         interpreter_stack.push_val(TypeAnnotatedValue::Bool(!result));
         Ok(())
@@ -405,6 +510,7 @@ mod internal {
 
     pub(crate) fn run_and_instruction(
         interpreter_stack: &mut InterpreterStack,
+        predicate_mode: PredicateMode,
     ) -> Result<(), String> {
         let left = interpreter_stack
             .pop()
@@ -413,10 +519,17 @@ mod internal {
             .pop()
             .ok_or("Internal Error: Failed to get RHS of &&".to_string())?;
 
-        let result = left.compare(&right, |a, b| match (a.get_bool(), b.get_bool()) {
-            (Some(a), Some(b)) => a && b,
-            _ => false,
-        })?;
+        let result = match predicate_mode {
+            PredicateMode::Lenient => RibInterpreterResult::Val(TypeAnnotatedValue::Bool(
+                left.is_truthy() && right.is_truthy(),
+            )),
+            PredicateMode::Strict => {
+                left.compare(&right, |a, b| match (a.get_bool(), b.get_bool()) {
+                    (Some(a), Some(b)) => a && b,
+                    _ => false,
+                })?
+            }
+        };
 
         interpreter_stack.push(result);
 
@@ -425,6 +538,7 @@ mod internal {
 
     pub(crate) fn run_or_instruction(
         interpreter_stack: &mut InterpreterStack,
+        predicate_mode: PredicateMode,
     ) -> Result<(), String> {
         let left = interpreter_stack
             .pop()
@@ -433,10 +547,17 @@ mod internal {
             .pop()
             .ok_or("Internal Error: Failed to get RHS of &&".to_string())?;
 
-        let result = left.compare(&right, |a, b| match (a.get_bool(), b.get_bool()) {
-            (Some(a), Some(b)) => a || b,
-            _ => false,
-        })?;
+        let result = match predicate_mode {
+            PredicateMode::Lenient => RibInterpreterResult::Val(TypeAnnotatedValue::Bool(
+                left.is_truthy() || right.is_truthy(),
+            )),
+            PredicateMode::Strict => {
+                left.compare(&right, |a, b| match (a.get_bool(), b.get_bool()) {
+                    (Some(a), Some(b)) => a || b,
+                    _ => false,
+                })?
+            }
+        };
 
         interpreter_stack.push(result);
 
@@ -461,6 +582,23 @@ mod internal {
         Ok(())
     }
 
+    pub(crate) fn run_equal_instruction(
+        interpreter_stack: &mut InterpreterStack,
+    ) -> Result<(), String> {
+        let left = interpreter_stack.pop().ok_or(
+            "Empty stack and failed to get a value to do the comparison operation".to_string(),
+        )?;
+        let right = interpreter_stack.pop().ok_or(
+            "Failed to get a value from the stack to do the comparison operation".to_string(),
+        )?;
+
+        let result = left.equal(&right)?;
+
+        interpreter_stack.push(result);
+
+        Ok(())
+    }
+
     pub(crate) fn run_select_field_instruction(
         field_name: String,
         interpreter_stack: &mut InterpreterStack,
@@ -486,6 +624,23 @@ mod internal {
                 interpreter_stack.push_val(inner_type_annotated_value);
                 Ok(())
             }
+            // The payload of the case, if it's the case of the value
+            RibInterpreterResult::Val(TypeAnnotatedValue::Variant(variant)) => {
+                if variant.case_name != field_name {
+                    return Err(format!(
+                        "Variant case {} is not the case of the value, which is {}",
+                        field_name, variant.case_name
+                    ));
+                }
+
+                let inner_type_annotated_value = variant
+                    .case_value
+                    .and_then(|value| value.type_annotated_value)
+                    .ok_or(format!("Variant case {} has no value", field_name))?;
+
+                interpreter_stack.push_val(inner_type_annotated_value);
+                Ok(())
+            }
             result => Err(format!(
                 "Expected a record value to select a field. Obtained {:?}",
                 result
@@ -493,6 +648,24 @@ mod internal {
         }
     }
 
+    pub(crate) fn run_select_dynamic_index_instruction(
+        interpreter_stack: &mut InterpreterStack,
+    ) -> Result<(), String> {
+        let index = interpreter_stack
+            .pop()
+            .ok_or("Failed to get an index from the stack to select an index".to_string())?;
+
+        match index.get_literal() {
+            Some(LiteralValue::Num(CoercedNumericValue::PosInt(index))) => {
+                run_select_index_instruction(interpreter_stack, index as usize)
+            }
+            _ => Err(format!(
+                "Expected a non-negative integer to select an index. But obtained {:?}",
+                index
+            )),
+        }
+    }
+
     pub(crate) fn run_select_index_instruction(
         interpreter_stack: &mut InterpreterStack,
         index: usize,
@@ -836,14 +1009,22 @@ mod internal {
         instruction_id: InstructionId,
         instruction_stack: &mut VecDeque<RibIR>,
         interpreter_stack: &mut InterpreterStack,
+        predicate_mode: PredicateMode,
     ) -> Result<(), String> {
         let condition = interpreter_stack.pop().ok_or(
             "Failed to get a value from the stack to do the comparison operation".to_string(),
         )?;
 
-        let predicate_bool = condition
-            .get_bool()
-            .ok_or("Expected a boolean value".to_string())?;
+        let predicate_bool = match (condition.get_bool(), predicate_mode) {
+            (Some(bool), _) => bool,
+            (None, PredicateMode::Lenient) => condition.is_truthy(),
+            (None, PredicateMode::Strict) => {
+                return Err(format!(
+                    "Non-boolean predicate. Expected the predicate to be a boolean, but found {:?}",
+                    condition
+                ))
+            }
+        };
 
         if !predicate_bool {
             drain_instruction_stack_until_label(instruction_id, instruction_stack);
@@ -943,6 +1124,486 @@ mod internal {
         }
     }
 
+    pub(crate) fn run_call_builtin_instruction(
+        builtin: BuiltinFunction,
+        arg_size: usize,
+        interpreter_stack: &mut InterpreterStack,
+    ) -> Result<Option<RibInterpreterResult>, String> {
+        let args = interpreter_stack.pop_n(arg_size).ok_or(format!(
+            "Internal Error: Failed to get arguments for the builtin function {}",
+            builtin
+        ))?;
+
+        match builtin {
+            BuiltinFunction::IsPresent => {
+                let is_present = match args.first().and_then(|x| x.get_val()) {
+                    Some(TypeAnnotatedValue::Option(option)) => option.value.is_some(),
+                    Some(_) => true,
+                    None => false,
+                };
+
+                interpreter_stack.push_val(TypeAnnotatedValue::Bool(is_present));
+                Ok(None)
+            }
+
+            BuiltinFunction::IsTruthy => {
+                let is_truthy = args.first().is_some_and(|x| x.is_truthy());
+
+                interpreter_stack.push_val(TypeAnnotatedValue::Bool(is_truthy));
+                Ok(None)
+            }
+
+            BuiltinFunction::Reject => {
+                let status = args
+                    .first()
+                    .and_then(|x| x.get_literal())
+                    .and_then(|x| match x {
+                        LiteralValue::Num(CoercedNumericValue::PosInt(status)) => {
+                            u16::try_from(status).ok()
+                        }
+                        _ => None,
+                    })
+                    .ok_or("reject expects a valid HTTP status code".to_string())?;
+
+                let message = args
+                    .get(1)
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("reject expects a message".to_string())?;
+
+                Ok(Some(RibInterpreterResult::Reject { status, message }))
+            }
+
+            BuiltinFunction::Coalesce => {
+                let present = args
+                    .iter()
+                    .filter_map(|x| x.get_val())
+                    .find_map(|value| match value {
+                        TypeAnnotatedValue::Option(option) => {
+                            option.value.and_then(|x| x.type_annotated_value)
+                        }
+                        other => Some(other),
+                    })
+                    .ok_or("None of the arguments of coalesce is present".to_string())?;
+
+                interpreter_stack.push_val(present);
+                Ok(None)
+            }
+
+            BuiltinFunction::HasField => {
+                let field_name = args
+                    .get(1)
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("hasField expects a field name".to_string())?;
+
+                let has_field = match args.first().and_then(|x| x.get_val()) {
+                    Some(TypeAnnotatedValue::Record(record)) => record
+                        .value
+                        .iter()
+                        .any(|name_value| name_value.name == field_name),
+                    other => {
+                        return Err(format!("hasField expects a record, but found {:?}", other))
+                    }
+                };
+
+                interpreter_stack.push_val(TypeAnnotatedValue::Bool(has_field));
+                Ok(None)
+            }
+
+            BuiltinFunction::Round | BuiltinFunction::Floor | BuiltinFunction::Ceil => {
+                let number = match args.first().and_then(|x| x.get_literal()) {
+                    Some(LiteralValue::Num(CoercedNumericValue::PosInt(value))) => value as f64,
+                    Some(LiteralValue::Num(CoercedNumericValue::NegInt(value))) => value as f64,
+                    Some(LiteralValue::Num(CoercedNumericValue::Float(value))) => value,
+                    other => {
+                        return Err(format!(
+                            "{} expects a number, but found {:?}",
+                            builtin, other
+                        ))
+                    }
+                };
+
+                let rounded = match builtin {
+                    BuiltinFunction::Round => number.round(),
+                    BuiltinFunction::Floor => number.floor(),
+                    _ => number.ceil(),
+                };
+
+                // The casting saturates, and therefore the range is checked first
+                if !rounded.is_finite() || rounded < i64::MIN as f64 || rounded >= i64::MAX as f64 {
+                    return Err(format!(
+                        "The result of {}({}) is out of the range of s64",
+                        builtin, number
+                    ));
+                }
+
+                interpreter_stack.push_val(TypeAnnotatedValue::S64(rounded as i64));
+                Ok(None)
+            }
+
+            BuiltinFunction::Split => {
+                let string = args
+                    .first()
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("split expects a string".to_string())?;
+
+                let separator = args
+                    .get(1)
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("split expects a separator".to_string())?;
+
+                if separator.is_empty() {
+                    return Err("split expects a non-empty separator".to_string());
+                }
+
+                let parts = string
+                    .split(separator.as_str())
+                    .map(|part| TypeAnnotatedValue::Str(part.to_string()))
+                    .collect();
+
+                interpreter_stack.push_list(parts, &analysed_type::str());
+                Ok(None)
+            }
+
+            BuiltinFunction::Base64Encode => {
+                let string = args
+                    .first()
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("base64Encode expects a string".to_string())?;
+
+                interpreter_stack.push_val(TypeAnnotatedValue::Str(
+                    URL_SAFE_NO_PAD.encode(string.as_bytes()),
+                ));
+                Ok(None)
+            }
+
+            BuiltinFunction::Base64Decode => {
+                let string = args
+                    .first()
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("base64Decode expects a string".to_string())?;
+
+                // The URL-safe alphabet, unless the string has a character of the standard one
+                // (Ex: the base64 request.rawBody of a body that is not UTF-8)
+                let alphabet = if string.contains(['+', '/']) {
+                    &alphabet::STANDARD
+                } else {
+                    &alphabet::URL_SAFE
+                };
+
+                let engine = GeneralPurpose::new(
+                    alphabet,
+                    GeneralPurposeConfig::new()
+                        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+                );
+
+                let bytes = engine
+                    .decode(string.as_bytes())
+                    .map_err(|err| format!("base64Decode expects a base64 string: {}", err))?;
+
+                let decoded = String::from_utf8(bytes)
+                    .map_err(|_| "base64Decode expects an encoded UTF-8 string".to_string())?;
+
+                interpreter_stack.push_val(TypeAnnotatedValue::Str(decoded));
+                Ok(None)
+            }
+
+            BuiltinFunction::Size => {
+                let size = match args.first().and_then(|x| x.get_val()) {
+                    Some(TypeAnnotatedValue::List(list)) => list.values.len(),
+                    other => return Err(format!("size expects a list, but found {:?}", other)),
+                };
+
+                interpreter_stack.push_val(TypeAnnotatedValue::U64(size as u64));
+                Ok(None)
+            }
+
+            BuiltinFunction::Format => {
+                let template = args
+                    .first()
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("format expects a string template".to_string())?;
+
+                let values = args[1..]
+                    .iter()
+                    .map(|arg| {
+                        arg.get_literal().map(|x| x.as_string()).ok_or(format!(
+                            "format expects the arguments to be strings, numbers or booleans, but found {:?}",
+                            arg.get_val()
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                interpreter_stack
+                    .push_val(TypeAnnotatedValue::Str(format_with(&template, &values)?));
+                Ok(None)
+            }
+
+            BuiltinFunction::ParseJson => {
+                let string = args
+                    .first()
+                    .and_then(|x| x.get_literal())
+                    .map(|x| x.as_string())
+                    .ok_or("parseJson expects a string".to_string())?;
+
+                let json: serde_json::Value = serde_json::from_str(&string)
+                    .map_err(|err| format!("parseJson expects a JSON string: {}", err))?;
+
+                let value = TypeAnnotatedValue::parse_with_type(&json, &json_type(&json)).map_err(
+                    |errs| {
+                        format!(
+                            "parseJson expects the elements of an array to have the same type: {}",
+                            errs.join(", ")
+                        )
+                    },
+                )?;
+
+                interpreter_stack.push_val(value);
+                Ok(None)
+            }
+
+            BuiltinFunction::Clamp => {
+                let numbers = args
+                    .iter()
+                    .map(|arg| match arg.get_literal() {
+                        Some(number @ LiteralValue::Num(_)) => Ok(number),
+                        _ => Err(format!(
+                            "clamp expects numbers, but found {:?}",
+                            arg.get_val()
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let (number, min, max) = (&numbers[0], &numbers[1], &numbers[2]);
+
+                if min > max {
+                    return Err(format!(
+                        "clamp expects the minimum ({}) to be at most the maximum ({})",
+                        min.as_string(),
+                        max.as_string()
+                    ));
+                }
+
+                let index = if number < min {
+                    1
+                } else if number > max {
+                    2
+                } else {
+                    0
+                };
+
+                let value = args[index]
+                    .get_val()
+                    .ok_or("clamp expects numbers".to_string())?;
+
+                interpreter_stack.push_val(value);
+                Ok(None)
+            }
+
+            BuiltinFunction::CoalesceTo => {
+                let value = match args.first().and_then(|x| x.get_val()) {
+                    Some(TypeAnnotatedValue::Option(option)) => {
+                        option.value.and_then(|x| x.type_annotated_value)
+                    }
+                    other => other,
+                };
+
+                let number = match value {
+                    Some(value) => number_of(&builtin, &value)?,
+                    None => {
+                        let default = args
+                            .get(1)
+                            .and_then(|x| x.get_val())
+                            .ok_or("coalesceTo expects a default".to_string())?;
+                        number_of(&builtin, &default)?
+                    }
+                };
+
+                interpreter_stack.push_val(TypeAnnotatedValue::F64(number));
+                Ok(None)
+            }
+
+            BuiltinFunction::Range => {
+                let (start, end) = match (args[0].get_val(), args[1].get_val()) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => return Err("range expects numbers as its bounds".to_string()),
+                };
+                let inclusive = args[2]
+                    .get_bool()
+                    .ok_or("range expects whether its end is included".to_string())?;
+
+                match (start.get_literal(), end.get_literal()) {
+                    (Some(start @ LiteralValue::Num(_)), Some(end @ LiteralValue::Num(_))) => {
+                        if start > end {
+                            return Err(format!(
+                                "The start of the range ({}) is greater than its end ({})",
+                                start.as_string(),
+                                end.as_string()
+                            ));
+                        }
+                    }
+                    _ => {
+                        return Err(format!(
+                            "range expects numbers as its bounds, but found {:?} and {:?}",
+                            start, end
+                        ))
+                    }
+                }
+
+                interpreter_stack.push_val(range_value(start, end, inclusive)?);
+                Ok(None)
+            }
+
+            BuiltinFunction::InRange => {
+                if let Some(TypeAnnotatedValue::List(list)) = args[1].get_val() {
+                    let value = args[0]
+                        .get_val()
+                        .ok_or("inRange expects a value".to_string())?;
+
+                    let in_list = list
+                        .values
+                        .into_iter()
+                        .filter_map(|element| element.type_annotated_value)
+                        .any(
+                            |element| match (element.get_literal(), value.get_literal()) {
+                                (Some(element), Some(value)) => element == value,
+                                _ => element == value,
+                            },
+                        );
+
+                    interpreter_stack.push_val(TypeAnnotatedValue::Bool(in_list));
+                    return Ok(None);
+                }
+
+                let value = match args[0].get_literal() {
+                    Some(value @ LiteralValue::Num(_)) => value,
+                    _ => {
+                        return Err(format!(
+                            "inRange expects a number, but found {:?}",
+                            args[0].get_val()
+                        ))
+                    }
+                };
+                let (start, end, inclusive) = match args[1].get_val() {
+                    Some(TypeAnnotatedValue::Record(range)) => range_bounds(&range)?,
+                    other => {
+                        return Err(format!(
+                            "inRange expects a range or a list, but found {:?}",
+                            other
+                        ))
+                    }
+                };
+
+                let in_range = value >= start && if inclusive { value <= end } else { value < end };
+
+                interpreter_stack.push_val(TypeAnnotatedValue::Bool(in_range));
+                Ok(None)
+            }
+        }
+    }
+
+    // A number, or a string of a number (Ex: a query parameter), as a floating point number
+    fn number_of(builtin: &BuiltinFunction, value: &TypeAnnotatedValue) -> Result<f64, String> {
+        match value.get_literal() {
+            Some(LiteralValue::Num(CoercedNumericValue::PosInt(value))) => Ok(value as f64),
+            Some(LiteralValue::Num(CoercedNumericValue::NegInt(value))) => Ok(value as f64),
+            Some(LiteralValue::Num(CoercedNumericValue::Float(value))) => Ok(value),
+            Some(LiteralValue::String(string)) => string
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("{} expects a number, but found \"{}\"", builtin, string)),
+            _ => Err(format!(
+                "{} expects a number, but found {:?}",
+                builtin, value
+            )),
+        }
+    }
+
+    // The value of a range, a record of its bounds and whether the end is included
+    fn range_value(
+        start: TypeAnnotatedValue,
+        end: TypeAnnotatedValue,
+        inclusive: bool,
+    ) -> Result<TypeAnnotatedValue, String> {
+        let bound_type = AnalysedType::try_from(&start)
+            .map_err(|_| "range expects numbers as its bounds".to_string())?;
+
+        let field = |name: &str, value: TypeAnnotatedValue| NameValuePair {
+            name: name.to_string(),
+            value: Some(golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+                type_annotated_value: Some(value),
+            }),
+        };
+        let field_type =
+            |name: &str, typ: &AnalysedType| golem_wasm_ast::analysis::protobuf::NameTypePair {
+                name: name.to_string(),
+                typ: Some(golem_wasm_ast::analysis::protobuf::Type::from(typ)),
+            };
+
+        Ok(TypeAnnotatedValue::Record(TypedRecord {
+            value: vec![
+                field("start", start),
+                field("end", end),
+                field("inclusive", TypeAnnotatedValue::Bool(inclusive)),
+            ],
+            typ: vec![
+                field_type("start", &bound_type),
+                field_type("end", &bound_type),
+                field_type("inclusive", &analysed_type::bool()),
+            ],
+        }))
+    }
+
+    fn range_bounds(range: &TypedRecord) -> Result<(LiteralValue, LiteralValue, bool), String> {
+        let field = |name: &str| {
+            range
+                .value
+                .iter()
+                .find(|field| field.name == name)
+                .and_then(|field| field.value.clone())
+                .and_then(|value| value.type_annotated_value)
+                .ok_or(format!("inRange expects a range, which has no `{}`", name))
+        };
+
+        let bound = |name: &str| match field(name)?.get_literal() {
+            Some(bound @ LiteralValue::Num(_)) => Ok(bound),
+            _ => Err(format!("The `{}` of the range is not a number", name)),
+        };
+
+        let inclusive = match field("inclusive")? {
+            TypeAnnotatedValue::Bool(inclusive) => inclusive,
+            _ => return Err("The `inclusive` of the range is not a bool".to_string()),
+        };
+
+        Ok((bound("start")?, bound("end")?, inclusive))
+    }
+
+    // The type of a JSON value which is not typed by the expression (Ex: the result of `parseJson`).
+    // An array has the type of its first element (or a list of strings if empty)
+    fn json_type(json: &serde_json::Value) -> AnalysedType {
+        match json {
+            serde_json::Value::Null => analysed_type::option(analysed_type::str()),
+            serde_json::Value::Bool(_) => analysed_type::bool(),
+            serde_json::Value::Number(_) => analysed_type::f64(),
+            serde_json::Value::String(_) => analysed_type::str(),
+            serde_json::Value::Array(elements) => {
+                analysed_type::list(elements.first().map_or(analysed_type::str(), json_type))
+            }
+            serde_json::Value::Object(fields) => analysed_type::record(
+                fields
+                    .iter()
+                    .map(|(name, value)| analysed_type::field(name, json_type(value)))
+                    .collect(),
+            ),
+        }
+    }
+
     pub(crate) fn run_concat_instruction(
         interpreter_stack: &mut InterpreterStack,
         arg_size: usize,
@@ -1009,9 +1670,12 @@ mod internal {
 mod interpreter_tests {
     use super::*;
     use crate::{InstructionId, VariableId};
-    use golem_wasm_ast::analysis::analysed_type::{field, list, record, s32};
+    use golem_wasm_ast::analysis::analysed_type::{
+        f64, field, list, option, record, s32, str, u32, u64,
+    };
+    use golem_wasm_ast::analysis::AnalysedType;
     use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
-    use golem_wasm_rpc::protobuf::{NameValuePair, TypedList, TypedRecord};
+    use golem_wasm_rpc::protobuf::{NameValuePair, TypedList, TypedOption, TypedRecord};
 
     #[tokio::test]
     async fn test_interpreter_for_literal() {
@@ -1041,6 +1705,126 @@ mod interpreter_tests {
         assert!(result.get_bool().unwrap());
     }
 
+    async fn equal(
+        left: (AnalysedType, &str),
+        right: (AnalysedType, &str),
+    ) -> Result<bool, String> {
+        let left = golem_wasm_rpc::type_annotated_value_from_str(&left.0, left.1).unwrap();
+        let right = golem_wasm_rpc::type_annotated_value_from_str(&right.0, right.1).unwrap();
+
+        let mut interpreter = Interpreter::default();
+
+        let instructions = RibByteCode {
+            instructions: vec![RibIR::PushLit(right), RibIR::PushLit(left), RibIR::EqualTo],
+        };
+
+        interpreter
+            .run(instructions)
+            .await
+            .map(|result| result.get_bool().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_equal_to_of_lists() {
+        // The items are compared in order
+        let cases = [
+            ("[1, 2, 3]", "[1, 2, 3]", true),
+            ("[]", "[]", true),
+            ("[1, 2, 3]", "[3, 2, 1]", false),
+            ("[1, 2]", "[1, 2, 3]", false),
+        ];
+
+        for (left, right, expected) in cases {
+            let result = equal((list(u64()), left), (list(u64()), right)).await;
+            assert_eq!(result, Ok(expected), "{} == {}", left, right);
+        }
+
+        let nested = list(list(str()));
+        let result = equal(
+            (nested.clone(), r#"[["a"], ["b", "c"]]"#),
+            (nested.clone(), r#"[["a"], ["b", "c"]]"#),
+        )
+        .await;
+        assert_eq!(result, Ok(true));
+
+        let result = equal(
+            (nested.clone(), r#"[["a"], ["b", "c"]]"#),
+            (nested, r#"[["a"], ["c", "b"]]"#),
+        )
+        .await;
+        assert_eq!(result, Ok(false));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_equal_to_of_records() {
+        let x_y = record(vec![
+            field("x", u64()),
+            field("y", record(vec![field("tags", list(str()))])),
+        ]);
+        let y_x = record(vec![
+            field("y", record(vec![field("tags", list(str()))])),
+            field("x", u64()),
+        ]);
+
+        // The order of the fields doesn't matter
+        let result = equal(
+            (x_y.clone(), r#"{x: 1, y: {tags: ["a", "b"]}}"#),
+            (y_x.clone(), r#"{y: {tags: ["a", "b"]}, x: 1}"#),
+        )
+        .await;
+        assert_eq!(result, Ok(true));
+
+        let result = equal(
+            (x_y.clone(), r#"{x: 1, y: {tags: ["a", "b"]}}"#),
+            (y_x, r#"{y: {tags: ["b", "a"]}, x: 1}"#),
+        )
+        .await;
+        assert_eq!(result, Ok(false));
+
+        let result = equal(
+            (x_y, r#"{x: 1, y: {tags: []}}"#),
+            (record(vec![field("x", u64())]), "{x: 1}"),
+        )
+        .await;
+        assert_eq!(result, Ok(false));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_equal_to_of_request_lists() {
+        let expr = r#"
+           let a: list<u64> = request.body.a;
+           let b: list<u64> = request.body.b;
+           if a == b then "same" else "different"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field(
+            "body",
+            record(vec![field("a", list(u64())), field("b", list(u64()))]),
+        )]);
+
+        for (body, expected) in [
+            ("{body: {a: [1, 2], b: [1, 2]}}", "same"),
+            ("{body: {a: [1, 2], b: [2, 1]}}", "different"),
+        ] {
+            let request =
+                golem_wasm_rpc::type_annotated_value_from_str(&request_type, body).unwrap();
+
+            let mut interpreter =
+                Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+            let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string()),
+                "{}",
+                body
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_interpreter_for_greater_than() {
         let mut interpreter = Interpreter::default();
@@ -1156,30 +1940,146 @@ mod interpreter_tests {
         assert!(result.is_err());
     }
 
-    #[tokio::test]
-    async fn test_interpreter_for_record() {
-        let mut interpreter = Interpreter::default();
+    fn if_else_byte_code(predicate: TypeAnnotatedValue) -> RibByteCode {
+        let else_id = InstructionId::init().increment_mut();
+        let end_id = else_id.increment();
 
-        let instructions = RibByteCode {
+        RibByteCode {
             instructions: vec![
-                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
-                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
-                RibIR::CreateAndPushRecord(record(vec![field("x", s32()), field("y", s32())])),
-                RibIR::UpdateRecord("x".to_string()),
-                RibIR::UpdateRecord("y".to_string()),
+                RibIR::PushLit(predicate),
+                RibIR::JumpIfFalse(else_id.clone()),
+                RibIR::PushLit(TypeAnnotatedValue::Str("then".to_string())),
+                RibIR::Jump(end_id.clone()),
+                RibIR::Label(else_id),
+                RibIR::PushLit(TypeAnnotatedValue::Str("else".to_string())),
+                RibIR::Label(end_id),
             ],
-        };
+        }
+    }
 
-        let result = interpreter.run(instructions).await.unwrap();
-        let expected = TypeAnnotatedValue::Record(TypedRecord {
-            value: vec![
-                NameValuePair {
-                    name: "x".to_string(),
-                    value: Some(golem_wasm_rpc::protobuf::TypeAnnotatedValue {
-                        type_annotated_value: Some(TypeAnnotatedValue::S32(1)),
-                    }),
-                },
-                NameValuePair {
+    #[tokio::test]
+    async fn test_interpreter_for_string_predicate_in_strict_mode() {
+        let mut interpreter = Interpreter::default();
+
+        let byte_code = if_else_byte_code(TypeAnnotatedValue::Str("hello".to_string()));
+
+        let result = interpreter.run(byte_code).await;
+        assert!(result.unwrap_err().starts_with("Non-boolean predicate"));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_string_predicate_in_lenient_mode() {
+        let byte_code = if_else_byte_code(TypeAnnotatedValue::Str("hello".to_string()));
+        let mut interpreter = Interpreter::default().with_predicate_mode(PredicateMode::Lenient);
+        let result = interpreter.run(byte_code).await.unwrap();
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("then".to_string())
+        );
+
+        let byte_code = if_else_byte_code(TypeAnnotatedValue::Str("".to_string()));
+        let mut interpreter = Interpreter::default().with_predicate_mode(PredicateMode::Lenient);
+        let result = interpreter.run(byte_code).await.unwrap();
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("else".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_truthy_predicates_in_lenient_mode() {
+        let cases = vec![
+            (TypeAnnotatedValue::Str("true".to_string()), "then"),
+            (TypeAnnotatedValue::U32(1), "then"),
+            (TypeAnnotatedValue::U32(0), "else"),
+            (
+                TypeAnnotatedValue::Option(Box::new(TypedOption {
+                    typ: Some(golem_wasm_ast::analysis::protobuf::Type::from(&str())),
+                    value: None,
+                })),
+                "else",
+            ),
+            (
+                TypeAnnotatedValue::List(TypedList {
+                    typ: Some(golem_wasm_ast::analysis::protobuf::Type::from(&str())),
+                    values: vec![],
+                }),
+                "else",
+            ),
+        ];
+
+        for (predicate, expected) in cases {
+            let mut interpreter =
+                Interpreter::default().with_predicate_mode(PredicateMode::Lenient);
+            let result = interpreter
+                .run(if_else_byte_code(predicate.clone()))
+                .await
+                .unwrap();
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string()),
+                "predicate {:?}",
+                predicate
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_truthy_operands_in_lenient_mode() {
+        let mut interpreter = Interpreter::default().with_predicate_mode(PredicateMode::Lenient);
+
+        let byte_code = RibByteCode {
+            instructions: vec![
+                RibIR::PushLit(TypeAnnotatedValue::U32(1)),
+                RibIR::PushLit(TypeAnnotatedValue::Str("".to_string())),
+                RibIR::Negate,
+                RibIR::And,
+            ],
+        };
+
+        let result = interpreter.run(byte_code).await.unwrap();
+        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::Bool(true));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_boolean_predicate_in_both_modes() {
+        for mode in [PredicateMode::Strict, PredicateMode::Lenient] {
+            let mut interpreter = Interpreter::default().with_predicate_mode(mode);
+            let result = interpreter
+                .run(if_else_byte_code(TypeAnnotatedValue::Bool(false)))
+                .await
+                .unwrap();
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str("else".to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_record() {
+        let mut interpreter = Interpreter::default();
+
+        let instructions = RibByteCode {
+            instructions: vec![
+                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
+                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
+                RibIR::CreateAndPushRecord(record(vec![field("x", s32()), field("y", s32())])),
+                RibIR::UpdateRecord("x".to_string()),
+                RibIR::UpdateRecord("y".to_string()),
+            ],
+        };
+
+        let result = interpreter.run(instructions).await.unwrap();
+        let expected = TypeAnnotatedValue::Record(TypedRecord {
+            value: vec![
+                NameValuePair {
+                    name: "x".to_string(),
+                    value: Some(golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+                        type_annotated_value: Some(TypeAnnotatedValue::S32(1)),
+                    }),
+                },
+                NameValuePair {
                     name: "y".to_string(),
                     value: Some(golem_wasm_rpc::protobuf::TypeAnnotatedValue {
                         type_annotated_value: Some(TypeAnnotatedValue::S32(2)),
@@ -1201,65 +2101,1100 @@ mod interpreter_tests {
     }
 
     #[tokio::test]
-    async fn test_interpreter_for_sequence() {
+    async fn test_interpreter_for_sequence() {
+        let mut interpreter = Interpreter::default();
+
+        let instructions = RibByteCode {
+            instructions: vec![
+                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
+                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
+                RibIR::PushList(list(s32()), 2),
+            ],
+        };
+
+        let result = interpreter.run(instructions).await.unwrap();
+        let expected = TypeAnnotatedValue::List(TypedList {
+            values: vec![
+                golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+                    type_annotated_value: Some(TypeAnnotatedValue::S32(1)),
+                },
+                golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+                    type_annotated_value: Some(TypeAnnotatedValue::S32(2)),
+                },
+            ],
+            typ: Some(golem_wasm_ast::analysis::protobuf::Type::from(&s32())),
+        });
+        assert_eq!(result.get_val().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_sequence_with_invalid_element() {
+        let mut interpreter = Interpreter::default();
+
+        let instructions = RibByteCode {
+            instructions: vec![
+                RibIR::PushLit(TypeAnnotatedValue::Str("two".to_string())),
+                RibIR::PushLit(TypeAnnotatedValue::U32(1)),
+                RibIR::PushList(list(u32()), 2),
+            ],
+        };
+
+        let result = interpreter.run(instructions).await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid list: [1]: expected u32, but found str \"two\"".to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_select_field() {
+        let mut interpreter = Interpreter::default();
+
+        let instructions = RibByteCode {
+            instructions: vec![
+                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
+                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
+                RibIR::CreateAndPushRecord(record(vec![field("x", s32())])),
+                RibIR::UpdateRecord("x".to_string()),
+                RibIR::SelectField("x".to_string()),
+            ],
+        };
+
+        let result = interpreter.run(instructions).await.unwrap();
+        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::S32(2));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_select_index() {
+        let mut interpreter = Interpreter::default();
+
+        let instructions = RibByteCode {
+            instructions: vec![
+                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
+                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
+                RibIR::PushList(list(s32()), 2),
+                RibIR::SelectIndex(0),
+            ],
+        };
+
+        let result = interpreter.run(instructions).await.unwrap();
+        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::S32(2));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_let_in_binding_reused() {
+        let mut interpreter = Interpreter::default();
+
+        let expr = r#"
+           let x: u64 = 2 in if x > 1u64 then x else 0u64
+        "#;
+
+        let mut expr = crate::Expr::from_text(expr).unwrap();
+        expr.infer_types(&crate::FunctionTypeRegistry::empty())
+            .unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::U64(2));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_trace_records_predicate() {
+        let expr = r#"
+           if request.path.user > 1u64 then "admin" else "user"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field("path", record(vec![field("user", u64())]))]);
+        let request =
+            golem_wasm_rpc::type_annotated_value_from_str(&request_type, "{path: {user: 2}}")
+                .unwrap();
+
+        let mut interpreter =
+            Interpreter::pure(HashMap::from([("request".to_string(), request)])).with_trace();
+        let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("admin".to_string())
+        );
+
+        let predicate_index = compiled
+            .byte_code
+            .instructions
+            .iter()
+            .position(|instruction| instruction == &RibIR::GreaterThan)
+            .unwrap();
+
+        let trace = interpreter.trace.unwrap();
+        let predicate = trace
+            .iter()
+            .find(|entry| entry.instruction_index == predicate_index)
+            .unwrap();
+
+        assert_eq!(
+            predicate.value,
+            RibInterpreterResult::Val(TypeAnnotatedValue::Bool(true))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_reject_on_missing_header() {
+        let expr = r#"
+           if !isPresent(request.headers.auth) then reject(401, "no auth") else "authorized"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field(
+            "headers",
+            record(vec![field("auth", option(str()))]),
+        )]);
+
+        let without_auth =
+            golem_wasm_rpc::type_annotated_value_from_str(&request_type, "{headers: {auth: none}}")
+                .unwrap();
+
+        let mut interpreter =
+            Interpreter::pure(HashMap::from([("request".to_string(), without_auth)]));
+        let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+        assert_eq!(
+            result,
+            RibInterpreterResult::Reject {
+                status: 401,
+                message: "no auth".to_string()
+            }
+        );
+
+        let with_auth = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            r#"{headers: {auth: some("secret")}}"#,
+        )
+        .unwrap();
+
+        let mut interpreter =
+            Interpreter::pure(HashMap::from([("request".to_string(), with_auth)]));
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("authorized".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_comparison_with_null() {
+        let expr = r#"
+           if request.query.cursor == null then "first page" else "next page"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field(
+            "query",
+            record(vec![field("cursor", option(str()))]),
+        )]);
+
+        for (query, expected) in [
+            ("{query: {cursor: none}}", "first page"),
+            (r#"{query: {cursor: some("abc")}}"#, "next page"),
+            (r#"{query: {cursor: some("")}}"#, "next page"),
+        ] {
+            let request =
+                golem_wasm_rpc::type_annotated_value_from_str(&request_type, query).unwrap();
+
+            let mut interpreter =
+                Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+            let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string()),
+                "{}",
+                query
+            );
+        }
+    }
+
+    // The request has no body, so evaluating the right operands (which select it) fails
+    fn request_without_body(auth: &str) -> HashMap<String, TypeAnnotatedValue> {
+        let request_type = record(vec![field(
+            "headers",
+            record(vec![field("auth", option(str()))]),
+        )]);
+
+        let request = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            &format!("{{headers: {{auth: {}}}}}", auth),
+        )
+        .unwrap();
+
+        HashMap::from([("request".to_string(), request)])
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_and_short_circuit() {
+        let expr = r#"
+           if isPresent(request.headers.auth) && isPresent(request.body.user) then "yes" else "no"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::pure(request_without_body("none"));
+        let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("no".to_string())
+        );
+
+        // The right operand is evaluated if the left one is true
+        let mut interpreter = Interpreter::pure(request_without_body(r#"some("secret")"#));
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_or_short_circuit() {
+        let expr = r#"
+           if isPresent(request.headers.auth) || isPresent(request.body.user) then "yes" else "no"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::pure(request_without_body(r#"some("secret")"#));
+        let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("yes".to_string())
+        );
+
+        // The right operand is evaluated if the left one is false
+        let mut interpreter = Interpreter::pure(request_without_body("none"));
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_coalesce() {
+        let expr = r#"
+           coalesce(request.headers.x-real-ip, request.headers.x-forwarded-for, "unknown")
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field(
+            "headers",
+            record(vec![
+                field("x-real-ip", option(str())),
+                field("x-forwarded-for", option(str())),
+            ]),
+        )]);
+
+        let cases = vec![
+            (
+                r#"{headers: {x-real-ip: some("10.0.0.1"), x-forwarded-for: some("10.0.0.2")}}"#,
+                "10.0.0.1",
+            ),
+            (
+                r#"{headers: {x-real-ip: none, x-forwarded-for: some("10.0.0.2")}}"#,
+                "10.0.0.2",
+            ),
+            (
+                r#"{headers: {x-real-ip: none, x-forwarded-for: none}}"#,
+                "unknown",
+            ),
+        ];
+
+        for (request, expected) in cases {
+            let request =
+                golem_wasm_rpc::type_annotated_value_from_str(&request_type, request).unwrap();
+
+            let mut interpreter =
+                Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+            let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_has_field() {
+        let cases = vec![
+            (r#"hasField(body, "coupon")"#, true),
+            (r#"hasField(body, "voucher")"#, false),
+        ];
+
+        for (condition, expected) in cases {
+            let expr = format!(
+                r#"
+                  let body = {{coupon: "SAVE10", total: "100"}};
+                  {}
+                "#,
+                condition
+            );
+
+            let expr = crate::Expr::from_text(&expr).unwrap();
+            let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+            let mut interpreter = Interpreter::default();
+            let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Bool(expected)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_has_field_of_request_body() {
+        let expr = r#"
+           let total: str = request.body.total;
+           if hasField(request.body, "coupon") then "discounted ${total}" else total
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        // The actual body can have more fields than the ones used in the expression
+        let cases = vec![
+            (
+                record(vec![field("total", str()), field("coupon", str())]),
+                r#"{body: {total: "100", coupon: "SAVE10"}}"#,
+                "discounted 100",
+            ),
+            (
+                record(vec![field("total", str())]),
+                r#"{body: {total: "100"}}"#,
+                "100",
+            ),
+        ];
+
+        for (body_type, request, expected) in cases {
+            let request_type = record(vec![field("body", body_type)]);
+            let request =
+                golem_wasm_rpc::type_annotated_value_from_str(&request_type, request).unwrap();
+
+            let mut interpreter =
+                Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+            let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_safe_navigation() {
+        let expr = r#"
+           request.body?.user?.name ?? "anonymous"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        // The chain is broken if any of its fields is missing
+        let cases = vec![
+            (
+                record(vec![field(
+                    "user",
+                    record(vec![field("name", str()), field("id", str())]),
+                )]),
+                r#"{body: {user: {name: "alice", id: "1"}}}"#,
+                "alice",
+            ),
+            (
+                record(vec![field("user", record(vec![field("id", str())]))]),
+                r#"{body: {user: {id: "1"}}}"#,
+                "anonymous",
+            ),
+            (
+                record(vec![field("id", str())]),
+                r#"{body: {id: "1"}}"#,
+                "anonymous",
+            ),
+        ];
+
+        for (body_type, request, expected) in cases {
+            let request_type = record(vec![field("body", body_type)]);
+            let request =
+                golem_wasm_rpc::type_annotated_value_from_str(&request_type, request).unwrap();
+
+            let mut interpreter =
+                Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+            let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_safe_navigation_mixed_with_strict_selection() {
+        let expr = r#"
+           request.body.user?.address.city ?? "unknown"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let cases = vec![
+            (
+                record(vec![field("address", record(vec![field("city", str())]))]),
+                r#"{body: {user: {address: {city: "Oslo"}}}}"#,
+                "Oslo",
+            ),
+            (
+                record(vec![field("name", str())]),
+                r#"{body: {user: {name: "alice"}}}"#,
+                "unknown",
+            ),
+        ];
+
+        for (user_type, request, expected) in cases {
+            let request_type = record(vec![field("body", record(vec![field("user", user_type)]))]);
+            let request =
+                golem_wasm_rpc::type_annotated_value_from_str(&request_type, request).unwrap();
+
+            let mut interpreter =
+                Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+            let result = interpreter.run(compiled.byte_code.clone()).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string())
+            );
+        }
+
+        // The strict selection still fails if the body has no user
+        let request_type = record(vec![field("body", record(vec![field("id", str())]))]);
+        let request =
+            golem_wasm_rpc::type_annotated_value_from_str(&request_type, r#"{body: {id: "1"}}"#)
+                .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        assert!(interpreter.run(compiled.byte_code).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_format() {
+        let expr = r#"
+           format("user-{}-region-{}", request.path.id, request.path.region)
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field(
+            "path",
+            record(vec![field("id", str()), field("region", str())]),
+        )]);
+        let request = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            r#"{path: {id: "42", region: "eu"}}"#,
+        )
+        .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("user-42-region-eu".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_format_with_escaped_braces() {
+        let expr = crate::Expr::from_text(r#"format("{{id: {}}}", "42")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("{id: 42}".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_format_with_count_mismatch() {
+        // The template is a literal, and therefore the mismatch is found when compiling
+        let expr = crate::Expr::from_text(r#"format("user-{}-region-{}", "42")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]);
+        assert!(compiled.is_err());
+
+        // Otherwise, it's found when evaluating
+        let expr = crate::Expr::from_text(r#"format(request.query.template, "42")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field("query", record(vec![field("template", str())]))]);
+        let request = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            r#"{query: {template: "{}-{}"}}"#,
+        )
+        .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert!(result
+            .unwrap_err()
+            .contains("has 2 placeholders, but 1 arguments are provided"));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_has_field_of_non_record() {
+        let expr = r#"
+           let x: u64 = 1;
+           hasField(x, "coupon")
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert!(result.unwrap_err().starts_with("hasField expects a record"));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_rounding() {
+        let cases = vec![
+            ("round(2.4)", 2),
+            ("round(2.5)", 3),
+            ("round(-2.5)", -3),
+            ("round(-2.4)", -2),
+            ("floor(2.7)", 2),
+            ("floor(-2.2)", -3),
+            ("ceil(2.2)", 3),
+            ("ceil(-2.7)", -2),
+            ("floor(7)", 7),
+        ];
+
+        for (expr, expected) in cases {
+            let expr = crate::Expr::from_text(expr).unwrap();
+            let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+            let mut interpreter = Interpreter::default();
+            let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+            assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::S64(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_rounding_request_values() {
+        let expr = r#"
+           let page: s64 = floor(request.query.page);
+           page
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field("query", record(vec![field("page", f64())]))]);
+        let request =
+            golem_wasm_rpc::type_annotated_value_from_str(&request_type, "{query: {page: 12.9}}")
+                .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::S64(12));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_rounding_non_number() {
+        let expr = crate::Expr::from_text(r#"ceil("12.5")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert!(result.unwrap_err().starts_with("ceil expects a number"));
+    }
+
+    async fn run_with_request(
+        expr: &str,
+        request_type: &AnalysedType,
+        request: &str,
+    ) -> Result<RibInterpreterResult, String> {
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request = golem_wasm_rpc::type_annotated_value_from_str(request_type, request).unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        interpreter.run(compiled.byte_code).await
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_clamp() {
+        let request_type = record(vec![field("query", record(vec![field("limit", f64())]))]);
+
+        let cases = vec![
+            ("{query: {limit: 0.0}}", 1.0),
+            ("{query: {limit: 50.0}}", 50.0),
+            ("{query: {limit: 500.0}}", 100.0),
+        ];
+
+        for (request, expected) in cases {
+            let result =
+                run_with_request("clamp(request.query.limit, 1, 100)", &request_type, request)
+                    .await
+                    .unwrap();
+
+            assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::F64(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_clamp_with_min_above_max() {
+        let request_type = record(vec![field("query", record(vec![field("limit", f64())]))]);
+
+        let result = run_with_request(
+            "clamp(request.query.limit, 100, 1)",
+            &request_type,
+            "{query: {limit: 50.0}}",
+        )
+        .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "clamp expects the minimum (100) to be at most the maximum (1)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_range_membership() {
+        let request_type = record(vec![field("query", record(vec![field("page", f64())]))]);
+
+        let cases = vec![
+            ("1..=100", 100.0, "yes"),
+            ("1..100", 100.0, "no"),
+            ("1..100", 1.0, "yes"),
+            ("1..=100", 0.0, "no"),
+            ("1..100", 99.5, "yes"),
+            ("5..5", 5.0, "no"),
+            ("5..=5", 5.0, "yes"),
+        ];
+
+        for (range, page, expected) in cases {
+            let result = run_with_request(
+                &format!(r#"if request.query.page in {} then "yes" else "no""#, range),
+                &request_type,
+                &format!("{{query: {{page: {:?}}}}}", page),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string()),
+                "{} in {}",
+                page,
+                range
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_list_membership() {
+        let request_type = record(vec![field("method", str())]);
+
+        let cases = vec![("GET", "yes"), ("HEAD", "yes"), ("POST", "no")];
+
+        for (method, expected) in cases {
+            let result = run_with_request(
+                r#"if request.method in ["GET", "HEAD"] then "yes" else "no""#,
+                &request_type,
+                &format!("{{method: {:?}}}", method),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str(expected.to_string()),
+                "{}",
+                method
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_range_value() {
+        let request_type = record(vec![field("query", record(vec![field("page", f64())]))]);
+
+        let result = run_with_request(
+            "let pages = 1..=100; request.query.page in pages",
+            &request_type,
+            "{query: {page: 100.0}}",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::Bool(true));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_reversed_range() {
+        let request_type = record(vec![field("query", record(vec![field("page", f64())]))]);
+
+        let result = run_with_request(
+            "request.query.page in 10..1",
+            &request_type,
+            "{query: {page: 5.0}}",
+        )
+        .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "The start of the range (10) is greater than its end (1)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_coalesce_to() {
+        let request_type = record(vec![field(
+            "query",
+            record(vec![field("limit", option(str()))]),
+        )]);
+
+        let cases = vec![
+            ("{query: {limit: none}}", 20.0),
+            (r#"{query: {limit: some("35")}}"#, 35.0),
+        ];
+
+        for (request, expected) in cases {
+            let result = run_with_request(
+                "coalesceTo(request.query.limit, 20)",
+                &request_type,
+                request,
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::F64(expected));
+        }
+
+        let result = run_with_request(
+            "coalesceTo(request.query.limit, 20)",
+            &request_type,
+            r#"{query: {limit: some("many")}}"#,
+        )
+        .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "coalesceTo expects a number, but found \"many\""
+        );
+    }
+
+    fn str_list(values: Vec<&str>) -> TypeAnnotatedValue {
+        TypeAnnotatedValue::List(TypedList {
+            values: values
+                .into_iter()
+                .map(|value| golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+                    type_annotated_value: Some(TypeAnnotatedValue::Str(value.to_string())),
+                })
+                .collect(),
+            typ: Some((&str()).into()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_split() {
+        let cases = vec![
+            (
+                r#"split("en-US,en;q=0.9,de", ",")"#,
+                vec!["en-US", "en;q=0.9", "de"],
+            ),
+            (r#"split("a::b::c", "::")"#, vec!["a", "b", "c"]),
+            (r#"split("a,,b,", ",")"#, vec!["a", "", "b", ""]),
+            (r#"split("en", ",")"#, vec!["en"]),
+            (r#"split("", ",")"#, vec![""]),
+        ];
+
+        for (expr, expected) in cases {
+            let expr = crate::Expr::from_text(expr).unwrap();
+            let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+            let mut interpreter = Interpreter::default();
+            let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+            assert_eq!(result.get_val().unwrap(), str_list(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_split_request_header() {
+        let expr = r#"
+           let languages = split(request.headers.accept-language, ",");
+           languages[0]
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field(
+            "headers",
+            record(vec![field("accept-language", str())]),
+        )]);
+
+        let request = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            r#"{headers: {accept-language: "de-CH,de;q=0.9,en;q=0.8"}}"#,
+        )
+        .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("de-CH".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_split_with_empty_separator() {
+        let expr = crate::Expr::from_text(r#"split("abc", "")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "split expects a non-empty separator".to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_size() {
+        let expr = r#"
+           let tags = request.path.tag;
+           { size: size(tags), parts: size(split("a,b,c", ",")) }
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field("path", record(vec![field("tag", list(str()))]))]);
+
+        let request = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            r#"{path: {tag: ["a", "b"]}}"#,
+        )
+        .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        let expected = golem_wasm_rpc::type_annotated_value_from_str(
+            &record(vec![field("size", u64()), field("parts", u64())]),
+            "{size: 2, parts: 3}",
+        )
+        .unwrap();
+
+        assert_eq!(result.get_val().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_base64_round_trip() {
+        let expr = r#"
+           let cursor = base64Encode("page=2?&size=10");
+           { cursor: cursor, decoded: base64Decode(cursor) }
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
         let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
 
-        let instructions = RibByteCode {
-            instructions: vec![
-                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
-                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
-                RibIR::PushList(list(s32()), 2),
-            ],
-        };
+        let expected = golem_wasm_rpc::type_annotated_value_from_str(
+            &record(vec![field("cursor", str()), field("decoded", str())]),
+            r#"{cursor: "cGFnZT0yPyZzaXplPTEw", decoded: "page=2?&size=10"}"#,
+        )
+        .unwrap();
 
-        let result = interpreter.run(instructions).await.unwrap();
-        let expected = TypeAnnotatedValue::List(TypedList {
-            values: vec![
-                golem_wasm_rpc::protobuf::TypeAnnotatedValue {
-                    type_annotated_value: Some(TypeAnnotatedValue::S32(1)),
-                },
-                golem_wasm_rpc::protobuf::TypeAnnotatedValue {
-                    type_annotated_value: Some(TypeAnnotatedValue::S32(2)),
-                },
-            ],
-            typ: Some(golem_wasm_ast::analysis::protobuf::Type::from(&s32())),
-        });
         assert_eq!(result.get_val().unwrap(), expected);
     }
 
     #[tokio::test]
-    async fn test_interpreter_for_select_field() {
+    async fn test_interpreter_for_base64_decode_with_padding() {
+        let expr = crate::Expr::from_text(r#"base64Decode("b2s=")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
         let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
 
-        let instructions = RibByteCode {
-            instructions: vec![
-                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
-                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
-                RibIR::CreateAndPushRecord(record(vec![field("x", s32())])),
-                RibIR::UpdateRecord("x".to_string()),
-                RibIR::SelectField("x".to_string()),
-            ],
-        };
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("ok".to_string())
+        );
+    }
 
-        let result = interpreter.run(instructions).await.unwrap();
-        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::S32(2));
+    #[tokio::test]
+    async fn test_interpreter_for_base64_decode_of_both_alphabets() {
+        // "???" is "Pz8/" in the standard alphabet, and "Pz8_" in the URL-safe one
+        let expr = r#"{ standard: base64Decode("Pz8/"), urlsafe: base64Decode("Pz8_") }"#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        let expected = golem_wasm_rpc::type_annotated_value_from_str(
+            &record(vec![field("standard", str()), field("urlsafe", str())]),
+            r#"{standard: "???", urlsafe: "???"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(result.get_val().unwrap(), expected);
     }
 
     #[tokio::test]
-    async fn test_interpreter_for_select_index() {
+    async fn test_interpreter_for_base64_decode_invalid_input() {
+        let expr = crate::Expr::from_text(r#"base64Decode("not base64!")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
         let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await;
 
-        let instructions = RibByteCode {
-            instructions: vec![
-                RibIR::PushLit(TypeAnnotatedValue::S32(1)),
-                RibIR::PushLit(TypeAnnotatedValue::S32(2)),
-                RibIR::PushList(list(s32()), 2),
-                RibIR::SelectIndex(0),
-            ],
-        };
+        assert!(result
+            .unwrap_err()
+            .starts_with("base64Decode expects a base64 string"));
+    }
 
-        let result = interpreter.run(instructions).await.unwrap();
-        assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::S32(2));
+    #[tokio::test]
+    async fn test_interpreter_for_parse_json() {
+        let expr = r#"
+           let filter = parseJson(request.query.filter);
+           filter.status.code
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field("query", record(vec![field("filter", str())]))]);
+
+        let request = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            r#"{query: {filter: "{\"status\": {\"code\": \"active\"}, \"limit\": 10}"}}"#,
+        )
+        .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("active".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_select_index_expr() {
+        let expr = r#"
+           let xs = ["a", "b", "c"];
+           xs[request.query.i]
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field("query", record(vec![field("i", u64())]))]);
+
+        let request =
+            golem_wasm_rpc::type_annotated_value_from_str(&request_type, "{query: {i: 2}}")
+                .unwrap();
+
+        let mut interpreter = Interpreter::pure(HashMap::from([("request".to_string(), request)]));
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("c".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_select_index_expr_out_of_range() {
+        let expr = r#"
+           let xs = ["a", "b", "c"];
+           let i: u64 = 5;
+           xs[i]
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert_eq!(result.unwrap_err(), "Index 5 not found in the list");
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_parse_invalid_json() {
+        let expr = crate::Expr::from_text(r#"parseJson("not json")"#).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await;
+
+        assert!(result
+            .unwrap_err()
+            .starts_with("parseJson expects a JSON string"));
+    }
+
+    #[tokio::test]
+    async fn test_interpreter_for_reject_skips_remaining_instructions() {
+        let expr = r#"
+           let x: str = if true then reject(403, "forbidden") else "allowed";
+           "unreachable"
+        "#;
+
+        let expr = crate::Expr::from_text(expr).unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result,
+            RibInterpreterResult::Reject {
+                status: 403,
+                message: "forbidden".to_string()
+            }
+        );
     }
 
     mod pattern_match_tests {
@@ -1289,6 +3224,74 @@ mod interpreter_tests {
             assert_eq!(result.get_val().unwrap(), TypeAnnotatedValue::U64(0));
         }
 
+        #[tokio::test]
+        async fn test_interpreter_for_pattern_match_on_ranges() {
+            // Ranges are half-open, the start is included and the end is not
+            let cases = [
+                (0, "low"),
+                (49, "low"),
+                (50, "mid"),
+                (79, "mid"),
+                (80, "high"),
+                (1000, "high"),
+            ];
+
+            for (score, expected) in cases {
+                let mut interpreter = Interpreter::default();
+
+                let expr = format!(
+                    r#"
+                   let score: u64 = {};
+
+                   match score {{
+                      0..50 => "low",
+                      50..80 => "mid",
+                      _ => "high"
+                   }}
+                "#,
+                    score
+                );
+
+                let mut expr = Expr::from_text(&expr).unwrap();
+                expr.infer_types(&FunctionTypeRegistry::empty()).unwrap();
+                let compiled = compiler::compile(&expr, &vec![]).unwrap();
+                let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+                assert_eq!(
+                    result.get_val().unwrap(),
+                    TypeAnnotatedValue::Str(expected.to_string()),
+                    "score {}",
+                    score
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn test_interpreter_for_pattern_match_on_overlapping_ranges() {
+            // The first matching arm wins
+            let mut interpreter = Interpreter::default();
+
+            let expr = r#"
+           let score: u64 = 45;
+
+           match score {
+              0..50 => "low",
+              40..80 => "mid",
+              _ => "high"
+           }
+        "#;
+
+            let mut expr = Expr::from_text(expr).unwrap();
+            expr.infer_types(&FunctionTypeRegistry::empty()).unwrap();
+            let compiled = compiler::compile(&expr, &vec![]).unwrap();
+            let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str("low".to_string())
+            );
+        }
+
         #[tokio::test]
         async fn test_interpreter_for_pattern_match_on_tuple() {
             let mut interpreter = Interpreter::default();
@@ -1500,6 +3503,75 @@ mod interpreter_tests {
         }
     }
 
+    mod list_argument_tests {
+        use crate::interpreter::env::InterpreterEnv;
+        use crate::interpreter::rib_interpreter::interpreter_tests::internal;
+        use crate::interpreter::stack::InterpreterStack;
+        use crate::{compiler, Expr, Interpreter, PredicateMode};
+        use golem_wasm_ast::analysis::analysed_type::{list, u32};
+        use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+        use golem_wasm_rpc::protobuf::{TypedList, TypedTuple};
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+
+        // An interpreter keeping the arguments of the worker function calls
+        fn capturing_interpreter(arguments: Arc<Mutex<Vec<TypeAnnotatedValue>>>) -> Interpreter {
+            Interpreter {
+                stack: InterpreterStack::default(),
+                env: InterpreterEnv {
+                    env: HashMap::new(),
+                    call_worker_function_async: Arc::new(
+                        move |_, args: Vec<TypeAnnotatedValue>| {
+                            arguments.lock().unwrap().extend(args);
+                            Box::pin(async {
+                                Ok(TypeAnnotatedValue::Tuple(TypedTuple {
+                                    typ: vec![],
+                                    value: vec![],
+                                }))
+                            })
+                        },
+                    ),
+                },
+                trace: None,
+                predicate_mode: PredicateMode::default(),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_interpreter_with_sequence_bound_to_list_parameter() {
+            let expr = Expr::from_text("foo([1, 2, 3])").unwrap();
+            let component_metadata =
+                internal::get_component_metadata("foo", vec![list(u32())], None);
+
+            let compiled = compiler::compile(&expr, &component_metadata).unwrap();
+
+            let arguments = Arc::new(Mutex::new(vec![]));
+            let mut rib_interpreter = capturing_interpreter(arguments.clone());
+            rib_interpreter.run(compiled.byte_code).await.unwrap();
+
+            let expected = TypeAnnotatedValue::List(TypedList {
+                values: [1, 2, 3]
+                    .into_iter()
+                    .map(|value| golem_wasm_rpc::protobuf::TypeAnnotatedValue {
+                        type_annotated_value: Some(TypeAnnotatedValue::U32(value)),
+                    })
+                    .collect(),
+                typ: Some(golem_wasm_ast::analysis::protobuf::Type::from(&u32())),
+            });
+
+            assert_eq!(*arguments.lock().unwrap(), vec![expected]);
+        }
+
+        #[test]
+        fn test_sequence_with_mismatching_element_bound_to_list_parameter() {
+            let expr = Expr::from_text(r#"foo([1, "two", 3])"#).unwrap();
+            let component_metadata =
+                internal::get_component_metadata("foo", vec![list(u32())], None);
+
+            assert!(compiler::compile(&expr, &component_metadata).is_err());
+        }
+    }
+
     mod dynamic_resource_parameter_tests {
         use crate::interpreter::rib_interpreter::interpreter_tests::internal;
         use crate::{compiler, Expr, Interpreter};
@@ -1788,6 +3860,128 @@ mod interpreter_tests {
         }
     }
 
+    mod typed_response_tests {
+        use crate::interpreter::rib_interpreter::interpreter_tests::internal;
+        use crate::{compiler, Expr};
+        use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+
+        #[tokio::test]
+        async fn test_select_field_of_record_response() {
+            let output_analysed_type = internal::get_analysed_type_record();
+
+            let result_value = internal::get_type_annotated_value(
+                &output_analysed_type,
+                r#"{request: {path: {user: "jak"}}, y: "baz"}"#,
+            );
+
+            let mut interpreter =
+                internal::static_test_interpreter(&output_analysed_type, &result_value);
+
+            let analysed_exports = internal::get_component_metadata(
+                "my-worker-function",
+                vec![internal::get_analysed_typ_str()],
+                Some(output_analysed_type),
+            );
+
+            let expr = r#"
+              let result = my-worker-function("foo");
+              result.request.path.user
+            "#;
+
+            let expr = Expr::from_text(expr).unwrap();
+            let compiled = compiler::compile(&expr, &analysed_exports).unwrap();
+            let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str("jak".to_string())
+            );
+        }
+
+        #[tokio::test]
+        async fn test_select_payload_of_variant_response() {
+            let output_analysed_type = internal::get_analysed_type_variant();
+
+            let result_value =
+                internal::get_type_annotated_value(&output_analysed_type, r#"process-user("jak")"#);
+
+            let mut interpreter =
+                internal::static_test_interpreter(&output_analysed_type, &result_value);
+
+            let analysed_exports = internal::get_component_metadata(
+                "my-worker-function",
+                vec![internal::get_analysed_typ_str()],
+                Some(output_analysed_type),
+            );
+
+            let expr = r#"
+              let result = my-worker-function("foo");
+              result.process-user
+            "#;
+
+            let expr = Expr::from_text(expr).unwrap();
+            let compiled = compiler::compile(&expr, &analysed_exports).unwrap();
+            let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+            assert_eq!(
+                result.get_val().unwrap(),
+                TypeAnnotatedValue::Str("jak".to_string())
+            );
+        }
+
+        #[tokio::test]
+        async fn test_select_other_case_of_variant_response() {
+            let output_analysed_type = internal::get_analysed_type_variant();
+
+            let result_value =
+                internal::get_type_annotated_value(&output_analysed_type, r#"register-user(1)"#);
+
+            let mut interpreter =
+                internal::static_test_interpreter(&output_analysed_type, &result_value);
+
+            let analysed_exports = internal::get_component_metadata(
+                "my-worker-function",
+                vec![internal::get_analysed_typ_str()],
+                Some(output_analysed_type),
+            );
+
+            let expr = r#"
+              let result = my-worker-function("foo");
+              result.process-user
+            "#;
+
+            let expr = Expr::from_text(expr).unwrap();
+            let compiled = compiler::compile(&expr, &analysed_exports).unwrap();
+            let result = interpreter.run(compiled.byte_code).await;
+
+            assert_eq!(
+                result,
+                Err(
+                    "Variant case process-user is not the case of the value, which is register-user"
+                        .to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn test_select_unknown_case_of_variant_response() {
+            let analysed_exports = internal::get_component_metadata(
+                "my-worker-function",
+                vec![internal::get_analysed_typ_str()],
+                Some(internal::get_analysed_type_variant()),
+            );
+
+            let expr = r#"
+              let result = my-worker-function("foo");
+              result.delete-user
+            "#;
+
+            let expr = Expr::from_text(expr).unwrap();
+
+            assert!(compiler::compile(&expr, &analysed_exports).is_err());
+        }
+    }
+
     mod internal {
         use crate::interpreter::env::InterpreterEnv;
         use crate::interpreter::stack::InterpreterStack;
@@ -2037,6 +4231,8 @@ mod interpreter_tests {
                     env: HashMap::new(),
                     call_worker_function_async: static_worker_invoke(result_type, result_value),
                 },
+                trace: None,
+                predicate_mode: PredicateMode::default(),
             }
         }
 
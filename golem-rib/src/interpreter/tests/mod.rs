@@ -2059,7 +2059,7 @@ mod comprehensive_test {
         use crate::interpreter::env::InterpreterEnv;
         use crate::interpreter::stack::InterpreterStack;
         use crate::interpreter::tests::comprehensive_test::{mock_data, test_utils};
-        use crate::{Interpreter, RibFunctionInvoke};
+        use crate::{Interpreter, PredicateMode, RibFunctionInvoke};
         use golem_wasm_ast::analysis::{AnalysedType, TypeStr};
         use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
         use golem_wasm_rpc::protobuf::TypedTuple;
@@ -2259,6 +2259,8 @@ mod comprehensive_test {
                     interpreter_env_input,
                     dynamic_worker_invoke(functions_and_result),
                 ),
+                trace: None,
+                predicate_mode: PredicateMode::default(),
             }
         }
 
@@ -12,24 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use builtin::*;
 pub use compiler::*;
+pub use eval_phase::*;
 pub use expr::*;
 pub use function_name::*;
+pub use function_registry::*;
 pub use inferred_type::*;
 pub use interpreter::*;
+pub use lint::*;
+pub use parser::config::{ParserConfig, ParserFeatures};
+pub use parser::source_map::{NodeId, SourceMap, Span};
 pub use parser::type_name::TypeName;
+pub use pipeline::*;
 pub use text::*;
 pub use type_inference::*;
 pub use type_registry::*;
 pub use variable_id::*;
 
+mod builtin;
 mod call_type;
 mod compiler;
+mod eval_phase;
 mod expr;
 mod function_name;
+mod function_registry;
 mod inferred_type;
 mod interpreter;
+mod lint;
 mod parser;
+mod pipeline;
 mod text;
 mod type_inference;
 mod type_refinement;
@@ -0,0 +1,211 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::VecDeque;
+
+use crate::Expr;
+
+// Lints are problems in an expression which don't stop it from compiling or evaluating,
+// but which are most likely a mistake. They are reported as messages, and it is up to
+// the caller to decide whether to warn about them or to reject the expression
+pub fn lint(expr: &Expr) -> Vec<String> {
+    let mut messages = vec![];
+
+    let mut queue = VecDeque::new();
+    queue.push_back(expr);
+
+    while let Some(expr) = queue.pop_back() {
+        if let Expr::PatternMatch(predicate, match_arms, _) = expr {
+            let ranges = match_arms
+                .iter()
+                .filter_map(|arm| internal::numeric_range(&arm.arm_pattern))
+                .collect::<Vec<_>>();
+
+            messages.extend(internal::overlapping_ranges(predicate, &ranges));
+        }
+
+        if let Some(message) = internal::undefined_root_field(expr) {
+            messages.push(message);
+        }
+
+        expr.visit_children_bottom_up(&mut queue);
+    }
+
+    messages
+}
+
+mod internal {
+    use crate::{ArmPattern, Expr, VariableId};
+
+    // The fields of the request. Selecting any other one (Ex: a typo like `request.pat`)
+    // only fails when the request is resolved, or is absent at runtime
+    const REQUEST_FIELDS: [&str; 7] = [
+        "path", "query", "headers", "body", "rawBody", "method", "cookies",
+    ];
+
+    // The fields of the worker (and its response) available to the response mapping
+    const WORKER_FIELDS: [&str; 4] = ["response", "name", "component_id", "idempotency_key"];
+
+    pub(crate) fn undefined_root_field(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::SelectField(record, field, _) => match record.as_ref() {
+                Expr::Identifier(VariableId::Global(name), _) => {
+                    let fields: &[&str] = match name.as_str() {
+                        "request" => &REQUEST_FIELDS,
+                        "worker" => &WORKER_FIELDS,
+                        _ => return None,
+                    };
+
+                    if fields.contains(&field.as_str()) {
+                        None
+                    } else {
+                        Some(format!(
+                            "`{}.{}` is not a field of the {}, which are {}",
+                            name,
+                            field,
+                            name,
+                            fields
+                                .iter()
+                                .map(|field| format!("`{}`", field))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ))
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub(crate) fn numeric_range(arm_pattern: &ArmPattern) -> Option<(f64, f64)> {
+        match arm_pattern {
+            ArmPattern::Range(start, end) => match (start.as_ref(), end.as_ref()) {
+                (Expr::Number(start, _, _), Expr::Number(end, _, _)) => {
+                    Some((start.value, end.value))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // Ranges are half-open, so `0..50` and `50..80` don't overlap. As the arms are tried
+    // in order, the values in an overlap never reach the later arm
+    pub(crate) fn overlapping_ranges(predicate: &Expr, ranges: &[(f64, f64)]) -> Vec<String> {
+        let mut messages = vec![];
+
+        for (index, (start, end)) in ranges.iter().enumerate() {
+            if start >= end {
+                messages.push(format!(
+                    "Range pattern `{}..{}` in match on `{}` is empty and never matches",
+                    start, end, predicate
+                ));
+                continue;
+            }
+
+            for (previous_start, previous_end) in ranges[..index].iter() {
+                if previous_start < end && start < previous_end {
+                    messages.push(format!(
+                        "Range patterns `{}..{}` and `{}..{}` in match on `{}` overlap, the values in both are matched by the first one",
+                        previous_start, previous_end, start, end, predicate
+                    ));
+                }
+            }
+        }
+
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint, Expr};
+
+    #[test]
+    fn test_adjacent_ranges_do_not_overlap() {
+        let expr = Expr::from_text(
+            r#"match request.query.score { 0..50 => "low", 50..80 => "mid", _ => "high" }"#,
+        )
+        .unwrap();
+
+        assert!(lint(&expr).is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_ranges() {
+        let expr = Expr::from_text(
+            r#"match request.query.score { 0..50 => "low", 40..80 => "mid", 80..80 => "none", _ => "high" }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lint(&expr),
+            vec![
+                "Range patterns `0..50` and `40..80` in match on `request.query.score` overlap, the values in both are matched by the first one".to_string(),
+                "Range pattern `80..80` in match on `request.query.score` is empty and never matches".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_fields() {
+        let expr = Expr::from_text(
+            r#"if request.headers.x-tenant == "acme" then request.path.user-id else request.body.user-id"#,
+        )
+        .unwrap();
+
+        assert!(lint(&expr).is_empty());
+    }
+
+    #[test]
+    fn test_undefined_request_field() {
+        let expr = Expr::from_text(r#"let user: str = request.pat.user-id; user"#).unwrap();
+
+        assert_eq!(
+            lint(&expr),
+            vec!["`request.pat` is not a field of the request, which are `path`, `query`, `headers`, `body`, `rawBody`, `method`, `cookies`".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_worker_fields() {
+        let expr = Expr::from_text(r#"{body: worker.response, headers: {x-worker: worker.name}}"#)
+            .unwrap();
+
+        assert!(lint(&expr).is_empty());
+    }
+
+    #[test]
+    fn test_undefined_worker_field() {
+        let expr = Expr::from_text(r#"{body: worker.respone, status: 200}"#).unwrap();
+
+        assert_eq!(
+            lint(&expr),
+            vec!["`worker.respone` is not a field of the worker, which are `response`, `name`, `component_id`, `idempotency_key`".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_ranges_in_nested_match() {
+        let expr = Expr::from_text(
+            r#"
+              let x: u64 = 1;
+              if x > 0u64 then match x { 0..10 => "a", 5..8 => "b", _ => "c" } else "d"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(lint(&expr).len(), 1);
+    }
+}
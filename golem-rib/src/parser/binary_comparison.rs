@@ -13,10 +13,14 @@
 // limitations under the License.
 
 use crate::parser::errors::RibParseError;
+use crate::parser::identifier::keyword;
 use combine::parser::char::string;
 use combine::{attempt, choice, ParseError, Parser};
 
-pub fn binary_op<Input>() -> impl Parser<Input, Output = BinaryOp>
+// Within the value of a `let`, `in` is not an operator, but the start of the body of the binding
+// (Ex: `let x = request.path.id in x`). A membership test in such a value has to be in a block
+// (Ex: `let valid = { page in 1..=100 }`)
+pub fn binary_op<Input>(allow_in: bool) -> impl Parser<Input, Output = BinaryOp>
 where
     Input: combine::Stream<Token = char>,
     RibParseError: Into<
@@ -26,20 +30,47 @@ where
     choice((
         attempt(string(">=")),
         attempt(string("<=")),
+        // Must be tried before `<`, so that `<>` isn't parsed as `<` followed by `>`
+        attempt(string("<>")),
         attempt(string("==")),
+        attempt(string("!=")),
         string("<"),
         string(">"),
         string("&&"),
+        // Must be tried before `||`, as both start with `|`
+        attempt(string("|>")),
         string("||"),
+        // Both start with `?`
+        attempt(string("??")),
+        attempt(string("?.")),
+        // Must be tried before `..`
+        attempt(string("..=")),
+        attempt(string("..")),
+        attempt(keyword("in").and_then(move |str| {
+            if allow_in {
+                Ok(str)
+            } else {
+                Err(RibParseError::Message(
+                    "`in` is the body of the let binding".to_string(),
+                ))
+            }
+        })),
     ))
     .and_then(|str| match str {
         ">" => Ok(BinaryOp::GreaterThan),
         "<" => Ok(BinaryOp::LessThan),
         "==" => Ok(BinaryOp::EqualTo),
+        "!=" | "<>" => Ok(BinaryOp::NotEqualTo),
         ">=" => Ok(BinaryOp::GreaterThanOrEqualTo),
         "<=" => Ok(BinaryOp::LessThanOrEqualTo),
         "&&" => Ok(BinaryOp::And),
         "||" => Ok(BinaryOp::Or),
+        "|>" => Ok(BinaryOp::Pipe),
+        "??" => Ok(BinaryOp::Coalesce),
+        "?." => Ok(BinaryOp::SafeNavigation),
+        ".." => Ok(BinaryOp::Range),
+        "..=" => Ok(BinaryOp::RangeInclusive),
+        "in" => Ok(BinaryOp::In),
         _ => Err(RibParseError::Message(
             "Invalid binary operator".to_string(),
         )),
@@ -52,18 +83,33 @@ pub enum BinaryOp {
     LessThanOrEqualTo,
     GreaterThanOrEqualTo,
     EqualTo,
+    NotEqualTo,
     And,
     Or,
+    Pipe,
+    Coalesce,
+    SafeNavigation,
+    Range,
+    RangeInclusive,
+    In,
 }
 
 #[cfg(test)]
 mod test {
     use crate::parser::rib_expr::rib_expr;
     use crate::{
-        DynamicParsedFunctionName, DynamicParsedFunctionReference, Expr, ParsedFunctionSite,
+        text, DynamicParsedFunctionName, DynamicParsedFunctionReference, Expr,
+        FunctionTypeRegistry, ParsedFunctionSite,
     };
     use combine::EasyParser;
 
+    // The expression with its calls of builtins resolved, like the calls the operators result in
+    fn with_builtins(input: &str) -> Expr {
+        let mut expr = Expr::from_text(input).unwrap();
+        expr.infer_builtins(&FunctionTypeRegistry::empty());
+        expr
+    }
+
     #[test]
     fn test_greater_than() {
         let input = "foo > bar";
@@ -129,6 +175,50 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_not_equal_to() {
+        let input = "foo != bar";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::not_equal_to(Expr::identifier("foo"), Expr::identifier("bar")),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sql_style_not_equal_to() {
+        let sql_style = text::from_string("${a <> b}");
+        let not_equal_to = text::from_string("${a != b}");
+
+        assert_eq!(sql_style, not_equal_to);
+        assert_eq!(
+            Expr::from_text("a <> b"),
+            Ok(Expr::not_equal_to(
+                Expr::identifier("a"),
+                Expr::identifier("b")
+            ))
+        );
+    }
+
+    #[test]
+    fn test_less_than_followed_by_greater_than_is_not_not_equal_to() {
+        let input = "foo < bar > baz";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::greater_than(
+                    Expr::less_than(Expr::identifier("foo"), Expr::identifier("bar")),
+                    Expr::identifier("baz")
+                ),
+                ""
+            ))
+        );
+    }
+
     #[test]
     fn test_binary_op_in_if_condition() {
         let input = "if true then foo > bar  else  bar == foo";
@@ -327,4 +417,115 @@ mod test {
             ))
         );
     }
+
+    #[test]
+    fn test_pipe_to_function_names() {
+        let piped = Expr::from_text("request.path.name |> trim |> uppercase").unwrap();
+        let nested = Expr::from_text("uppercase(trim(request.path.name))").unwrap();
+
+        assert_eq!(piped, nested);
+    }
+
+    #[test]
+    fn test_pipe_to_calls_with_arguments() {
+        let piped = Expr::from_text(r#"request.headers.tags |> split(",") |> foo"#).unwrap();
+        let nested = Expr::from_text(r#"foo(split(request.headers.tags, ","))"#).unwrap();
+
+        assert_eq!(piped, nested);
+    }
+
+    #[test]
+    fn test_pipe_in_comparison() {
+        let piped = Expr::from_text(r#"x |> trim == "foo""#).unwrap();
+        let nested = Expr::from_text(r#"trim(x) == "foo""#).unwrap();
+
+        assert_eq!(piped, nested);
+    }
+
+    #[test]
+    fn test_comparison_with_null() {
+        let cases = [
+            (
+                "request.query.cursor == null",
+                "!isPresent(request.query.cursor)",
+            ),
+            (
+                "null == request.query.cursor",
+                "!isPresent(request.query.cursor)",
+            ),
+            (
+                "request.query.cursor != null",
+                "isPresent(request.query.cursor)",
+            ),
+            (
+                "request.query.cursor == none",
+                "!isPresent(request.query.cursor)",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                Expr::from_text(input).unwrap(),
+                with_builtins(expected),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_coalesce_operator() {
+        let operator =
+            Expr::from_text("request.headers.x-real-ip ?? request.headers.host ?? \"unknown\"")
+                .unwrap();
+        let call =
+            with_builtins("coalesce(request.headers.x-real-ip, request.headers.host, \"unknown\")");
+
+        assert_eq!(operator, call);
+    }
+
+    #[test]
+    fn test_coalesce_binds_tighter_than_comparisons() {
+        let left = Expr::from_text("request.path.limit ?? 10 > 5").unwrap();
+        let right = Expr::from_text("5 < request.path.limit ?? 10").unwrap();
+
+        assert_eq!(left, with_builtins("coalesce(request.path.limit, 10) > 5"));
+        assert_eq!(right, with_builtins("5 < coalesce(request.path.limit, 10)"));
+    }
+
+    #[test]
+    fn test_range_operators() {
+        let inclusive = Expr::from_text("request.query.page in 1..=100").unwrap();
+        let exclusive = Expr::from_text("page in 0..limit ?? 10").unwrap();
+
+        assert_eq!(
+            inclusive,
+            with_builtins("inRange(request.query.page, range(1, 100, true))")
+        );
+        assert_eq!(
+            exclusive,
+            with_builtins("inRange(page, range(0, limit ?? 10, false))")
+        );
+    }
+
+    #[test]
+    fn test_in_is_the_body_of_a_let_binding() {
+        let body = Expr::from_text("let x = page in x..10").unwrap();
+        let block = Expr::from_text("let x = { page in 1..10 }; x").unwrap();
+
+        assert_eq!(body, with_builtins("let x = page; range(x, 10, false)"));
+        assert_eq!(
+            block,
+            with_builtins("let x = inRange(page, range(1, 10, false)); x")
+        );
+    }
+
+    #[test]
+    fn test_pipe_to_non_function() {
+        let result = Expr::from_text("x |> 1");
+
+        assert!(result
+            .unwrap_err()
+            .contains("Expected a function on the right side of `|>`"));
+    }
 }
@@ -12,16 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{DynamicParsedFunctionName, DynamicParsedFunctionReference};
+use crate::{BuiltinFunction, DynamicParsedFunctionName, DynamicParsedFunctionReference};
 use combine::error::Commit;
 use combine::parser::char::{alpha_num, string};
 use combine::parser::char::{char, spaces};
 use combine::parser::repeat::take_until;
-use combine::sep_by;
+use combine::sep_end_by;
 use combine::{any, attempt, between, choice, many1, optional, parser, token, ParseError, Parser};
 
 use crate::expr::Expr;
 use crate::function_name::{ParsedFunctionSite, SemVer};
+use crate::parser::config;
 use crate::parser::errors::RibParseError;
 use crate::parser::rib_expr::rib_expr;
 
@@ -38,13 +39,45 @@ where
         between(
             char('(').skip(spaces()),
             char(')').skip(spaces()),
-            sep_by(rib_expr().skip(spaces()), char(',').skip(spaces())),
+            sep_end_by(rib_expr().skip(spaces()), char(',').skip(spaces())),
         ),
     )
-        .map(|(name, args)| Expr::call(name, args))
+        .map(|(name, args)| {
+            config::require_call(&name);
+            Expr::call(name, args)
+        })
         .message("Invalid function call")
 }
 
+// A call of a builtin written as `builtin::coalesce(a, b)`, which is how the calls the operators
+// are desugared to (Ex: `??`) are written back as text. Unlike `coalesce(a, b)`, it's never
+// resolved to a function exported by the component
+pub fn builtin_call<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: combine::Stream<Token = char>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    (
+        string("builtin::")
+            .with(many1(alpha_num()))
+            .and_then(|name: String| {
+                BuiltinFunction::from_name(&name).ok_or(
+                    RibParseError::Message(format!("Unknown builtin function {}", name)).into(),
+                )
+            })
+            .skip(spaces()),
+        between(
+            char('(').skip(spaces()),
+            char(')').skip(spaces()),
+            sep_end_by(rib_expr().skip(spaces()), char(',').skip(spaces())),
+        ),
+    )
+        .map(|(builtin, args)| Expr::call_builtin(builtin, args))
+        .message("Invalid builtin call")
+}
+
 pub fn function_name<Input>() -> impl Parser<Input, Output = DynamicParsedFunctionName>
 where
     Input: combine::Stream<Token = char>,
@@ -281,6 +314,25 @@ mod function_call_tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_call_with_trailing_comma() {
+        let input = "foo(bar, baz,)";
+        let result = rib_expr().easy_parse(input);
+        let expected = Ok((
+            Expr::call(
+                DynamicParsedFunctionName {
+                    site: ParsedFunctionSite::Global,
+                    function: DynamicParsedFunctionReference::Function {
+                        function: "foo".to_string(),
+                    },
+                },
+                vec![Expr::identifier("bar"), Expr::identifier("baz")],
+            ),
+            "",
+        ));
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn test_call_with_multiple_args_and_spaces() {
         let input = "foo(bar, baz, qux)";
@@ -13,11 +13,14 @@
 // limitations under the License.
 
 use combine::parser::char::{alpha_num, char, spaces, string};
-use combine::{attempt, not_followed_by, ParseError, Parser};
+use combine::{attempt, choice, look_ahead, many, optional, ParseError, Parser};
 
 use crate::expr::Expr;
+use crate::parser::config;
 use crate::parser::errors::RibParseError;
+use crate::parser::identifier::{keyword, keyword_alias};
 use crate::parser::rib_expr::rib_expr;
+use crate::ParserFeatures;
 
 pub fn conditional<Input>() -> impl Parser<Input, Output = Expr>
 where
@@ -27,23 +30,81 @@ where
     >,
 {
     // Use attempt only for the initial "if" to resolve ambiguity with identifiers
-    attempt(
-        string("if")
-            .skip(not_followed_by(alpha_num().or(char('-')).or(char('_'))))
-            .skip(spaces()),
-    )
-    .with(
+    attempt(keyword("if").skip(spaces())).with(
         (
             rib_expr().skip(spaces()),
-            string("then").skip(spaces()),
+            then_keyword().skip(spaces()),
             rib_expr().skip(spaces()),
-            string("else").skip(spaces()),
+            else_keyword().skip(spaces()),
             rib_expr().skip(spaces()),
         )
-            .map(|(cond, _, then_expr, _, else_expr)| Expr::cond(cond, then_expr, else_expr)),
+            .map(|(cond, _, then_expr, _, else_expr)| {
+                config::require(ParserFeatures::CONDITIONALS);
+                Expr::cond(cond, then_expr, else_expr)
+            }),
     )
 }
 
+// A statement that is a conditional followed by `then` (Ex: `if a then b else c then d else e`)
+// is almost always a chained conditional with the `if` missing after `else`.
+// Checked on whole statements, as a conditional within the condition of another one is
+// followed by `then` (Ex: `if if a then b else c then d else e`)
+pub fn missing_if_after_else<Input>(statement: Expr) -> impl Parser<Input, Output = Expr>
+where
+    Input: combine::Stream<Token = char>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    optional(attempt(look_ahead(then_keyword()))).and_then(move |dangling_then| {
+        match (&statement, dangling_then) {
+            (Expr::Cond(..), Some(_)) => Err(RibParseError::Message(
+                "Missing `if` after `else`, use `else if` to chain conditionals".to_string(),
+            )),
+            _ => Ok(statement.clone()),
+        }
+    })
+}
+
+fn then_keyword<Input>() -> impl Parser<Input, Output = ()>
+where
+    Input: combine::Stream<Token = char>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    keyword("then").map(|_| ())
+}
+
+// Recognises the `else` keyword, and the common misspellings of the `else if` continuation
+// so that they are reported as such, instead of as an unexpected identifier
+fn else_keyword<Input>() -> impl Parser<Input, Output = ()>
+where
+    Input: combine::Stream<Token = char>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    choice((
+        keyword_alias("else").map(|keyword: &str| keyword.to_string()),
+        attempt(string("elif")).map(|keyword: &str| keyword.to_string()),
+        string("else")
+            .with(many(alpha_num().or(char('-')).or(char('_'))))
+            .map(|suffix: String| format!("else{}", suffix)),
+    ))
+    .and_then(|keyword: String| match keyword.as_str() {
+        "else" => Ok(()),
+        "elif" | "elseif" => Err(RibParseError::Message(format!(
+            "Unexpected `{}`, use `else if` to chain conditionals",
+            keyword
+        ))),
+        _ => Err(RibParseError::Message(format!(
+            "Unexpected `{}`, expected `else`",
+            keyword
+        ))),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use combine::EasyParser;
@@ -105,6 +166,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_else_if_chain_with_irregular_spacing() {
+        let input = "if foo then bar else   if baz then qux else\n  if quux then corge else grault";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::cond(
+                    Expr::identifier("foo"),
+                    Expr::identifier("bar"),
+                    Expr::cond(
+                        Expr::identifier("baz"),
+                        Expr::identifier("qux"),
+                        Expr::cond(
+                            Expr::identifier("quux"),
+                            Expr::identifier("corge"),
+                            Expr::identifier("grault")
+                        )
+                    )
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_elseif_is_rejected() {
+        let input = "if foo then bar elseif baz then qux else quux";
+        let result = Expr::from_text(input).unwrap_err();
+        assert!(result.contains("Unexpected `elseif`, use `else if` to chain conditionals"));
+    }
+
+    #[test]
+    fn test_elif_is_rejected() {
+        let input = "if foo then bar elif baz then qux else quux";
+        let result = Expr::from_text(input).unwrap_err();
+        assert!(result.contains("Unexpected `elif`, use `else if` to chain conditionals"));
+    }
+
+    #[test]
+    fn test_else_followed_by_identifier_without_space_is_rejected() {
+        let input = "if foo then bar elsebaz";
+        let result = Expr::from_text(input).unwrap_err();
+        assert!(result.contains("Unexpected `elsebaz`, expected `else`"));
+    }
+
+    #[test]
+    fn test_else_missing_if_in_chain() {
+        let input = "if foo then bar else baz then qux else quux";
+        let result = Expr::from_text(input).unwrap_err();
+        assert!(result.contains("Missing `if` after `else`, use `else if` to chain conditionals"));
+    }
+
+    #[test]
+    fn test_if_condition_inside_condition() {
+        let input = "if if a then b else c then d else e";
+        let result = Expr::from_text(input);
+        assert_eq!(
+            result,
+            Ok(Expr::cond(
+                Expr::cond(
+                    Expr::identifier("a"),
+                    Expr::identifier("b"),
+                    Expr::identifier("c")
+                ),
+                Expr::identifier("d"),
+                Expr::identifier("e")
+            ))
+        );
+    }
+
     #[test]
     fn test_if_condition_inside_then() {
         let input = "if foo then if bar then baz else qux else quux";
@@ -0,0 +1,591 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::BitOr;
+
+use crate::expr::Expr;
+use crate::parser::binary_comparison::BinaryOp;
+use crate::parser::identifier::RESERVED_KEYWORDS;
+use crate::{
+    BuiltinFunction, DynamicParsedFunctionName, DynamicParsedFunctionReference, ParsedFunctionSite,
+};
+
+// Configuration of the Rib parser, for deployments that need to deviate from the default syntax.
+// `ParserConfig::default()` allows only the default features (see `ParserFeatures`),
+// while `Expr::from_text` parses all of them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParserConfig {
+    // Alternative spellings of the keywords (Ex: `when` for `if`), from alias to keyword
+    keyword_aliases: HashMap<String, String>,
+    // The categories of syntax which are allowed
+    features: ParserFeatures,
+}
+
+// A set of categories of syntax, to restrict what an expression can do (Ex: no function calls).
+// The default is the syntax Rib had before the features were introduced,
+// and new syntax is added to it only once it's stable. Until then, it has to be opted into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParserFeatures(u32);
+
+impl ParserFeatures {
+    // `let x = ...`
+    pub const LET: ParserFeatures = ParserFeatures(1);
+    // `if ... then ... else ...`
+    pub const CONDITIONALS: ParserFeatures = ParserFeatures(1 << 1);
+    // `match ... { ... }`
+    pub const PATTERN_MATCH: ParserFeatures = ParserFeatures(1 << 2);
+    // Calls of worker functions. Builtin functions (Ex: `isPresent`) are always allowed.
+    // Variant constructors (Ex: `register-user(...)`) can't be told apart from function calls
+    // when parsing, so they are only allowed along with the function calls
+    pub const FUNCTION_CALLS: ParserFeatures = ParserFeatures(1 << 3);
+    // `==`, `<`, `<=`, `>` and `>=`, including the comparisons with `null` (Ex: `x == null`)
+    pub const COMPARISONS: ParserFeatures = ParserFeatures(1 << 4);
+    // `&&`, `||` and `!`
+    pub const BOOLEAN_OPERATORS: ParserFeatures = ParserFeatures(1 << 5);
+    // `let x = ... in body`
+    pub const LET_IN: ParserFeatures = ParserFeatures(1 << 6);
+    // `!=` (and `<>`)
+    pub const NOT_EQUAL: ParserFeatures = ParserFeatures(1 << 7);
+    // `value |> f`. Piping into a worker function also needs `FUNCTION_CALLS`
+    pub const PIPE: ParserFeatures = ParserFeatures(1 << 8);
+    // `a ?? b`
+    pub const COALESCE: ParserFeatures = ParserFeatures(1 << 9);
+    // `a?.b`
+    pub const SAFE_NAVIGATION: ParserFeatures = ParserFeatures(1 << 10);
+    // `start..end` and `start..=end`, including the range patterns of a match arm
+    pub const RANGES: ParserFeatures = ParserFeatures(1 << 11);
+    // `value in range` (or `value in list`)
+    pub const IN: ParserFeatures = ParserFeatures(1 << 12);
+
+    const NAMED: [(ParserFeatures, &'static str); 13] = [
+        (ParserFeatures::LET, "let"),
+        (ParserFeatures::CONDITIONALS, "conditionals"),
+        (ParserFeatures::PATTERN_MATCH, "pattern-match"),
+        (ParserFeatures::FUNCTION_CALLS, "function-calls"),
+        (ParserFeatures::COMPARISONS, "comparisons"),
+        (ParserFeatures::BOOLEAN_OPERATORS, "boolean-operators"),
+        (ParserFeatures::LET_IN, "let-in"),
+        (ParserFeatures::NOT_EQUAL, "not-equal"),
+        (ParserFeatures::PIPE, "pipe"),
+        (ParserFeatures::COALESCE, "coalesce"),
+        (ParserFeatures::SAFE_NAVIGATION, "safe-navigation"),
+        (ParserFeatures::RANGES, "ranges"),
+        (ParserFeatures::IN, "in"),
+    ];
+
+    pub fn none() -> ParserFeatures {
+        ParserFeatures(0)
+    }
+
+    pub fn all() -> ParserFeatures {
+        ParserFeatures::NAMED
+            .iter()
+            .fold(ParserFeatures::none(), |acc, (feature, _)| acc | *feature)
+    }
+
+    pub fn contains(&self, features: ParserFeatures) -> bool {
+        self.0 & features.0 == features.0
+    }
+
+    pub fn with(self, features: ParserFeatures) -> ParserFeatures {
+        self | features
+    }
+
+    pub fn without(self, features: ParserFeatures) -> ParserFeatures {
+        ParserFeatures(self.0 & !features.0)
+    }
+}
+
+impl Default for ParserFeatures {
+    fn default() -> Self {
+        ParserFeatures::LET
+            | ParserFeatures::CONDITIONALS
+            | ParserFeatures::PATTERN_MATCH
+            | ParserFeatures::FUNCTION_CALLS
+            | ParserFeatures::COMPARISONS
+            | ParserFeatures::BOOLEAN_OPERATORS
+    }
+}
+
+impl BitOr for ParserFeatures {
+    type Output = ParserFeatures;
+
+    fn bitor(self, rhs: ParserFeatures) -> ParserFeatures {
+        ParserFeatures(self.0 | rhs.0)
+    }
+}
+
+impl Display for ParserFeatures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = ParserFeatures::NAMED
+            .iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+impl ParserConfig {
+    pub fn with_keyword_alias(
+        mut self,
+        alias: impl Into<String>,
+        keyword: impl Into<String>,
+    ) -> Self {
+        self.keyword_aliases.insert(alias.into(), keyword.into());
+        self
+    }
+
+    pub fn keyword_aliases(&self) -> &HashMap<String, String> {
+        &self.keyword_aliases
+    }
+
+    pub fn with_features(mut self, features: ParserFeatures) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn features(&self) -> ParserFeatures {
+        self.features
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (alias, keyword) in &self.keyword_aliases {
+            if !RESERVED_KEYWORDS.contains(&keyword.as_str()) {
+                return Err(format!(
+                    "Invalid keyword alias `{}`: `{}` is not a keyword",
+                    alias, keyword
+                ));
+            }
+
+            if RESERVED_KEYWORDS.contains(&alias.as_str()) {
+                return Err(format!(
+                    "Invalid keyword alias `{}`: a keyword can't be an alias of another keyword",
+                    alias
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn parse_with_config(input: &str, config: &ParserConfig) -> Result<Expr, String> {
+    config.validate()?;
+
+    // The config is applied while parsing: the keyword parsers look up the aliases,
+    // and the features are checked before the syntax they are about is desugared
+    // (Ex: `a?.b` is a conditional within a pattern match)
+    let active = internal::ActiveConfig::start(config);
+
+    let expr = Expr::from_text(input)?;
+
+    if let Some(disabled) = active.first_disabled() {
+        return Err(format!("Parse error: feature `{}` is disabled", disabled));
+    }
+
+    Ok(expr)
+}
+
+// The keyword the word is an alias of, in the config of the parse in progress
+pub(crate) fn keyword_of_alias(word: &str) -> Option<String> {
+    internal::keyword_of_alias(word)
+}
+
+pub(crate) fn has_keyword_aliases() -> bool {
+    internal::has_keyword_aliases()
+}
+
+// Called by the parsers on the use of a syntax, which fails `parse_with_config`
+// if the syntax isn't in the features
+pub(crate) fn require(feature: ParserFeatures) {
+    internal::require(feature)
+}
+
+pub(crate) fn require_operator(op: &BinaryOp) {
+    require(match op {
+        BinaryOp::GreaterThan
+        | BinaryOp::LessThan
+        | BinaryOp::LessThanOrEqualTo
+        | BinaryOp::GreaterThanOrEqualTo
+        | BinaryOp::EqualTo => ParserFeatures::COMPARISONS,
+        BinaryOp::NotEqualTo => ParserFeatures::NOT_EQUAL,
+        BinaryOp::And | BinaryOp::Or => ParserFeatures::BOOLEAN_OPERATORS,
+        BinaryOp::Pipe => ParserFeatures::PIPE,
+        BinaryOp::Coalesce => ParserFeatures::COALESCE,
+        BinaryOp::SafeNavigation => ParserFeatures::SAFE_NAVIGATION,
+        BinaryOp::Range | BinaryOp::RangeInclusive => ParserFeatures::RANGES,
+        BinaryOp::In => ParserFeatures::IN,
+    })
+}
+
+// A call of a global function named after a builtin (Ex: `isPresent(x)`) is a builtin call
+pub(crate) fn require_call(function_name: &DynamicParsedFunctionName) {
+    match (&function_name.site, &function_name.function) {
+        (ParsedFunctionSite::Global, DynamicParsedFunctionReference::Function { function })
+            if BuiltinFunction::from_name(function).is_some() => {}
+        _ => require(ParserFeatures::FUNCTION_CALLS),
+    }
+}
+
+mod internal {
+    use crate::parser::config::{ParserConfig, ParserFeatures};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    struct State {
+        keyword_aliases: HashMap<String, String>,
+        features: ParserFeatures,
+        first_disabled: Option<ParserFeatures>,
+    }
+
+    thread_local! {
+        static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+    }
+
+    // Applies the config to the parsing on the current thread until dropped
+    pub(crate) struct ActiveConfig;
+
+    impl ActiveConfig {
+        pub(crate) fn start(config: &ParserConfig) -> ActiveConfig {
+            STATE.with(|state| {
+                *state.borrow_mut() = Some(State {
+                    keyword_aliases: config.keyword_aliases.clone(),
+                    features: config.features,
+                    first_disabled: None,
+                })
+            });
+            ActiveConfig
+        }
+
+        // The first feature which was used while being disabled
+        pub(crate) fn first_disabled(&self) -> Option<ParserFeatures> {
+            STATE.with(|state| {
+                state
+                    .borrow()
+                    .as_ref()
+                    .and_then(|state| state.first_disabled)
+            })
+        }
+    }
+
+    impl Drop for ActiveConfig {
+        fn drop(&mut self) {
+            STATE.with(|state| *state.borrow_mut() = None);
+        }
+    }
+
+    // Without an active config, every feature is allowed
+    pub(crate) fn require(feature: ParserFeatures) {
+        STATE.with(|state| {
+            if let Some(state) = state.borrow_mut().as_mut() {
+                if !state.features.contains(feature) && state.first_disabled.is_none() {
+                    state.first_disabled = Some(feature);
+                }
+            }
+        })
+    }
+
+    pub(crate) fn keyword_of_alias(word: &str) -> Option<String> {
+        STATE.with(|state| {
+            state
+                .borrow()
+                .as_ref()
+                .and_then(|state| state.keyword_aliases.get(word).cloned())
+        })
+    }
+
+    pub(crate) fn has_keyword_aliases() -> bool {
+        STATE.with(|state| {
+            state
+                .borrow()
+                .as_ref()
+                .is_some_and(|state| !state.keyword_aliases.is_empty())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::config::{parse_with_config, ParserConfig, ParserFeatures};
+    use crate::Expr;
+
+    #[test]
+    fn test_default_config_parses_as_from_text() {
+        let input = r#"if foo then "when" else bar"#;
+
+        assert_eq!(
+            parse_with_config(input, &ParserConfig::default()),
+            Expr::from_text(input)
+        );
+    }
+
+    #[test]
+    fn test_keyword_aliases() {
+        let config = ParserConfig::default()
+            .with_keyword_alias("when", "if")
+            .with_keyword_alias("otherwise", "else");
+
+        let result = parse_with_config(
+            r#"when request.when then "when" otherwise when_not"#,
+            &config,
+        );
+
+        let expected = Expr::from_text(r#"if request.when then "when" else when_not"#);
+
+        assert!(expected.is_ok());
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_keyword_aliases_in_interpolation() {
+        let config = ParserConfig::default()
+            .with_keyword_alias("when", "if")
+            .with_keyword_alias("otherwise", "else");
+
+        let result = parse_with_config(
+            r#""id-${when request.path.id > 10 then "big" otherwise "small"}""#,
+            &config,
+        );
+
+        let expected =
+            Expr::from_text(r#""id-${if request.path.id > 10 then "big" else "small"}""#);
+
+        assert!(expected.is_ok());
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_keyword_aliases_of_constructors() {
+        let config = ParserConfig::default()
+            .with_keyword_alias("nothing", "none")
+            .with_keyword_alias("just", "some");
+
+        let result = parse_with_config(
+            r#"match request.path.id { just(x) => x, nothing => just(0) }"#,
+            &config,
+        );
+
+        let expected =
+            Expr::from_text(r#"match request.path.id { some(x) => x, none => some(0) }"#);
+
+        assert!(expected.is_ok());
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_keyword_alias_is_not_an_identifier() {
+        let config = ParserConfig::default().with_keyword_alias("when", "if");
+
+        assert!(parse_with_config("let when = 1; when", &config).is_err());
+        assert_eq!(
+            parse_with_config("let x = request.when; x", &config),
+            Expr::from_text("let x = request.when; x")
+        );
+    }
+
+    #[test]
+    fn test_error_position_with_keyword_aliases() {
+        let config = ParserConfig::default().with_keyword_alias("otherwise", "else");
+
+        // The position of `3` in the text as written, not with `otherwise` replaced by `else`
+        let result = parse_with_config("if x then 1 otherwise 2 3", &config).unwrap_err();
+
+        assert!(result.contains("line: 1, column: 25"), "{}", result);
+    }
+
+    #[test]
+    fn test_aliases_are_not_applied_by_default() {
+        let result = parse_with_config("when foo then bar otherwise baz", &ParserConfig::default());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_alias_of_unknown_keyword() {
+        let config = ParserConfig::default().with_keyword_alias("when", "unless");
+
+        assert_eq!(
+            parse_with_config("when foo then bar else baz", &config),
+            Err("Invalid keyword alias `when`: `unless` is not a keyword".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_features() {
+        let features = ParserConfig::default().features();
+
+        assert_eq!(
+            features,
+            ParserFeatures::LET
+                | ParserFeatures::CONDITIONALS
+                | ParserFeatures::PATTERN_MATCH
+                | ParserFeatures::FUNCTION_CALLS
+                | ParserFeatures::COMPARISONS
+                | ParserFeatures::BOOLEAN_OPERATORS
+        );
+        assert_ne!(features, ParserFeatures::all());
+    }
+
+    #[test]
+    fn test_opt_in_features() {
+        let cases = [
+            (
+                "let x = request.path.id in x",
+                ParserFeatures::LET_IN,
+                "let-in",
+            ),
+            (
+                "request.path.id != 1",
+                ParserFeatures::NOT_EQUAL,
+                "not-equal",
+            ),
+            (
+                r#"request.body |> split(",")"#,
+                ParserFeatures::PIPE,
+                "pipe",
+            ),
+            (
+                "request.query.limit ?? 10",
+                ParserFeatures::COALESCE,
+                "coalesce",
+            ),
+            (
+                "request.body?.user",
+                ParserFeatures::SAFE_NAVIGATION,
+                "safe-navigation",
+            ),
+            ("1..=10", ParserFeatures::RANGES, "ranges"),
+            (
+                r#"match request.path.id { 0..10 => "small", _ => "big" }"#,
+                ParserFeatures::RANGES,
+                "ranges",
+            ),
+            ("request.path.id in [1, 2]", ParserFeatures::IN, "in"),
+        ];
+
+        for (input, feature, name) in cases {
+            assert_eq!(
+                parse_with_config(input, &ParserConfig::default()),
+                Err(format!("Parse error: feature `{}` is disabled", name)),
+                "{}",
+                input
+            );
+
+            let enabled = ParserConfig::default()
+                .with_features(ParserConfig::default().features().with(feature));
+
+            assert_eq!(
+                parse_with_config(input, &enabled),
+                Expr::from_text(input),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_features_are_checked_before_desugaring() {
+        // `?.` is desugared to a pattern match with a conditional and a builtin call
+        let input = r#"request.body?.user ?? "guest""#;
+        let config = ParserConfig::default()
+            .with_features(ParserFeatures::SAFE_NAVIGATION | ParserFeatures::COALESCE);
+
+        assert_eq!(parse_with_config(input, &config), Expr::from_text(input));
+
+        // `== null` is desugared to `!isPresent(..)`
+        let input = "request.query.cursor == null";
+        let config = ParserConfig::default().with_features(ParserFeatures::COMPARISONS);
+
+        assert_eq!(parse_with_config(input, &config), Expr::from_text(input));
+
+        let config = ParserConfig::default().with_features(ParserFeatures::none());
+
+        assert_eq!(
+            parse_with_config(input, &config),
+            Err("Parse error: feature `comparisons` is disabled".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pipe_into_worker_function_is_a_function_call() {
+        let config = ParserConfig::default().with_features(
+            ParserConfig::default()
+                .features()
+                .with(ParserFeatures::PIPE)
+                .without(ParserFeatures::FUNCTION_CALLS),
+        );
+
+        assert_eq!(
+            parse_with_config("request.body |> trim", &config),
+            Err("Parse error: feature `function-calls` is disabled".to_string())
+        );
+        assert_eq!(
+            parse_with_config("request.body |> isPresent", &config),
+            Expr::from_text("request.body |> isPresent")
+        );
+    }
+
+    #[test]
+    fn test_all_features_parse_as_from_text() {
+        let input = r#"let x = request.body?.user ?? "guest" in x != "admin" && 1 in 0..10"#;
+        let config = ParserConfig::default().with_features(ParserFeatures::all());
+
+        assert_eq!(parse_with_config(input, &config), Expr::from_text(input));
+    }
+
+    #[test]
+    fn test_function_calls_feature() {
+        let input = r#"let result = golem:it/api.{get-cart-contents}("a", "b"); result"#;
+
+        let enabled = ParserConfig::default();
+        let disabled = ParserConfig::default()
+            .with_features(ParserFeatures::all().without(ParserFeatures::FUNCTION_CALLS));
+
+        assert_eq!(parse_with_config(input, &enabled), Expr::from_text(input));
+        assert_eq!(
+            parse_with_config(input, &disabled),
+            Err("Parse error: feature `function-calls` is disabled".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builtin_calls_are_not_function_calls() {
+        let input = r#"if isPresent(request.headers.user) then "yes" else "no""#;
+
+        let config = ParserConfig::default()
+            .with_features(ParserFeatures::all().without(ParserFeatures::FUNCTION_CALLS));
+
+        assert_eq!(parse_with_config(input, &config), Expr::from_text(input));
+    }
+
+    #[test]
+    fn test_nested_disabled_feature() {
+        let input = r#"if request.path.id > 10 then "big" else "small""#;
+
+        let config = ParserConfig::default()
+            .with_features(ParserFeatures::CONDITIONALS | ParserFeatures::LET);
+
+        assert_eq!(
+            parse_with_config(input, &config),
+            Err("Parse error: feature `comparisons` is disabled".to_string())
+        );
+
+        let config = config.with_features(config.features().with(ParserFeatures::COMPARISONS));
+
+        assert_eq!(parse_with_config(input, &config), Expr::from_text(input));
+    }
+}
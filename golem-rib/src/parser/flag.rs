@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use combine::parser::char::digit;
-use combine::sep_by;
+use combine::sep_end_by;
 use combine::{
     between, many1,
     parser::char::{char as char_, letter, spaces},
@@ -38,7 +38,7 @@ where
             between(
                 char_('{').skip(spaces()),
                 char_('}').skip(spaces()),
-                sep_by(flag_name.skip(spaces()), char_(',').skip(spaces())),
+                sep_end_by(flag_name.skip(spaces()), char_(',').skip(spaces())),
             )
             .map(Expr::flags),
         )
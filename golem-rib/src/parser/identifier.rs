@@ -12,15 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use combine::parser::char::digit;
-use combine::parser::char::{char as char_, letter};
-use combine::{many, ParseError, Parser, Stream};
+use combine::error::Commit;
+use combine::parser::char::{alpha_num, char as char_, digit, letter, string};
+use combine::{many, not_followed_by, parser, ParseError, Parser, Stream};
 
 use crate::expr::Expr;
+use crate::parser::config;
 use crate::parser::errors::RibParseError;
 
-const RESERVED_KEYWORDS: &[&str] = &[
-    "if", "then", "else", "match", "ok", "some", "err", "none", "let",
+pub(crate) const RESERVED_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "match", "ok", "some", "err", "none", "null", "let", "in",
 ];
 
 pub fn identifier<Input>() -> impl Parser<Input, Output = Expr>
@@ -35,6 +36,28 @@ where
         .map(Expr::identifier)
         .message("Invalid identifier")
 }
+
+// The field selected from a record (Ex: `when` in `request.when`), which may be an alias
+// of a keyword, as it can't be mistaken for the keyword
+pub fn field_identifier<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    name_text()
+        .and_then(|ident: String| {
+            if RESERVED_KEYWORDS.contains(&ident.as_str()) {
+                Err(RibParseError::Message(format!("{} is a keyword", ident)))
+            } else {
+                Ok(Expr::identifier(ident))
+            }
+        })
+        .message("Invalid identifier")
+}
+
 pub fn identifier_text<Input>() -> impl Parser<Input, Output = String>
 where
     Input: Stream<Token = char>,
@@ -42,6 +65,24 @@ where
     RibParseError: Into<
         <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
     >,
+{
+    name_text().and_then(|ident: String| {
+        if RESERVED_KEYWORDS.contains(&ident.as_str()) {
+            Err(RibParseError::Message(format!("{} is a keyword", ident)))
+        } else if let Some(keyword) = config::keyword_of_alias(&ident) {
+            Err(RibParseError::Message(format!(
+                "{} is an alias of the keyword {}",
+                ident, keyword
+            )))
+        } else {
+            Ok(ident)
+        }
+    })
+}
+
+fn name_text<Input>() -> impl Parser<Input, Output = String>
+where
+    Input: Stream<Token = char>,
 {
     (
         letter(),
@@ -52,13 +93,58 @@ where
             vec.extend(s);
             vec.iter().collect::<String>()
         })
-        .and_then(|ident: String| {
-            if RESERVED_KEYWORDS.contains(&ident.as_str()) {
-                Err(RibParseError::Message(format!("{} is a keyword", ident)))
-            } else {
-                Ok(ident)
+}
+
+// A keyword (Ex: `if`), or an alias of it in the parser config (Ex: `when`).
+// Not followed by a character of an identifier, so that `iffy` is an identifier
+pub fn keyword<Input>(keyword: &'static str) -> impl Parser<Input, Output = &'static str>
+where
+    Input: Stream<Token = char>,
+{
+    keyword_alias(keyword)
+        .or(string(keyword).skip(not_followed_by(alpha_num().or(char_('-')).or(char_('_')))))
+}
+
+// An alias of the keyword, looked up in the keyword aliases of the parser config.
+// Unless the next word is such an alias, it consumes nothing and adds nothing to the error,
+// so a parser without aliases fails exactly like the keyword alone
+pub fn keyword_alias<Input>(keyword: &'static str) -> impl Parser<Input, Output = &'static str>
+where
+    Input: Stream<Token = char>,
+{
+    parser(move |input| {
+        let _: &mut Input = input;
+        let position = input.position();
+        let start = input.checkpoint();
+        let mut word = String::new();
+
+        if config::has_keyword_aliases() {
+            loop {
+                let before = input.checkpoint();
+                match input.uncons() {
+                    Ok(ch) if is_word_char(ch) => word.push(ch),
+                    _ => {
+                        input
+                            .reset(before)
+                            .map_err(|err| Commit::Peek(err.into()))?;
+                        break;
+                    }
+                }
             }
-        })
+        }
+
+        if !word.is_empty() && config::keyword_of_alias(&word).as_deref() == Some(keyword) {
+            Ok((keyword, Commit::Commit(())))
+        } else {
+            input.reset(start).map_err(|err| Commit::Peek(err.into()))?;
+            Err(Commit::Peek(Input::Error::empty(position).into()))
+        }
+    })
+}
+
+// The characters an identifier (or a keyword) is made of
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '-'
 }
 
 #[cfg(test)]
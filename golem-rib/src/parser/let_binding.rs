@@ -12,18 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use combine::parser::char::{alpha_num, char};
+use combine::parser::char::char;
 use combine::{
     attempt, not_followed_by, optional,
-    parser::char::{char as char_, spaces, string},
+    parser::char::{char as char_, spaces},
     ParseError, Parser,
 };
 
 use crate::expr::Expr;
+use crate::parser::config;
 use crate::parser::errors::RibParseError;
-use crate::parser::identifier::identifier_text;
-use crate::parser::rib_expr::rib_expr;
+use crate::parser::identifier::{identifier_text, keyword};
+use crate::parser::rib_expr::{let_value, rib_expr};
 use crate::parser::type_name::parse_type_name;
+use crate::ParserFeatures;
 
 pub fn let_binding<Input>() -> impl Parser<Input, Output = Expr>
 where
@@ -32,10 +34,7 @@ where
         <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
     >,
 {
-    attempt(
-        string("let").skip(not_followed_by(alpha_num().or(char('-')).or(char('_'))).skip(spaces())),
-    )
-    .with(
+    attempt(keyword("let").skip(spaces())).with(
         (
             let_variable().skip(spaces()),
             optional(
@@ -44,19 +43,44 @@ where
                     .with(parse_type_name())
                     .skip(spaces()),
             ),
-            char_('=').skip(spaces()),
-            rib_expr(),
+            char_('=')
+                .skip(not_followed_by(char('=')))
+                .skip(spaces())
+                .message("Expected `=` in let binding"),
+            let_value(),
+            optional(let_body()),
         )
-            .map(|(var, optional_type, _, expr)| {
-                if let Some(type_name) = optional_type {
+            .map(|(var, optional_type, _, expr, body)| {
+                config::require(ParserFeatures::LET);
+                if body.is_some() {
+                    config::require(ParserFeatures::LET_IN);
+                }
+
+                let binding = if let Some(type_name) = optional_type {
                     Expr::let_binding_with_type(var, type_name, expr)
                 } else {
                     Expr::let_binding(var.as_str(), expr)
+                };
+
+                // The encoding of `Expr::let_in`, keeping the type annotation
+                match body {
+                    Some(body) => Expr::multiple(vec![binding, body]),
+                    None => binding,
                 }
             }),
     )
 }
 
+fn let_body<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: combine::Stream<Token = char>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    attempt(keyword("in").skip(spaces())).with(rib_expr())
+}
+
 fn let_variable<Input>() -> impl Parser<Input, Output = String>
 where
     Input: combine::Stream<Token = char>,
@@ -72,8 +96,9 @@ where
 mod tests {
     use combine::EasyParser;
 
+    use crate::function_name::DynamicParsedFunctionName;
     use crate::parser::type_name::TypeName;
-    use crate::{InferredType, VariableId};
+    use crate::{text, InferredType, VariableId};
 
     use super::*;
 
@@ -87,6 +112,122 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_let_binding_with_in() {
+        let input = "let x = request.path.user_id in x";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::let_in(
+                    "x",
+                    Expr::select_field(
+                        Expr::select_field(Expr::identifier("request"), "path"),
+                        "user_id"
+                    ),
+                    Expr::identifier("x")
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_nested_let_binding_with_in() {
+        let input = "let x = foo in let y = x in y";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::let_in(
+                    "x",
+                    Expr::identifier("foo"),
+                    Expr::let_in("y", Expr::identifier("x"), Expr::identifier("y"))
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_let_binding_followed_by_result() {
+        let input = "${ let x = request.path.id; x > 1 }";
+        let result = text::from_string(input);
+        assert_eq!(
+            result,
+            Ok(Expr::multiple(vec![
+                Expr::let_binding(
+                    "x",
+                    Expr::select_field(
+                        Expr::select_field(Expr::identifier("request"), "path"),
+                        "id"
+                    )
+                ),
+                Expr::greater_than(Expr::identifier("x"), Expr::number(1f64))
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_two_let_bindings_followed_by_result() {
+        let input = r#""user-${let x = request.path.id; let y = foo(x); y}""#;
+        let result = Expr::from_text(input);
+        assert_eq!(
+            result,
+            Ok(Expr::concat(vec![
+                Expr::literal("user-"),
+                Expr::multiple(vec![
+                    Expr::let_binding(
+                        "x",
+                        Expr::select_field(
+                            Expr::select_field(Expr::identifier("request"), "path"),
+                            "id"
+                        )
+                    ),
+                    Expr::let_binding(
+                        "y",
+                        Expr::call(
+                            DynamicParsedFunctionName::parse("foo").unwrap(),
+                            vec![Expr::identifier("x")]
+                        )
+                    ),
+                    Expr::identifier("y")
+                ])
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_let_binding_without_result() {
+        let result = text::from_string("${ let x = request.path.id; }");
+        assert!(result
+            .unwrap_err()
+            .contains("Expected an expression after `;`"));
+
+        let result = Expr::from_text(r#""user-${let x = request.path.id;}""#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_semicolon_in_static_part_of_literal() {
+        let input = r#""a;b-${foo}""#;
+        let result = Expr::from_text(input);
+        assert_eq!(
+            result,
+            Ok(Expr::concat(vec![
+                Expr::literal("a;b-"),
+                Expr::identifier("foo")
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_let_binding_with_double_equals() {
+        let input = "let foo == bar";
+        let result = rib_expr().easy_parse(input);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_let_binding_with_sequence() {
         let input = "let foo = [bar, baz]";
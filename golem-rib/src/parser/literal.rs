@@ -30,14 +30,14 @@ parser! {
 }
 
 mod internal {
-    use combine::parser::char::{char as char_, char, letter, space};
+    use combine::parser::char::{char as char_, letter, space};
     use combine::parser::char::{digit, spaces};
     use combine::parser::repeat::many;
-    use combine::{between, choice, many1, sep_by, ParseError, Parser};
+    use combine::{between, choice, many1, ParseError, Parser};
 
     use crate::expr::Expr;
     use crate::parser::errors::RibParseError;
-    use crate::parser::rib_expr::rib_expr;
+    use crate::parser::rib_expr::statements;
 
     // Literal can handle string interpolation
     pub fn literal_<Input>() -> impl Parser<Input, Output = Expr>
@@ -85,6 +85,11 @@ mod internal {
             letter().or(space()).or(digit()).or(char_('_').or(char_('-')
                 .or(char_('.'))
                 .or(char_('/'))
+                .or(char_(';'))
+                // Only `${` starts an interpolation, so braces (Ex: of a `format` template)
+                // are a part of the text
+                .or(char_('{'))
+                .or(char_('}'))
                 .or(char_(':').or(char_('@'))))),
         )
         .map(LiteralTerm::Static)
@@ -101,31 +106,11 @@ mod internal {
         between(
             char_('$').with(char_('{')).skip(spaces()),
             char_('}'),
-            block(),
+            statements(),
         )
         .map(LiteralTerm::Dynamic)
     }
 
-    pub fn block<Input>() -> impl Parser<Input, Output = Expr>
-    where
-        Input: combine::Stream<Token = char>,
-        RibParseError: Into<
-            <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
-        >,
-    {
-        spaces().with(
-            sep_by(rib_expr().skip(spaces()), char(';').skip(spaces())).map(
-                |expressions: Vec<Expr>| {
-                    if expressions.len() == 1 {
-                        expressions.first().unwrap().clone()
-                    } else {
-                        Expr::multiple(expressions)
-                    }
-                },
-            ),
-        )
-    }
-
     enum LiteralTerm {
         Static(String),
         Dynamic(Expr),
@@ -16,9 +16,10 @@ mod binary_comparison;
 mod boolean;
 pub(crate) mod call;
 mod cond;
+pub(crate) mod config;
 mod errors;
 mod flag;
-mod identifier;
+pub(crate) mod identifier;
 mod let_binding;
 pub(crate) mod literal;
 mod multi_line_code_block;
@@ -26,11 +27,14 @@ mod not;
 mod number;
 mod optional;
 mod pattern_match;
+pub(crate) mod reader;
 mod record;
+pub(crate) mod recovery;
 mod result;
 pub(crate) mod rib_expr;
 mod select_field;
 mod select_index;
 mod sequence;
+pub(crate) mod source_map;
 mod tuple;
 pub(crate) mod type_name;
@@ -20,6 +20,7 @@ use combine::{
 
 use crate::expr::Expr;
 use crate::parser::errors::RibParseError;
+use crate::parser::rib_expr::statements;
 
 pub fn multi_line_block<Input>() -> impl Parser<Input, Output = Expr>
 where
@@ -31,39 +32,10 @@ where
     spaces().with(between(
         char_('{').skip(spaces()),
         char_('}').skip(spaces()),
-        internal::block().skip(spaces()),
+        statements().skip(spaces()),
     ))
 }
 
-mod internal {
-    use combine::parser::char::{char, spaces};
-    use combine::{sep_by, ParseError, Parser};
-
-    use crate::parser::errors::RibParseError;
-    use crate::parser::rib_expr::rib_expr;
-    use crate::Expr;
-
-    // A block is different to a complete rib-program that the it may not be the end of the stream
-    pub fn block<Input>() -> impl Parser<Input, Output = Expr>
-    where
-        Input: combine::Stream<Token = char>,
-        RibParseError: Into<
-            <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
-        >,
-    {
-        spaces().with(
-            sep_by(rib_expr().skip(spaces()), char(';').skip(spaces())).map(
-                |expressions: Vec<Expr>| {
-                    if expressions.len() == 1 {
-                        expressions.first().unwrap().clone()
-                    } else {
-                        Expr::multiple(expressions)
-                    }
-                },
-            ),
-        )
-    }
-}
 #[cfg(test)]
 mod tests {
     use crate::expr::Expr;
@@ -16,8 +16,10 @@ use combine::parser::char::{spaces, string};
 use combine::{ParseError, Parser};
 
 use crate::expr::Expr;
+use crate::parser::config;
 use crate::parser::errors::RibParseError;
 use crate::parser::rib_expr::rib_expr;
+use crate::ParserFeatures;
 
 pub fn not<Input>() -> impl Parser<Input, Output = Expr>
 where
@@ -29,7 +31,10 @@ where
     spaces()
         .with(
             (string("!").skip(spaces()), rib_expr())
-                .map(|(_, expr)| Expr::not(expr))
+                .map(|(_, expr)| {
+                    config::require(ParserFeatures::BOOLEAN_OPERATORS);
+                    Expr::not(expr)
+                })
                 .message("Unable to parse not"),
         )
         .message("Unable to parse not")
@@ -12,8 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use combine::parser::char::{char, digit, spaces};
-use combine::{many1, optional, ParseError, Parser};
+use combine::parser::char::{alpha_num, char, digit, spaces};
+use combine::{attempt, choice, many1, not_followed_by, one_of, optional, ParseError, Parser};
 
 use crate::expr::Expr;
 use crate::parser::errors::RibParseError;
@@ -28,29 +28,132 @@ where
     >,
 {
     spaces()
-        .with(
-            (
-                many1(digit().or(char('-')).or(char('.'))),
-                optional(parse_basic_type()),
-            )
-                .and_then(|(s, typ_name): (Vec<char>, Option<TypeName>)| {
-                    let primitive = s.into_iter().collect::<String>().parse::<f64>();
-
-                    match primitive {
-                        Ok(primitive) => {
-                            if let Some(typ_name) = typ_name {
-                                Ok(Expr::number_with_type_name(primitive, typ_name.clone()))
-                            } else {
-                                Ok(Expr::number(primitive))
-                            }
-                        }
-                        Err(_) => {
-                            Err(RibParseError::Message("Unable to parse number".to_string()).into())
-                        }
+        .with(choice((radix_integer(), decimal_number())))
+        .message("Unable to parse number")
+}
+
+fn decimal_number<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: combine::Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    (
+        // A `.` followed by another one is the start of a range (Ex: `1..10`)
+        many1(
+            digit()
+                .or(char('-'))
+                .or(attempt(char('.').skip(not_followed_by(char('.'))))),
+        ),
+        optional(parse_basic_type()),
+    )
+        .and_then(|(s, typ_name): (Vec<char>, Option<TypeName>)| {
+            let primitive = s.into_iter().collect::<String>().parse::<f64>();
+
+            match primitive {
+                Ok(primitive) => {
+                    if let Some(typ_name) = typ_name {
+                        Ok(Expr::number_with_type_name(primitive, typ_name.clone()))
+                    } else {
+                        Ok(Expr::number(primitive))
                     }
-                }),
+                }
+                Err(_) => Err(RibParseError::Message("Unable to parse number".to_string()).into()),
+            }
+        })
+}
+
+// Integers with a `0x` (hexadecimal), `0b` (binary) or `0o` (octal) prefix, optionally
+// followed by an integer type (Ex: `0xFFu8`). Once the prefix is parsed, an invalid digit
+// is an error, instead of backtracking to the other kinds of expressions
+fn radix_integer<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: combine::Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    (
+        attempt((optional(char('-')), char('0'), one_of("xbo".chars()))),
+        many1(alpha_num()),
+    )
+        .and_then(
+            |((sign, _, prefix), digits): ((Option<char>, char, char), String)| {
+                internal::parse_radix_integer(sign.is_some(), prefix, &digits)
+                    .map_err(|message| RibParseError::Message(message).into())
+            },
         )
-        .message("Unable to parse number")
+}
+
+mod internal {
+    use crate::expr::Expr;
+    use crate::parser::type_name::TypeName;
+
+    // Integers beyond this can't be represented exactly by the (f64) number expressions
+    const MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+    const INTEGER_TYPES: [(&str, TypeName); 8] = [
+        ("u8", TypeName::U8),
+        ("u16", TypeName::U16),
+        ("u32", TypeName::U32),
+        ("u64", TypeName::U64),
+        ("s8", TypeName::S8),
+        ("s16", TypeName::S16),
+        ("s32", TypeName::S32),
+        ("s64", TypeName::S64),
+    ];
+
+    pub(crate) fn parse_radix_integer(
+        negative: bool,
+        prefix: char,
+        digits_and_type: &str,
+    ) -> Result<Expr, String> {
+        let (radix, radix_name) = match prefix {
+            'x' => (16, "hexadecimal"),
+            'b' => (2, "binary"),
+            _ => (8, "octal"),
+        };
+
+        let (digits, type_name) = INTEGER_TYPES
+            .iter()
+            .find_map(|(suffix, type_name)| {
+                digits_and_type
+                    .strip_suffix(suffix)
+                    .map(|digits| (digits, Some(type_name.clone())))
+            })
+            .unwrap_or((digits_and_type, None));
+
+        let literal = format!("{}0{}{}", if negative { "-" } else { "" }, prefix, digits);
+
+        if let Some(invalid) = digits.chars().find(|c| !c.is_digit(radix)) {
+            return Err(format!(
+                "Invalid digit `{}` in {} literal {}",
+                invalid, radix_name, literal
+            ));
+        }
+
+        let value = u64::from_str_radix(digits, radix)
+            .ok()
+            .filter(|value| *value <= MAX_EXACT_INTEGER)
+            .ok_or(format!(
+                "The {} literal {} is too large",
+                radix_name, literal
+            ))?;
+
+        let value = if negative {
+            -(value as f64)
+        } else {
+            value as f64
+        };
+
+        match type_name {
+            Some(type_name) => Ok(Expr::number_with_type_name(value, type_name)),
+            None => Ok(Expr::number(value)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -103,4 +206,50 @@ mod tests {
         let expected = Expr::number_with_type_name(-123f64, TypeName::F64);
         assert_eq!(result, Ok((expected, "")));
     }
+
+    #[test]
+    fn test_hexadecimal_number() {
+        let result = number().easy_parse("0xFF");
+        assert_eq!(result, Ok((Expr::number(255f64), "")));
+
+        let result = number().easy_parse("0x1au8");
+        let expected = Expr::number_with_type_name(26f64, TypeName::U8);
+        assert_eq!(result, Ok((expected, "")));
+    }
+
+    #[test]
+    fn test_binary_number() {
+        let result = number().easy_parse("0b1010");
+        assert_eq!(result, Ok((Expr::number(10f64), "")));
+
+        let result = number().easy_parse("-0b11s32");
+        let expected = Expr::number_with_type_name(-3f64, TypeName::S32);
+        assert_eq!(result, Ok((expected, "")));
+    }
+
+    #[test]
+    fn test_octal_number() {
+        let result = number().easy_parse("0o755");
+        assert_eq!(result, Ok((Expr::number(493f64), "")));
+    }
+
+    #[test]
+    fn test_radix_number_with_invalid_digit() {
+        let result = Expr::from_text("0b1021").unwrap_err();
+        assert!(result.contains("Invalid digit `2` in binary literal 0b1021"));
+
+        let result = Expr::from_text("0xFG").unwrap_err();
+        assert!(result.contains("Invalid digit `G` in hexadecimal literal 0xFG"));
+    }
+
+    #[test]
+    fn test_radix_numbers_in_sequence() {
+        let result = Expr::from_text("[0xFF, 0b1, 12]");
+        let expected = Expr::sequence(vec![
+            Expr::number(255f64),
+            Expr::number(1f64),
+            Expr::number(12f64),
+        ]);
+        assert_eq!(result, Ok(expected));
+    }
 }
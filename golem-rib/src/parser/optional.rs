@@ -12,16 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use combine::parser::char::alpha_num;
 use combine::parser::char::spaces;
-use combine::{
-    attempt, choice, not_followed_by,
-    parser::char::{char, string},
-    ParseError, Parser,
-};
+use combine::{attempt, choice, parser::char::char, ParseError, Parser};
 
 use crate::expr::Expr;
 use crate::parser::errors::RibParseError;
+use crate::parser::identifier::keyword;
 
 use super::rib_expr::rib_expr;
 
@@ -33,14 +29,14 @@ where
     >,
 {
     choice((
-        attempt(string("some").skip(char('('))).with(
+        attempt(keyword("some").skip(char('('))).with(
             rib_expr()
                 .skip(spaces())
                 .skip(char(')'))
                 .map(|expr| Expr::option(Some(expr))),
         ),
-        (attempt(string("none").skip(not_followed_by(alpha_num().or(char('-')).or(char('_')))))
-            .map(|_| Expr::option(None))),
+        // `null` is the same as `none`, for the comparisons with an absent value (Ex: `x == null`)
+        (attempt(choice((attempt(keyword("none")), keyword("null")))).map(|_| Expr::option(None))),
     ))
     .message("Invalid syntax for Option type")
 }
@@ -68,6 +64,13 @@ mod tests {
         assert_eq!(result, Ok((Expr::option(None), "")));
     }
 
+    #[test]
+    fn test_null() {
+        let input = "null";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(result, Ok((Expr::option(None), "")));
+    }
+
     #[test]
     fn test_nested_some() {
         let input = "some(some(foo))";
@@ -12,14 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use combine::parser::char::{alpha_num, char, spaces, string};
-use combine::{attempt, not_followed_by, sep_by1, ParseError, Parser};
+use combine::parser::char::{char, spaces};
+use combine::{attempt, sep_end_by1, ParseError, Parser};
 
 use match_arm::*;
 
 use crate::expr::Expr;
+use crate::parser::config;
 use crate::parser::errors::RibParseError;
+use crate::parser::identifier::keyword;
 use crate::parser::rib_expr::rib_expr;
+use crate::ParserFeatures;
 
 pub fn pattern_match<Input>() -> impl Parser<Input, Output = Expr>
 where
@@ -28,23 +31,22 @@ where
         <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
     >,
 {
-    let arms = sep_by1(match_arm().skip(spaces()), char(',').skip(spaces()));
-
-    attempt(
-        string("match")
-            .skip(not_followed_by(alpha_num().or(char('_')).or(char('-'))))
-            .skip(spaces()),
-    )
-    .with(
-        (
-            rib_expr().skip(spaces()),
-            char('{').skip(spaces()),
-            arms.skip(spaces()),
-            char('}').skip(spaces()),
+    let arms = sep_end_by1(match_arm().skip(spaces()), char(',').skip(spaces()));
+
+    attempt(keyword("match").skip(spaces()))
+        .with(
+            (
+                rib_expr().skip(spaces()),
+                char('{').skip(spaces()),
+                arms.skip(spaces()),
+                char('}').skip(spaces()),
+            )
+                .map(|(expr, _, arms, _)| {
+                    config::require(ParserFeatures::PATTERN_MATCH);
+                    Expr::pattern_match(expr, arms)
+                }),
         )
-            .map(|(expr, _, arms, _)| Expr::pattern_match(expr, arms)),
-    )
-    .message("Invalid syntax for pattern match")
+        .message("Invalid syntax for pattern match")
 }
 
 mod match_arm {
@@ -105,6 +107,7 @@ mod arm_pattern {
                 )
                     .map(|(iden, _, pattern)| ArmPattern::As(iden, Box::new(pattern))),
             ),
+            attempt(arm_pattern_range()),
             attempt(arm_pattern_literal()),
         ))
     }
@@ -118,17 +121,20 @@ mod arm_pattern {
 }
 
 mod internal {
-    use combine::many1;
     use combine::parser::char::{digit, letter};
     use combine::parser::char::{spaces, string};
-    use combine::sep_by;
-    use combine::{attempt, sep_by1};
+    use combine::sep_end_by;
+    use combine::{attempt, sep_end_by1};
     use combine::{choice, ParseError};
+    use combine::{many1, optional};
     use combine::{parser::char::char as char_, Parser};
 
-    use crate::expr::ArmPattern;
+    use crate::expr::{ArmPattern, Expr};
+    use crate::parser::config;
     use crate::parser::errors::RibParseError;
+    use crate::parser::identifier::keyword;
     use crate::parser::pattern_match::arm_pattern::*;
+    use crate::ParserFeatures;
 
     use crate::parser::rib_expr::rib_expr;
 
@@ -157,6 +163,43 @@ mod internal {
         rib_expr().map(|lit| ArmPattern::Literal(Box::new(lit)))
     }
 
+    // A half-open range of integers, `start..end`, where `start` is included and `end` is not
+    pub(crate) fn arm_pattern_range<Input>() -> impl Parser<Input, Output = ArmPattern>
+    where
+        Input: combine::Stream<Token = char>,
+        RibParseError: Into<
+            <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+        >,
+    {
+        (
+            range_bound().skip(spaces()),
+            string("..").skip(spaces()),
+            range_bound(),
+        )
+            .map(|(start, _, end)| {
+                config::require(ParserFeatures::RANGES);
+                ArmPattern::range(start, end)
+            })
+    }
+
+    fn range_bound<Input>() -> impl Parser<Input, Output = Expr>
+    where
+        Input: combine::Stream<Token = char>,
+        RibParseError: Into<
+            <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+        >,
+    {
+        (optional(char_('-')), many1(digit()))
+            .and_then(|(sign, digits): (Option<char>, String)| {
+                let bound = format!("{}{}", sign.map(String::from).unwrap_or_default(), digits);
+                bound
+                    .parse::<f64>()
+                    .map(Expr::number)
+                    .map_err(|_| RibParseError::Message("Invalid range bound".to_string()).into())
+            })
+            .message("Unable to parse range bound")
+    }
+
     pub(crate) fn alias_name<Input>() -> impl Parser<Input, Output = String>
     where
         Input: combine::Stream<Token = char>,
@@ -179,10 +222,14 @@ mod internal {
         let custom = (
             constructor_type_name().skip(spaces()),
             string("(").skip(spaces()),
-            sep_by(arm_pattern().skip(spaces()), char_(',').skip(spaces())),
+            sep_end_by(arm_pattern().skip(spaces()), char_(',').skip(spaces())),
             string(")").skip(spaces()),
         )
-            .map(|(name, _, patterns, _)| ArmPattern::Constructor(name, patterns));
+            .map(|(name, _, patterns, _)| {
+                // An alias of a constructor keyword (Ex: `some`) is the constructor itself
+                let name = config::keyword_of_alias(&name).unwrap_or(name);
+                ArmPattern::Constructor(name, patterns)
+            });
 
         attempt(none_constructor()).or(custom)
     }
@@ -194,7 +241,7 @@ mod internal {
             <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
         >,
     {
-        string("none").map(|_| ArmPattern::constructor("none", vec![]))
+        keyword("none").map(|_| ArmPattern::constructor("none", vec![]))
     }
 
     fn tuple_arm_pattern_constructor<Input>() -> impl Parser<Input, Output = ArmPattern>
@@ -206,7 +253,7 @@ mod internal {
     {
         (
             string("(").skip(spaces()),
-            sep_by(arm_pattern().skip(spaces()), char_(',').skip(spaces())),
+            sep_end_by(arm_pattern().skip(spaces()), char_(',').skip(spaces())),
             string(")").skip(spaces()),
         )
             .map(|(_, patterns, _)| ArmPattern::TupleConstructor(patterns))
@@ -221,7 +268,7 @@ mod internal {
     {
         (
             string("[").skip(spaces()),
-            sep_by(arm_pattern().skip(spaces()), char_(',').skip(spaces())),
+            sep_end_by(arm_pattern().skip(spaces()), char_(',').skip(spaces())),
             string("]").skip(spaces()),
         )
             .map(|(_, patterns, _)| ArmPattern::ListConstructor(patterns))
@@ -241,7 +288,7 @@ mod internal {
     {
         (
             string("{").skip(spaces()),
-            sep_by1(key_arm_pattern().skip(spaces()), char_(',').skip(spaces())),
+            sep_end_by1(key_arm_pattern().skip(spaces()), char_(',').skip(spaces())),
             string("}").skip(spaces()),
         )
             .map(|(_, patterns, _)| {
@@ -449,4 +496,37 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_pattern_match_with_ranges() {
+        let input = r#"match request.query.score { 0..50 => "low", 50 .. 80 => "mid", -10..0 => "negative", _ => "high" }"#;
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::pattern_match(
+                    Expr::select_field(
+                        Expr::select_field(Expr::identifier("request"), "query"),
+                        "score"
+                    ),
+                    vec![
+                        MatchArm::new(
+                            ArmPattern::range(Expr::number(0f64), Expr::number(50f64)),
+                            Expr::literal("low")
+                        ),
+                        MatchArm::new(
+                            ArmPattern::range(Expr::number(50f64), Expr::number(80f64)),
+                            Expr::literal("mid")
+                        ),
+                        MatchArm::new(
+                            ArmPattern::range(Expr::number(-10f64), Expr::number(0f64)),
+                            Expr::literal("negative")
+                        ),
+                        MatchArm::new(ArmPattern::WildCard, Expr::literal("high")),
+                    ]
+                ),
+                ""
+            ))
+        );
+    }
 }
@@ -0,0 +1,200 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::cell::RefCell;
+use std::io::{BufReader, Read};
+use std::rc::Rc;
+
+use combine::stream::{buffered, position, IteratorStream};
+use combine::{EasyParser, Parser};
+
+use crate::expr::Expr;
+use crate::parser::rib_expr::rib_program;
+
+// The number of characters the parser can backtrack over when parsing from a reader.
+// Only this window of the input is kept in memory, instead of the whole program
+pub(crate) const LOOKAHEAD: usize = 64 * 1024;
+
+// Parse a Rib program from a reader (Ex: a large generated template in a file),
+// decoding and tokenizing the input as it is read. The result is the same as `Expr::from_text`,
+// unless a single expression requires backtracking over more than `LOOKAHEAD` characters
+pub fn parse_reader<R: Read>(reader: R) -> Result<Expr, String> {
+    let io_error = Rc::new(RefCell::new(None));
+    let chars = internal::Utf8Chars::new(BufReader::new(reader), io_error.clone());
+
+    let stream =
+        buffered::Stream::new(position::Stream::new(IteratorStream::new(chars)), LOOKAHEAD);
+
+    let result = rib_program()
+        .easy_parse(stream)
+        .map(|(expr, _)| expr)
+        .map_err(|err| format!("{}", err));
+
+    // A read failure ends the stream early, which would otherwise show up as a confusing parse error
+    match io_error.take() {
+        Some(err) => Err(format!("Failed to read the Rib program: {}", err)),
+        None => result,
+    }
+}
+
+mod internal {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::io::{BufRead, ErrorKind};
+    use std::rc::Rc;
+
+    // Decodes the characters of a UTF-8 reader, a buffer at a time.
+    // The iterator stops at the first read (or decoding) error, which is kept in `error`
+    pub(crate) struct Utf8Chars<R> {
+        reader: R,
+        decoded: VecDeque<char>,
+        // The bytes of a character split across two reads
+        incomplete: Vec<u8>,
+        error: Rc<RefCell<Option<String>>>,
+        done: bool,
+    }
+
+    impl<R: BufRead> Utf8Chars<R> {
+        pub(crate) fn new(reader: R, error: Rc<RefCell<Option<String>>>) -> Self {
+            Utf8Chars {
+                reader,
+                decoded: VecDeque::new(),
+                incomplete: vec![],
+                error,
+                done: false,
+            }
+        }
+
+        fn fill(&mut self) {
+            let buffer = match self.reader.fill_buf() {
+                Ok(buffer) => buffer,
+                Err(err) if err.kind() == ErrorKind::Interrupted => return,
+                Err(err) => {
+                    self.fail(err.to_string());
+                    return;
+                }
+            };
+
+            if buffer.is_empty() {
+                if self.incomplete.is_empty() {
+                    self.done = true;
+                } else {
+                    self.fail("Incomplete UTF-8 character at the end of input".to_string());
+                }
+                return;
+            }
+
+            let length = buffer.len();
+            self.incomplete.extend_from_slice(buffer);
+            self.reader.consume(length);
+
+            let valid_up_to = match std::str::from_utf8(&self.incomplete) {
+                Ok(_) => self.incomplete.len(),
+                // The last character continues in the next buffer
+                Err(err) if err.error_len().is_none() => err.valid_up_to(),
+                Err(err) => {
+                    self.fail(err.to_string());
+                    return;
+                }
+            };
+
+            let rest = self.incomplete.split_off(valid_up_to);
+            // Already validated above
+            let text = std::str::from_utf8(&self.incomplete).unwrap_or_default();
+            self.decoded.extend(text.chars());
+            self.incomplete = rest;
+        }
+
+        fn fail(&mut self, error: String) {
+            *self.error.borrow_mut() = Some(error);
+            self.done = true;
+        }
+    }
+
+    impl<R: BufRead> Iterator for Utf8Chars<R> {
+        type Item = char;
+
+        fn next(&mut self) -> Option<char> {
+            while self.decoded.is_empty() && !self.done {
+                self.fill();
+            }
+
+            self.decoded.pop_front()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use crate::parser::reader::parse_reader;
+    use crate::Expr;
+
+    #[test]
+    fn test_parse_reader_large_program() {
+        let mut program = String::new();
+        for i in 0..5000 {
+            program.push_str(&format!(
+                "let x{}: u64 = {};\nlet y{} = {{name: \"ünïcödé-{}\", value: [x{}, some(x{})]}};\n",
+                i, i, i, i, i, i
+            ));
+        }
+        program.push_str("y4999");
+
+        let expected = Expr::from_text(&program).unwrap();
+        let result = parse_reader(Cursor::new(program.into_bytes())).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_reader_reports_syntax_errors() {
+        let program = "let x = 1;\nlet y = some [x);\ny";
+
+        let result = parse_reader(Cursor::new(program.as_bytes()));
+
+        assert!(result
+            .unwrap_err()
+            .starts_with("Parse error at line: 2, column: 9"));
+    }
+
+    #[test]
+    fn test_parse_reader_character_split_across_reads() {
+        // Returns a single byte at a time, splitting every multibyte character
+        struct ByteByByte(Cursor<Vec<u8>>);
+
+        impl Read for ByteByByte {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = buf.len().min(1);
+                self.0.read(&mut buf[..len])
+            }
+        }
+
+        let program = r#"let x = "héllo wörld ✓"; x"#;
+        let reader = std::io::BufReader::with_capacity(1, ByteByByte(Cursor::new(program.into())));
+
+        let result = parse_reader(reader);
+
+        assert_eq!(result, Expr::from_text(program));
+    }
+
+    #[test]
+    fn test_parse_reader_invalid_utf8() {
+        let result = parse_reader(Cursor::new(vec![b'"', 0xff, 0xfe, b'"']));
+
+        assert!(result
+            .unwrap_err()
+            .starts_with("Failed to read the Rib program"));
+    }
+}
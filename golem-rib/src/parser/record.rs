@@ -15,7 +15,7 @@
 use combine::{
     between, many1, parser,
     parser::char::{char as char_, letter, spaces},
-    sep_by1, ParseError, Parser, Stream,
+    sep_end_by1, ParseError, Parser, Stream,
 };
 
 use crate::expr::Expr;
@@ -46,7 +46,7 @@ where
             between(
                 char_('{').skip(spaces()),
                 char_('}').skip(spaces()),
-                sep_by1(field().skip(spaces()), char_(',').skip(spaces())),
+                sep_end_by1(field().skip(spaces()), char_(',').skip(spaces())),
             )
             .map(|fields: Vec<Field>| {
                 Expr::record(
@@ -100,6 +100,7 @@ mod tests {
     use combine::EasyParser;
 
     use super::*;
+    use crate::BuiltinFunction;
 
     #[test]
     fn test_singleton_record() {
@@ -143,6 +144,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_record_with_trailing_comma() {
+        let input = "{foo: bar, baz: qux,}";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::record(vec![
+                    ("foo".to_string(), Expr::identifier("bar")),
+                    ("baz".to_string(), Expr::identifier("qux"))
+                ]),
+                ""
+            ))
+        );
+    }
+
     #[test]
     fn test_record_with_invalid_values() {
         let input = "{ foo: 'bar' }";
@@ -223,4 +240,36 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_record_with_coalesce_field() {
+        let input =
+            r#"{limit: request.query.limit ?? 10, order: order ?? "asc", big: count ?? 0 > 10}"#;
+        let result = rib_expr().easy_parse(input);
+
+        let coalesce = |value: &str, default: &str| {
+            Expr::call_builtin(
+                BuiltinFunction::Coalesce,
+                vec![
+                    Expr::from_text(value).unwrap(),
+                    Expr::from_text(default).unwrap(),
+                ],
+            )
+        };
+
+        assert_eq!(
+            result,
+            Ok((
+                Expr::record(vec![
+                    ("limit".to_string(), coalesce("request.query.limit", "10")),
+                    ("order".to_string(), coalesce("order", r#""asc""#)),
+                    (
+                        "big".to_string(),
+                        Expr::greater_than(coalesce("count", "0"), Expr::number(10f64))
+                    )
+                ]),
+                ""
+            ))
+        );
+    }
 }
@@ -0,0 +1,148 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use combine::stream::position;
+use combine::{eof, EasyParser, Parser};
+
+use crate::expr::Expr;
+use crate::parser::rib_expr::{rib_expr, rib_program};
+
+// Parse a Rib program, and if it fails, recover at the statement boundaries (top level `;`)
+// and parse each statement on its own, to report the errors in all the statements
+// instead of only the first one. Positions in the errors are relative to the whole input.
+pub fn parse_all(input: &str) -> Result<Expr, Vec<String>> {
+    match rib_program().easy_parse(position::Stream::new(input)) {
+        Ok((expr, _)) => Ok(expr),
+        Err(err) => {
+            let mut errors = vec![];
+
+            for statement in internal::split_statements(input) {
+                let stream = position::Stream::with_positioner(statement.text, statement.start);
+
+                if let Err(err) = rib_expr().skip(eof()).easy_parse(stream) {
+                    errors.push(format!("{}", err));
+                }
+            }
+
+            // The error can't be attributed to a single statement (Ex: an unbalanced bracket)
+            if errors.is_empty() {
+                errors.push(format!("{}", err));
+            }
+
+            Err(errors)
+        }
+    }
+}
+
+mod internal {
+    use combine::stream::position::SourcePosition;
+
+    pub(crate) struct Statement<'a> {
+        pub(crate) text: &'a str,
+        pub(crate) start: SourcePosition,
+    }
+
+    // Split the program at the `;` which are not within brackets or string literals.
+    // Blank statements are skipped
+    pub(crate) fn split_statements(input: &str) -> Vec<Statement> {
+        let mut statements = vec![];
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut start_index = 0;
+        let mut start = SourcePosition::default();
+        let mut current = SourcePosition::default();
+
+        for (index, ch) in input.char_indices() {
+            match ch {
+                '"' => in_string = !in_string,
+                '(' | '[' | '{' if !in_string => depth += 1,
+                ')' | ']' | '}' if !in_string => depth -= 1,
+                ';' if !in_string && depth <= 0 => {
+                    push_statement(&mut statements, &input[start_index..index], start);
+                    start_index = index + ch.len_utf8();
+                }
+                _ => {}
+            }
+
+            if ch == '\n' {
+                current.line += 1;
+                current.column = 1;
+            } else {
+                current.column += 1;
+            }
+
+            if start_index == index + ch.len_utf8() {
+                start = current;
+            }
+        }
+
+        push_statement(&mut statements, &input[start_index..], start);
+
+        statements
+    }
+
+    fn push_statement<'a>(
+        statements: &mut Vec<Statement<'a>>,
+        text: &'a str,
+        start: SourcePosition,
+    ) {
+        if !text.trim().is_empty() {
+            statements.push(Statement { text, start });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::recovery::parse_all;
+    use crate::Expr;
+
+    #[test]
+    fn test_parse_all_valid_program() {
+        let input = r#"
+          let x = 1;
+          let y = 2;
+          foo(x, y)"#;
+
+        assert_eq!(parse_all(input), Ok(Expr::from_text(input).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_all_reports_independent_errors() {
+        let input = r#"
+          let x = some [1);
+          let y = 2;
+          let z = ok [y);
+          z"#;
+
+        let errors = parse_all(input).unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("Parse error at line: 2"));
+        assert!(errors[0].contains("some is a keyword"));
+        assert!(errors[1].contains("Parse error at line: 4"));
+        assert!(errors[1].contains("ok is a keyword"));
+    }
+
+    #[test]
+    fn test_parse_all_unbalanced_brackets() {
+        let input = r#"
+          let x = [1, 2;
+          x"#;
+
+        let errors = parse_all(input).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+    }
+}
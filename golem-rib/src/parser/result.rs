@@ -13,14 +13,11 @@
 // limitations under the License.
 
 use combine::parser::char::spaces;
-use combine::{
-    attempt, choice,
-    parser::char::{char, string},
-    ParseError, Parser,
-};
+use combine::{attempt, choice, parser::char::char, ParseError, Parser};
 
 use crate::expr::Expr;
 use crate::parser::errors::RibParseError;
+use crate::parser::identifier::keyword;
 
 use super::rib_expr::rib_expr;
 
@@ -32,9 +29,9 @@ where
     >,
 {
     choice((
-        attempt(string("ok").skip(char('(')))
+        attempt(keyword("ok").skip(char('(')))
             .with((rib_expr().skip(spaces()), char(')')).map(|(expr, _)| Expr::ok(expr))),
-        attempt(string("err").skip(char('(')))
+        attempt(keyword("err").skip(char('(')))
             .with((rib_expr().skip(spaces()), char(')')).map(|(expr, _)| Expr::err(expr))),
     ))
     .message("Invalid syntax for Result type")
@@ -12,19 +12,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use combine::parser;
 use combine::parser::char;
 use combine::parser::char::{char, spaces};
-use combine::{eof, ParseError, Parser};
-use combine::{parser, sep_by};
+use combine::{eof, many, optional, ParseError, Parser};
 
 use crate::expr::Expr;
+use crate::parser::cond::missing_if_after_else;
+use crate::parser::config;
 use crate::parser::errors::RibParseError;
+use crate::parser::source_map::spanned;
 
 use super::binary_comparison::BinaryOp;
 
 // Parse a full Rib Program, and we expect the parser to fully consume the stream
-// unlike rib block expression
+// unlike rib block expression. A program that's empty (or only whitespace) is an empty string,
+// just like an empty template
 pub fn rib_program<Input>() -> impl Parser<Input, Output = Expr>
+where
+    Input: combine::Stream<Token = char>,
+    RibParseError: Into<
+        <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+    >,
+{
+    statements().skip(eof()).map(|expr| match expr {
+        Expr::Multiple(exprs, _) if exprs.is_empty() => Expr::empty_expr(),
+        expr => expr,
+    })
+}
+
+// Statements separated by `;`, such as `let x = request.path.id; x + 1`,
+// where the value of the last one is the value of the whole sequence.
+// Used by a rib program, a block, and the code within `${..}` of a literal
+pub fn statements<Input>() -> impl Parser<Input, Output = Expr>
 where
     Input: combine::Stream<Token = char>,
     RibParseError: Into<
@@ -32,15 +52,28 @@ where
     >,
 {
     spaces().with(
-        sep_by(rib_expr().skip(spaces()), char(';').skip(spaces()))
-            .map(|expressions: Vec<Expr>| {
-                if expressions.len() == 1 {
-                    expressions.first().unwrap().clone()
-                } else {
-                    Expr::multiple(expressions)
-                }
-            })
-            .skip(eof()),
+        optional((
+            rib_expr()
+                .skip(spaces())
+                .then(missing_if_after_else::<Input>),
+            many(
+                char(';').skip(spaces()).with(
+                    rib_expr()
+                        .skip(spaces())
+                        .message("Expected an expression after `;`")
+                        .then(missing_if_after_else::<Input>),
+                ),
+            ),
+        ))
+        .map(|statements: Option<(Expr, Vec<Expr>)>| match statements {
+            None => Expr::multiple(vec![]),
+            Some((first, rest)) if rest.is_empty() => first,
+            Some((first, rest)) => {
+                let mut expressions = vec![first];
+                expressions.extend(rest);
+                Expr::multiple(expressions)
+            }
+        }),
     )
 }
 
@@ -49,11 +82,20 @@ parser! {
     pub fn rib_expr[Input]()(Input) -> Expr
     where [Input: combine::Stream<Token = char>, RibParseError: Into<<Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError>,]
     {
-       rib_expr_()
+       rib_expr_(true)
     }
 }
 
-pub fn rib_expr_<Input>() -> impl Parser<Input, Output = Expr>
+// The value of a let binding, where `in` starts the body of the binding
+parser! {
+    pub fn let_value[Input]()(Input) -> Expr
+    where [Input: combine::Stream<Token = char>, RibParseError: Into<<Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError>,]
+    {
+       rib_expr_(false)
+    }
+}
+
+pub fn rib_expr_<Input>(allow_in: bool) -> impl Parser<Input, Output = Expr>
 where
     Input: combine::Stream<Token = char>,
     RibParseError: Into<
@@ -61,28 +103,53 @@ where
     >,
 {
     spaces()
-        .with(
-            (internal::simple_expr(), internal::rib_expr_rest()).map(|(expr, rest)| {
-                // FIXME: Respect operator precedence
-                rest.into_iter().fold(expr, |acc, (op, next)| match op {
-                    BinaryOp::GreaterThan => Expr::greater_than(acc, next),
-                    BinaryOp::LessThan => Expr::less_than(acc, next),
-                    BinaryOp::LessThanOrEqualTo => Expr::less_than_or_equal_to(acc, next),
-                    BinaryOp::GreaterThanOrEqualTo => Expr::greater_than_or_equal_to(acc, next),
-                    BinaryOp::EqualTo => Expr::equal_to(acc, next),
-                    BinaryOp::And => Expr::and(acc, next),
-                    BinaryOp::Or => Expr::or(acc, next),
-                })
-            }),
-        )
+        .with(spanned(
+            (internal::simple_expr(), internal::rib_expr_rest(allow_in)).and_then(
+                |(expr, rest)| {
+                    for (op, _) in &rest {
+                        config::require_operator(op);
+                    }
+
+                    let (expr, rest) = internal::apply_safe_navigation(expr, rest)
+                        .map_err(RibParseError::Message)?;
+                    let (expr, rest) = internal::apply_coalesce(expr, rest);
+                    let (expr, rest) = internal::apply_range(expr, rest);
+
+                    // FIXME: Respect operator precedence
+                    rest.into_iter()
+                        .try_fold(expr, |acc, (op, next)| match op {
+                            BinaryOp::GreaterThan => Ok(Expr::greater_than(acc, next)),
+                            BinaryOp::LessThan => Ok(Expr::less_than(acc, next)),
+                            BinaryOp::LessThanOrEqualTo => {
+                                Ok(Expr::less_than_or_equal_to(acc, next))
+                            }
+                            BinaryOp::GreaterThanOrEqualTo => {
+                                Ok(Expr::greater_than_or_equal_to(acc, next))
+                            }
+                            BinaryOp::EqualTo => Ok(internal::equal_to(acc, next)),
+                            BinaryOp::NotEqualTo => Ok(internal::not_equal_to(acc, next)),
+                            BinaryOp::And => Ok(Expr::and(acc, next)),
+                            BinaryOp::Or => Ok(Expr::or(acc, next)),
+                            BinaryOp::Pipe => internal::pipe(acc, next),
+                            BinaryOp::Coalesce => Ok(internal::coalesce(acc, next)),
+                            BinaryOp::SafeNavigation => internal::safe_navigation(acc, false, next),
+                            BinaryOp::Range => Ok(internal::range(acc, next, false)),
+                            BinaryOp::RangeInclusive => Ok(internal::range(acc, next, true)),
+                            BinaryOp::In => Ok(internal::in_range(acc, next)),
+                        })
+                        .map_err(RibParseError::Message)
+                },
+            ),
+        ))
         .skip(spaces())
 }
 
 mod internal {
     use crate::parser::binary_comparison::{binary_op, BinaryOp};
     use crate::parser::boolean::boolean_literal;
-    use crate::parser::call::call;
+    use crate::parser::call::{builtin_call, call};
     use crate::parser::cond::conditional;
+    use crate::parser::config;
     use crate::parser::errors::RibParseError;
     use crate::parser::flag::flag;
     use crate::parser::identifier::identifier;
@@ -96,13 +163,19 @@ mod internal {
     use crate::parser::record::record;
     use crate::parser::result::result;
 
+    use crate::call_type::CallType;
     use crate::parser::select_field::select_field;
     use crate::parser::select_index::select_index;
     use crate::parser::sequence::sequence;
+    use crate::parser::source_map::spanned;
     use crate::parser::tuple::tuple;
-    use crate::Expr;
+    use crate::{
+        ArmPattern, BuiltinFunction, DynamicParsedFunctionName, DynamicParsedFunctionReference,
+        Expr, MatchArm, ParsedFunctionSite,
+    };
     use combine::parser::char::spaces;
     use combine::{attempt, choice, many, parser, ParseError, Parser, Stream};
+    use std::collections::{HashSet, VecDeque};
 
     // A simple expression is a composition of all parsers that doesn't involve left recursion
     pub fn simple_expr_<Input>() -> impl Parser<Input, Output = Expr>
@@ -113,7 +186,7 @@ mod internal {
         >,
     {
         spaces()
-            .with(choice((
+            .with(spanned(choice((
                 pattern_match(),
                 let_binding(),
                 conditional(),
@@ -127,10 +200,11 @@ mod internal {
                 not(),
                 option(),
                 result(),
+                attempt(builtin_call()),
                 attempt(call()),
                 identifier(),
                 number(),
-            )))
+            ))))
             .skip(spaces())
     }
 
@@ -142,24 +216,298 @@ mod internal {
         }
     }
 
-    pub fn rib_expr_rest_<Input>() -> impl Parser<Input, Output = Vec<(BinaryOp, Expr)>>
+    pub fn rib_expr_rest_<Input>(
+        allow_in: bool,
+    ) -> impl Parser<Input, Output = Vec<(BinaryOp, Expr)>>
     where
         Input: combine::Stream<Token = char>,
         RibParseError: Into<
             <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
         >,
     {
-        many((binary_op(), simple_expr()))
+        many((binary_op(allow_in), simple_expr()))
     }
 
     parser! {
-        pub(crate) fn rib_expr_rest[Input]()(Input) -> Vec<(BinaryOp, Expr)>
+        pub(crate) fn rib_expr_rest[Input](allow_in: bool)(Input) -> Vec<(BinaryOp, Expr)>
         where [Input: Stream<Token = char>, RibParseError: Into<<Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError>,]
         {
-            rib_expr_rest_()
+            rib_expr_rest_(*allow_in)
+        }
+    }
+
+    // `value |> f` is a call of `f` with `value` as the first argument. The function is either
+    // just a name (Ex: `value |> trim`), or a call with the rest of the arguments
+    // (Ex: `value |> split(",")`). The result is the same `Expr::Call` as the nested call
+    pub(crate) fn pipe(value: Expr, function: Expr) -> Result<Expr, String> {
+        match function {
+            Expr::Call(CallType::Function(function_name), args, _) => {
+                let mut piped_args = vec![value];
+                piped_args.extend(args);
+                Ok(Expr::call(function_name, piped_args))
+            }
+            Expr::Identifier(variable_id, _) if variable_id.is_global() => {
+                let function_name = DynamicParsedFunctionName {
+                    site: ParsedFunctionSite::Global,
+                    function: DynamicParsedFunctionReference::Function {
+                        function: variable_id.name(),
+                    },
+                };
+                config::require_call(&function_name);
+                Ok(Expr::call(function_name, vec![value]))
+            }
+            _ => Err(format!(
+                "Expected a function on the right side of `|>`, but found {}",
+                function
+            )),
+        }
+    }
+
+    // A comparison with `null` (or `none`) checks whether the other side is absent, so
+    // `request.query.cursor == null` is true if the query parameter is missing (or `none`),
+    // and false if it has any value. Like with `isPresent`, a missing field counts as `null`
+    pub(crate) fn equal_to(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (Expr::Option(None, _), other) | (other, Expr::Option(None, _)) => {
+                Expr::not(is_present(other))
+            }
+            (left, right) => Expr::equal_to(left, right),
+        }
+    }
+
+    pub(crate) fn not_equal_to(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (Expr::Option(None, _), other) | (other, Expr::Option(None, _)) => is_present(other),
+            (left, right) => Expr::not_equal_to(left, right),
+        }
+    }
+
+    // Always the builtin, even if the component exports an `isPresent` function
+    fn is_present(expr: Expr) -> Expr {
+        Expr::call_builtin(BuiltinFunction::IsPresent, vec![expr])
+    }
+
+    // `?.` binds tighter than any operator, as it's a part of the selection
+    // (Ex: `request.body?.user ?? "guest"` is `(request.body?.user) ?? "guest"`)
+    pub(crate) fn apply_safe_navigation(
+        expr: Expr,
+        rest: Vec<(BinaryOp, Expr)>,
+    ) -> Result<(Expr, Vec<(BinaryOp, Expr)>), String> {
+        let mut first = (expr, false);
+        let mut applied: Vec<(BinaryOp, (Expr, bool))> = vec![];
+
+        for (op, next) in rest {
+            match op {
+                BinaryOp::SafeNavigation => {
+                    let last = match applied.last_mut() {
+                        Some((_, last)) => last,
+                        None => &mut first,
+                    };
+                    let (base, is_optional) = std::mem::replace(last, (Expr::empty_expr(), false));
+                    *last = (safe_navigation(base, is_optional, next)?, true);
+                }
+                op => applied.push((op, (next, false))),
+            }
+        }
+
+        Ok((
+            first.0,
+            applied
+                .into_iter()
+                .map(|(op, (expr, _))| (op, expr))
+                .collect(),
+        ))
+    }
+
+    // The variable bound to the value of the base, when navigating further from it
+    const SAFE_NAVIGATION_VALUE: &str = "safe-navigation-value";
+
+    // `base?.field` is `some(base.field)` if the base has the field, and `none` otherwise,
+    // instead of failing like `base.field`. The selections following the field
+    // (Ex: `.name` in `base?.user.name`) are applied only if the field is present.
+    // If the base is itself the result of a `?.`, the navigation continues only if it's `some`,
+    // so `request.body?.user?.name` is `none` whether `user` or `name` is missing.
+    // A base that is not just a selection (Ex: the result of `f(x)`) is evaluated once,
+    // and navigated from as if it were optional
+    pub(crate) fn safe_navigation(
+        base: Expr,
+        is_optional: bool,
+        path: Expr,
+    ) -> Result<Expr, String> {
+        let field = path_root(&path).ok_or(format!(
+            "Expected a field on the right side of `?.`, but found {}",
+            path
+        ))?;
+
+        let navigate = |record: Expr| {
+            Expr::cond(
+                Expr::call_builtin(
+                    BuiltinFunction::HasField,
+                    vec![record.clone(), Expr::literal(&field)],
+                ),
+                Expr::option(Some(rebase(path.clone(), record))),
+                Expr::option(None),
+            )
+        };
+
+        if !is_optional && is_selection(&base) {
+            return Ok(navigate(base));
+        }
+
+        let base = if is_optional {
+            base
+        } else {
+            Expr::option(Some(base))
+        };
+
+        // The value is bound only within the arm, which has nothing but the path,
+        // so the name only has to differ from the variables the path refers to (Ex: an index)
+        let value = fresh_variable(&path);
+
+        Ok(Expr::pattern_match(
+            base,
+            vec![
+                MatchArm::new(
+                    ArmPattern::constructor(
+                        "some",
+                        vec![ArmPattern::literal(Expr::identifier(&value))],
+                    ),
+                    navigate(Expr::identifier(&value)),
+                ),
+                MatchArm::new(ArmPattern::constructor("none", vec![]), Expr::option(None)),
+            ],
+        ))
+    }
+
+    // Whether evaluating the expression again is the same as reusing its value,
+    // as it only selects from a variable (Ex: `request.body.user`)
+    fn is_selection(expr: &Expr) -> bool {
+        match expr {
+            Expr::Identifier(..) => true,
+            Expr::SelectField(inner, _, _) | Expr::SelectIndex(inner, _, _) => is_selection(inner),
+            _ => false,
+        }
+    }
+
+    fn fresh_variable(path: &Expr) -> String {
+        let mut names = HashSet::new();
+        let mut queue = VecDeque::from([path]);
+
+        while let Some(expr) = queue.pop_back() {
+            if let Expr::Identifier(variable_id, _) = expr {
+                names.insert(variable_id.name());
+            }
+            expr.visit_children_bottom_up(&mut queue);
+        }
+
+        (0..)
+            .map(|suffix| match suffix {
+                0 => SAFE_NAVIGATION_VALUE.to_string(),
+                suffix => format!("{}-{}", SAFE_NAVIGATION_VALUE, suffix),
+            })
+            .find(|name| !names.contains(name))
+            .unwrap()
+    }
+
+    // The name of the field a selection path (Ex: `user.name[0]`) starts with
+    fn path_root(path: &Expr) -> Option<String> {
+        match path {
+            Expr::Identifier(variable_id, _) => Some(variable_id.name()),
+            Expr::SelectField(inner, _, _)
+            | Expr::SelectIndex(inner, _, _)
+            | Expr::SelectIndexExpr(inner, _, _) => path_root(inner),
+            _ => None,
+        }
+    }
+
+    // Select the path from the record, instead of the variable the path starts with
+    fn rebase(path: Expr, record: Expr) -> Expr {
+        match path {
+            Expr::Identifier(variable_id, _) => Expr::select_field(record, variable_id.name()),
+            Expr::SelectField(inner, field, _) => Expr::select_field(rebase(*inner, record), field),
+            Expr::SelectIndex(inner, index, _) => Expr::select_index(rebase(*inner, record), index),
+            Expr::SelectIndexExpr(inner, index, _) => {
+                Expr::select_index_expr(rebase(*inner, record), *index)
+            }
+            other => other,
         }
     }
 
+    // `??` binds tighter than the other operators, so that `a ?? 10 > b` is `(a ?? 10) > b`,
+    // and `b < a ?? 10` is `b < (a ?? 10)`. The remaining operators are left as they are
+    pub(crate) fn apply_coalesce(
+        expr: Expr,
+        rest: Vec<(BinaryOp, Expr)>,
+    ) -> (Expr, Vec<(BinaryOp, Expr)>) {
+        let mut first = expr;
+        let mut applied: Vec<(BinaryOp, Expr)> = vec![];
+
+        for (op, next) in rest {
+            match op {
+                BinaryOp::Coalesce => match applied.pop() {
+                    Some((last_op, last)) => applied.push((last_op, coalesce(last, next))),
+                    None => first = coalesce(first, next),
+                },
+                op => applied.push((op, next)),
+            }
+        }
+
+        (first, applied)
+    }
+
+    // `a ?? b` is a call of the `coalesce` builtin, and `a ?? b ?? c` is `coalesce(a, b, c)`.
+    // Unlike a call written as `coalesce(a, b)`, it isn't shadowed by an exported `coalesce`
+    pub(crate) fn coalesce(value: Expr, default: Expr) -> Expr {
+        match value {
+            Expr::Call(CallType::Builtin(BuiltinFunction::Coalesce), mut args, _) => {
+                args.push(default);
+                Expr::call_builtin(BuiltinFunction::Coalesce, args)
+            }
+            value => Expr::call_builtin(BuiltinFunction::Coalesce, vec![value, default]),
+        }
+    }
+
+    // `..` and `..=` bind tighter than the comparisons and `in`, but not `??`, so that
+    // `page in 1..=limit ?? 100` is `page in (1..=(limit ?? 100))`
+    pub(crate) fn apply_range(
+        expr: Expr,
+        rest: Vec<(BinaryOp, Expr)>,
+    ) -> (Expr, Vec<(BinaryOp, Expr)>) {
+        let mut first = expr;
+        let mut applied: Vec<(BinaryOp, Expr)> = vec![];
+
+        for (op, next) in rest {
+            let inclusive = match op {
+                BinaryOp::Range => false,
+                BinaryOp::RangeInclusive => true,
+                op => {
+                    applied.push((op, next));
+                    continue;
+                }
+            };
+
+            match applied.pop() {
+                Some((last_op, last)) => applied.push((last_op, range(last, next, inclusive))),
+                None => first = range(first, next, inclusive),
+            }
+        }
+
+        (first, applied)
+    }
+
+    // `start..end` is a call of the `range` builtin, and `start..=end` includes the end
+    pub(crate) fn range(start: Expr, end: Expr, inclusive: bool) -> Expr {
+        Expr::call_builtin(
+            BuiltinFunction::Range,
+            vec![start, end, Expr::boolean(inclusive)],
+        )
+    }
+
+    // `value in range` (or `value in list`) is a call of the `inRange` builtin
+    pub(crate) fn in_range(value: Expr, range: Expr) -> Expr {
+        Expr::call_builtin(BuiltinFunction::InRange, vec![value, range])
+    }
+
     fn flag_or_record<Input>() -> impl Parser<Input, Output = Expr>
     where
         Input: combine::Stream<Token = char>,
@@ -20,7 +20,7 @@ use internal::*;
 
 use crate::expr::Expr;
 use crate::parser::errors::RibParseError;
-use crate::parser::identifier::identifier;
+use crate::parser::identifier::field_identifier;
 use crate::parser::record::record;
 
 parser! {
@@ -56,7 +56,7 @@ mod internal {
                 choice((
                     attempt(select_field()),
                     attempt(select_index()),
-                    attempt(identifier()),
+                    attempt(field_identifier()),
                 )),
             )
                 .map(|(base, _, opt)| {
@@ -84,6 +84,10 @@ mod internal {
                 let inner_select = build_selector(base, *second)?;
                 Some(Expr::select_index(inner_select, last_index))
             }
+            Expr::SelectIndexExpr(second, last_index, _) => {
+                let inner_select = build_selector(base, *second)?;
+                Some(Expr::select_index_expr(inner_select, *last_index))
+            }
             _ => None,
         }
     }
@@ -126,7 +130,9 @@ mod tests {
     use combine::EasyParser;
 
     use crate::expr::*;
+    use crate::function_name::DynamicParsedFunctionName;
     use crate::parser::rib_expr::rib_expr;
+    use crate::BuiltinFunction;
 
     #[test]
     fn test_select_field() {
@@ -295,4 +301,180 @@ mod tests {
             ))
         );
     }
+
+    fn has_field(record: Expr, field: &str) -> Expr {
+        Expr::call_builtin(
+            BuiltinFunction::HasField,
+            vec![record, Expr::literal(field)],
+        )
+    }
+
+    fn request_body() -> Expr {
+        Expr::select_field(Expr::identifier("request"), "body")
+    }
+
+    #[test]
+    fn test_safe_navigation() {
+        let input = "request.body?.user";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::cond(
+                    has_field(request_body(), "user"),
+                    Expr::option(Some(Expr::select_field(request_body(), "user"))),
+                    Expr::option(None)
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_chained_safe_navigation() {
+        let input = "request.body?.user?.name";
+        let result = rib_expr().easy_parse(input);
+        let value = Expr::identifier("safe-navigation-value");
+        assert_eq!(
+            result,
+            Ok((
+                Expr::pattern_match(
+                    Expr::cond(
+                        has_field(request_body(), "user"),
+                        Expr::option(Some(Expr::select_field(request_body(), "user"))),
+                        Expr::option(None)
+                    ),
+                    vec![
+                        MatchArm::new(
+                            ArmPattern::constructor(
+                                "some",
+                                vec![ArmPattern::Literal(Box::new(value.clone()))]
+                            ),
+                            Expr::cond(
+                                has_field(value.clone(), "name"),
+                                Expr::option(Some(Expr::select_field(value, "name"))),
+                                Expr::option(None)
+                            ),
+                        ),
+                        MatchArm::new(ArmPattern::constructor("none", vec![]), Expr::option(None)),
+                    ]
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_safe_navigation_mixed_with_strict_selection() {
+        let input = "request.body.user?.address.city";
+        let result = rib_expr().easy_parse(input);
+        let user = Expr::select_field(request_body(), "user");
+        assert_eq!(
+            result,
+            Ok((
+                Expr::cond(
+                    has_field(user.clone(), "address"),
+                    Expr::option(Some(Expr::select_field(
+                        Expr::select_field(user, "address"),
+                        "city"
+                    ))),
+                    Expr::option(None)
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_safe_navigation_binds_tighter_than_operators() {
+        let result = Expr::from_text(r#"request.body?.user ?? "guest""#);
+        let expected = Expr::call_builtin(
+            BuiltinFunction::Coalesce,
+            vec![
+                Expr::cond(
+                    has_field(request_body(), "user"),
+                    Expr::option(Some(Expr::select_field(request_body(), "user"))),
+                    Expr::option(None),
+                ),
+                Expr::literal("guest"),
+            ],
+        );
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_safe_navigation_evaluates_the_base_once() {
+        let input = "foo(x)?.name";
+        let result = rib_expr().easy_parse(input);
+        let value = Expr::identifier("safe-navigation-value");
+        assert_eq!(
+            result,
+            Ok((
+                Expr::pattern_match(
+                    Expr::option(Some(Expr::call(
+                        DynamicParsedFunctionName::parse("foo").unwrap(),
+                        vec![Expr::identifier("x")]
+                    ))),
+                    vec![
+                        MatchArm::new(
+                            ArmPattern::constructor(
+                                "some",
+                                vec![ArmPattern::Literal(Box::new(value.clone()))]
+                            ),
+                            Expr::cond(
+                                has_field(value.clone(), "name"),
+                                Expr::option(Some(Expr::select_field(value, "name"))),
+                                Expr::option(None)
+                            ),
+                        ),
+                        MatchArm::new(ArmPattern::constructor("none", vec![]), Expr::option(None)),
+                    ]
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_safe_navigation_value_does_not_shadow_the_variables_of_the_path() {
+        let input = "foo(x)?.items[safe-navigation-value]";
+        let result = rib_expr().easy_parse(input);
+        let value = Expr::identifier("safe-navigation-value-1");
+        assert_eq!(
+            result,
+            Ok((
+                Expr::pattern_match(
+                    Expr::option(Some(Expr::call(
+                        DynamicParsedFunctionName::parse("foo").unwrap(),
+                        vec![Expr::identifier("x")]
+                    ))),
+                    vec![
+                        MatchArm::new(
+                            ArmPattern::constructor(
+                                "some",
+                                vec![ArmPattern::Literal(Box::new(value.clone()))]
+                            ),
+                            Expr::cond(
+                                has_field(value.clone(), "items"),
+                                Expr::option(Some(Expr::select_index_expr(
+                                    Expr::select_field(value, "items"),
+                                    Expr::identifier("safe-navigation-value")
+                                ))),
+                                Expr::option(None)
+                            ),
+                        ),
+                        MatchArm::new(ArmPattern::constructor("none", vec![]), Expr::option(None)),
+                    ]
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_safe_navigation_to_non_field() {
+        let input = "request.body?.foo(1)";
+        let result = Expr::from_text(input);
+        assert!(result.is_err());
+    }
 }
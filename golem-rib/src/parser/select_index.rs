@@ -32,18 +32,16 @@ where
         (
             base_expr().skip(spaces()),
             char_('[').skip(spaces()),
-            pos_num().skip(spaces()),
-            char_(']').skip(spaces()),
+            index().skip(spaces()),
             optional(nested_indices()),
         )
-            .map(
-                |(expr, _, number, _, possible_indices)| match possible_indices {
-                    Some(indices) => {
-                        build_select_index_from(Expr::select_index(expr, number), indices)
-                    }
-                    None => Expr::select_index(expr, number),
-                },
-            ),
+            .map(|(expr, _, index, possible_indices)| {
+                let select_index = index.select_from(expr);
+                match possible_indices {
+                    Some(indices) => build_select_index_from(select_index, indices),
+                    None => select_index,
+                }
+            }),
     )
 }
 
@@ -51,34 +49,59 @@ mod internal {
     use combine::parser::char::char as char_;
 
     use crate::parser::number::number;
+    use crate::parser::rib_expr::rib_expr;
     use crate::parser::sequence::sequence;
 
     use super::*;
 
-    pub(crate) fn build_select_index_from(base_expr: Expr, indices: Vec<usize>) -> Expr {
+    // A literal index is kept as it is, and anything else
+    // between the brackets is an expression evaluated at runtime
+    pub(crate) enum Index {
+        Literal(usize),
+        Expr(Expr),
+    }
+
+    impl Index {
+        pub(crate) fn select_from(self, expr: Expr) -> Expr {
+            match self {
+                Index::Literal(index) => Expr::select_index(expr, index),
+                Index::Expr(index) => Expr::select_index_expr(expr, index),
+            }
+        }
+    }
+
+    pub(crate) fn build_select_index_from(base_expr: Expr, indices: Vec<Index>) -> Expr {
         let mut result = base_expr;
         for index in indices {
-            result = Expr::select_index(result, index);
+            result = index.select_from(result);
         }
         result
     }
 
-    pub(crate) fn nested_indices<Input>() -> impl Parser<Input, Output = Vec<usize>>
+    pub(crate) fn nested_indices<Input>() -> impl Parser<Input, Output = Vec<Index>>
     where
         Input: combine::Stream<Token = char>,
         RibParseError: Into<
             <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
         >,
     {
-        many1(
-            (
-                char_('[').skip(spaces()),
-                pos_num().skip(spaces()),
-                char_(']').skip(spaces()),
-            )
-                .map(|(_, number, _)| number),
-        )
-        .map(|result: Vec<usize>| result)
+        many1((char_('[').skip(spaces()), index().skip(spaces())).map(|(_, index)| index))
+            .map(|result: Vec<Index>| result)
+    }
+
+    // Parses the index along with the closing bracket
+    pub(crate) fn index<Input>() -> impl Parser<Input, Output = Index>
+    where
+        Input: combine::Stream<Token = char>,
+        RibParseError: Into<
+            <Input::Error as ParseError<Input::Token, Input::Range, Input::Position>>::StreamError,
+        >,
+    {
+        choice((
+            attempt((pos_num().skip(spaces()), char_(']')))
+                .map(|(number, _)| Index::Literal(number)),
+            (rib_expr().skip(spaces()), char_(']')).map(|(expr, _)| Index::Expr(expr)),
+        ))
     }
 
     pub(crate) fn pos_num<Input>() -> impl Parser<Input, Output = usize>
@@ -140,4 +163,58 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_select_index_with_expr() {
+        let input = "foo[request.query.i]";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::select_index_expr(
+                    Expr::identifier("foo"),
+                    Expr::select_field(
+                        Expr::select_field(Expr::identifier("request"), "query"),
+                        "i"
+                    )
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_recursive_select_index_with_expr() {
+        let input = "foo[i][0][ j ]";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::select_index_expr(
+                    Expr::select_index(
+                        Expr::select_index_expr(Expr::identifier("foo"), Expr::identifier("i")),
+                        0
+                    ),
+                    Expr::identifier("j")
+                ),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_select_index_with_expr_in_select_field() {
+        let input = "foo.bar[i]";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::select_index_expr(
+                    Expr::select_field(Expr::identifier("foo"), "bar"),
+                    Expr::identifier("i")
+                ),
+                ""
+            ))
+        );
+    }
 }
@@ -14,7 +14,7 @@
 
 use combine::parser::char::{char, spaces};
 use combine::{between, Parser};
-use combine::{sep_by, ParseError};
+use combine::{sep_end_by, ParseError};
 
 use crate::expr::Expr;
 use crate::parser::errors::RibParseError;
@@ -32,7 +32,7 @@ where
             between(
                 char('['),
                 char(']'),
-                sep_by(rib_expr(), char(',').skip(spaces())),
+                sep_end_by(rib_expr(), char(',').skip(spaces())),
             )
             .map(Expr::sequence),
         )
@@ -75,6 +75,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sequence_with_trailing_comma() {
+        let input = "[foo, bar, ]";
+        let result = rib_expr().easy_parse(input);
+        assert_eq!(
+            result,
+            Ok((
+                Expr::sequence(vec![Expr::identifier("foo"), Expr::identifier("bar")]),
+                ""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sequence_with_double_comma() {
+        let result = Expr::from_text("[foo,, bar]");
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_sequence_of_not() {
         let input = "[!foo, !bar]";
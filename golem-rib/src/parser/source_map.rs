@@ -0,0 +1,418 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use combine::stream::position;
+use combine::{produce, EasyParser, Parser};
+
+use crate::expr::Expr;
+use crate::parser::rib_expr::rib_program;
+
+// Identifies a node of a parsed expression: the index of the node in a pre-order traversal,
+// visiting the children in source order. It's stable as long as the expression doesn't change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+// A byte range of the parsed text, excluding the surrounding whitespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+}
+
+// The spans of the nodes of a parsed expression, for tooling (Ex: editors).
+// A node may have no span if it doesn't correspond to a piece of the text
+// (Ex: the static parts of an interpolated string)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    spans: HashMap<NodeId, Span>,
+}
+
+impl SourceMap {
+    pub fn span(&self, node_id: NodeId) -> Option<Span> {
+        self.spans.get(&node_id).copied()
+    }
+
+    // The nodes of the expression along with their ids
+    pub fn nodes(expr: &Expr) -> Vec<(NodeId, &Expr)> {
+        let mut nodes = vec![];
+        internal::collect_nodes(expr, &mut nodes);
+        nodes
+    }
+}
+
+// Parse like `Expr::from_text`, additionally returning the spans of the nodes.
+// The spans are recorded while parsing, along with a fingerprint of the parsed expression,
+// and are then assigned to the nodes of the final expression with the same fingerprints.
+// The spans of the branches which were backtracked from are dropped as soon as the parser
+// backtracks, so they don't end up in the source map
+pub fn parse_with_source_map(input: &str) -> Result<(Expr, SourceMap), String> {
+    let _recording = internal::Recording::start();
+
+    let stream = position::Stream::with_positioner(input, internal::OffsetPositioner::default());
+
+    let expr = rib_program()
+        .easy_parse(stream)
+        .map(|t| t.0)
+        .map_err(|err| format!("{}", err))?;
+
+    let recorded = internal::Recorded::new(
+        internal::take_recorded()
+            .into_iter()
+            .map(|(span, fingerprint)| (internal::trim(input, span), fingerprint)),
+    );
+
+    let mut source_map = SourceMap::default();
+    let bounds = internal::trim(input, Span::new(0, input.len()));
+    let root_span = recorded
+        .find(internal::fingerprint(&expr), bounds)
+        .or(Some(bounds));
+    internal::assign(
+        &expr,
+        root_span,
+        bounds,
+        &recorded,
+        &mut 0,
+        &mut source_map.spans,
+    );
+
+    Ok((expr, source_map))
+}
+
+// Record the span of the expression parsed by `parser`, if the source map is being built
+pub(crate) fn spanned<Input, P>(parser: P) -> impl Parser<Input, Output = Expr>
+where
+    Input: combine::Stream<Token = char>,
+    P: Parser<Input, Output = Expr>,
+{
+    (
+        produce(internal::current_offset),
+        parser,
+        produce(internal::current_offset),
+    )
+        .map(|(start, expr, end)| {
+            if let (Some(start), Some(end)) = (start, end) {
+                internal::record(Span::new(start, end), internal::fingerprint(&expr));
+            }
+            expr
+        })
+}
+
+mod internal {
+    use std::cell::{Cell, RefCell};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{HashMap, VecDeque};
+    use std::fmt::Write;
+    use std::hash::Hasher;
+
+    use combine::stream::position::{Positioner, SourcePosition};
+
+    use crate::expr::Expr;
+    use crate::parser::source_map::{NodeId, Span};
+
+    thread_local! {
+        // The offset of the text being parsed, if the spans are being recorded
+        static OFFSET: Cell<Option<usize>> = const { Cell::new(None) };
+        // The recorded spans, in the order the parsers finished, so their ends don't decrease
+        static RECORDED: RefCell<Vec<(Span, u64)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Enables the recording of spans on the current thread until dropped
+    pub(crate) struct Recording;
+
+    impl Recording {
+        pub(crate) fn start() -> Recording {
+            OFFSET.with(|offset| offset.set(Some(0)));
+            RECORDED.with(|recorded| recorded.borrow_mut().clear());
+            Recording
+        }
+    }
+
+    impl Drop for Recording {
+        fn drop(&mut self) {
+            OFFSET.with(|offset| offset.set(None));
+            RECORDED.with(|recorded| recorded.borrow_mut().clear());
+        }
+    }
+
+    pub(crate) fn current_offset() -> Option<usize> {
+        OFFSET.with(|offset| offset.get())
+    }
+
+    fn advance(offset: usize) {
+        OFFSET.with(|current| {
+            if current.get().is_some() {
+                current.set(Some(offset));
+            }
+        })
+    }
+
+    // Going back to an offset drops the spans of the expressions parsed past it
+    fn backtrack(offset: usize) {
+        if current_offset().is_some() {
+            OFFSET.with(|current| current.set(Some(offset)));
+            RECORDED.with(|recorded| {
+                let mut recorded = recorded.borrow_mut();
+                while recorded.last().is_some_and(|(span, _)| span.end > offset) {
+                    recorded.pop();
+                }
+            })
+        }
+    }
+
+    // Empty spans come from the expressions parsed separately within a parser
+    // (Ex: resource parameters), which don't move the offset of the text being parsed
+    pub(crate) fn record(span: Span, fingerprint: u64) {
+        if span.start < span.end {
+            RECORDED.with(|recorded| recorded.borrow_mut().push((span, fingerprint)))
+        }
+    }
+
+    pub(crate) fn take_recorded() -> Vec<(Span, u64)> {
+        RECORDED.with(|recorded| std::mem::take(&mut *recorded.borrow_mut()))
+    }
+
+    // Identifies an expression by a hash of its contents, so the recorded spans don't keep
+    // a copy of every parsed expression
+    pub(crate) fn fingerprint(expr: &Expr) -> u64 {
+        struct HashWriter(DefaultHasher);
+
+        impl Write for HashWriter {
+            fn write_str(&mut self, s: &str) -> std::fmt::Result {
+                self.0.write(s.as_bytes());
+                Ok(())
+            }
+        }
+
+        let mut writer = HashWriter(DefaultHasher::new());
+        let _ = write!(writer, "{:?}", expr);
+        writer.0.finish()
+    }
+
+    // The recorded spans of each fingerprint, ordered by their position
+    pub(crate) struct Recorded {
+        spans: HashMap<u64, Vec<Span>>,
+    }
+
+    impl Recorded {
+        pub(crate) fn new(recorded: impl IntoIterator<Item = (Span, u64)>) -> Recorded {
+            let mut spans: HashMap<u64, Vec<Span>> = HashMap::new();
+            for (span, fingerprint) in recorded {
+                spans.entry(fingerprint).or_default().push(span);
+            }
+            for spans in spans.values_mut() {
+                spans.sort_by_key(|span| (span.start, span.end));
+            }
+            Recorded { spans }
+        }
+
+        // The earliest recorded span of the expression within the bounds
+        pub(crate) fn find(&self, fingerprint: u64, bounds: Span) -> Option<Span> {
+            let spans = self.spans.get(&fingerprint)?;
+            let first = spans.partition_point(|span| span.start < bounds.start);
+            spans[first..]
+                .iter()
+                .take_while(|span| span.start <= bounds.end)
+                .find(|span| span.end <= bounds.end)
+                .copied()
+        }
+    }
+
+    // Tracks the byte offset in the text next to the line and column used in the errors,
+    // and publishes it to the parsers through the recording state
+    #[derive(Debug, Clone, Default)]
+    pub(crate) struct OffsetPositioner {
+        source: SourcePosition,
+        offset: usize,
+    }
+
+    impl Positioner<char> for OffsetPositioner {
+        type Position = SourcePosition;
+        type Checkpoint = OffsetPositioner;
+
+        fn position(&self) -> SourcePosition {
+            self.source
+        }
+
+        fn update(&mut self, token: &char) {
+            self.source.update(token);
+            self.offset += token.len_utf8();
+            advance(self.offset);
+        }
+
+        fn checkpoint(&self) -> OffsetPositioner {
+            self.clone()
+        }
+
+        fn reset(&mut self, checkpoint: OffsetPositioner) {
+            *self = checkpoint;
+            backtrack(self.offset);
+        }
+    }
+
+    pub(crate) fn trim(input: &str, span: Span) -> Span {
+        let text = &input[span.start..span.end];
+        let start = span.start + (text.len() - text.trim_start().len());
+        let end = span.end - (text.len() - text.trim_end().len());
+
+        Span::new(start, end.max(start))
+    }
+
+    fn children(expr: &Expr) -> Vec<&Expr> {
+        let mut queue = VecDeque::new();
+        expr.visit_children_bottom_up(&mut queue);
+        queue.into_iter().collect()
+    }
+
+    pub(crate) fn collect_nodes<'a>(expr: &'a Expr, nodes: &mut Vec<(NodeId, &'a Expr)>) {
+        nodes.push((NodeId(nodes.len()), expr));
+
+        for child in children(expr) {
+            collect_nodes(child, nodes);
+        }
+    }
+
+    // Assign the ids in pre-order, and the spans to the nodes. A node without a recorded span
+    // (Ex: `a && b` within `a && b && c`) gets the span covering its children
+    pub(crate) fn assign(
+        expr: &Expr,
+        span: Option<Span>,
+        bounds: Span,
+        recorded: &Recorded,
+        next_id: &mut usize,
+        spans: &mut HashMap<NodeId, Span>,
+    ) -> Option<Span> {
+        let node_id = NodeId(*next_id);
+        *next_id += 1;
+
+        let bounds = span.unwrap_or(bounds);
+        let mut cursor = bounds.start;
+        let mut covered: Option<Span> = None;
+
+        for child in children(expr) {
+            let child_bounds = Span::new(cursor, bounds.end);
+            let child_span = recorded.find(fingerprint(child), child_bounds);
+
+            if let Some(child_span) =
+                assign(child, child_span, child_bounds, recorded, next_id, spans)
+            {
+                cursor = child_span.end;
+                covered = Some(match covered {
+                    Some(covered) => Span::new(covered.start, child_span.end),
+                    None => child_span,
+                });
+            }
+        }
+
+        let span = span.or(covered);
+
+        if let Some(span) = span {
+            spans.insert(node_id, span);
+        }
+
+        span
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use combine::stream::position::Positioner;
+
+    use crate::parser::source_map::internal::{record, take_recorded, OffsetPositioner, Recording};
+    use crate::parser::source_map::{parse_with_source_map, SourceMap, Span};
+    use crate::Expr;
+
+    #[test]
+    fn test_source_map_does_not_change_the_expr() {
+        let input = r#"let x = request.path.id; if x > 1 then foo(x, "${x}-a") else [1, 2]"#;
+
+        let (expr, _) = parse_with_source_map(input).unwrap();
+
+        assert_eq!(Ok(expr), Expr::from_text(input));
+    }
+
+    #[test]
+    fn test_span_of_cond_predicate() {
+        let input = r#"  if request.body.count > 10 then "é" else "b" "#;
+
+        let (expr, source_map) = parse_with_source_map(input).unwrap();
+        let nodes = SourceMap::nodes(&expr);
+
+        let (predicate_id, predicate) = nodes[1];
+        let span = source_map.span(predicate_id).unwrap();
+
+        assert_eq!(
+            Ok(predicate.clone()),
+            Expr::from_text("request.body.count > 10")
+        );
+        assert_eq!(span, Span::new(5, 28));
+        assert_eq!(&input[span.start..span.end], "request.body.count > 10");
+
+        let else_id = nodes.last().unwrap().0;
+        let span = source_map.span(else_id).unwrap();
+
+        assert_eq!(&input[span.start..span.end], r#""b""#);
+        assert_eq!(source_map.span(nodes[0].0), Some(Span::new(2, 47)));
+    }
+
+    #[test]
+    fn test_spans_of_repeated_expressions() {
+        let input = "foo(x, x)";
+
+        let (expr, source_map) = parse_with_source_map(input).unwrap();
+
+        let spans = SourceMap::nodes(&expr)
+            .into_iter()
+            .map(|(node_id, _)| source_map.span(node_id))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            spans,
+            vec![
+                Some(Span::new(0, 9)),
+                Some(Span::new(4, 5)),
+                Some(Span::new(7, 8))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backtracking_drops_the_spans_past_the_checkpoint() {
+        let _recording = Recording::start();
+        let mut positioner = OffsetPositioner::default();
+
+        for token in "ab".chars() {
+            positioner.update(&token);
+        }
+        record(Span::new(0, 2), 1);
+        let checkpoint = positioner.checkpoint();
+
+        for token in "cde".chars() {
+            positioner.update(&token);
+        }
+        record(Span::new(2, 4), 2);
+        record(Span::new(2, 5), 3);
+
+        positioner.reset(checkpoint);
+
+        assert_eq!(take_recorded(), vec![(Span::new(0, 2), 1)]);
+    }
+}
@@ -15,7 +15,7 @@
 use combine::{
     between,
     parser::char::{char, spaces},
-    sep_by, ParseError, Parser,
+    sep_end_by, ParseError, Parser,
 };
 
 use crate::expr::Expr;
@@ -35,7 +35,7 @@ where
             between(
                 char('('),
                 char(')'),
-                sep_by(rib_expr(), char(',').skip(spaces())),
+                sep_end_by(rib_expr(), char(',').skip(spaces())),
             )
             .map(Expr::tuple),
         )
@@ -19,7 +19,7 @@ use bincode::{Decode, Encode};
 use combine::parser::char;
 use combine::parser::char::{char, spaces, string};
 use combine::parser::choice::choice;
-use combine::{attempt, between, sep_by, Parser};
+use combine::{attempt, between, sep_end_by, Parser};
 use combine::{parser, ParseError};
 
 use golem_api_grpc::proto::golem::rib::type_name::Kind as InnerTypeName;
@@ -263,7 +263,7 @@ where
         .with(between(
             char('<').skip(spaces()),
             char('>').skip(spaces()),
-            sep_by(parse_type_name(), char(',').skip(spaces())),
+            sep_end_by(parse_type_name(), char(',').skip(spaces())),
         ))
         .map(TypeName::Tuple)
 }
@@ -0,0 +1,160 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::identifier::RESERVED_KEYWORDS;
+use crate::Expr;
+
+// A chain of named expressions (Ex: extract, validate and then build the worker request),
+// as an alternative to a single large expression. Each step can refer to the results
+// of the previous steps by their names, and the result of the last step is the result of the pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub steps: Vec<NamedStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedStep {
+    pub name: String,
+    pub expr: Expr,
+}
+
+impl NamedStep {
+    pub fn new(name: impl Into<String>, expr: Expr) -> Self {
+        NamedStep {
+            name: name.into(),
+            expr,
+        }
+    }
+}
+
+impl Pipeline {
+    pub fn new(steps: Vec<NamedStep>) -> Self {
+        Pipeline { steps }
+    }
+
+    // The pipeline as a single expression, where each step is a let binding:
+    // `let step1 = ...; let step2 = ...; step2`
+    pub fn to_expr(&self) -> Result<Expr, String> {
+        self.validate()?;
+
+        let mut exprs = self
+            .steps
+            .iter()
+            .map(|step| Expr::let_binding(&step.name, step.expr.clone()))
+            .collect::<Vec<_>>();
+
+        // validate ensures there is at least one step
+        let last = &self.steps[self.steps.len() - 1];
+        exprs.push(Expr::identifier(&last.name));
+
+        Ok(Expr::multiple(exprs))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.steps.is_empty() {
+            return Err("A pipeline needs at least one step".to_string());
+        }
+
+        let mut names = HashSet::new();
+
+        for step in &self.steps {
+            if RESERVED_KEYWORDS.contains(&step.name.as_str()) {
+                return Err(format!(
+                    "Invalid pipeline step name `{}`: it is a keyword",
+                    step.name
+                ));
+            }
+
+            if !names.insert(step.name.as_str()) {
+                return Err(format!("Duplicate pipeline step name `{}`", step.name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::Interpreter;
+    use crate::pipeline::{NamedStep, Pipeline};
+    use crate::Expr;
+    use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
+
+    #[tokio::test]
+    async fn test_pipeline_step_references_previous_step() {
+        let pipeline = Pipeline::new(vec![
+            NamedStep::new(
+                "user",
+                Expr::from_text(r#"{id: 1u64, name: "jak"}"#).unwrap(),
+            ),
+            NamedStep::new(
+                "greeting",
+                Expr::from_text(r#""hello ${user.name}""#).unwrap(),
+            ),
+        ]);
+
+        let expr = pipeline.to_expr().unwrap();
+        let compiled = crate::compiler::compile(&expr, &vec![]).unwrap();
+
+        let mut interpreter = Interpreter::default();
+        let result = interpreter.run(compiled.byte_code).await.unwrap();
+
+        assert_eq!(
+            result.get_val().unwrap(),
+            TypeAnnotatedValue::Str("hello jak".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pipeline_to_expr() {
+        let pipeline = Pipeline::new(vec![
+            NamedStep::new("x", Expr::from_text("1u64").unwrap()),
+            NamedStep::new("y", Expr::from_text("x").unwrap()),
+        ]);
+
+        let expected = Expr::multiple(vec![
+            Expr::let_binding("x", Expr::from_text("1u64").unwrap()),
+            Expr::let_binding("y", Expr::identifier("x")),
+            Expr::identifier("y"),
+        ]);
+
+        assert_eq!(pipeline.to_expr(), Ok(expected));
+    }
+
+    #[test]
+    fn test_invalid_pipelines() {
+        assert!(Pipeline::new(vec![]).to_expr().is_err());
+
+        let duplicate = Pipeline::new(vec![
+            NamedStep::new("x", Expr::from_text("1u64").unwrap()),
+            NamedStep::new("x", Expr::from_text("2u64").unwrap()),
+        ]);
+
+        assert_eq!(
+            duplicate.to_expr(),
+            Err("Duplicate pipeline step name `x`".to_string())
+        );
+
+        let keyword = Pipeline::new(vec![NamedStep::new(
+            "match",
+            Expr::from_text("1u64").unwrap(),
+        )]);
+
+        assert!(keyword.to_expr().is_err());
+    }
+}
@@ -13,7 +13,10 @@
 // limitations under the License.
 
 use crate::expr::Expr;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
+mod sexp;
 mod writer;
 
 use crate::text::writer::WriterError;
@@ -33,10 +36,40 @@ pub fn from_string(input: impl AsRef<str>) -> Result<Expr, String> {
     }
 }
 
+// Unlike `from_string`, doesn't stop at the first syntax error, and reports
+// the errors in all the statements of the program
+pub fn parse_all(input: impl AsRef<str>) -> Result<Expr, Vec<String>> {
+    crate::parser::recovery::parse_all(input.as_ref())
+}
+
+// Parse a Rib program from a reader, without loading the whole program into memory first
+pub fn parse_reader(reader: impl std::io::Read) -> Result<Expr, String> {
+    crate::parser::reader::parse_reader(reader)
+}
+
 pub fn to_string(expr: &Expr) -> Result<String, WriterError> {
     writer::write_expr(expr)
 }
 
+// The tree of the expression as an S-expression, for tooling (see `sexp::write_sexp`).
+// Unlike `to_string`, it isn't meant to be parsed back as Rib
+pub fn to_sexp(expr: &Expr) -> String {
+    sexp::write_sexp(expr)
+}
+
+// A hash of the parsed expression, which is the same for templates differing only in formatting
+// (Ex: whitespace, or being wrapped in `${..}`), to be used as a cache key instead of the raw text.
+// Expressions are expected to be hashed as parsed, as type inference annotates (and rewrites) the tree
+pub fn canonical_hash(expr: &Expr) -> u64 {
+    // Not every node of the tree is `Hash`, but the binary encoding of equal trees is the same
+    let encoded = bincode::encode_to_vec(expr, bincode::config::standard())
+        .expect("Failed to encode the expression");
+
+    let mut hasher = DefaultHasher::new();
+    encoded.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod interpolation_tests {
     use crate::{text, Expr};
@@ -61,6 +94,41 @@ mod interpolation_tests {
             )]))
         );
     }
+
+    #[test]
+    fn test_empty_or_whitespace_only_input() {
+        assert_eq!(text::from_string(""), Ok(Expr::literal("")));
+        assert_eq!(text::from_string("   "), Ok(Expr::literal("")));
+        assert_eq!(text::from_string("\n\n"), Ok(Expr::literal("")));
+        assert_eq!(text::from_string("${}"), Ok(Expr::literal("")));
+    }
+}
+
+#[cfg(test)]
+mod canonical_hash_tests {
+    use crate::text::{canonical_hash, from_string};
+
+    fn hash(input: &str) -> u64 {
+        canonical_hash(&from_string(input).unwrap())
+    }
+
+    #[test]
+    fn test_canonical_hash_ignores_whitespace() {
+        assert_eq!(hash("${a>b}"), hash("${a > b}"));
+        assert_eq!(hash("${a>b}"), hash("a  >\n  b"));
+        assert_eq!(
+            hash(r#"${let x = {foo: "bar"}; x.foo}"#),
+            hash(r#"let x={foo:"bar"};   x.foo"#)
+        );
+    }
+
+    #[test]
+    fn test_canonical_hash_of_different_expressions() {
+        assert_ne!(hash("${a > b}"), hash("${a >= b}"));
+        assert_ne!(hash("${a > b}"), hash("${b > a}"));
+        // Whitespace within string literals is significant
+        assert_ne!(hash(r#"${"a b"}"#), hash(r#"${"ab"}"#));
+    }
 }
 
 #[cfg(test)]
@@ -1764,3 +1832,84 @@ mod if_cond_tests {
         assert_eq!((expr_str, input_expr), (expected_str, output_expr));
     }
 }
+
+#[cfg(test)]
+mod builtin_tests {
+    use crate::expr::Expr;
+    use crate::text::{from_string, to_string};
+    use crate::BuiltinFunction;
+
+    #[test]
+    fn test_round_trip_builtins() {
+        for builtin in BuiltinFunction::all() {
+            let input_expr = Expr::call_builtin(
+                builtin.clone(),
+                vec![Expr::identifier("foo"), Expr::literal("bar")],
+            );
+            let expr_str = to_string(&input_expr).unwrap();
+            let expected_str = format!("builtin::{}(foo, \"bar\")", builtin.name());
+            let output_expr = from_string(expr_str.as_str()).unwrap();
+            assert_eq!((expr_str, input_expr), (expected_str, output_expr));
+        }
+    }
+
+    #[test]
+    fn test_round_trip_coalesce_operator() {
+        let input_expr = from_string("request.query.limit ?? request.query.size ?? 10").unwrap();
+        let expr_str = to_string(&input_expr).unwrap();
+        let expected_str =
+            "builtin::coalesce(request.query.limit, request.query.size, 10)".to_string();
+        let output_expr = from_string(expr_str.as_str()).unwrap();
+        assert_eq!((expr_str, input_expr), (expected_str, output_expr));
+    }
+
+    #[test]
+    fn test_round_trip_safe_navigation() {
+        let input_expr = from_string("request.body?.user").unwrap();
+        let expr_str = to_string(&input_expr).unwrap();
+        let expected_str =
+            r#"if builtin::hasField(request.body, "user") then some(request.body.user) else none"#
+                .to_string();
+        let output_expr = from_string(expr_str.as_str()).unwrap();
+        assert_eq!((expr_str, input_expr), (expected_str, output_expr));
+    }
+
+    #[test]
+    fn test_round_trip_comparison_with_null() {
+        let input_expr = from_string("request.query.cursor == null").unwrap();
+        let expr_str = to_string(&input_expr).unwrap();
+        let expected_str = "!builtin::isPresent(request.query.cursor)".to_string();
+        let output_expr = from_string(expr_str.as_str()).unwrap();
+        assert_eq!((expr_str, input_expr), (expected_str, output_expr));
+
+        let input_expr = from_string("request.query.cursor != null").unwrap();
+        let expr_str = to_string(&input_expr).unwrap();
+        let expected_str = "builtin::isPresent(request.query.cursor)".to_string();
+        let output_expr = from_string(expr_str.as_str()).unwrap();
+        assert_eq!((expr_str, input_expr), (expected_str, output_expr));
+    }
+
+    #[test]
+    fn test_round_trip_in_range() {
+        let input_expr = from_string("request.path.page in 1..=10").unwrap();
+        let expr_str = to_string(&input_expr).unwrap();
+        let expected_str =
+            "builtin::inRange(request.path.page, builtin::range(1, 10, true))".to_string();
+        let output_expr = from_string(expr_str.as_str()).unwrap();
+        assert_eq!((expr_str, input_expr), (expected_str, output_expr));
+    }
+
+    #[test]
+    fn test_round_trip_in_list() {
+        let input_expr = from_string(r#"request.method in ["GET", "HEAD"]"#).unwrap();
+        let expr_str = to_string(&input_expr).unwrap();
+        let expected_str = r#"builtin::inRange(request.method, ["GET", "HEAD"])"#.to_string();
+        let output_expr = from_string(expr_str.as_str()).unwrap();
+        assert_eq!((expr_str, input_expr), (expected_str, output_expr));
+    }
+
+    #[test]
+    fn test_unknown_builtin() {
+        assert!(from_string("builtin::unknown(foo)").is_err());
+    }
+}
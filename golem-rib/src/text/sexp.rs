@@ -0,0 +1,221 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::expr::Expr;
+use std::fmt::Display;
+
+// Renders the tree of the expression as an S-expression, for tools that prefer it to JSON
+// (Ex: `(cond (greater-than (select-field (identifier request) "count") (number 1)) ...)`).
+// Every node is a list headed by the kebab-case name of its kind. Names of variables are symbols,
+// while the field names, the flags and the names of the called functions are strings,
+// as they aren't always valid symbols (Ex: `golem:it/api.{get-cart-contents}`)
+pub fn write_sexp(expr: &Expr) -> String {
+    internal::expr(expr).to_string()
+}
+
+enum Sexp {
+    Symbol(String),
+    String(String),
+    List(Vec<Sexp>),
+}
+
+impl Display for Sexp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sexp::Symbol(symbol) => write!(f, "{}", symbol),
+            Sexp::String(string) => {
+                write!(
+                    f,
+                    "\"{}\"",
+                    string.replace('\\', "\\\\").replace('"', "\\\"")
+                )
+            }
+            Sexp::List(items) => {
+                write!(f, "(")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+mod internal {
+    use crate::expr::{ArmPattern, Expr, MatchArm};
+    use crate::text::sexp::Sexp;
+
+    fn symbol(symbol: impl ToString) -> Sexp {
+        Sexp::Symbol(symbol.to_string())
+    }
+
+    fn string(string: impl ToString) -> Sexp {
+        Sexp::String(string.to_string())
+    }
+
+    fn node(name: &str, children: impl IntoIterator<Item = Sexp>) -> Sexp {
+        Sexp::List(std::iter::once(symbol(name)).chain(children).collect())
+    }
+
+    fn exprs<'a>(exprs: impl IntoIterator<Item = &'a Expr>) -> Vec<Sexp> {
+        exprs.into_iter().map(expr).collect()
+    }
+
+    pub(crate) fn expr(expr: &Expr) -> Sexp {
+        match expr {
+            Expr::Let(variable_id, type_name, value, _) => {
+                let mut children = vec![symbol(variable_id)];
+                if let Some(type_name) = type_name {
+                    children.push(node("type", [symbol(type_name)]));
+                }
+                children.push(self::expr(value));
+                node("let", children)
+            }
+            Expr::SelectField(value, field, _) => {
+                node("select-field", [self::expr(value), string(field)])
+            }
+            Expr::SelectIndex(value, index, _) => {
+                node("select-index", [self::expr(value), symbol(index)])
+            }
+            Expr::SelectIndexExpr(value, index, _) => {
+                node("select-index", [self::expr(value), self::expr(index)])
+            }
+            Expr::Sequence(items, _) => node("sequence", exprs(items)),
+            Expr::Record(fields, _) => node(
+                "record",
+                fields
+                    .iter()
+                    .map(|(name, value)| Sexp::List(vec![string(name), self::expr(value)])),
+            ),
+            Expr::Tuple(items, _) => node("tuple", exprs(items)),
+            Expr::Literal(value, _) => node("literal", [string(value)]),
+            Expr::Number(number, type_name, _) => node(
+                "number",
+                std::iter::once(symbol(number)).chain(type_name.iter().map(symbol)),
+            ),
+            Expr::Flags(flags, _) => node("flags", flags.iter().map(string)),
+            Expr::Identifier(variable_id, _) => node("identifier", [symbol(variable_id)]),
+            Expr::Boolean(value, _) => node("boolean", [symbol(if *value { "#t" } else { "#f" })]),
+            Expr::Concat(parts, _) => node("concat", exprs(parts)),
+            Expr::Multiple(statements, _) => node("multiple", exprs(statements)),
+            Expr::Not(value, _) => node("not", [self::expr(value)]),
+            Expr::GreaterThan(left, right, _) => node("greater-than", exprs([&**left, &**right])),
+            Expr::And(left, right, _) => node("and", exprs([&**left, &**right])),
+            Expr::Or(left, right, _) => node("or", exprs([&**left, &**right])),
+            Expr::GreaterThanOrEqualTo(left, right, _) => {
+                node("greater-than-or-equal-to", exprs([&**left, &**right]))
+            }
+            Expr::LessThanOrEqualTo(left, right, _) => {
+                node("less-than-or-equal-to", exprs([&**left, &**right]))
+            }
+            Expr::EqualTo(left, right, _) => node("equal-to", exprs([&**left, &**right])),
+            Expr::LessThan(left, right, _) => node("less-than", exprs([&**left, &**right])),
+            Expr::Cond(predicate, then_expr, else_expr, _) => {
+                node("cond", exprs([&**predicate, &**then_expr, &**else_expr]))
+            }
+            Expr::PatternMatch(matched, arms, _) => node(
+                "pattern-match",
+                std::iter::once(self::expr(matched)).chain(arms.iter().map(match_arm)),
+            ),
+            Expr::Option(Some(value), _) => node("some", [self::expr(value)]),
+            Expr::Option(None, _) => node("none", []),
+            Expr::Result(Ok(value), _) => node("ok", [self::expr(value)]),
+            Expr::Result(Err(value), _) => node("err", [self::expr(value)]),
+            Expr::Call(call_type, args, _) => node(
+                "call",
+                std::iter::once(string(call_type)).chain(exprs(args)),
+            ),
+            Expr::Unwrap(value, _) => node("unwrap", [self::expr(value)]),
+            Expr::Throw(message, _) => node("throw", [string(message)]),
+            Expr::GetTag(value, _) => node("get-tag", [self::expr(value)]),
+        }
+    }
+
+    fn match_arm(arm: &MatchArm) -> Sexp {
+        node(
+            "arm",
+            [
+                arm_pattern(&arm.arm_pattern),
+                expr(&arm.arm_resolution_expr),
+            ],
+        )
+    }
+
+    fn arm_pattern(pattern: &ArmPattern) -> Sexp {
+        match pattern {
+            ArmPattern::WildCard => symbol("_"),
+            ArmPattern::As(name, pattern) => node("as", [symbol(name), arm_pattern(pattern)]),
+            ArmPattern::Constructor(name, patterns) => node(
+                "constructor",
+                std::iter::once(string(name)).chain(patterns.iter().map(arm_pattern)),
+            ),
+            ArmPattern::TupleConstructor(patterns) => {
+                node("tuple-constructor", patterns.iter().map(arm_pattern))
+            }
+            ArmPattern::RecordConstructor(fields) => node(
+                "record-constructor",
+                fields
+                    .iter()
+                    .map(|(name, pattern)| Sexp::List(vec![string(name), arm_pattern(pattern)])),
+            ),
+            ArmPattern::ListConstructor(patterns) => {
+                node("list-constructor", patterns.iter().map(arm_pattern))
+            }
+            ArmPattern::Literal(value) => expr(value),
+            ArmPattern::Range(start, end) => node("range", [expr(start), expr(end)]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::text::to_sexp;
+    use crate::Expr;
+
+    #[test]
+    fn test_sexp_of_cond() {
+        let expr = Expr::from_text(r#"if request.body.count > 1 then "many" else "one""#).unwrap();
+
+        assert_eq!(
+            to_sexp(&expr),
+            r#"(cond (greater-than (select-field (select-field (identifier request) "body") "count") (number 1)) (literal "many") (literal "one"))"#
+        );
+    }
+
+    #[test]
+    fn test_sexp_of_let_and_call() {
+        let expr =
+            Expr::from_text(r#"let x: u64 = request.path.user-id; foo(x, "a", [1, 2], {a: true})"#)
+                .unwrap();
+
+        assert_eq!(
+            to_sexp(&expr),
+            r#"(multiple (let x (type u64) (select-field (select-field (identifier request) "path") "user-id")) (call "foo" (identifier x) (literal "a") (sequence (number 1) (number 2)) (record ("a" (boolean #t)))))"#
+        );
+    }
+
+    #[test]
+    fn test_sexp_of_pattern_match() {
+        let expr =
+            Expr::from_text(r#"match x { some(y) => y, none => "empty", _ => "other" }"#).unwrap();
+
+        assert_eq!(
+            to_sexp(&expr),
+            r#"(pattern-match (identifier x) (arm (constructor "some" (identifier y)) (identifier y)) (arm (constructor "none") (literal "empty")) (arm _ (literal "other")))"#
+        );
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::call_type::CallType;
 use crate::expr::Expr;
 use crate::MatchArm;
 use std::fmt::Display;
@@ -93,6 +94,12 @@ impl<W: Write> Writer<W> {
                 self.write_display(index)?;
                 self.write_display("]")
             }
+            Expr::SelectIndexExpr(expr, index, _) => {
+                self.write_expr(expr)?;
+                self.write_display("[")?;
+                self.write_expr(index)?;
+                self.write_display("]")
+            }
             Expr::Sequence(sequence, _) => {
                 self.write_display("[")?;
                 for (idx, expr) in sequence.iter().enumerate() {
@@ -241,7 +248,11 @@ impl<W: Write> Writer<W> {
             },
 
             Expr::Call(invocation_name, params, _) => {
-                let function_name = invocation_name.to_string();
+                // A builtin is qualified, so that it isn't read back as an exported function
+                let function_name = match invocation_name {
+                    CallType::Builtin(builtin) => format!("builtin::{}", builtin.name()),
+                    invocation_name => invocation_name.to_string(),
+                };
 
                 self.write_str(function_name)?;
                 self.write_display("(")?;
@@ -420,6 +431,12 @@ mod internal {
                 Expr::Identifier(s, _) => writer.write_str(s.name()),
                 any_expr => writer.write_expr(&any_expr),
             },
+
+            ArmPattern::Range(start, end) => {
+                writer.write_expr(start)?;
+                writer.write_str("..")?;
+                writer.write_expr(end)
+            }
         }
     }
 }
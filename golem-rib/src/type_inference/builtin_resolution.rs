@@ -0,0 +1,154 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Expr, FunctionTypeRegistry};
+
+// Convert the global function calls such as `reject(401, "no auth")` to builtin calls,
+// unless the component exports a function (or a variant) with the same name
+pub fn infer_builtins(expr: &mut Expr, function_type_registry: &FunctionTypeRegistry) {
+    internal::convert_function_calls_to_builtin_calls(expr, function_type_registry);
+}
+
+mod internal {
+    use crate::call_type::CallType;
+    use crate::{
+        BuiltinFunction, DynamicParsedFunctionReference, Expr, FunctionTypeRegistry,
+        ParsedFunctionSite, RegistryKey,
+    };
+    use std::collections::VecDeque;
+
+    pub(crate) fn convert_function_calls_to_builtin_calls(
+        expr: &mut Expr,
+        function_type_registry: &FunctionTypeRegistry,
+    ) {
+        let mut queue = VecDeque::new();
+        queue.push_back(expr);
+
+        while let Some(expr) = queue.pop_back() {
+            if let Expr::Call(CallType::Function(parsed_function_name), args, inferred_type) = expr
+            {
+                if let (
+                    ParsedFunctionSite::Global,
+                    DynamicParsedFunctionReference::Function { function },
+                ) = (&parsed_function_name.site, &parsed_function_name.function)
+                {
+                    let key = RegistryKey::FunctionName(function.clone());
+
+                    if !function_type_registry.types.contains_key(&key) {
+                        if let Some(builtin) = BuiltinFunction::from_name(function) {
+                            *expr = Expr::Call(
+                                CallType::Builtin(builtin),
+                                args.clone(),
+                                inferred_type.clone(),
+                            );
+                        }
+                    }
+                }
+            }
+
+            expr.visit_children_mut_bottom_up(&mut queue);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::call_type::CallType;
+    use crate::{Expr, FunctionTypeRegistry};
+    use golem_wasm_ast::analysis::analysed_type::{bool, str};
+    use golem_wasm_ast::analysis::{
+        AnalysedExport, AnalysedFunction, AnalysedFunctionParameter, AnalysedFunctionResult,
+    };
+    use std::collections::VecDeque;
+
+    // A component exporting functions with the names of the builtins
+    fn shadowing_registry(names: &[&str]) -> FunctionTypeRegistry {
+        let exports = names
+            .iter()
+            .map(|name| {
+                AnalysedExport::Function(AnalysedFunction {
+                    name: name.to_string(),
+                    parameters: vec![AnalysedFunctionParameter {
+                        name: "param0".to_string(),
+                        typ: str(),
+                    }],
+                    results: vec![AnalysedFunctionResult {
+                        name: None,
+                        typ: bool(),
+                    }],
+                })
+            })
+            .collect();
+
+        FunctionTypeRegistry::from_export_metadata(&exports)
+    }
+
+    fn call_types(expr: &Expr) -> Vec<String> {
+        let mut call_types = vec![];
+        let mut queue = VecDeque::from([expr]);
+
+        while let Some(expr) = queue.pop_back() {
+            if let Expr::Call(call_type, _, _) = expr {
+                call_types.push(match call_type {
+                    CallType::Builtin(builtin) => format!("builtin {}", builtin),
+                    other => format!("function {}", other),
+                });
+            }
+            expr.visit_children_bottom_up(&mut queue);
+        }
+
+        call_types
+    }
+
+    #[test]
+    fn test_operators_are_not_shadowed_by_exports() {
+        let registry =
+            shadowing_registry(&["coalesce", "isPresent", "hasField", "range", "inRange"]);
+
+        let cases = [
+            (
+                r#"request.query.limit ?? "10""#,
+                r#"coalesce(request.query.limit, "10")"#,
+                "coalesce",
+            ),
+            (
+                "request.query.cursor == null",
+                "!isPresent(request.query.cursor)",
+                "isPresent",
+            ),
+            (
+                "request.body?.user",
+                r#"hasField(request.body, "user")"#,
+                "hasField",
+            ),
+        ];
+
+        for (operator, call, name) in cases {
+            let mut operator = Expr::from_text(operator).unwrap();
+            let mut call = Expr::from_text(call).unwrap();
+            operator.infer_builtins(&registry);
+            call.infer_builtins(&registry);
+
+            assert_eq!(call_types(&operator), vec![format!("builtin {}", name)]);
+            assert_eq!(call_types(&call), vec![format!("function {}", name)]);
+        }
+
+        let mut operator = Expr::from_text("request.query.page in 1..=100").unwrap();
+        operator.infer_builtins(&registry);
+
+        let mut call_types = call_types(&operator);
+        call_types.sort();
+        assert_eq!(call_types, vec!["builtin inRange", "builtin range"]);
+    }
+}
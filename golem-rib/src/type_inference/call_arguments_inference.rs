@@ -153,6 +153,8 @@ mod internal {
                 )
                 .map_err(|e| e.to_string())
             }
+
+            CallType::Builtin(builtin) => builtin.infer_types(args, inferred_type),
         }
     }
 
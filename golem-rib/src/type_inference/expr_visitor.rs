@@ -9,6 +9,10 @@ pub fn visit_children_bottom_up_mut<'a>(expr: &'a mut Expr, queue: &mut VecDeque
         Expr::Let(_, _, expr, _) => queue.push_back(&mut *expr),
         Expr::SelectField(expr, _, _) => queue.push_back(&mut *expr),
         Expr::SelectIndex(expr, _, _) => queue.push_back(&mut *expr),
+        Expr::SelectIndexExpr(expr, index, _) => {
+            queue.push_back(&mut *expr);
+            queue.push_back(&mut *index);
+        }
         Expr::Sequence(exprs, _) => queue.extend(exprs.iter_mut()),
         Expr::Record(exprs, _) => queue.extend(exprs.iter_mut().map(|(_, expr)| &mut **expr)),
         Expr::Tuple(exprs, _) => queue.extend(exprs.iter_mut()),
@@ -88,6 +92,10 @@ pub fn visit_children_bottom_up<'a>(expr: &'a Expr, queue: &mut VecDeque<&'a Exp
         Expr::Let(_, _, expr, _) => queue.push_back(expr),
         Expr::SelectField(expr, _, _) => queue.push_back(expr),
         Expr::SelectIndex(expr, _, _) => queue.push_back(expr),
+        Expr::SelectIndexExpr(expr, index, _) => {
+            queue.push_back(expr);
+            queue.push_back(index);
+        }
         Expr::Sequence(exprs, _) => queue.extend(exprs.iter()),
         Expr::Record(exprs, _) => queue.extend(exprs.iter().map(|(_, expr)| expr.deref())),
         Expr::Tuple(exprs, _) => queue.extend(exprs.iter()),
@@ -166,6 +174,10 @@ pub fn visit_children_mut_top_down<'a>(expr: &'a mut Expr, queue: &mut VecDeque<
         Expr::Let(_, _, expr, _) => queue.push_front(&mut *expr),
         Expr::SelectField(expr, _, _) => queue.push_front(&mut *expr),
         Expr::SelectIndex(expr, _, _) => queue.push_front(&mut *expr),
+        Expr::SelectIndexExpr(expr, index, _) => {
+            queue.push_front(&mut *expr);
+            queue.push_front(&mut *index);
+        }
         Expr::Sequence(exprs, _) => {
             for expr in exprs.iter_mut() {
                 queue.push_front(expr);
@@ -159,6 +159,7 @@ mod internal {
                 }
             }
             ArmPattern::Literal(expr) => accumulate_types_of_identifiers(&mut *expr, state),
+            ArmPattern::Range(_, _) => {}
         }
     }
 
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub use builtin_resolution::*;
 pub use call_arguments_inference::*;
 pub use enum_resolution::*;
 pub use expr_visitor::*;
@@ -29,6 +30,7 @@ pub use type_reset::*;
 pub use type_unification::*;
 pub use variant_resolution::*;
 
+mod builtin_resolution;
 mod call_arguments_inference;
 mod expr_visitor;
 mod identifier_inference;
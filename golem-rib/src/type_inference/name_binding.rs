@@ -13,21 +13,28 @@
 // limitations under the License.
 
 use crate::Expr;
-use std::collections::VecDeque;
 
+// A block nested in an expression (Ex: the body of `let x = e in body`) is a scope of its own,
+// so the variables it binds are not visible after it
 pub fn name_binding_local_variables(expr: &mut Expr) {
     let mut identifier_id_state = internal::IdentifierVariableIdState::new();
-    let mut queue = VecDeque::new();
-    queue.push_front(expr);
+    internal::bind_local_variables(expr, &mut identifier_id_state);
+}
+
+mod internal {
+    use crate::{Expr, VariableId};
+    use std::collections::{HashMap, VecDeque};
 
-    // Start from the end
-    while let Some(expr) = queue.pop_front() {
+    pub(crate) fn bind_local_variables(
+        expr: &mut Expr,
+        identifier_id_state: &mut IdentifierVariableIdState,
+    ) {
         match expr {
             Expr::Let(variable_id, _, expr, _) => {
+                bind_local_variables(expr, identifier_id_state);
                 let field_name = variable_id.name();
                 identifier_id_state.update_variable_id(&field_name); // Increment the variable_id
                 *variable_id = identifier_id_state.lookup(&field_name).unwrap();
-                queue.push_front(expr);
             }
 
             Expr::Identifier(variable_id, _) if !variable_id.is_match_binding() => {
@@ -38,35 +45,64 @@ pub fn name_binding_local_variables(expr: &mut Expr) {
                 }
             }
 
+            Expr::Multiple(exprs, _) => {
+                let outer_scope = identifier_id_state.scope();
+                for expr in exprs.iter_mut() {
+                    bind_local_variables(expr, identifier_id_state);
+                }
+                identifier_id_state.end_scope(outer_scope);
+            }
+
             _ => {
-                expr.visit_children_mut_top_down(&mut queue);
+                let mut children = VecDeque::new();
+                expr.visit_children_mut_top_down(&mut children);
+                while let Some(child) = children.pop_front() {
+                    bind_local_variables(child, identifier_id_state);
+                }
             }
         }
     }
-}
-
-mod internal {
-    use crate::VariableId;
-    use std::collections::HashMap;
 
-    pub(crate) struct IdentifierVariableIdState(HashMap<String, VariableId>);
+    pub(crate) struct IdentifierVariableIdState {
+        // The variables in scope
+        variables: HashMap<String, VariableId>,
+        // The latest id of each name. It isn't reset when a scope ends, so that a variable
+        // bound after a block doesn't get the id of the one bound within the block
+        latest: HashMap<String, VariableId>,
+    }
 
     impl IdentifierVariableIdState {
         pub(crate) fn new() -> Self {
-            IdentifierVariableIdState(HashMap::new())
+            IdentifierVariableIdState {
+                variables: HashMap::new(),
+                latest: HashMap::new(),
+            }
         }
 
         pub(crate) fn update_variable_id(&mut self, identifier: &str) {
-            self.0
+            let variable_id = self
+                .latest
                 .entry(identifier.to_string())
                 .and_modify(|x| {
                     *x = x.increment_local_variable_id();
                 })
-                .or_insert(VariableId::local(identifier, 0));
+                .or_insert(VariableId::local(identifier, 0))
+                .clone();
+
+            self.variables.insert(identifier.to_string(), variable_id);
         }
 
         pub(crate) fn lookup(&self, identifier: &str) -> Option<VariableId> {
-            self.0.get(identifier).cloned()
+            self.variables.get(identifier).cloned()
+        }
+
+        // The variables in scope, to be restored with `end_scope`
+        pub(crate) fn scope(&self) -> HashMap<String, VariableId> {
+            self.variables.clone()
+        }
+
+        pub(crate) fn end_scope(&mut self, outer_scope: HashMap<String, VariableId>) {
+            self.variables = outer_scope;
         }
     }
 }
@@ -236,4 +272,62 @@ mod name_binding_tests {
 
         assert_eq!(expr, expected);
     }
+
+    fn call_foo(variable_id: VariableId) -> Expr {
+        Expr::Call(
+            CallType::Function(DynamicParsedFunctionName {
+                site: ParsedFunctionSite::Global,
+                function: DynamicParsedFunctionReference::Function {
+                    function: "foo".to_string(),
+                },
+            }),
+            vec![Expr::Identifier(variable_id, InferredType::Unknown)],
+            InferredType::Unknown,
+        )
+    }
+
+    fn let_x(id: u32, value: f64) -> Expr {
+        Expr::Let(
+            VariableId::local("x", id),
+            None,
+            Box::new(Expr::number(value)),
+            InferredType::Unknown,
+        )
+    }
+
+    #[test]
+    fn test_name_binding_let_in_is_scoped_to_the_body() {
+        let mut expr = Expr::from_text("let x = 1 in foo(x); foo(x)").unwrap();
+
+        expr.name_binding_local_variables();
+
+        // The x after the body is not the bound one
+        let expected = Expr::multiple(vec![
+            Expr::multiple(vec![let_x(0, 1f64), call_foo(VariableId::local("x", 0))]),
+            call_foo(VariableId::global("x".to_string())),
+        ]);
+
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn test_name_binding_shadowing_in_block() {
+        let rib_expr = r#"
+          let x = 1;
+          { let x = 2; foo(x) };
+          foo(x)
+        "#;
+
+        let mut expr = Expr::from_text(rib_expr).unwrap();
+
+        expr.name_binding_local_variables();
+
+        let expected = Expr::multiple(vec![
+            let_x(0, 1f64),
+            Expr::multiple(vec![let_x(1, 2f64), call_foo(VariableId::local("x", 1))]),
+            call_foo(VariableId::local("x", 0)),
+        ]);
+
+        assert_eq!(expr, expected);
+    }
 }
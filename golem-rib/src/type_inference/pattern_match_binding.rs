@@ -88,7 +88,7 @@ mod internal {
                     match_identifiers.extend(new_match_identifiers);
                 }
 
-                ArmPattern::WildCard => {}
+                ArmPattern::WildCard | ArmPattern::Range(_, _) => {}
                 ArmPattern::As(name, arm_pattern) => {
                     let match_identifier = MatchIdentifier::new(name.clone(), global_arm_index);
                     match_identifiers.push(match_identifier);
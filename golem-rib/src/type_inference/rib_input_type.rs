@@ -33,6 +33,35 @@ impl RibInputTypeInfo {
         }
     }
 
+    // Whether the part of a global input at the path (Ex: `["request", "body"]`) is used by the script.
+    // The input types only have the fields that are selected, so if a value is used as a whole
+    // (and isn't a record), all of its parts are considered as referenced
+    pub fn is_referenced(&self, path: &[&str]) -> bool {
+        let (root, fields) = match path.split_first() {
+            Some(split) => split,
+            None => return !self.types.is_empty(),
+        };
+
+        let mut current = match self.types.get(*root) {
+            Some(analysed_type) => analysed_type,
+            None => return false,
+        };
+
+        for field in fields {
+            match current {
+                AnalysedType::Record(record) => {
+                    match record.fields.iter().find(|pair| pair.name == *field) {
+                        Some(pair) => current = &pair.typ,
+                        None => return false,
+                    }
+                }
+                _ => return true,
+            }
+        }
+
+        true
+    }
+
     pub fn from_expr(expr: &mut Expr) -> Result<RibInputTypeInfo, String> {
         let mut queue = VecDeque::new();
 
@@ -78,3 +107,30 @@ impl From<RibInputTypeInfo> for ProtoRibInputType {
         ProtoRibInputType { types }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{compiler, Expr};
+
+    #[test]
+    fn test_is_referenced() {
+        let expr = Expr::from_text(r#"${let x: str = request.path.user-id; x}"#).unwrap();
+        let compiled = compiler::compile(&expr, &vec![]).unwrap();
+        let input = compiled.global_input_type_info;
+
+        assert!(input.is_referenced(&["request"]));
+        assert!(input.is_referenced(&["request", "path", "user-id"]));
+        assert!(!input.is_referenced(&["request", "body"]));
+        assert!(!input.is_referenced(&["worker"]));
+    }
+
+    #[test]
+    fn test_is_referenced_by_whole_value() {
+        let expr = Expr::from_text(r#"${let x: str = request.body; x}"#).unwrap();
+        let compiled = compiler::compile(&expr, &vec![]).unwrap();
+        let input = compiled.global_input_type_info;
+
+        assert!(input.is_referenced(&["request", "body"]));
+        assert!(input.is_referenced(&["request", "body", "name"]));
+    }
+}
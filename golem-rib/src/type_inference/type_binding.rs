@@ -48,6 +48,7 @@ mod internal {
             | Expr::Let(_, _, _, inferred_type)
             | Expr::SelectField(_, _, inferred_type)
             | Expr::SelectIndex(_, _, inferred_type)
+            | Expr::SelectIndexExpr(_, _, inferred_type)
             | Expr::Sequence(_, inferred_type)
             | Expr::Record(_, inferred_type)
             | Expr::Tuple(_, inferred_type)
@@ -75,6 +75,11 @@ pub fn type_check(expr: &Expr) -> Result<(), Vec<String>> {
                 queue.push_back(inner);
                 internal::accumulate_errors(expr, inferred_type.type_check(), &mut errors);
             }
+            Expr::SelectIndexExpr(inner, index, inferred_type) => {
+                queue.push_back(inner);
+                queue.push_back(index);
+                internal::accumulate_errors(expr, inferred_type.type_check(), &mut errors);
+            }
             _ => expr.visit_children_bottom_up(&mut queue),
         }
     }
@@ -57,6 +57,15 @@ pub fn type_pull_up(expr: &Expr) -> Result<Expr, String> {
                 )?;
             }
 
+            Expr::SelectIndexExpr(expr, index, current_inferred_type) => {
+                internal::handle_select_index_expr(
+                    expr,
+                    index,
+                    current_inferred_type,
+                    &mut inferred_type_stack,
+                )?;
+            }
+
             Expr::Result(Ok(_), current_inferred_type) => {
                 internal::handle_result_ok(expr, current_inferred_type, &mut inferred_type_stack);
             }
@@ -315,6 +324,33 @@ mod internal {
         Ok(())
     }
 
+    pub(crate) fn handle_select_index_expr(
+        original_selection_expr: &Expr,
+        original_index_expr: &Expr,
+        current_index_type: &InferredType,
+        inferred_type_stack: &mut VecDeque<Expr>,
+    ) -> Result<(), String> {
+        let index_expr = inferred_type_stack
+            .pop_front()
+            .unwrap_or(original_index_expr.clone());
+        let expr = inferred_type_stack
+            .pop_front()
+            .unwrap_or(original_selection_expr.clone());
+        let inferred_type_of_selection_expr = expr.inferred_type();
+        let refined_list = ListType::refine(&inferred_type_of_selection_expr).ok_or(format!(
+            "Cannot get index {} since it is not a list type. Found: {:?}",
+            index_expr, inferred_type_of_selection_expr
+        ))?;
+        let new_select_index = Expr::SelectIndexExpr(
+            Box::new(expr.clone()),
+            Box::new(index_expr),
+            current_index_type.merge(refined_list.inner_type()),
+        );
+        inferred_type_stack.push_front(new_select_index);
+
+        Ok(())
+    }
+
     pub(crate) fn handle_result_ok(
         original_ok_expr: &Expr,
         current_ok_type: &InferredType,
@@ -611,6 +647,20 @@ mod internal {
                 );
                 inferred_type_stack.push_front(new_call);
             }
+
+            CallType::Builtin(builtin) => {
+                let new_inferred_type = match builtin.return_type(&new_arg_exprs) {
+                    Some(return_type) => inferred_type.merge(return_type),
+                    None => inferred_type.clone(),
+                };
+
+                let new_call = Expr::Call(
+                    CallType::Builtin(builtin.clone()),
+                    new_arg_exprs,
+                    new_inferred_type,
+                );
+                inferred_type_stack.push_front(new_call);
+            }
         }
     }
 
@@ -720,6 +770,21 @@ mod internal {
         select_field: &str,
         select_from_type: &InferredType,
     ) -> Result<InferredType, String> {
+        // Selecting a case of a variant (Ex: `response.process-user`) is its payload
+        if let InferredType::Variant(cases) = select_from_type {
+            return match cases.iter().find(|(name, _)| name == select_field) {
+                Some((_, Some(payload_type))) => Ok(payload_type.clone()),
+                Some((_, None)) => Err(format!(
+                    "Cannot select {} since the variant case has no value",
+                    select_field
+                )),
+                None => Err(format!(
+                    "Cannot select {} since it is not a case of the variant. Found: {:?}",
+                    select_field, select_from_type
+                )),
+            };
+        }
+
         let refined_record = RecordType::refine(select_from_type).ok_or(format!(
             "Cannot select {} since it is not a record type. Found: {:?}",
             select_field, select_from_type
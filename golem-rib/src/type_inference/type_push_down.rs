@@ -22,11 +22,14 @@ pub fn push_types_down(expr: &mut Expr) -> Result<(), String> {
     while let Some(expr) = queue.pop_back() {
         match expr {
             Expr::SelectField(expr, field, inferred_type) => {
-                let field_type = inferred_type.clone();
-                let record_type = vec![(field.to_string(), field_type)];
-                let inferred_record_type = InferredType::Record(record_type);
+                // A variant (Ex: a worker response) is selected by its cases, and is not a record
+                if !matches!(expr.inferred_type(), InferredType::Variant(_)) {
+                    let field_type = inferred_type.clone();
+                    let record_type = vec![(field.to_string(), field_type)];
+                    let inferred_record_type = InferredType::Record(record_type);
 
-                expr.add_infer_type_mut(inferred_record_type);
+                    expr.add_infer_type_mut(inferred_record_type);
+                }
                 queue.push_back(expr);
             }
 
@@ -36,6 +39,15 @@ pub fn push_types_down(expr: &mut Expr) -> Result<(), String> {
                 expr.add_infer_type_mut(inferred_record_type);
                 queue.push_back(expr);
             }
+
+            Expr::SelectIndexExpr(expr, index, inferred_type) => {
+                let field_type = inferred_type.clone();
+                let inferred_record_type = InferredType::List(Box::new(field_type));
+                expr.add_infer_type_mut(inferred_record_type);
+                index.add_infer_type_mut(InferredType::U64);
+                queue.push_back(expr);
+                queue.push_back(index);
+            }
             Expr::Cond(cond, then, else_, inferred_type) => {
                 then.add_infer_type_mut(inferred_type.clone());
                 else_.add_infer_type_mut(inferred_type.clone());
@@ -237,6 +249,12 @@ mod internal {
                 expr.add_infer_type_mut(predicate_type.clone());
                 expr.push_types_down()?;
             }
+            ArmPattern::Range(start, end) => {
+                for bound in [start, end] {
+                    bound.add_infer_type_mut(predicate_type.clone());
+                    bound.push_types_down()?;
+                }
+            }
             ArmPattern::As(_, pattern) => {
                 update_arm_pattern_type(pattern, predicate_type)?;
             }
@@ -218,6 +218,23 @@ pub fn unify_types(expr: &mut Expr) -> Result<(), Vec<String>> {
                 }
             }
 
+            Expr::SelectIndexExpr(expr, index, inferred_type) => {
+                queue.push(expr);
+                queue.push(index);
+                let unified_inferred_type = inferred_type.unify_types_and_verify();
+
+                match unified_inferred_type {
+                    Ok(unified_type) => *inferred_type = unified_type,
+                    Err(e) => {
+                        errors.push(format!(
+                            "Unable to resolve the type of index selection {}",
+                            expr_str
+                        ));
+                        errors.extend(e);
+                    }
+                }
+            }
+
             Expr::Let(_, _, expr, _) => {
                 queue.push(expr);
             }
@@ -373,6 +390,10 @@ mod internal {
             ArmPattern::Literal(expr) => {
                 queue.push(expr);
             }
+            ArmPattern::Range(start, end) => {
+                queue.push(start);
+                queue.push(end);
+            }
             ArmPattern::As(_, pattern) => {
                 push_arm_pattern_expr(pattern, queue);
             }
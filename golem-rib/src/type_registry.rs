@@ -52,6 +52,7 @@ impl RegistryKey {
                 RegistryKey::FunctionName(variant_name.clone())
             }
             CallType::EnumConstructor(enum_name) => RegistryKey::FunctionName(enum_name.clone()),
+            CallType::Builtin(builtin) => RegistryKey::FunctionName(builtin.name().to_string()),
             CallType::Function(function_name) => match function_name.site.interface_name() {
                 None => RegistryKey::FunctionName(function_name.function_name()),
                 Some(interface_name) => RegistryKey::FunctionNameWithInterface {
@@ -18,7 +18,7 @@ use std::path::Path;
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Connection, Executor, PgConnection, Pool, Postgres, Sqlite, SqliteConnection};
-use tracing::info;
+use tracing::{error, info};
 
 use golem_common::config::{DbPostgresConfig, DbSqliteConfig};
 
@@ -61,6 +61,7 @@ pub async fn postgres_migrate(config: &DbPostgresConfig, path: &str) -> Result<(
         "DB migration: postgresql://{}:{}/{}?currentSchema={}, path: {}",
         config.host, config.port, config.database, schema, path
     );
+    check_migration_dir(path)?;
     let mut conn = PgConnection::connect_with(&create_postgres_options(config)).await?;
     let sql = format!("CREATE SCHEMA IF NOT EXISTS {};", schema);
     conn.execute(sqlx::query(&sql)).await?;
@@ -102,9 +103,104 @@ pub async fn create_sqlite_pool(config: &DbSqliteConfig) -> Result<Pool<Sqlite>,
 
 pub async fn sqlite_migrate(config: &DbSqliteConfig, path: &str) -> Result<(), Box<dyn Error>> {
     info!("DB migration: sqlite://{}, path: {}", config.database, path);
+    check_migration_dir(path)?;
     let mut conn = SqliteConnection::connect_with(&create_sqlite_options(config)).await?;
     let migrator = sqlx::migrate::Migrator::new(Path::new(path)).await?;
     migrator.run(&mut conn).await?;
     let _ = conn.close().await;
     Ok(())
 }
+
+// Without any migration the service would start with no tables, and the error of the migrator
+// doesn't tell why. A relative path is resolved from the working directory, so running
+// the service from another directory is the usual cause
+fn check_migration_dir(path: &str) -> Result<(), String> {
+    let dir = Path::new(path);
+
+    let error = if !dir.is_dir() {
+        Some(format!(
+            "DB migration directory {} does not exist (working directory: {}). Run the service from the directory containing the migrations, or fix the path",
+            path,
+            working_dir()
+        ))
+    } else {
+        let has_migrations = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read DB migration directory {}: {}", path, e))?
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.path().extension().is_some_and(|ext| ext == "sql"));
+
+        if has_migrations {
+            None
+        } else {
+            Some(format!(
+                "DB migration directory {} contains no migrations (*.sql files). Check that the migrations are deployed along with the service",
+                path
+            ))
+        }
+    };
+
+    match error {
+        Some(error) => {
+            error!("{}", error);
+            Err(error)
+        }
+        None => Ok(()),
+    }
+}
+
+fn working_dir() -> String {
+    std::env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or("unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::sqlite_migrate;
+    use golem_common::config::DbSqliteConfig;
+
+    fn test_config(name: &str) -> DbSqliteConfig {
+        DbSqliteConfig {
+            database: std::env::temp_dir()
+                .join(format!("{}-{}.db", name, std::process::id()))
+                .display()
+                .to_string(),
+            max_connections: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_migration_dir_is_reported() {
+        let result = sqlite_migrate(
+            &test_config("missing-migrations"),
+            "/tmp/golem-missing-migration-dir",
+        )
+        .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("DB migration directory /tmp/golem-missing-migration-dir does not exist"));
+    }
+
+    #[tokio::test]
+    async fn empty_migration_dir_is_reported() {
+        let dir =
+            std::env::temp_dir().join(format!("golem-empty-migrations-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("README.md"), "not a migration").unwrap();
+        let path = dir.display().to_string();
+
+        let result = sqlite_migrate(&test_config("empty-migrations"), &path).await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "DB migration directory {} contains no migrations (*.sql files). Check that the migrations are deployed along with the service",
+                path
+            )
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -168,14 +168,14 @@ impl RoutingTableService for RoutingTableServiceDefault {
             .get_or_insert_simple(&(), || {
                 Box::pin(async move {
                     let response = client
-                        .call(|client| {
+                        .call("get_routing_table", |client| {
                             Box::pin(
                                 client
                                     .get_routing_table(shardmanager::v1::GetRoutingTableRequest {}),
                             )
                         })
                         .await
-                        .map_err(RoutingTableError::ShardManagerGrpcError)?;
+                        .map_err(|err| RoutingTableError::ShardManagerGrpcError(err.into()))?;
                     match response.into_inner() {
                         shardmanager::v1::GetRoutingTableResponse {
                             result:
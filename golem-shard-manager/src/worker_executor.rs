@@ -210,14 +210,14 @@ impl WorkerExecutorServiceDefault {
 
         let assign_shards_response = timeout(
             self.config.assign_shards_timeout,
-            self.client.call(pod.uri(), move |client| {
+            self.client.call(pod.uri(), "assign_shards", move |client| {
                 let assign_shards_request = assign_shards_request.clone();
                 Box::pin(client.assign_shards(assign_shards_request))
             }),
         )
         .await
         .map_err(|_: Elapsed| ShardManagerError::Timeout)?
-        .map_err(ShardManagerError::GrpcError)?;
+        .map_err(|err| ShardManagerError::GrpcError(err.into()))?;
 
         match assign_shards_response.into_inner() {
             golem::workerexecutor::v1::AssignShardsResponse {
@@ -251,14 +251,14 @@ impl WorkerExecutorServiceDefault {
 
         let revoke_shards_response = timeout(
             self.config.revoke_shards_timeout,
-            self.client.call(pod.uri(), move |client| {
+            self.client.call(pod.uri(), "revoke_shards", move |client| {
                 let revoke_shards_request = revoke_shards_request.clone();
                 Box::pin(client.revoke_shards(revoke_shards_request))
             }),
         )
         .await
         .map_err(|_: Elapsed| ShardManagerError::Timeout)?
-        .map_err(ShardManagerError::GrpcError)?;
+        .map_err(|err| ShardManagerError::GrpcError(err.into()))?;
 
         match revoke_shards_response.into_inner() {
             golem::workerexecutor::v1::RevokeShardsResponse {
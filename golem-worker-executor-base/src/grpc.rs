@@ -41,6 +41,7 @@ use golem_api_grpc::proto::golem::workerexecutor::v1::{
     GetWorkersMetadataResponse, InvokeAndAwaitWorkerRequest, InvokeAndAwaitWorkerResponseTyped,
     InvokeAndAwaitWorkerSuccess, UpdateWorkerRequest, UpdateWorkerResponse,
 };
+use golem_common::client::GrpcCallError;
 use golem_common::grpc::{
     proto_account_id_string, proto_component_id_string, proto_idempotency_key_string,
     proto_promise_id_string, proto_target_worker_id_string, proto_worker_id_string,
@@ -110,6 +111,12 @@ impl<E> From<Status> for GrpcError<E> {
     }
 }
 
+impl<E> From<GrpcCallError> for GrpcError<E> {
+    fn from(value: GrpcCallError) -> Self {
+        Self::Status(value.into())
+    }
+}
+
 impl<E> From<String> for GrpcError<E> {
     fn from(value: String) -> Self {
         Self::Unexpected(value)
@@ -83,7 +83,7 @@ impl ShardManagerService for ShardManagerServiceGrpc {
                 let pod_name = pod_name.clone();
                 Box::pin(async move {
                     let response = client
-                        .call(move |client| {
+                        .call("register", move |client| {
                             Box::pin(client.register(shardmanager::v1::RegisterRequest {
                                 host: host.clone(),
                                 port: *port as i32,
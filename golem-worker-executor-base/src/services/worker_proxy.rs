@@ -23,7 +23,7 @@ use golem_api_grpc::proto::golem::worker::v1::{
     UpdateWorkerRequest, UpdateWorkerResponse, WorkerError,
 };
 use golem_api_grpc::proto::golem::worker::{InvocationContext, InvokeParameters, UpdateMode};
-use golem_common::client::GrpcClient;
+use golem_common::client::{GrpcCallError, GrpcClient};
 use golem_common::model::{ComponentVersion, IdempotencyKey, OwnedWorkerId, WorkerId};
 use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
 use golem_wasm_rpc::{Value, WitValue};
@@ -108,6 +108,12 @@ impl From<tonic::Status> for WorkerProxyError {
     }
 }
 
+impl From<GrpcCallError> for WorkerProxyError {
+    fn from(value: GrpcCallError) -> Self {
+        Self::InternalError(GolemError::unknown(format!("gRPC error: {}", value)))
+    }
+}
+
 impl From<WorkerError> for WorkerProxyError {
     fn from(value: WorkerError) -> Self {
         match value.error {
@@ -193,7 +199,7 @@ impl WorkerProxy for RemoteWorkerProxy {
 
         let response: InvokeAndAwaitTypedResponse = self
             .client
-            .call(move |client| {
+            .call("invoke_and_await_typed", move |client| {
                 Box::pin(client.invoke_and_await_typed(authorised_grpc_request(
                     InvokeAndAwaitRequest {
                         worker_id: Some(owned_worker_id.worker_id().into_target_worker_id().into()),
@@ -259,7 +265,7 @@ impl WorkerProxy for RemoteWorkerProxy {
 
         let response: InvokeResponse = self
             .client
-            .call(move |client| {
+            .call("invoke", move |client| {
                 Box::pin(client.invoke(authorised_grpc_request(
                     InvokeRequest {
                         worker_id: Some(owned_worker_id.worker_id().into_target_worker_id().into()),
@@ -297,7 +303,7 @@ impl WorkerProxy for RemoteWorkerProxy {
 
         let response: UpdateWorkerResponse = self
             .client
-            .call(move |client| {
+            .call("update_worker", move |client| {
                 Box::pin(client.update_worker(authorised_grpc_request(
                     UpdateWorkerRequest {
                         worker_id: Some(owned_worker_id.worker_id().into()),
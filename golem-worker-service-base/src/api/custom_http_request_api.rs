@@ -2,14 +2,21 @@ use std::future::Future;
 use std::sync::Arc;
 
 use crate::api_definition::http::CompiledHttpApiDefinition;
+use crate::app_config::NonUtf8RequestBodyPolicy;
 use crate::worker_service_rib_interpreter::{DefaultRibInterpreter, WorkerServiceRibInterpreter};
+use base64::Engine;
 use futures_util::FutureExt;
 use hyper::header::HOST;
 use poem::http::StatusCode;
 use poem::{Body, Endpoint, Request, Response};
 use tracing::{error, info};
 
-use crate::http::{ApiInputPath, InputHttpRequest};
+use crate::http::access_log::MatchedRoute;
+use crate::http::default_route::DefaultRoute;
+use crate::http::route_circuit_breaker::{RouteCircuitBreaker, RouteKey};
+use crate::http::streamed_body;
+use crate::http::streamed_body::ReadError;
+use crate::http::InputHttpRequest;
 use crate::service::api_definition_lookup::ApiDefinitionsLookup;
 
 use crate::worker_binding::RequestToWorkerBindingResolver;
@@ -22,6 +29,14 @@ pub struct CustomHttpRequestApi {
     pub worker_service_rib_interpreter: Arc<dyn WorkerServiceRibInterpreter + Sync + Send>,
     pub api_definition_lookup_service:
         Arc<dyn ApiDefinitionsLookup<InputHttpRequest, CompiledHttpApiDefinition> + Sync + Send>,
+    // Responds to the requests that don't match any route, instead of rejecting them with 405
+    pub default_route: Option<Arc<DefaultRoute>>,
+    // Rejects the requests of the routes whose workers mostly fail
+    pub route_circuit_breaker: Option<Arc<RouteCircuitBreaker>>,
+    // What to do with the request bodies that are not valid UTF-8
+    pub non_utf8_request_body: NonUtf8RequestBodyPolicy,
+    // The results of the workers larger than this are rejected with 502, before they're rendered
+    pub response_body_limit: Option<u64>,
 }
 
 impl CustomHttpRequestApi {
@@ -38,15 +53,50 @@ impl CustomHttpRequestApi {
         Self {
             worker_service_rib_interpreter: evaluator,
             api_definition_lookup_service,
+            default_route: None,
+            route_circuit_breaker: None,
+            non_utf8_request_body: NonUtf8RequestBodyPolicy::Reject,
+            response_body_limit: None,
+        }
+    }
+
+    pub fn with_default_route(self, default_route: DefaultRoute) -> Self {
+        Self {
+            default_route: Some(Arc::new(default_route)),
+            ..self
+        }
+    }
+
+    pub fn with_route_circuit_breaker(self, route_circuit_breaker: RouteCircuitBreaker) -> Self {
+        Self {
+            route_circuit_breaker: Some(Arc::new(route_circuit_breaker)),
+            ..self
+        }
+    }
+
+    pub fn with_non_utf8_request_body(self, policy: NonUtf8RequestBodyPolicy) -> Self {
+        Self {
+            non_utf8_request_body: policy,
+            ..self
+        }
+    }
+
+    pub fn with_response_body_limit(self, limit: u64) -> Self {
+        Self {
+            response_body_limit: Some(limit),
+            ..self
         }
     }
 
     pub async fn execute(&self, request: Request) -> Response {
-        let (req_parts, body) = request.into_parts();
-        let headers = req_parts.headers;
-        let uri = req_parts.uri;
+        let mut input_http_request = InputHttpRequest::from_request(&request);
+        let body = request.into_body();
 
-        let host = match headers.get(HOST).and_then(|h| h.to_str().ok()) {
+        let host = match input_http_request
+            .headers
+            .get(HOST)
+            .and_then(|h| h.to_str().ok())
+        {
             Some(host) => host.to_string(),
             None => {
                 return Response::builder()
@@ -57,30 +107,6 @@ impl CustomHttpRequestApi {
 
         info!("API request host: {}", host);
 
-        let json_request_body: serde_json::Value = if body.is_empty() {
-            serde_json::Value::Null
-        } else {
-            match body.into_json().await {
-                Ok(json_request_body) => json_request_body,
-                Err(err) => {
-                    error!("API request host: {} - error: {}", host, err);
-                    return Response::builder()
-                        .status(StatusCode::BAD_REQUEST)
-                        .body(Body::from_string("Request body parse error".to_string()));
-                }
-            }
-        };
-
-        let input_http_request = InputHttpRequest {
-            input_path: ApiInputPath {
-                base_path: uri.path().to_string(),
-                query_path: uri.query().map(|x| x.to_string()),
-            },
-            headers,
-            req_method: req_parts.method,
-            req_body: json_request_body,
-        };
-
         let possible_api_definitions = match self
             .api_definition_lookup_service
             .get(input_http_request.clone())
@@ -98,14 +124,137 @@ impl CustomHttpRequestApi {
             }
         };
 
+        if let Some(default_route) = &self.default_route {
+            if !input_http_request.matches_route(&possible_api_definitions) {
+                return default_route.response(&input_http_request).await;
+            }
+        }
+
+        // The body is only read (and buffered) if the route uses it. Workers are invoked
+        // with the values evaluated by Rib, so an unused body is never read at all,
+        // no matter how large it is. If the route only reads fields of the body,
+        // it's streamed, and read only until these fields. If the route also reads the body
+        // as it was sent, the body is read once, and its JSON value is parsed from the text.
+        // A body that is not UTF-8 has no JSON value, and unless it's rejected,
+        // request.body is absent, while request.rawBody depends on the policy
+        let references_raw_body =
+            input_http_request.route_references_raw_body(&possible_api_definitions);
+        let references_body =
+            !body.is_empty() && input_http_request.route_references_body(&possible_api_definitions);
+
+        if references_raw_body {
+            let bytes = match body.into_vec().await {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    error!("API request host: {} - error: {}", host, err);
+                    return Response::builder()
+                        .status(StatusCode::BAD_REQUEST)
+                        .body(Body::from_string("Request body read error".to_string()));
+                }
+            };
+
+            match String::from_utf8(bytes) {
+                Ok(raw_body) => {
+                    if references_body && !raw_body.is_empty() {
+                        match serde_json::from_str(&raw_body) {
+                            Ok(json_request_body) => {
+                                input_http_request.req_body = json_request_body
+                            }
+                            Err(err) => {
+                                error!("API request host: {} - error: {}", host, err);
+                                return Response::builder().status(StatusCode::BAD_REQUEST).body(
+                                    Body::from_string("Request body parse error".to_string()),
+                                );
+                            }
+                        }
+                    }
+
+                    input_http_request.req_raw_body = Some(raw_body);
+                }
+                Err(err) => match self.non_utf8_request_body {
+                    NonUtf8RequestBodyPolicy::Reject => {
+                        error!("API request host: {} - error: {}", host, err);
+                        return non_utf8_body_response();
+                    }
+                    NonUtf8RequestBodyPolicy::Base64 => {
+                        input_http_request.req_raw_body =
+                            Some(base64::engine::general_purpose::STANDARD.encode(err.as_bytes()));
+                    }
+                    NonUtf8RequestBodyPolicy::Absent => {}
+                },
+            }
+        } else if references_body {
+            let request_body =
+                match input_http_request.route_request_body_fields(&possible_api_definitions) {
+                    Some(fields) => streamed_body::read_json_fields(body, &fields).await,
+                    None => streamed_body::read_json(body).await,
+                };
+
+            match request_body {
+                Ok(json_request_body) => input_http_request.req_body = json_request_body,
+                Err(ReadError::NotUtf8)
+                    if self.non_utf8_request_body != NonUtf8RequestBodyPolicy::Reject => {}
+                Err(err) => {
+                    error!("API request host: {} - error: {}", host, err);
+                    return match err {
+                        ReadError::Read(_) => Response::builder()
+                            .status(StatusCode::BAD_REQUEST)
+                            .body(Body::from_string("Request body read error".to_string())),
+                        ReadError::NotUtf8 => non_utf8_body_response(),
+                        ReadError::Invalid(_) => Response::builder()
+                            .status(StatusCode::BAD_REQUEST)
+                            .body(Body::from_string("Request body parse error".to_string())),
+                    };
+                }
+            }
+        }
+
         match input_http_request
             .resolve_worker_binding(possible_api_definitions)
             .await
         {
             Ok(resolved_worker_binding) => {
-                resolved_worker_binding
-                    .interpret_response_mapping(&self.worker_service_rib_interpreter)
-                    .await
+                let matched_route = MatchedRoute {
+                    route: resolved_worker_binding.route.clone(),
+                    worker_name: resolved_worker_binding.worker_detail.worker_name.clone(),
+                };
+
+                let route_call = match &self.route_circuit_breaker {
+                    Some(route_circuit_breaker) => {
+                        let route_key = RouteKey {
+                            host: host.clone(),
+                            api_definition: resolved_worker_binding.api_definition.clone(),
+                            route: matched_route.route.clone(),
+                        };
+
+                        match route_circuit_breaker.allow(&route_key) {
+                            Some(route_call) => Some(route_call),
+                            None => {
+                                let mut response =
+                                    route_circuit_breaker.service_unavailable(&route_key);
+                                response.extensions_mut().insert(matched_route);
+                                return response;
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
+                let mut response = resolved_worker_binding
+                    .interpret_response_mapping(
+                        &self.worker_service_rib_interpreter,
+                        self.response_body_limit,
+                    )
+                    .await;
+
+                if let (Some(route_circuit_breaker), Some(route_call)) =
+                    (&self.route_circuit_breaker, route_call)
+                {
+                    route_circuit_breaker.record(route_call, response.status());
+                }
+
+                response.extensions_mut().insert(matched_route);
+                response
             }
 
             Err(msg) => {
@@ -119,6 +268,14 @@ impl CustomHttpRequestApi {
     }
 }
 
+fn non_utf8_body_response() -> Response {
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(Body::from_string(
+            "Request body is not valid UTF-8".to_string(),
+        ))
+}
+
 impl Endpoint for CustomHttpRequestApi {
     type Output = Response;
 
@@ -126,3 +283,459 @@ impl Endpoint for CustomHttpRequestApi {
         self.execute(req).map(Ok)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::custom_http_request_api::CustomHttpRequestApi;
+    use crate::api_definition::http::{
+        CompiledHttpApiDefinition, ComponentMetadataDictionary, HttpApiDefinition, MethodPattern,
+        Route,
+    };
+    use crate::app_config::{
+        DefaultRouteConfig, NonUtf8RequestBodyPolicy, RequestBodyLimitConfig,
+        ResponseBodyLimitConfig,
+    };
+    use crate::http::catch_panic::CatchPanic;
+    use crate::http::default_route::DefaultRoute;
+    use crate::http::request_body_limit::RequestBodyLimit;
+    use crate::http::response_body_limit::ResponseBodyLimit;
+    use crate::http::InputHttpRequest;
+    use crate::metrics;
+    use crate::service::api_definition_lookup::{ApiDefinitionLookupError, ApiDefinitionsLookup};
+    use crate::worker_bridge_execution::{
+        WorkerRequest, WorkerRequestExecutor, WorkerRequestExecutorError, WorkerResponse,
+    };
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use futures_util::StreamExt;
+    use golem_api_grpc::proto::golem::apidefinition as grpc_apidefinition;
+    use golem_common::model::ComponentId;
+    use golem_service_base::model::VersionedComponentId;
+    use http::{StatusCode, Uri};
+    use poem::{Body, Endpoint, EndpointExt, Request};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    struct NoWorkerRequestExecutor;
+
+    #[async_trait]
+    impl WorkerRequestExecutor for NoWorkerRequestExecutor {
+        async fn execute(
+            &self,
+            _resolved_worker_request: WorkerRequest,
+        ) -> Result<WorkerResponse, WorkerRequestExecutorError> {
+            Err("No workers in this test".into())
+        }
+    }
+
+    struct TestApiDefinitionLookup(CompiledHttpApiDefinition);
+
+    #[async_trait]
+    impl ApiDefinitionsLookup<InputHttpRequest, CompiledHttpApiDefinition> for TestApiDefinitionLookup {
+        async fn get(
+            &self,
+            _input: InputHttpRequest,
+        ) -> Result<Vec<CompiledHttpApiDefinition>, ApiDefinitionLookupError> {
+            Ok(vec![self.0.clone()])
+        }
+    }
+
+    fn get_api(response: &str) -> CustomHttpRequestApi {
+        let versioned_component_id = VersionedComponentId {
+            component_id: ComponentId::try_from("0b6d9cd8-f373-4e29-8a5a-548e61b868a5").unwrap(),
+            version: 0,
+        };
+
+        let yaml_string = format!(
+            r#"
+          id: upload-api
+          version: 0.0.1
+          createdAt: 2024-08-21T07:42:15.696Z
+          routes:
+          - method: Post
+            path: /upload
+            binding:
+              type: wit-worker
+              componentId:
+                componentId: 0b6d9cd8-f373-4e29-8a5a-548e61b868a5
+                version: 0
+              workerName: '${{"uploader"}}'
+              response: '${{{}}}'
+        "#,
+            response
+        );
+
+        let definition: HttpApiDefinition = serde_yaml::from_str(yaml_string.as_str()).unwrap();
+
+        api_of(definition, versioned_component_id)
+    }
+
+    fn api_of(
+        definition: HttpApiDefinition,
+        versioned_component_id: VersionedComponentId,
+    ) -> CustomHttpRequestApi {
+        let metadata = ComponentMetadataDictionary {
+            metadata: HashMap::from([(versioned_component_id, vec![])]),
+        };
+
+        let compiled =
+            CompiledHttpApiDefinition::from_http_api_definition(&definition, &metadata).unwrap();
+
+        CustomHttpRequestApi::new(
+            Arc::new(NoWorkerRequestExecutor),
+            Arc::new(TestApiDefinitionLookup(compiled)),
+        )
+    }
+
+    // A body of `chunks` chunks, which are only created when the body is read
+    fn counted_body(chunks: usize, read_chunks: Arc<AtomicUsize>) -> Body {
+        let stream = futures_util::stream::iter(0..chunks).map(move |index| {
+            read_chunks.fetch_add(1, Ordering::SeqCst);
+            let chunk = if index == 0 {
+                b"{\"name\": \"foo\", \"padding\": \"".to_vec()
+            } else if index == chunks - 1 {
+                b"\"}".to_vec()
+            } else {
+                vec![b'a'; CHUNK_SIZE]
+            };
+            Ok::<_, std::io::Error>(Bytes::from(chunk))
+        });
+
+        Body::from_bytes_stream(stream)
+    }
+
+    fn post_upload(body: Body) -> Request {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri(Uri::from_static("http://localhost/upload"))
+            .header(http::header::HOST, "localhost")
+            .body(body)
+    }
+
+    #[tokio::test]
+    async fn evaluation_duration_is_recorded_per_route() {
+        let api = get_api(r#""ok""#);
+        let evaluations_before = metrics::gateway_evaluations("POST /upload");
+
+        let response = api.execute(post_upload(Body::empty())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(metrics::gateway_evaluations("POST /upload") > evaluations_before);
+    }
+
+    #[tokio::test]
+    async fn result_over_the_response_body_limit_is_rejected() {
+        let api = get_api(r#""aaaaaaaaaaaaaaaaaaaa""#).with_response_body_limit(10);
+
+        let response = api.execute(post_upload(Body::empty())).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        let api = get_api(r#""aaaaa""#).with_response_body_limit(10);
+
+        let response = api.execute(post_upload(Body::empty())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn large_body_is_not_read_if_not_referenced() {
+        let api = get_api(r#""ok""#);
+        let read_chunks = Arc::new(AtomicUsize::new(0));
+
+        // 1 GiB, which is never read
+        let body = counted_body(16 * 1024, read_chunks.clone());
+
+        let response = api.execute(post_upload(body)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(read_chunks.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn body_is_read_if_referenced() {
+        let api = get_api("let padding: str = request.body.padding; padding");
+        let read_chunks = Arc::new(AtomicUsize::new(0));
+
+        let body = counted_body(4, read_chunks.clone());
+
+        let response = api.execute(post_upload(body)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(read_chunks.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn body_is_read_only_until_the_referenced_fields() {
+        let api = get_api("let name: str = request.body.name; name");
+        let read_chunks = Arc::new(AtomicUsize::new(0));
+
+        // 1 GiB, of which only the first chunk (with the name) is read
+        let body = counted_body(16 * 1024, read_chunks.clone());
+
+        let response = api.execute(post_upload(body)).await;
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::OK);
+        assert_eq!(body.into_string().await.unwrap(), "foo");
+        assert_eq!(read_chunks.load(Ordering::SeqCst), 1);
+    }
+
+    // The API behind the middlewares of the gateway, in the order they're composed in
+    fn with_middlewares(api: CustomHttpRequestApi, max_request_bytes: u64) -> impl Endpoint {
+        api.with(CatchPanic::new("gateway"))
+            .with(RequestBodyLimit::new(RequestBodyLimitConfig {
+                max_bytes: max_request_bytes,
+                routes: vec![],
+            }))
+            .with(ResponseBodyLimit::new(ResponseBodyLimitConfig::default()))
+    }
+
+    #[tokio::test]
+    async fn chunked_body_is_streamed_through_the_middlewares() {
+        let api = with_middlewares(
+            get_api("let name: str = request.body.name; name"),
+            16 * CHUNK_SIZE as u64,
+        );
+        let read_chunks = Arc::new(AtomicUsize::new(0));
+
+        // 1 GiB without a content length, of which only the first chunk (with the name) is read
+        let body = counted_body(16 * 1024, read_chunks.clone());
+
+        let response = api.call(post_upload(body)).await.unwrap();
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::OK);
+        assert_eq!(body.into_string().await.unwrap(), "foo");
+        assert_eq!(read_chunks.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn chunked_body_over_the_limit_is_rejected_through_the_middlewares() {
+        let api = with_middlewares(
+            get_api("let padding: str = request.body.padding; padding"),
+            16 * CHUNK_SIZE as u64,
+        );
+        let read_chunks = Arc::new(AtomicUsize::new(0));
+
+        let body = counted_body(64, read_chunks.clone());
+
+        let response = api.call(post_upload(body)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        // Read only until the limit
+        assert!(read_chunks.load(Ordering::SeqCst) < 64);
+    }
+
+    #[tokio::test]
+    async fn raw_body_is_the_body_as_it_was_sent() {
+        let api = get_api("let raw: str = request.rawBody; raw");
+        let sent = "{ \"name\":  \"foo\",\n  \"tags\": [] }\n";
+
+        let response = api
+            .execute(post_upload(Body::from_string(sent.to_string())))
+            .await;
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::OK);
+        assert_eq!(body.into_string().await.unwrap(), sent);
+    }
+
+    #[tokio::test]
+    async fn raw_body_and_body_are_read_together() {
+        let api = get_api(
+            r#"let raw: str = request.rawBody; let name: str = request.body.name; if name == "foo" then raw else "unexpected name""#,
+        );
+        let read_chunks = Arc::new(AtomicUsize::new(0));
+
+        let response = api
+            .execute(post_upload(counted_body(3, read_chunks.clone())))
+            .await;
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::OK);
+        assert_eq!(
+            body.into_string().await.unwrap(),
+            format!(
+                "{{\"name\": \"foo\", \"padding\": \"{}\"}}",
+                "a".repeat(CHUNK_SIZE)
+            )
+        );
+        // Read once, for both of them
+        assert_eq!(read_chunks.load(Ordering::SeqCst), 3);
+    }
+
+    // Not valid UTF-8, as 0xff is never a part of a UTF-8 character
+    fn non_utf8_body() -> Body {
+        Body::from(vec![0xff, 0xfe, 0x00, 0x61])
+    }
+
+    #[tokio::test]
+    async fn non_utf8_body_is_rejected() {
+        let api = get_api("let raw: str = request.rawBody; raw");
+
+        let response = api.execute(post_upload(non_utf8_body())).await;
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body.into_string().await.unwrap(),
+            "Request body is not valid UTF-8"
+        );
+    }
+
+    #[tokio::test]
+    async fn non_utf8_body_is_exposed_in_base64() {
+        let api = get_api("let raw: str = request.rawBody; raw")
+            .with_non_utf8_request_body(NonUtf8RequestBodyPolicy::Base64);
+
+        let response = api.execute(post_upload(non_utf8_body())).await;
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::OK);
+        assert_eq!(body.into_string().await.unwrap(), "//4AYQ==");
+    }
+
+    #[tokio::test]
+    async fn non_utf8_body_is_absent() {
+        let api = get_api(r#"if isPresent(request.rawBody) then "present" else "absent""#)
+            .with_non_utf8_request_body(NonUtf8RequestBodyPolicy::Absent);
+
+        let response = api.execute(post_upload(non_utf8_body())).await;
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::OK);
+        assert_eq!(body.into_string().await.unwrap(), "absent");
+    }
+
+    #[tokio::test]
+    async fn non_utf8_body_is_rejected_when_its_json_value_is_read() {
+        for response in [
+            "let name: str = request.body.name; name",
+            "let body: str = request.body; body",
+        ] {
+            let api = get_api(response);
+
+            let response = api.execute(post_upload(non_utf8_body())).await;
+            let (parts, body) = response.into_parts();
+
+            assert_eq!(parts.status, StatusCode::BAD_REQUEST);
+            assert_eq!(
+                body.into_string().await.unwrap(),
+                "Request body is not valid UTF-8"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn non_utf8_body_has_no_json_value_unless_rejected() {
+        for policy in [
+            NonUtf8RequestBodyPolicy::Base64,
+            NonUtf8RequestBodyPolicy::Absent,
+        ] {
+            let api = get_api("let name: str = request.body.name; name")
+                .with_non_utf8_request_body(policy);
+
+            let response = api.execute(post_upload(non_utf8_body())).await;
+
+            // Not rejected, but request.body is absent, so the field the route reads is missing,
+            // as any other field of the request that wasn't sent
+            assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        }
+    }
+
+    #[tokio::test]
+    async fn route_with_more_methods_matches_each_of_them() {
+        let request: crate::api::HttpApiDefinitionRequest = serde_yaml::from_str(
+            r#"
+          id: upload-api
+          version: 0.0.1
+          routes:
+          - method: Post
+            methods: [Put]
+            path: /upload
+            binding:
+              componentId:
+                componentId: 0b6d9cd8-f373-4e29-8a5a-548e61b868a5
+                version: 0
+              workerName: '${"uploader"}'
+              response: '${"ok"}'
+        "#,
+        )
+        .unwrap();
+
+        let definition = HttpApiDefinition::new(request.try_into().unwrap(), chrono::Utc::now());
+
+        // Stored as a single route, keeping all of its methods
+        assert_eq!(definition.routes.len(), 1);
+        let route = definition.routes[0].clone();
+        assert_eq!(
+            route.all_methods(),
+            vec![MethodPattern::Post, MethodPattern::Put]
+        );
+        let stored = grpc_apidefinition::HttpRoute::try_from(route.clone()).unwrap();
+        assert_eq!(Route::try_from(stored).unwrap(), route);
+        assert_eq!(
+            crate::api::Route::try_from(route).unwrap().methods,
+            vec![MethodPattern::Put]
+        );
+
+        let api = api_of(
+            definition,
+            VersionedComponentId {
+                component_id: ComponentId::try_from("0b6d9cd8-f373-4e29-8a5a-548e61b868a5")
+                    .unwrap(),
+                version: 0,
+            },
+        );
+
+        let upload = |method: http::Method| {
+            Request::builder()
+                .method(method)
+                .uri(Uri::from_static("http://localhost/upload"))
+                .header(http::header::HOST, "localhost")
+                .finish()
+        };
+
+        let response = api.execute(upload(http::Method::POST)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = api.execute(upload(http::Method::PUT)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = api.execute(upload(http::Method::GET)).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn unmatched_request_gets_the_default_response() {
+        let default_route = DefaultRoute::from_config(&DefaultRouteConfig {
+            status: 404,
+            body: "${let page: str = request.query.page; page}".to_string(),
+            headers: HashMap::from([("x-default-route".to_string(), "true".to_string())]),
+        })
+        .unwrap();
+
+        let api = get_api(r#""ok""#).with_default_route(default_route);
+
+        let request = Request::builder()
+            .method(http::Method::GET)
+            .uri(Uri::from_static("http://localhost/missing?page=missing"))
+            .header(http::header::HOST, "localhost")
+            .finish();
+
+        let response = api.execute(request).await;
+        let (parts, body) = response.into_parts();
+
+        assert_eq!(parts.status, StatusCode::NOT_FOUND);
+        assert_eq!(parts.headers.get("x-default-route").unwrap(), "true");
+        assert_eq!(body.into_string().await.unwrap(), "missing");
+
+        let response = api.execute(post_upload(Body::empty())).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
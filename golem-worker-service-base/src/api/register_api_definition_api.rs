@@ -9,6 +9,8 @@ use crate::api_definition::http::{
     AllPathPatterns, CompiledHttpApiDefinition, CompiledRoute, MethodPattern,
 };
 use crate::api_definition::{ApiDefinitionId, ApiSite, ApiVersion};
+use crate::parser::rib_parser::RibParser;
+use crate::parser::GolemParser;
 use crate::worker_binding::CompiledGolemWorkerBinding;
 use rib::{Expr, RibInputTypeInfo};
 
@@ -96,6 +98,10 @@ impl From<CompiledHttpApiDefinition> for HttpApiDefinitionWithTypeInfo {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
 pub struct Route {
     pub method: MethodPattern,
+    // More methods the route accepts, besides `method` (Ex: `Head` for a `Get` route)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[oai(default, skip_serializing_if_is_empty)]
+    pub methods: Vec<MethodPattern>,
     pub path: String,
     pub binding: GolemWorkerBinding,
 }
@@ -103,6 +109,9 @@ pub struct Route {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
 pub struct RouteWithTypeInfo {
     pub method: MethodPattern,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[oai(default, skip_serializing_if_is_empty)]
+    pub methods: Vec<MethodPattern>,
     pub path: String,
     pub binding: GolemWorkerBindingWithTypeInfo,
 }
@@ -110,10 +119,12 @@ pub struct RouteWithTypeInfo {
 impl From<CompiledRoute> for RouteWithTypeInfo {
     fn from(value: CompiledRoute) -> Self {
         let method = value.method;
+        let methods = value.methods;
         let path = value.path.to_string();
         let binding = value.binding.into();
         Self {
             method,
+            methods,
             path,
             binding,
         }
@@ -238,6 +249,7 @@ impl TryFrom<crate::api_definition::http::Route> for Route {
 
         Ok(Self {
             method: value.method,
+            methods: value.methods,
             path,
             binding,
         })
@@ -254,6 +266,7 @@ impl TryInto<crate::api_definition::http::Route> for Route {
 
         Ok(crate::api_definition::http::Route {
             method: self.method,
+            methods: self.methods,
             path,
             binding,
         })
@@ -287,19 +300,26 @@ impl TryInto<crate::worker_binding::GolemWorkerBinding> for GolemWorkerBinding {
     type Error = String;
 
     fn try_into(self) -> Result<crate::worker_binding::GolemWorkerBinding, Self::Error> {
-        let response: crate::worker_binding::ResponseMapping = {
-            let r = rib::from_string(self.response.as_str()).map_err(|e| e.to_string())?;
-            crate::worker_binding::ResponseMapping(r)
-        };
-
-        let worker_name: Expr =
-            rib::from_string(self.worker_name.as_str()).map_err(|e| e.to_string())?;
-
-        let idempotency_key = if let Some(key) = &self.idempotency_key {
-            Some(rib::from_string(key).map_err(|e| e.to_string())?)
-        } else {
-            None
-        };
+        // All the templates are parsed, to report all their errors at once
+        const TEMPLATES: [&str; 3] = ["response", "worker name", "idempotency key"];
+
+        let mut templates = vec![self.response.as_str(), self.worker_name.as_str()];
+        templates.extend(self.idempotency_key.as_deref());
+
+        let mut parsed = RibParser::new()
+            .try_parse_all(&templates)
+            .map_err(|errors| {
+                errors
+                    .iter()
+                    .map(|e| format!("Invalid {}: {}", TEMPLATES[e.index], e.error))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })?
+            .into_iter();
+
+        let response = crate::worker_binding::ResponseMapping(parsed.next().unwrap());
+        let worker_name: Expr = parsed.next().unwrap();
+        let idempotency_key = parsed.next();
 
         Ok(crate::worker_binding::GolemWorkerBinding {
             component_id: self.component_id,
@@ -406,11 +426,17 @@ impl TryFrom<crate::api_definition::http::Route> for grpc_apidefinition::HttpRou
         let path = value.path.to_string();
         let binding = grpc_apidefinition::WorkerBinding::try_from(value.binding)?;
         let method: grpc_apidefinition::HttpMethod = value.method.into();
+        let methods = value
+            .methods
+            .into_iter()
+            .map(|method| grpc_apidefinition::HttpMethod::from(method) as i32)
+            .collect();
 
         let result = grpc_apidefinition::HttpRoute {
             method: method as i32,
             path,
             binding: Some(binding),
+            methods,
         };
 
         Ok(result)
@@ -422,12 +448,18 @@ impl TryFrom<CompiledRoute> for golem_api_grpc::proto::golem::apidefinition::Com
 
     fn try_from(value: CompiledRoute) -> Result<Self, Self::Error> {
         let method = value.method as i32;
+        let methods = value
+            .methods
+            .into_iter()
+            .map(|method| method as i32)
+            .collect();
         let path = value.path.to_string();
         let binding = value.binding.try_into()?;
         Ok(Self {
             method,
             path,
             binding: Some(binding),
+            methods,
         })
     }
 }
@@ -439,10 +471,16 @@ impl TryFrom<golem_api_grpc::proto::golem::apidefinition::CompiledHttpRoute> for
         value: golem_api_grpc::proto::golem::apidefinition::CompiledHttpRoute,
     ) -> Result<Self, Self::Error> {
         let method = MethodPattern::try_from(value.method)?;
+        let methods = value
+            .methods
+            .into_iter()
+            .map(MethodPattern::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
         let path = AllPathPatterns::parse(value.path.as_str()).map_err(|e| e.to_string())?;
         let binding = value.binding.ok_or("binding is missing")?.try_into()?;
         Ok(CompiledRoute {
             method,
+            methods,
             path,
             binding,
         })
@@ -474,9 +512,15 @@ impl TryFrom<grpc_apidefinition::HttpRoute> for crate::api_definition::http::Rou
         let binding = value.binding.ok_or("binding is missing")?.try_into()?;
 
         let method: MethodPattern = value.method.try_into()?;
+        let methods = value
+            .methods
+            .into_iter()
+            .map(MethodPattern::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
 
         let result = crate::api_definition::http::Route {
             method,
+            methods,
             path,
             binding,
         };
@@ -8,6 +8,7 @@ use derive_more::Display;
 use golem_service_base::model::{Component, VersionedComponentId};
 use golem_wasm_ast::analysis::AnalysedExport;
 use poem_openapi::Enum;
+use rib::PredicateMode;
 use serde::{Deserialize, Serialize, Serializer};
 use serde_json::Value;
 
@@ -98,11 +99,26 @@ impl CompiledHttpApiDefinition {
     pub fn from_http_api_definition(
         http_api_definition: &HttpApiDefinition,
         metadata_dictionary: &ComponentMetadataDictionary,
+    ) -> Result<Self, RouteCompilationErrors> {
+        Self::from_http_api_definition_with_predicate_mode(
+            http_api_definition,
+            metadata_dictionary,
+            PredicateMode::Strict,
+        )
+    }
+
+    // The Rib scripts of the routes are compiled with the given predicate mode,
+    // which decides whether their conditions must be booleans
+    pub fn from_http_api_definition_with_predicate_mode(
+        http_api_definition: &HttpApiDefinition,
+        metadata_dictionary: &ComponentMetadataDictionary,
+        predicate_mode: PredicateMode,
     ) -> Result<Self, RouteCompilationErrors> {
         let mut compiled_routes = vec![];
 
         for route in &http_api_definition.routes {
-            let compiled_route = CompiledRoute::from_route(route, metadata_dictionary)?;
+            let compiled_route =
+                CompiledRoute::from_route(route, metadata_dictionary, predicate_mode)?;
             compiled_routes.push(compiled_route);
         }
 
@@ -349,17 +365,32 @@ impl Display for PathPattern {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub struct Route {
     pub method: MethodPattern,
+    // More methods the route accepts, besides `method` (Ex: `Head` for a `Get` route).
+    // The route is stored once, and matched with each of its methods
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<MethodPattern>,
     pub path: AllPathPatterns,
     pub binding: GolemWorkerBinding,
 }
 
+impl Route {
+    pub fn all_methods(&self) -> Vec<MethodPattern> {
+        all_methods(&self.method, &self.methods)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
 pub struct CompiledRoute {
     pub method: MethodPattern,
+    pub methods: Vec<MethodPattern>,
     pub path: AllPathPatterns,
     pub binding: CompiledGolemWorkerBinding,
 }
 
+fn all_methods(method: &MethodPattern, methods: &[MethodPattern]) -> Vec<MethodPattern> {
+    std::iter::once(method).chain(methods).cloned().collect()
+}
+
 #[derive(Debug)]
 pub enum RouteCompilationErrors {
     MetadataNotFoundError(VersionedComponentId),
@@ -389,6 +420,7 @@ impl CompiledRoute {
     pub fn from_route(
         route: &Route,
         metadata_dictionary: &ComponentMetadataDictionary,
+        predicate_mode: PredicateMode,
     ) -> Result<Self, RouteCompilationErrors> {
         let metadata = metadata_dictionary
             .metadata
@@ -397,22 +429,31 @@ impl CompiledRoute {
                 route.binding.component_id.clone(),
             ))?;
 
-        let binding =
-            CompiledGolemWorkerBinding::from_golem_worker_binding(&route.binding, metadata)
-                .map_err(RouteCompilationErrors::RibCompilationError)?;
+        let binding = CompiledGolemWorkerBinding::from_golem_worker_binding(
+            &route.binding,
+            metadata,
+            predicate_mode,
+        )
+        .map_err(RouteCompilationErrors::RibCompilationError)?;
 
         Ok(CompiledRoute {
             method: route.method.clone(),
+            methods: route.methods.clone(),
             path: route.path.clone(),
             binding,
         })
     }
+
+    pub fn all_methods(&self) -> Vec<MethodPattern> {
+        all_methods(&self.method, &self.methods)
+    }
 }
 
 impl From<CompiledRoute> for Route {
     fn from(compiled_route: CompiledRoute) -> Self {
         Route {
             method: compiled_route.method,
+            methods: compiled_route.methods,
             path: compiled_route.path,
             binding: compiled_route.binding.into(),
         }
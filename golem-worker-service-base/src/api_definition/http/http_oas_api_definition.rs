@@ -163,6 +163,7 @@ mod internal {
         Ok(Route {
             path: path_pattern.clone(),
             method,
+            methods: vec![],
             binding,
         })
     }
@@ -272,6 +273,7 @@ mod tests {
             Ok(Route {
                 path: path_pattern,
                 method: MethodPattern::Get,
+                methods: vec![],
                 binding: GolemWorkerBinding {
                     worker_name: Expr::multiple(vec![
                         Expr::let_binding_with_type(
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::time::Duration;
 
@@ -10,6 +11,7 @@ use golem_common::config::{ConfigExample, HasConfigExamples, RetryConfig};
 use golem_common::config::{DbConfig, DbSqliteConfig};
 use golem_common::tracing::TracingConfig;
 use golem_service_base::routing_table::RoutingTableConfig;
+use rib::PredicateMode;
 
 // The base configuration for the worker service
 // If there are extra configurations for custom services,
@@ -25,12 +27,50 @@ pub struct WorkerServiceBaseConfig {
     pub worker_grpc_port: u16,
     pub routing_table: RoutingTableConfig,
     pub worker_executor_retries: RetryConfig,
+    // How often the addresses of the worker executors are resolved again,
+    // to reconnect to the ones whose address changed
+    #[serde(with = "humantime_serde")]
+    pub worker_executor_refresh_interval: Duration,
+    #[serde(with = "humantime_serde")]
+    pub shutdown_drain_timeout: Duration,
+    pub custom_request_body_limit: RequestBodyLimitConfig,
+    pub custom_response_body_limit: ResponseBodyLimitConfig,
+    pub custom_request_concurrency_limit: ConcurrencyLimitConfig,
+    // How the gateway handles the request bodies that are not valid UTF-8
+    pub non_utf8_request_body: NonUtf8RequestBodyPolicy,
+    // Whether the conditions of the Rib scripts of the API definitions must be booleans ("strict"),
+    // or can be of any type and are evaluated by their truthiness ("lenient").
+    // Applies to the API definitions created or updated from then on
+    pub rib_predicate_mode: PredicateMode,
+    // If enabled, the service fails to start if any of the stored API definitions
+    // can't be decoded, instead of failing the requests routed to them
+    pub validate_api_definitions_on_startup: bool,
+    // The response to the gateway requests that don't match any route.
+    // If not set, these requests are rejected with 405
+    pub default_route: Option<DefaultRouteConfig>,
+    // Logs a line per gateway request. Not logged at all if not set
+    pub access_log: Option<AccessLogConfig>,
+    // Rejects the requests of the routes whose workers mostly fail.
+    // If not set, the workers are invoked regardless of the errors
+    pub route_circuit_breaker: Option<RouteCircuitBreakerConfig>,
+    pub api_docs: ApiDocsConfig,
+    pub worker_grpc_server: GrpcServerConfig,
+    pub runtime: RuntimeConfig,
 }
 
 impl WorkerServiceBaseConfig {
     pub fn is_local_env(&self) -> bool {
         self.environment.to_lowercase() == "local"
     }
+
+    // The UI of the API docs to serve, if any
+    pub fn api_docs_ui(&self) -> Option<ApiDocsUi> {
+        if self.api_docs.enabled.unwrap_or(self.is_local_env()) {
+            Some(self.api_docs.ui)
+        } else {
+            None
+        }
+    }
 }
 
 impl Default for WorkerServiceBaseConfig {
@@ -54,6 +94,20 @@ impl Default for WorkerServiceBaseConfig {
                 multiplier: 10.0,
                 max_jitter_factor: Some(0.15),
             },
+            worker_executor_refresh_interval: Duration::from_secs(30),
+            shutdown_drain_timeout: Duration::from_secs(30),
+            custom_request_body_limit: RequestBodyLimitConfig::default(),
+            custom_response_body_limit: ResponseBodyLimitConfig::default(),
+            custom_request_concurrency_limit: ConcurrencyLimitConfig::default(),
+            non_utf8_request_body: NonUtf8RequestBodyPolicy::default(),
+            rib_predicate_mode: PredicateMode::default(),
+            validate_api_definitions_on_startup: false,
+            default_route: None,
+            access_log: None,
+            route_circuit_breaker: None,
+            api_docs: ApiDocsConfig::default(),
+            worker_grpc_server: GrpcServerConfig::default(),
+            runtime: RuntimeConfig::default(),
         }
     }
 }
@@ -70,6 +124,182 @@ impl HasConfigExamples<WorkerServiceBaseConfig> for WorkerServiceBaseConfig {
     }
 }
 
+// Limits the size of the request bodies accepted by the custom request (gateway) server.
+// Routes are matched by the longest path prefix, falling back to `max_bytes`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestBodyLimitConfig {
+    pub max_bytes: u64,
+    pub routes: Vec<RouteRequestBodyLimit>,
+}
+
+impl RequestBodyLimitConfig {
+    pub fn limit_for(&self, path: &str) -> u64 {
+        self.routes
+            .iter()
+            .filter(|route| path.starts_with(route.path_prefix.as_str()))
+            .max_by_key(|route| route.path_prefix.len())
+            .map_or(self.max_bytes, |route| route.max_bytes)
+    }
+}
+
+impl Default for RequestBodyLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 10 * 1024 * 1024,
+            routes: vec![],
+        }
+    }
+}
+
+// Limits the size of the response bodies of the custom request (gateway) server.
+// A larger response (Ex: from a misbehaving worker) is either rejected with 502, or truncated
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResponseBodyLimitConfig {
+    pub max_bytes: u64,
+    pub on_exceeded: ResponseBodyLimitAction,
+}
+
+impl Default for ResponseBodyLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 10 * 1024 * 1024,
+            on_exceeded: ResponseBodyLimitAction::Reject,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseBodyLimitAction {
+    Reject,
+    Truncate,
+}
+
+impl ResponseBodyLimitAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseBodyLimitAction::Reject => "reject",
+            ResponseBodyLimitAction::Truncate => "truncate",
+        }
+    }
+}
+
+// The UI of the API docs, served at `/docs`, and pointed at the spec of the running service.
+// If `enabled` is not set, the UI is served only in the local environment
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ApiDocsConfig {
+    pub enabled: Option<bool>,
+    pub ui: ApiDocsUi,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiDocsUi {
+    #[default]
+    SwaggerUi,
+    Rapidoc,
+}
+
+// What the gateway does with a request body that is not valid UTF-8 (Ex: binary data),
+// when the route reads the body. It's either rejected with 400, or exposed to the template as
+// `request.rawBody` encoded in base64 (with no JSON `request.body`), or treated as absent
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NonUtf8RequestBodyPolicy {
+    #[default]
+    Reject,
+    Base64,
+    Absent,
+}
+
+// The response of the gateway to the requests that don't match any route.
+// `body` is a Rib expression (optionally wrapped in `${..}`), which can refer to the
+// headers and the query parameters of the request (Ex: `request.headers.host`)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DefaultRouteConfig {
+    pub status: u16,
+    pub body: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+// The access log line of a gateway request is rendered from `format`, in which the fields
+// `{method}`, `{path}`, `{status}`, `{latency_ms}`, `{route}` (the matched route), `{worker}`
+// (the name of the invoked worker) and `{header.<name>}` (a request header) are replaced
+// with their values, or `-` if they don't have one (Ex: an unmatched request has no route)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccessLogConfig {
+    pub format: String,
+    #[serde(default)]
+    pub level: AccessLogLevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+// A route is opened when more than `error_rate_threshold` (Ex: 0.5) of its last `window_size`
+// requests got a 5xx response, once there are at least `min_requests` of them. The requests
+// to an open route are rejected with 503 for `cool_down`, after which a single request probes
+// whether the route recovered
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RouteCircuitBreakerConfig {
+    pub window_size: usize,
+    pub min_requests: usize,
+    pub error_rate_threshold: f64,
+    #[serde(with = "humantime_serde")]
+    pub cool_down: Duration,
+}
+
+// HTTP/2 settings of the gRPC server. The ones not set are the defaults of tonic
+// (no limit of the concurrent streams, and windows of 64 KiB)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GrpcServerConfig {
+    pub max_concurrent_streams: Option<u32>,
+    pub initial_connection_window_size: Option<u32>,
+    pub initial_stream_window_size: Option<u32>,
+}
+
+// Settings of the Tokio runtime of the service. The ones not set are the defaults of Tokio
+// (a stack of 2 MiB, and 512 blocking threads). A larger stack helps with deeply nested
+// templates, whose parsing is recursive, and more blocking threads with stalled DB migrations
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    pub thread_stack_size: Option<usize>,
+    pub max_blocking_threads: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RouteRequestBodyLimit {
+    pub path_prefix: String,
+    pub max_bytes: u64,
+}
+
+// Limits the number of requests the custom request (gateway) server handles at the same time.
+// Requests over the limit are not queued, but rejected with 503 and a `Retry-After` header
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConcurrencyLimitConfig {
+    pub max_in_flight: usize,
+    #[serde(with = "humantime_serde")]
+    pub retry_after: Duration,
+}
+
+impl Default for ConcurrencyLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: 1024,
+            retry_after: Duration::from_secs(1),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ComponentServiceConfig {
     pub host: String,
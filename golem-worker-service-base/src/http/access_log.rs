@@ -0,0 +1,322 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::app_config::{AccessLogConfig, AccessLogLevel};
+use poem::endpoint::EitherEndpoint;
+use poem::http::{HeaderMap, Method};
+use poem::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, trace, warn};
+
+// The route a gateway request was matched to, and the worker it invoked.
+// Added to the extensions of the response, to be logged by `AccessLog`
+#[derive(Debug, Clone)]
+pub struct MatchedRoute {
+    pub route: String,
+    pub worker_name: String,
+}
+
+// Logs a line per request handled by the custom request server, rendered from
+// the configured format. If disabled, the endpoint is used as it is
+pub struct AccessLog {
+    format: Option<Arc<AccessLogFormat>>,
+}
+
+impl AccessLog {
+    pub fn new(config: Option<&AccessLogConfig>) -> std::result::Result<Self, String> {
+        let format = config.map(AccessLogFormat::parse).transpose()?;
+
+        Ok(Self {
+            format: format.map(Arc::new),
+        })
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for AccessLog {
+    type Output = EitherEndpoint<E, AccessLogEndpoint<E>>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        match &self.format {
+            Some(format) => EitherEndpoint::B(AccessLogEndpoint {
+                inner: ep,
+                format: format.clone(),
+            }),
+            None => EitherEndpoint::A(ep),
+        }
+    }
+}
+
+pub struct AccessLogEndpoint<E> {
+    inner: E,
+    format: Arc<AccessLogFormat>,
+}
+
+impl<E: Endpoint> Endpoint for AccessLogEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let start = Instant::now();
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+        let headers = self.format.logged_headers(req.headers());
+
+        let result = self.inner.call(req).await.map(IntoResponse::into_response);
+
+        let entry = AccessLogEntry {
+            method,
+            path,
+            headers,
+            latency: start.elapsed(),
+            status: match &result {
+                Ok(response) => response.status().as_u16(),
+                Err(err) => err.status().as_u16(),
+            },
+            matched_route: result
+                .as_ref()
+                .ok()
+                .and_then(|response| response.extensions().get::<MatchedRoute>().cloned()),
+        };
+
+        self.format.log(&entry);
+
+        result
+    }
+}
+
+struct AccessLogEntry {
+    method: Method,
+    path: String,
+    headers: Vec<Option<String>>,
+    latency: Duration,
+    status: u16,
+    matched_route: Option<MatchedRoute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Method,
+    Path,
+    Status,
+    LatencyMs,
+    Route,
+    Worker,
+    // The index of the header in the logged headers
+    Header(usize),
+}
+
+#[derive(Debug)]
+struct AccessLogFormat {
+    segments: Vec<Segment>,
+    // The request headers used in the format, the only ones kept until the request is logged
+    header_names: Vec<String>,
+    level: AccessLogLevel,
+}
+
+impl AccessLogFormat {
+    fn parse(config: &AccessLogConfig) -> std::result::Result<Self, String> {
+        let mut segments = vec![];
+        let mut header_names: Vec<String> = vec![];
+        let mut rest = config.format.as_str();
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or(format!(
+                    "Invalid access log format, `{{` is not closed: {}",
+                    config.format
+                ))?;
+
+            let field = &rest[start + 1..end];
+
+            let segment = match field {
+                "method" => Segment::Method,
+                "path" => Segment::Path,
+                "status" => Segment::Status,
+                "latency_ms" => Segment::LatencyMs,
+                "route" => Segment::Route,
+                "worker" => Segment::Worker,
+                _ => match field.strip_prefix("header.") {
+                    Some(name) if !name.is_empty() => {
+                        let name = name.to_lowercase();
+                        let index = match header_names.iter().position(|known| *known == name) {
+                            Some(index) => index,
+                            None => {
+                                header_names.push(name);
+                                header_names.len() - 1
+                            }
+                        };
+                        Segment::Header(index)
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Invalid access log format, unknown field `{}`",
+                            field
+                        ))
+                    }
+                },
+            };
+
+            segments.push(segment);
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Self {
+            segments,
+            header_names,
+            level: config.level,
+        })
+    }
+
+    fn logged_headers(&self, headers: &HeaderMap) -> Vec<Option<String>> {
+        self.header_names
+            .iter()
+            .map(|name| {
+                headers
+                    .get(name.as_str())
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string())
+            })
+            .collect()
+    }
+
+    fn render(&self, entry: &AccessLogEntry) -> String {
+        let mut line = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => line.push_str(text),
+                Segment::Method => line.push_str(entry.method.as_str()),
+                Segment::Path => line.push_str(&entry.path),
+                Segment::Status => line.push_str(&entry.status.to_string()),
+                Segment::LatencyMs => line.push_str(&entry.latency.as_millis().to_string()),
+                Segment::Route => line.push_str(
+                    entry
+                        .matched_route
+                        .as_ref()
+                        .map_or("-", |matched| matched.route.as_str()),
+                ),
+                Segment::Worker => line.push_str(
+                    entry
+                        .matched_route
+                        .as_ref()
+                        .map_or("-", |matched| matched.worker_name.as_str()),
+                ),
+                Segment::Header(index) => {
+                    line.push_str(entry.headers[*index].as_deref().unwrap_or("-"))
+                }
+            }
+        }
+
+        line
+    }
+
+    fn log(&self, entry: &AccessLogEntry) {
+        let line = self.render(entry);
+
+        match self.level {
+            AccessLogLevel::Trace => trace!("{}", line),
+            AccessLogLevel::Debug => debug!("{}", line),
+            AccessLogLevel::Info => info!("{}", line),
+            AccessLogLevel::Warn => warn!("{}", line),
+            AccessLogLevel::Error => error!("{}", line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app_config::{AccessLogConfig, AccessLogLevel};
+    use crate::http::access_log::{AccessLog, MatchedRoute};
+    use http::StatusCode;
+    use poem::test::TestClient;
+    use poem::web::Path;
+    use poem::{handler, Endpoint, EndpointExt, IntoResponse, Response, Route};
+    use tracing_test::traced_test;
+
+    #[handler]
+    fn get_user(Path(id): Path<String>) -> Response {
+        let mut response = StatusCode::CREATED.into_response();
+        response.extensions_mut().insert(MatchedRoute {
+            route: "GET /users/{id}".to_string(),
+            worker_name: format!("user-{}", id),
+        });
+        response
+    }
+
+    fn make_route(format: &str) -> impl Endpoint {
+        let access_log = AccessLog::new(Some(&AccessLogConfig {
+            format: format.to_string(),
+            level: AccessLogLevel::Info,
+        }))
+        .unwrap();
+
+        Route::new()
+            .at("/users/:id", poem::get(get_user))
+            .with(access_log)
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn access_log_line_has_the_templated_fields() {
+        let client = TestClient::new(make_route(
+            "{method} {path} {status} {latency_ms}ms route={route} worker={worker} agent={header.User-Agent} trace={header.x-trace}",
+        ));
+
+        let response = client
+            .get("/users/1")
+            .header("user-agent", "curl/8.0")
+            .send()
+            .await;
+        response.assert_status(StatusCode::CREATED);
+
+        assert!(logs_contain("GET /users/1 201 "));
+        assert!(logs_contain(
+            "ms route=GET /users/{id} worker=user-1 agent=curl/8.0 trace=-"
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn unmatched_request_is_logged_without_route() {
+        let client = TestClient::new(make_route("{method} {path} {status} route={route}"));
+
+        let response = client.get("/orders/1").send().await;
+        response.assert_status(StatusCode::NOT_FOUND);
+
+        assert!(logs_contain("GET /orders/1 404 route=-"));
+    }
+
+    #[test]
+    fn unknown_field_is_rejected() {
+        let result = AccessLog::new(Some(&AccessLogConfig {
+            format: "{method} {size}".to_string(),
+            level: AccessLogLevel::Info,
+        }));
+
+        assert_eq!(
+            result.err(),
+            Some("Invalid access log format, unknown field `size`".to_string())
+        );
+    }
+}
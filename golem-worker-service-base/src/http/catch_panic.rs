@@ -0,0 +1,155 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::metrics;
+use futures::FutureExt;
+use http::StatusCode;
+use poem::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use tracing::{error, info_span, Instrument};
+use uuid::Uuid;
+
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+// Turns a panic of the endpoint (Ex: a bug in the evaluation of a route) into a 500 response,
+// instead of dropping the connection. The endpoint runs in a span with a correlation id,
+// also sent back in the response, so the panic (logged with the backtrace by the panic hook
+// of the tracing setup) can be found from the response the client got.
+pub struct CatchPanic {
+    server: &'static str,
+}
+
+impl CatchPanic {
+    // The name of the server is the label of the panics in the metrics
+    pub fn new(server: &'static str) -> Self {
+        Self { server }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for CatchPanic {
+    type Output = CatchPanicEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CatchPanicEndpoint {
+            inner: ep,
+            server: self.server,
+        }
+    }
+}
+
+pub struct CatchPanicEndpoint<E> {
+    inner: E,
+    server: &'static str,
+}
+
+impl<E: Endpoint> Endpoint for CatchPanicEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let correlation_id = Uuid::new_v4();
+        let span = info_span!("handler", correlation_id = %correlation_id);
+
+        match AssertUnwindSafe(self.inner.call(req).instrument(span))
+            .catch_unwind()
+            .await
+        {
+            Ok(result) => result.map(IntoResponse::into_response),
+            Err(panic) => {
+                error!(
+                    correlation_id = %correlation_id,
+                    "Request handler panicked: {}",
+                    panic_message(panic.as_ref())
+                );
+                metrics::record_handler_panic(self.server);
+                Ok(internal_server_error(correlation_id))
+            }
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "unknown panic"
+    }
+}
+
+fn internal_server_error(correlation_id: Uuid) -> Response {
+    Response::builder()
+        .status(StatusCode::INTERNAL_SERVER_ERROR)
+        .header(CORRELATION_ID_HEADER, correlation_id.to_string())
+        .body(Body::from_string(format!(
+            "Internal server error, correlation id: {}",
+            correlation_id
+        )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::catch_panic::{CatchPanic, CORRELATION_ID_HEADER};
+    use crate::metrics;
+    use http::StatusCode;
+    use poem::test::TestClient;
+    use poem::{handler, EndpointExt, Route};
+    use tracing_test::traced_test;
+
+    #[handler]
+    fn panicking() -> String {
+        panic!("boom")
+    }
+
+    #[handler]
+    fn hello() -> String {
+        "hello".to_string()
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn panic_is_turned_into_internal_server_error() {
+        let route = Route::new()
+            .at("/panic", poem::get(panicking))
+            .at("/hello", poem::get(hello))
+            .with(CatchPanic::new("catch-panic-test"));
+        let client = TestClient::new(route);
+
+        let response = client.get("/panic").send().await;
+        response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let correlation_id = response
+            .0
+            .headers()
+            .get(CORRELATION_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        response
+            .assert_text(format!(
+                "Internal server error, correlation id: {}",
+                correlation_id
+            ))
+            .await;
+
+        assert!(logs_contain("Request handler panicked: boom"));
+        assert!(logs_contain(&correlation_id));
+        assert_eq!(metrics::handler_panics("catch-panic-test"), 1);
+
+        let response = client.get("/hello").send().await;
+        response.assert_status_is_ok();
+        response.assert_text("hello").await;
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::app_config::ConcurrencyLimitConfig;
+use crate::metrics;
+use http::header::RETRY_AFTER;
+use http::StatusCode;
+use poem::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+// Caps the number of requests handled at the same time by the custom request server.
+// Requests over the cap are rejected right away with 503 instead of being queued,
+// so a burst of traffic can't pile up on the workers (or in memory).
+pub struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    config: Arc<ConcurrencyLimitConfig>,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(config: ConcurrencyLimitConfig) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(config.max_in_flight)),
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ConcurrencyLimit {
+    type Output = ConcurrencyLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ConcurrencyLimitEndpoint {
+            inner: ep,
+            semaphore: self.semaphore.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+pub struct ConcurrencyLimitEndpoint<E> {
+    inner: E,
+    semaphore: Arc<Semaphore>,
+    config: Arc<ConcurrencyLimitConfig>,
+}
+
+impl<E: Endpoint> Endpoint for ConcurrencyLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let _permit = match self.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                warn!(
+                    "Rejecting request to {}, the limit of {} concurrent requests is reached",
+                    req.uri().path(),
+                    self.config.max_in_flight
+                );
+                metrics::record_gateway_rejected_request();
+                return Ok(service_unavailable(self.config.retry_after));
+            }
+        };
+
+        // Decremented on drop, so a cancelled request is not counted forever
+        let _in_flight = InFlightGuard::new();
+
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+struct InFlightGuard;
+
+impl InFlightGuard {
+    fn new() -> Self {
+        metrics::inc_gateway_in_flight_requests();
+        InFlightGuard
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        metrics::dec_gateway_in_flight_requests();
+    }
+}
+
+fn service_unavailable(retry_after: Duration) -> Response {
+    // Retry-After is in whole seconds, a sub-second delay is rounded up
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header(RETRY_AFTER, seconds.to_string())
+        .body(Body::from_string(
+            "Too many concurrent requests".to_string(),
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app_config::ConcurrencyLimitConfig;
+    use crate::http::concurrency_limit::ConcurrencyLimit;
+    use crate::metrics;
+    use http::StatusCode;
+    use poem::test::TestClient;
+    use poem::web::Data;
+    use poem::{handler, Endpoint, EndpointExt, Route};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Semaphore;
+
+    // Keeps the requests to `/wait` (and their slots) busy until the test opens it
+    #[derive(Default)]
+    struct Gate {
+        entered: AtomicUsize,
+        open: Semaphore,
+    }
+
+    #[handler]
+    async fn wait(gate: Data<&Arc<Gate>>) -> &'static str {
+        gate.entered.fetch_add(1, Ordering::SeqCst);
+        let _ = gate.open.acquire().await.unwrap();
+        "done"
+    }
+
+    #[handler]
+    fn now() -> &'static str {
+        "now"
+    }
+
+    fn make_route(gate: Arc<Gate>) -> impl Endpoint {
+        Route::new()
+            .at("/wait", poem::get(wait))
+            .at("/now", poem::get(now))
+            .data(gate)
+            .with(ConcurrencyLimit::new(ConcurrencyLimitConfig {
+                max_in_flight: 2,
+                retry_after: Duration::from_millis(1500),
+            }))
+    }
+
+    #[tokio::test]
+    async fn requests_under_the_limit_are_accepted() {
+        let client = TestClient::new(make_route(Arc::new(Gate::default())));
+
+        for _ in 0..5 {
+            let response = client.get("/now").send().await;
+            response.assert_status_is_ok();
+            response.assert_text("now").await;
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_over_the_limit_are_rejected_until_slots_free_up() {
+        let gate = Arc::new(Gate::default());
+        let client = TestClient::new(make_route(gate.clone()));
+        let rejected_before = metrics::gateway_rejected_requests();
+
+        let pending = async {
+            let (first, second) =
+                tokio::join!(client.get("/wait").send(), client.get("/wait").send());
+            first.assert_status_is_ok();
+            second.assert_status_is_ok();
+        };
+
+        let saturated = async {
+            while gate.entered.load(Ordering::SeqCst) < 2 {
+                tokio::task::yield_now().await;
+            }
+
+            for _ in 0..3 {
+                let response = client.get("/now").send().await;
+                response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+                response.assert_header("retry-after", "2");
+            }
+
+            gate.open.add_permits(2);
+        };
+
+        tokio::join!(pending, saturated);
+
+        assert!(metrics::gateway_rejected_requests() >= rejected_before + 3);
+
+        let response = client.get("/now").send().await;
+        response.assert_status_is_ok();
+        response.assert_text("now").await;
+    }
+}
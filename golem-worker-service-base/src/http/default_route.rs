@@ -0,0 +1,116 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashMap;
+
+use http::{HeaderName, HeaderValue, StatusCode};
+use rib::{RibByteCode, RibInputTypeInfo, RibInterpreterResult};
+
+use crate::api_definition::http::QueryInfo;
+use crate::app_config::DefaultRouteConfig;
+use crate::http::InputHttpRequest;
+use crate::worker_binding::{HttpRequestDetails, RequestDetails, RibInputValueResolver};
+use crate::worker_bridge_execution::to_response::ToResponse;
+
+// The compiled `DefaultRouteConfig`, responding to the requests that don't match any route
+#[derive(Debug, Clone)]
+pub struct DefaultRoute {
+    status: StatusCode,
+    body: RibByteCode,
+    body_input: RibInputTypeInfo,
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl DefaultRoute {
+    pub fn from_config(config: &DefaultRouteConfig) -> Result<DefaultRoute, String> {
+        let status = StatusCode::from_u16(config.status)
+            .map_err(|err| format!("Invalid status of the default route: {}", err))?;
+
+        let expr = rib::from_string(config.body.as_str())
+            .map_err(|err| format!("Invalid body of the default route: {}", err))?;
+
+        let compiled =
+            rib::compile_with_limited_globals(&expr, &vec![], Some(vec!["request".to_string()]))
+                .map_err(|err| {
+                    format!("Failed to compile the body of the default route: {}", err)
+                })?;
+
+        let headers = config
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let name = HeaderName::try_from(name.as_str())
+                    .map_err(|err| format!("Invalid header of the default route: {}", err))?;
+                let value = HeaderValue::try_from(value.as_str())
+                    .map_err(|err| format!("Invalid header of the default route: {}", err))?;
+                Ok((name, value))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(DefaultRoute {
+            status,
+            body: compiled.byte_code,
+            body_input: compiled.global_input_type_info,
+            headers,
+        })
+    }
+
+    // The body is evaluated with the headers and the query parameters of the request.
+    // The configured status is used unless the body rejects the request with `reject`
+    pub async fn response(&self, request: &InputHttpRequest) -> poem::Response {
+        let query_values = request.input_path.query_values().unwrap_or_default();
+        let query_infos = query_values
+            .keys()
+            .map(|key| QueryInfo {
+                key_name: key.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let request_details = match RequestDetails::from(
+            &HashMap::new(),
+            &query_values,
+            &query_infos,
+            &request.req_body,
+            &request.headers,
+        ) {
+            Ok(request_details) => request_details,
+            Err(err) => {
+                return err
+                    .join(", ")
+                    .to_response(&RequestDetails::Http(HttpRequestDetails::empty()))
+            }
+        };
+
+        let rib_input = match request_details.resolve_rib_input_value(&self.body_input) {
+            Ok(rib_input) => rib_input,
+            Err(err) => return err.to_response(&request_details),
+        };
+
+        let result = match rib::interpret_pure(&self.body, &rib_input.value).await {
+            Ok(result) => result,
+            Err(err) => return err.to_response(&request_details),
+        };
+
+        let mut response = result.to_response(&request_details);
+
+        if !matches!(result, RibInterpreterResult::Reject { .. }) {
+            response.set_status(self.status);
+        }
+
+        for (name, value) in &self.headers {
+            response.headers_mut().insert(name.clone(), value.clone());
+        }
+
+        response
+    }
+}
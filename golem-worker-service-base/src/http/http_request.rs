@@ -10,9 +10,32 @@ pub struct InputHttpRequest {
     pub headers: HeaderMap,
     pub req_method: Method,
     pub req_body: Value,
+    // The body as it was sent, if the route reads it (`request.rawBody`)
+    pub req_raw_body: Option<String>,
+    pub variables: RequestVariables,
 }
 
 impl InputHttpRequest {
+    // The request without its body. The body is read separately, and only if the
+    // matched route refers to it
+    pub fn from_request(request: &poem::Request) -> InputHttpRequest {
+        InputHttpRequest {
+            input_path: ApiInputPath {
+                base_path: request.uri().path().to_string(),
+                query_path: request.uri().query().map(|x| x.to_string()),
+            },
+            headers: request.headers().clone(),
+            req_method: request.method().clone(),
+            req_body: Value::Null,
+            req_raw_body: None,
+            variables: request
+                .extensions()
+                .get::<RequestVariables>()
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
     pub fn get_host(&self) -> Option<ApiSiteString> {
         self.headers
             .get("host")
@@ -21,6 +44,12 @@ impl InputHttpRequest {
     }
 }
 
+// Values computed before the request is resolved (Ex: the subject resolved by an auth middleware),
+// which the Rib scripts can read (but not change) under the `vars` root (Ex: `${vars.subject}`).
+// A middleware provides them by adding them to the extensions of the request
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RequestVariables(pub HashMap<String, Value>);
+
 #[derive(Clone)]
 pub struct ApiInputPath {
     pub base_path: String,
@@ -28,33 +57,47 @@ pub struct ApiInputPath {
 }
 
 impl ApiInputPath {
-    // Return the value of each query variable in a HashMap
+    // Return the value of each query variable in a HashMap.
+    // If a key is repeated, the last value is returned
     pub fn query_components(&self) -> Option<HashMap<String, String>> {
-        if let Some(query_path) = self.query_path.clone() {
-            let mut query_components: HashMap<String, String> = HashMap::new();
-            let query_parts = query_path.split('&').map(|x| x.trim());
-
-            for part in query_parts {
-                let key_value: Vec<&str> = part.split('=').map(|x| x.trim()).collect();
+        self.query_values().map(|query_values| {
+            query_values
+                .into_iter()
+                .filter_map(|(key, values)| values.last().map(|value| (key, value.clone())))
+                .collect()
+        })
+    }
 
-                if let (Some(key), Some(value)) = (key_value.first(), key_value.get(1)) {
-                    query_components.insert(key.to_string(), value.to_string());
+    // Return all the values of each query variable, in the order they appear in the query.
+    // Keys and values are percent-decoded (and `+` is read as a space). A key without a value
+    // (Ex: `?flag`) has an empty value, just like `?flag=`
+    pub fn query_values(&self) -> Option<HashMap<String, Vec<String>>> {
+        self.query_path.as_ref().map(|query_path| {
+            let mut query_values: HashMap<String, Vec<String>> = HashMap::new();
+
+            for (key, value) in url::form_urlencoded::parse(query_path.as_bytes()) {
+                if !key.is_empty() {
+                    query_values
+                        .entry(key.into_owned())
+                        .or_default()
+                        .push(value.into_owned());
                 }
             }
-            Some(query_components)
-        } else {
-            None
-        }
+
+            query_values
+        })
     }
 }
 
 pub mod router {
-    use crate::api_definition::http::CompiledRoute;
+    use crate::api_definition::http::{CompiledHttpApiDefinition, CompiledRoute};
     use crate::worker_binding::CompiledGolemWorkerBinding;
     use crate::{
         api_definition::http::{PathPattern, QueryInfo, VarInfo},
         http::router::{Router, RouterPattern},
     };
+    use hyper::http::Method;
+    use std::fmt::Display;
 
     #[derive(Debug, Clone)]
     pub struct RouteEntry {
@@ -62,17 +105,42 @@ pub mod router {
         pub path_params: Vec<(VarInfo, usize)>,
         pub query_params: Vec<QueryInfo>,
         pub binding: CompiledGolemWorkerBinding,
+        // The method and the path pattern of the route (Ex: `GET /users/{id}`), to identify it
+        pub route: String,
+        // The API definition of the route (Ex: `users-api@0.0.1`), if built from the definitions
+        pub api_definition: Option<String>,
     }
 
-    pub fn build(routes: Vec<CompiledRoute>) -> Router<RouteEntry> {
-        let mut router = Router::new();
+    // Two routes with the same method and the same path (up to the names of the path variables)
+    // are a conflict, as it's undefined which of them would be used. Overlapping routes are not,
+    // as the literal segments take precedence over the variables (Ex: `/users/me` over `/users/{id}`)
+    pub fn build(routes: Vec<CompiledRoute>) -> Result<Router<RouteEntry>, Vec<RouteConflict>> {
+        build_entries(routes.into_iter().map(|route| (None, route)))
+    }
 
-        for route in routes {
-            let method = route.method.into();
-            let path = route.path;
-            let binding = route.binding;
+    // Like `build`, with the routes of the definitions, each entry knowing its definition
+    pub fn build_for_definitions(
+        definitions: &[CompiledHttpApiDefinition],
+    ) -> Result<Router<RouteEntry>, Vec<RouteConflict>> {
+        build_entries(definitions.iter().flat_map(|definition| {
+            let api_definition = format!("{}@{}", definition.id, definition.version);
+            definition
+                .routes
+                .iter()
+                .map(move |route| (Some(api_definition.clone()), route.clone()))
+        }))
+    }
+
+    fn build_entries(
+        routes: impl Iterator<Item = (Option<String>, CompiledRoute)>,
+    ) -> Result<Router<RouteEntry>, Vec<RouteConflict>> {
+        let mut router = Router::new();
+        let mut added: Vec<(Method, Vec<RouterPattern>, String)> = vec![];
+        let mut conflicts = vec![];
 
-            let path_params = path
+        for (api_definition, route) in routes {
+            let path_params: Vec<(VarInfo, usize)> = route
+                .path
                 .path_patterns
                 .iter()
                 .enumerate()
@@ -82,22 +150,180 @@ pub mod router {
                 })
                 .collect();
 
-            let entry = RouteEntry {
-                path_params,
-                query_params: path.query_params,
-                binding,
-            };
+            let path_string = route.path.to_string();
 
-            let path: Vec<RouterPattern> = path
+            let path: Vec<RouterPattern> = route
+                .path
                 .path_patterns
                 .iter()
                 .map(|x| x.clone().into())
                 .collect();
 
-            router.add_route(method, path, entry);
+            // A route with more methods is matched with each of them
+            for method in route.all_methods() {
+                let method: Method = method.into();
+
+                let entry = RouteEntry {
+                    path_params: path_params.clone(),
+                    query_params: route.path.query_params.clone(),
+                    binding: route.binding.clone(),
+                    route: format!("{} {}", method, path_string),
+                    api_definition: api_definition.clone(),
+                };
+
+                if router.add_route(method.clone(), path.clone(), entry) {
+                    added.push((method, path.clone(), path_string.clone()));
+                } else {
+                    let existing_path = added
+                        .iter()
+                        .find(|(added_method, added_path, _)| {
+                            *added_method == method && *added_path == path
+                        })
+                        .map(|(_, _, path)| path.clone())
+                        .unwrap_or_default();
+
+                    conflicts.push(RouteConflict {
+                        method,
+                        path: path_string.clone(),
+                        existing_path,
+                    });
+                }
+            }
         }
 
-        router
+        if conflicts.is_empty() {
+            Ok(router)
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    // Why a request resolves (or doesn't resolve) to each of the routes
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RouteMatchReport {
+        pub matched_route: Option<String>,
+        pub rejected_routes: Vec<RouteRejection>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RouteRejection {
+        pub route: String,
+        pub reason: String,
+    }
+
+    // Matches the request against every route, in the order the routes are given.
+    // The matched route is the one the router resolves the request to, and every other route
+    // is reported with the first reason it doesn't match
+    pub fn explain(
+        routes: Vec<CompiledRoute>,
+        method: &Method,
+        path: &str,
+    ) -> Result<RouteMatchReport, Vec<RouteConflict>> {
+        let candidates = routes
+            .iter()
+            .flat_map(|route| {
+                let patterns: Vec<RouterPattern> = route
+                    .path
+                    .path_patterns
+                    .iter()
+                    .map(|x| x.clone().into())
+                    .collect();
+
+                route.all_methods().into_iter().map(move |method| {
+                    let route_method: Method = method.into();
+                    let name = format!("{} {}", route_method, route.path);
+
+                    (route_method, patterns.clone(), name)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let router = build(routes)?;
+        let segments: Vec<&str> = RouterPattern::split(path).collect();
+
+        let matched_route = router
+            .check_path(method, &segments)
+            .map(|entry| entry.route.clone());
+
+        let rejected_routes = candidates
+            .into_iter()
+            .filter(|(_, _, name)| matched_route.as_ref() != Some(name))
+            .map(|(route_method, patterns, name)| {
+                let reason = rejection_reason(&route_method, &patterns, method, &segments)
+                    .unwrap_or_else(|| match &matched_route {
+                        Some(matched) => format!("route {} takes precedence", matched),
+                        None => "route is not reachable".to_string(),
+                    });
+
+                RouteRejection {
+                    route: name,
+                    reason,
+                }
+            })
+            .collect();
+
+        Ok(RouteMatchReport {
+            matched_route,
+            rejected_routes,
+        })
+    }
+
+    fn rejection_reason(
+        route_method: &Method,
+        patterns: &[RouterPattern],
+        method: &Method,
+        segments: &[&str],
+    ) -> Option<String> {
+        if route_method != method {
+            return Some(format!("method {} does not match {}", method, route_method));
+        }
+
+        let has_catch_all = matches!(patterns.last(), Some(RouterPattern::CatchAll));
+
+        let segment_count_matches = if has_catch_all {
+            segments.len() >= patterns.len() - 1
+        } else {
+            segments.len() == patterns.len()
+        };
+
+        if !segment_count_matches {
+            return Some(format!(
+                "expected {} path segments, but found {}",
+                patterns.len(),
+                segments.len()
+            ));
+        }
+
+        patterns
+            .iter()
+            .zip(segments)
+            .enumerate()
+            .find_map(|(index, (pattern, segment))| match pattern {
+                RouterPattern::Literal(literal) if literal.0 != *segment => Some(format!(
+                    "path segment {} `{}` does not match `{}`",
+                    index + 1,
+                    segment,
+                    literal.0
+                )),
+                _ => None,
+            })
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RouteConflict {
+        pub method: Method,
+        pub path: String,
+        pub existing_path: String,
+    }
+
+    impl Display for RouteConflict {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "Route {} {} conflicts with the route {} {}",
+                self.method, self.path, self.method, self.existing_path
+            )
+        }
     }
 }
 
@@ -106,11 +332,12 @@ mod tests {
     use crate::api_definition::http::{
         CompiledHttpApiDefinition, ComponentMetadataDictionary, HttpApiDefinition,
     };
-    use crate::getter::Getter;
-    use crate::http::http_request::{ApiInputPath, InputHttpRequest};
+    use crate::api_definition::ApiSiteString;
+    use crate::getter::{Getter, GetterExt};
+    use crate::http::http_request::{router, ApiInputPath, InputHttpRequest, RequestVariables};
     use crate::path::Path;
     use crate::worker_binding::{
-        RequestDetails, RequestToWorkerBindingResolver, RibInputTypeMismatch,
+        RequestDetails, RequestToWorkerBindingResolver, RibInputTypeMismatch, RibResultTooLarge,
     };
     use crate::worker_bridge_execution::to_response::ToResponse;
     use crate::worker_bridge_execution::{
@@ -259,6 +486,7 @@ mod tests {
         worker_name: String,
         function_name: String,
         function_params: Value,
+        idempotency_key: Option<String>,
     }
 
     impl ToResponse<TestResponse> for EvaluationError {
@@ -267,6 +495,12 @@ mod tests {
         }
     }
 
+    impl ToResponse<TestResponse> for RibResultTooLarge {
+        fn to_response(&self, _request_details: &RequestDetails) -> TestResponse {
+            panic!("{}", self.to_string())
+        }
+    }
+
     impl ToResponse<TestResponse> for RibInputTypeMismatch {
         fn to_response(&self, _request_details: &RequestDetails) -> TestResponse {
             panic!("{}", self.to_string())
@@ -299,10 +533,18 @@ mod tests {
                 .unwrap()
                 .as_string();
 
+            let idempotency_key = self
+                .get_val()
+                .and_then(|x| x.get_optional(&Path::from_key("idempotency_key")))
+                .and_then(|x| x.get_optional(&Path::from_key("idempotency-key")))
+                .and_then(|x| x.get_literal())
+                .map(|x| x.as_string());
+
             TestResponse {
                 worker_name,
                 function_name,
                 function_params,
+                idempotency_key,
             }
         }
     }
@@ -364,7 +606,9 @@ mod tests {
             .await
             .unwrap();
 
-        resolved_route.interpret_response_mapping(&evaluator).await
+        resolved_route
+            .interpret_response_mapping(&evaluator, None)
+            .await
     }
 
     #[tokio::test]
@@ -504,6 +748,92 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[tokio::test]
+    async fn test_worker_request_resolution_with_repeated_query_params() {
+        async fn params(query: Option<&str>, expression: &str) -> Value {
+            let api_request = get_api_request("foo/1", query, &HeaderMap::new(), Value::Null);
+
+            let api_specification: HttpApiDefinition = get_api_spec(
+                "foo/{user-id}?{tag}",
+                "${let x: u64 = request.path.user-id; \"shopping-cart-${x}\"}",
+                expression,
+            );
+
+            execute(&api_request, &api_specification)
+                .await
+                .function_params
+        }
+
+        let as_list = r#"
+            let tags: list<str> = request.path.tag;
+            let count: u64 = size(tags);
+            let response = golem:it/api.{get-cart-contents}(tags[0], "${count}");
+            response
+        "#;
+
+        let as_single_value = r#"
+            let response = golem:it/api.{get-cart-contents}(request.path.tag, "single");
+            response
+        "#;
+
+        // A repeated key is a list of the values in order
+        assert_eq!(
+            params(Some("tag=a&tag=b"), as_list).await,
+            Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("2".to_string()),
+            ])
+        );
+
+        // A key that occurs once is a list of one element when read as a list
+        assert_eq!(
+            params(Some("tag=a"), as_list).await,
+            Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("1".to_string()),
+            ])
+        );
+
+        // A repeated key read as a single value is its last value
+        assert_eq!(
+            params(Some("tag=a&tag=b"), as_single_value).await,
+            Value::Array(vec![
+                Value::String("b".to_string()),
+                Value::String("single".to_string()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_worker_request_resolution_with_absent_query_param() {
+        let api_request = get_api_request("foo/1", None, &HeaderMap::new(), Value::Null);
+
+        let expression = r#"
+            let tags: list<str> = request.path.tag;
+            let response = golem:it/api.{get-cart-contents}(tags[0], tags[0]);
+            response
+        "#;
+
+        let api_specification: HttpApiDefinition = get_api_spec(
+            "foo/{user-id}?{tag}",
+            "${let x: u64 = request.path.user-id; \"shopping-cart-${x}\"}",
+            expression,
+        );
+
+        let compiled_api_spec = CompiledHttpApiDefinition::from_http_api_definition(
+            &api_specification,
+            &get_metadata(),
+        )
+        .unwrap();
+
+        let result = api_request
+            .resolve_worker_binding(vec![compiled_api_spec])
+            .await
+            .map(|x| x.worker_detail);
+
+        assert!(result.unwrap_err().0.contains("tag"));
+    }
+
     #[tokio::test]
     async fn test_worker_request_cond_expr_resolution() {
         let empty_headers = HeaderMap::new();
@@ -924,6 +1254,364 @@ mod tests {
         test_key(&headers, Some(IdempotencyKey::new("bar".to_string()))).await;
     }
 
+    #[tokio::test]
+    async fn test_worker_idempotency_key_expression() {
+        async fn invoke(path: &str, user: &str, idempotency_key: &str) -> TestResponse {
+            let mut headers = HeaderMap::new();
+            headers.insert("user", HeaderValue::from_str(user).unwrap());
+            // The computed key takes precedence over the header
+            headers.insert("idempotency-key", HeaderValue::from_str("ignored").unwrap());
+            let api_request = get_api_request(path, None, &headers, Value::Null);
+
+            let expression = r#"
+            let response = golem:it/api.{get-cart-contents}("foo", "bar");
+            response
+            "#;
+
+            let mut api_specification: HttpApiDefinition = get_api_spec(
+                "getcartcontent/{cart-id}",
+                "${let x: u64 = request.path.cart-id; \"shopping-cart-${x}\"}",
+                expression,
+            );
+            api_specification.routes[0].binding.idempotency_key =
+                Some(rib::from_string(idempotency_key).unwrap());
+
+            execute(&api_request, &api_specification).await
+        }
+
+        let key = r#"${let user: str = request.headers.user; let x: u64 = request.path.cart-id; "${user}-cart-${x}"}"#;
+
+        let first = invoke("/getcartcontent/1", "alice", key).await;
+        let retried = invoke("/getcartcontent/1", "alice", key).await;
+        let other = invoke("/getcartcontent/2", "alice", key).await;
+
+        assert_eq!(first.idempotency_key, Some("alice-cart-1".to_string()));
+        assert_eq!(first.idempotency_key, retried.idempotency_key);
+        assert_eq!(other.idempotency_key, Some("alice-cart-2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_worker_idempotency_key_expression_not_string() {
+        let api_request =
+            get_api_request("/getcartcontent/1", None, &HeaderMap::new(), Value::Null);
+
+        let expression = r#"
+            let response = golem:it/api.{get-cart-contents}("foo", "bar");
+            response
+            "#;
+
+        let mut api_specification: HttpApiDefinition = get_api_spec(
+            "getcartcontent/{cart-id}",
+            "${let x: u64 = request.path.cart-id; \"shopping-cart-${x}\"}",
+            expression,
+        );
+        api_specification.routes[0].binding.idempotency_key =
+            Some(rib::from_string("${let x: u64 = request.path.cart-id; x}").unwrap());
+
+        let compiled_api_spec = CompiledHttpApiDefinition::from_http_api_definition(
+            &api_specification,
+            &get_metadata(),
+        )
+        .unwrap();
+
+        let result = api_request
+            .resolve_worker_binding(vec![compiled_api_spec])
+            .await
+            .map(|x| x.worker_detail);
+
+        assert_eq!(
+            result.unwrap_err().0,
+            "Idempotency key is not a Rib expression that resolves to String"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_worker_request_resolution_with_cookies() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "cookie",
+            HeaderValue::from_str(r#"theme=dark; session="a%2Fb""#).unwrap(),
+        );
+        let api_request = get_api_request("foo/1", None, &headers, Value::Null);
+
+        let expression = r#"
+          let response = golem:it/api.{get-cart-contents}("foo", "bar");
+          response
+        "#;
+
+        let api_specification: HttpApiDefinition = get_api_spec(
+            "foo/{user-id}",
+            "${let session: str = request.cookies.session; \"shopping-cart-${session}\"}",
+            expression,
+        );
+
+        let test_response = execute(&api_request, &api_specification).await;
+
+        assert_eq!(test_response.worker_name, "shopping-cart-a/b");
+    }
+
+    #[tokio::test]
+    async fn test_worker_request_resolution_with_variables() {
+        let mut api_request = get_api_request("foo/1", None, &HeaderMap::new(), Value::Null);
+        api_request.variables = RequestVariables(HashMap::from([(
+            "subject".to_string(),
+            Value::String("alice".to_string()),
+        )]));
+
+        let expression = r#"
+          let subject: str = vars.subject;
+          let response = golem:it/api.{get-cart-contents}(subject, "bar");
+          response
+        "#;
+
+        let api_specification: HttpApiDefinition = get_api_spec(
+            "foo/{user-id}",
+            "${let subject: str = vars.subject; \"shopping-cart-${subject}\"}",
+            expression,
+        );
+
+        let test_response = execute(&api_request, &api_specification).await;
+
+        assert_eq!(test_response.worker_name, "shopping-cart-alice");
+        assert_eq!(
+            test_response.function_params,
+            Value::Array(vec![
+                Value::String("alice".to_string()),
+                Value::String("bar".to_string()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_worker_request_resolution_with_missing_variable() {
+        let mut api_request = get_api_request("foo/1", None, &HeaderMap::new(), Value::Null);
+        api_request.variables = RequestVariables(HashMap::from([(
+            "tenant".to_string(),
+            Value::String("acme".to_string()),
+        )]));
+
+        let expression = r#"
+          let response = golem:it/api.{get-cart-contents}("foo", "bar");
+          response
+        "#;
+
+        let api_specification: HttpApiDefinition = get_api_spec(
+            "foo/{user-id}",
+            "${let subject: str = vars.subject; \"shopping-cart-${subject}\"}",
+            expression,
+        );
+
+        let compiled_api_spec = CompiledHttpApiDefinition::from_http_api_definition(
+            &api_specification,
+            &get_metadata(),
+        )
+        .unwrap();
+
+        let result = api_request
+            .resolve_worker_binding(vec![compiled_api_spec])
+            .await
+            .map(|x| x.worker_detail);
+
+        assert!(result
+            .unwrap_err()
+            .0
+            .starts_with("Failed to resolve rib input value of variables"));
+    }
+
+    #[tokio::test]
+    async fn test_worker_request_resolution_with_conflicting_routes() {
+        let empty_headers = HeaderMap::new();
+        let api_request = get_api_request("/getcartcontent/1", None, &empty_headers, Value::Null);
+
+        let expression = r#"
+            let response = golem:it/api.{get-cart-contents}("foo", "bar");
+            response
+            "#;
+
+        // The same path, with differently named variables
+        let definitions = ["getcartcontent/{cart-id}", "getcartcontent/{id}"]
+            .into_iter()
+            .map(|path| {
+                let api_specification = get_api_spec(path, "\"shopping-cart\"", expression);
+                CompiledHttpApiDefinition::from_http_api_definition(
+                    &api_specification,
+                    &get_metadata(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let result = api_request.resolve_worker_binding(definitions).await;
+
+        let error = result.map(|_| ()).unwrap_err();
+        assert_eq!(
+            error.0,
+            "Conflicting routes: Route GET /getcartcontent/{id} conflicts with the route GET /getcartcontent/{cart-id}"
+        );
+    }
+
+    #[test]
+    fn test_route_match_report() {
+        let expression = r#"
+            let response = golem:it/api.{get-cart-contents}("foo", "bar");
+            response
+            "#;
+
+        let routes = ["users/{id}", "users/me", "users/{id}/carts", "carts/{id}"]
+            .into_iter()
+            .flat_map(|path| {
+                let api_specification = get_api_spec(path, "\"shopping-cart\"", expression);
+                CompiledHttpApiDefinition::from_http_api_definition(
+                    &api_specification,
+                    &get_metadata(),
+                )
+                .unwrap()
+                .routes
+            })
+            .collect::<Vec<_>>();
+
+        let report = router::explain(routes.clone(), &Method::GET, "/users/1").unwrap();
+
+        let expected_rejections = vec![
+            router::RouteRejection {
+                route: "GET /users/me".to_string(),
+                reason: "path segment 2 `1` does not match `me`".to_string(),
+            },
+            router::RouteRejection {
+                route: "GET /users/{id}/carts".to_string(),
+                reason: "expected 3 path segments, but found 2".to_string(),
+            },
+            router::RouteRejection {
+                route: "GET /carts/{id}".to_string(),
+                reason: "path segment 1 `users` does not match `carts`".to_string(),
+            },
+        ];
+
+        assert_eq!(report.matched_route, Some("GET /users/{id}".to_string()));
+        assert_eq!(report.rejected_routes, expected_rejections);
+
+        let report = router::explain(routes.clone(), &Method::GET, "/users/me").unwrap();
+
+        assert_eq!(report.matched_route, Some("GET /users/me".to_string()));
+        assert_eq!(
+            report.rejected_routes[0],
+            router::RouteRejection {
+                route: "GET /users/{id}".to_string(),
+                reason: "route GET /users/me takes precedence".to_string(),
+            }
+        );
+
+        let report = router::explain(routes, &Method::POST, "/users/1").unwrap();
+
+        assert_eq!(report.matched_route, None);
+        assert_eq!(
+            report.rejected_routes[0],
+            router::RouteRejection {
+                route: "GET /users/{id}".to_string(),
+                reason: "method POST does not match GET".to_string(),
+            }
+        );
+    }
+
+    fn query_path(query: &str) -> ApiInputPath {
+        ApiInputPath {
+            base_path: "/".to_string(),
+            query_path: Some(query.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_query_without_query_string() {
+        let input_path = ApiInputPath {
+            base_path: "/".to_string(),
+            query_path: None,
+        };
+
+        assert_eq!(input_path.query_components(), None);
+        assert_eq!(input_path.query_values(), None);
+    }
+
+    #[test]
+    fn test_query_empty_values() {
+        let query_components = query_path("a=&b&c=1&=2").query_components().unwrap();
+
+        let expected = HashMap::from([
+            ("a".to_string(), "".to_string()),
+            ("b".to_string(), "".to_string()),
+            ("c".to_string(), "1".to_string()),
+        ]);
+
+        assert_eq!(query_components, expected);
+    }
+
+    #[test]
+    fn test_query_url_encoding() {
+        let query_components =
+            query_path("name=John%20Doe&greeting=hello+world&first%2Dname=J%26D")
+                .query_components()
+                .unwrap();
+
+        let expected = HashMap::from([
+            ("name".to_string(), "John Doe".to_string()),
+            ("greeting".to_string(), "hello world".to_string()),
+            ("first-name".to_string(), "J&D".to_string()),
+        ]);
+
+        assert_eq!(query_components, expected);
+    }
+
+    #[test]
+    fn test_query_repeated_keys() {
+        let input_path = query_path("tag=a&id=1&tag=b&tag=c");
+
+        let expected_values = HashMap::from([
+            (
+                "tag".to_string(),
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            ),
+            ("id".to_string(), vec!["1".to_string()]),
+        ]);
+
+        let expected_components = HashMap::from([
+            ("tag".to_string(), "c".to_string()),
+            ("id".to_string(), "1".to_string()),
+        ]);
+
+        assert_eq!(input_path.query_values().unwrap(), expected_values);
+        assert_eq!(input_path.query_components().unwrap(), expected_components);
+    }
+
+    #[test]
+    fn test_input_http_request_from_request() {
+        let request = poem::Request::builder()
+            .method(Method::POST)
+            .uri(http::Uri::from_static("http://localhost/foo/1?tag=a&tag=b"))
+            .header("host", "localhost")
+            .extension(RequestVariables(HashMap::from([(
+                "subject".to_string(),
+                Value::String("alice".to_string()),
+            )])))
+            .body("{}");
+
+        let input_http_request = InputHttpRequest::from_request(&request);
+
+        assert_eq!(input_http_request.input_path.base_path, "/foo/1");
+        assert_eq!(
+            input_http_request.input_path.query_path,
+            Some("tag=a&tag=b".to_string())
+        );
+        assert_eq!(input_http_request.req_method, Method::POST);
+        assert_eq!(input_http_request.req_body, Value::Null);
+        assert_eq!(
+            input_http_request.variables.0.get("subject"),
+            Some(&Value::String("alice".to_string()))
+        );
+        assert_eq!(
+            input_http_request.get_host(),
+            Some(ApiSiteString("localhost".to_string()))
+        );
+    }
+
     fn get_api_request(
         base_path: &str,
         query_path: Option<&str>,
@@ -938,6 +1626,8 @@ mod tests {
             headers: headers.clone(),
             req_method: Method::GET,
             req_body,
+            req_raw_body: None,
+            variables: RequestVariables::default(),
         }
     }
 
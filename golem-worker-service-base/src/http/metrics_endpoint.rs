@@ -0,0 +1,411 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use http::header::{ACCEPT, CONTENT_TYPE};
+use http::StatusCode;
+use poem::{Body, Endpoint, Request, Response, Result};
+use prometheus::{Encoder, Registry, TextEncoder};
+use tracing::error;
+
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+// Serves the metrics of the registry, in the OpenMetrics exposition format
+// if the scraper asks for it in the `Accept` header, and in the classic Prometheus
+// text format otherwise (like `poem::endpoint::PrometheusExporter`)
+pub struct MetricsEndpoint {
+    registry: Registry,
+}
+
+impl MetricsEndpoint {
+    pub fn new(registry: Registry) -> Self {
+        Self { registry }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsFormat {
+    Prometheus,
+    OpenMetrics,
+}
+
+impl MetricsFormat {
+    // Picks the format with the higher quality in the `Accept` header,
+    // preferring OpenMetrics when both are equally acceptable
+    pub fn negotiate(accept: Option<&str>) -> MetricsFormat {
+        let Some(accept) = accept else {
+            return MetricsFormat::Prometheus;
+        };
+
+        let mut open_metrics_quality = 0.0;
+        let mut text_quality = 0.0;
+
+        for media_range in accept.split(',') {
+            let mut parts = media_range.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let quality = parts
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|quality| quality.parse::<f64>().ok())
+                .unwrap_or(1.0);
+
+            match media_type.as_str() {
+                "application/openmetrics-text" => {
+                    open_metrics_quality = f64::max(open_metrics_quality, quality)
+                }
+                "text/plain" | "text/*" | "*/*" => text_quality = f64::max(text_quality, quality),
+                _ => {}
+            }
+        }
+
+        if open_metrics_quality > 0.0 && open_metrics_quality >= text_quality {
+            MetricsFormat::OpenMetrics
+        } else {
+            MetricsFormat::Prometheus
+        }
+    }
+}
+
+impl Endpoint for MetricsEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let accept = req
+            .headers()
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok());
+
+        let families = self.registry.gather();
+
+        let response = match MetricsFormat::negotiate(accept) {
+            MetricsFormat::OpenMetrics => Response::builder()
+                .header(CONTENT_TYPE, OPENMETRICS_CONTENT_TYPE)
+                .body(Body::from_string(internal::encode_open_metrics(&families))),
+            MetricsFormat::Prometheus => {
+                let encoder = TextEncoder::new();
+                let mut buffer = Vec::new();
+
+                match encoder.encode(&families, &mut buffer) {
+                    Ok(()) => Response::builder()
+                        .header(CONTENT_TYPE, encoder.format_type())
+                        .body(Body::from_vec(buffer)),
+                    Err(err) => {
+                        error!("Failed to encode the metrics: {}", err);
+                        Response::builder()
+                            .status(StatusCode::INTERNAL_SERVER_ERROR)
+                            .finish()
+                    }
+                }
+            }
+        };
+
+        Ok(response)
+    }
+}
+
+mod internal {
+    use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+    use std::fmt::Write;
+
+    // Renders the metric families in the OpenMetrics text format. It's the classic format,
+    // except that counter families are named without the `_total` suffix of their samples,
+    // untyped metrics are `unknown`, and the exposition ends with `# EOF`
+    pub(crate) fn encode_open_metrics(families: &[MetricFamily]) -> String {
+        let mut output = String::new();
+
+        for family in families {
+            let sample_name = family.get_name();
+
+            let (family_name, metric_type) = match family.get_field_type() {
+                MetricType::COUNTER => (
+                    sample_name.strip_suffix("_total").unwrap_or(sample_name),
+                    "counter",
+                ),
+                MetricType::GAUGE => (sample_name, "gauge"),
+                MetricType::HISTOGRAM => (sample_name, "histogram"),
+                MetricType::SUMMARY => (sample_name, "summary"),
+                MetricType::UNTYPED => (sample_name, "unknown"),
+            };
+
+            if !family.get_help().is_empty() {
+                let _ = writeln!(
+                    output,
+                    "# HELP {} {}",
+                    family_name,
+                    escape_help(family.get_help())
+                );
+            }
+            let _ = writeln!(output, "# TYPE {} {}", family_name, metric_type);
+
+            for metric in family.get_metric() {
+                let labels = metric.get_label();
+
+                match family.get_field_type() {
+                    MetricType::COUNTER => write_sample(
+                        &mut output,
+                        &format!("{}_total", family_name),
+                        labels,
+                        None,
+                        metric.get_counter().get_value(),
+                    ),
+                    MetricType::GAUGE => write_sample(
+                        &mut output,
+                        family_name,
+                        labels,
+                        None,
+                        metric.get_gauge().get_value(),
+                    ),
+                    MetricType::UNTYPED => write_sample(
+                        &mut output,
+                        family_name,
+                        labels,
+                        None,
+                        metric.get_untyped().get_value(),
+                    ),
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let bucket_name = format!("{}_bucket", family_name);
+                        let mut has_inf_bucket = false;
+
+                        for bucket in histogram.get_bucket() {
+                            let upper_bound = bucket.get_upper_bound();
+                            has_inf_bucket |= upper_bound == f64::INFINITY;
+
+                            write_sample(
+                                &mut output,
+                                &bucket_name,
+                                labels,
+                                Some(("le", format_value(upper_bound))),
+                                bucket.get_cumulative_count() as f64,
+                            );
+                        }
+
+                        if !has_inf_bucket {
+                            write_sample(
+                                &mut output,
+                                &bucket_name,
+                                labels,
+                                Some(("le", format_value(f64::INFINITY))),
+                                histogram.get_sample_count() as f64,
+                            );
+                        }
+
+                        write_sample(
+                            &mut output,
+                            &format!("{}_sum", family_name),
+                            labels,
+                            None,
+                            histogram.get_sample_sum(),
+                        );
+                        write_sample(
+                            &mut output,
+                            &format!("{}_count", family_name),
+                            labels,
+                            None,
+                            histogram.get_sample_count() as f64,
+                        );
+                    }
+                    MetricType::SUMMARY => {
+                        let summary = metric.get_summary();
+
+                        for quantile in summary.get_quantile() {
+                            write_sample(
+                                &mut output,
+                                family_name,
+                                labels,
+                                Some(("quantile", format_value(quantile.get_quantile()))),
+                                quantile.get_value(),
+                            );
+                        }
+
+                        write_sample(
+                            &mut output,
+                            &format!("{}_sum", family_name),
+                            labels,
+                            None,
+                            summary.get_sample_sum(),
+                        );
+                        write_sample(
+                            &mut output,
+                            &format!("{}_count", family_name),
+                            labels,
+                            None,
+                            summary.get_sample_count() as f64,
+                        );
+                    }
+                }
+            }
+        }
+
+        output.push_str("# EOF\n");
+        output
+    }
+
+    fn write_sample(
+        output: &mut String,
+        name: &str,
+        labels: &[LabelPair],
+        extra_label: Option<(&str, String)>,
+        value: f64,
+    ) {
+        let mut pairs = labels
+            .iter()
+            .map(|label| {
+                format!(
+                    "{}=\"{}\"",
+                    label.get_name(),
+                    escape_label_value(label.get_value())
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if let Some((name, value)) = extra_label {
+            pairs.push(format!("{}=\"{}\"", name, value));
+        }
+
+        if pairs.is_empty() {
+            let _ = writeln!(output, "{} {}", name, format_value(value));
+        } else {
+            let _ = writeln!(
+                output,
+                "{}{{{}}} {}",
+                name,
+                pairs.join(","),
+                format_value(value)
+            );
+        }
+    }
+
+    fn format_value(value: f64) -> String {
+        if value == f64::INFINITY {
+            "+Inf".to_string()
+        } else if value == f64::NEG_INFINITY {
+            "-Inf".to_string()
+        } else if value.is_nan() {
+            "NaN".to_string()
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn escape_help(help: &str) -> String {
+        help.replace('\\', "\\\\").replace('\n', "\\n")
+    }
+
+    fn escape_label_value(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::metrics_endpoint::{MetricsEndpoint, MetricsFormat, OPENMETRICS_CONTENT_TYPE};
+    use poem::test::TestClient;
+    use poem::Route;
+    use prometheus::{IntCounterVec, IntGauge, Opts, Registry};
+
+    fn make_registry() -> Registry {
+        let registry = Registry::new();
+
+        let requests = IntCounterVec::new(
+            Opts::new("requests_total", "Number of requests"),
+            &["method"],
+        )
+        .unwrap();
+        let in_flight = IntGauge::new("in_flight", "Number of requests in flight").unwrap();
+
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry.register(Box::new(in_flight.clone())).unwrap();
+
+        requests.with_label_values(&["get"]).inc_by(3);
+        in_flight.set(2);
+
+        registry
+    }
+
+    #[test]
+    fn test_negotiate_format() {
+        assert_eq!(MetricsFormat::negotiate(None), MetricsFormat::Prometheus);
+        assert_eq!(
+            MetricsFormat::negotiate(Some("text/plain")),
+            MetricsFormat::Prometheus
+        );
+        assert_eq!(
+            MetricsFormat::negotiate(Some(
+                "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5,*/*;q=0.1"
+            )),
+            MetricsFormat::OpenMetrics
+        );
+        assert_eq!(
+            MetricsFormat::negotiate(Some("application/openmetrics-text;q=0.2,text/plain")),
+            MetricsFormat::Prometheus
+        );
+        assert_eq!(
+            MetricsFormat::negotiate(Some("application/openmetrics-text;q=0")),
+            MetricsFormat::Prometheus
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_format() {
+        let client =
+            TestClient::new(Route::new().nest("/metrics", MetricsEndpoint::new(make_registry())));
+
+        let response = client
+            .get("/metrics")
+            .header("Accept", "text/plain;version=0.0.4")
+            .send()
+            .await;
+
+        response.assert_status_is_ok();
+        response.assert_content_type("text/plain; version=0.0.4");
+        response
+            .assert_text(
+                "# HELP in_flight Number of requests in flight\n\
+                 # TYPE in_flight gauge\n\
+                 in_flight 2\n\
+                 # HELP requests_total Number of requests\n\
+                 # TYPE requests_total counter\n\
+                 requests_total{method=\"get\"} 3\n",
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_open_metrics_format() {
+        let client =
+            TestClient::new(Route::new().nest("/metrics", MetricsEndpoint::new(make_registry())));
+
+        let response = client
+            .get("/metrics")
+            .header("Accept", "application/openmetrics-text;version=1.0.0")
+            .send()
+            .await;
+
+        response.assert_status_is_ok();
+        response.assert_content_type(OPENMETRICS_CONTENT_TYPE);
+        response
+            .assert_text(
+                "# HELP in_flight Number of requests in flight\n\
+                 # TYPE in_flight gauge\n\
+                 in_flight 2\n\
+                 # HELP requests Number of requests\n\
+                 # TYPE requests counter\n\
+                 requests_total{method=\"get\"} 3\n\
+                 # EOF\n",
+            )
+            .await;
+    }
+}
@@ -1,5 +1,15 @@
 pub use http_request::*;
 
+pub mod access_log;
+pub mod catch_panic;
+pub mod concurrency_limit;
+pub mod default_route;
 pub mod http_request;
+pub mod metrics_endpoint;
+pub mod request_body_limit;
+pub mod response_body_limit;
+pub mod route_circuit_breaker;
+pub mod streamed_body;
+pub mod trace_context;
 
 pub mod router;
@@ -0,0 +1,223 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::app_config::RequestBodyLimitConfig;
+use futures_util::StreamExt;
+use http::header::CONTENT_LENGTH;
+use http::StatusCode;
+use poem::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+// Rejects requests with a body larger than the configured limit with 413. A body with a content
+// length is rejected before it reaches the custom request handler, and one without it
+// as soon as the handler reads more than the limit.
+pub struct RequestBodyLimit {
+    config: Arc<RequestBodyLimitConfig>,
+}
+
+impl RequestBodyLimit {
+    pub fn new(config: RequestBodyLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RequestBodyLimit {
+    type Output = RequestBodyLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequestBodyLimitEndpoint {
+            inner: ep,
+            config: self.config.clone(),
+        }
+    }
+}
+
+pub struct RequestBodyLimitEndpoint<E> {
+    inner: E,
+    config: Arc<RequestBodyLimitConfig>,
+}
+
+impl<E: Endpoint> Endpoint for RequestBodyLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let limit = self.config.limit_for(req.uri().path());
+
+        let content_length = req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+
+        match content_length {
+            Some(length) if length > limit => {
+                warn!(
+                    "Request body of {} bytes to {} exceeds the limit of {} bytes",
+                    length,
+                    req.uri().path(),
+                    limit
+                );
+                return Ok(payload_too_large(limit));
+            }
+            Some(_) => {}
+            // Without a content length (chunked encoding) the size is only known once the body
+            // is read. The bytes are counted as the handler reads them, so the body is still
+            // streamed (Ex: read only until the fields the route uses), and reading it fails
+            // once it exceeds the limit, which is then the response
+            None => {
+                let path = req.uri().path().to_string();
+                let exceeded = Arc::new(AtomicBool::new(false));
+                let body = limited_body(req.take_body(), limit, exceeded.clone());
+                req.set_body(body);
+
+                let response = self.inner.call(req).await;
+
+                if exceeded.load(Ordering::SeqCst) {
+                    warn!(
+                        "Request body to {} exceeds the limit of {} bytes",
+                        path, limit
+                    );
+                    return Ok(payload_too_large(limit));
+                }
+
+                return response.map(IntoResponse::into_response);
+            }
+        }
+
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+// The body, which fails to be read further once more than `limit` bytes are read from it
+fn limited_body(body: Body, limit: u64, exceeded: Arc<AtomicBool>) -> Body {
+    let mut read: u64 = 0;
+
+    let stream = body.into_bytes_stream().map(move |chunk| {
+        let chunk = chunk?;
+        read += chunk.len() as u64;
+
+        if read > limit {
+            exceeded.store(true, Ordering::SeqCst);
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Request body exceeds the limit of {} bytes", limit),
+            ))
+        } else {
+            Ok(chunk)
+        }
+    });
+
+    Body::from_bytes_stream(stream)
+}
+
+fn payload_too_large(limit: u64) -> Response {
+    Response::builder()
+        .status(StatusCode::PAYLOAD_TOO_LARGE)
+        .body(Body::from_string(format!(
+            "Request body exceeds the limit of {} bytes",
+            limit
+        )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app_config::{RequestBodyLimitConfig, RouteRequestBodyLimit};
+    use crate::http::request_body_limit::RequestBodyLimit;
+    use http::StatusCode;
+    use poem::test::TestClient;
+    use poem::{handler, Endpoint, EndpointExt, Route};
+
+    #[handler]
+    fn echo_len(body: String) -> String {
+        body.len().to_string()
+    }
+
+    fn make_route() -> impl Endpoint {
+        Route::new()
+            .at("/small", poem::post(echo_len))
+            .at("/large/upload", poem::post(echo_len))
+            .with(RequestBodyLimit::new(RequestBodyLimitConfig {
+                max_bytes: 10,
+                routes: vec![RouteRequestBodyLimit {
+                    path_prefix: "/large".to_string(),
+                    max_bytes: 100,
+                }],
+            }))
+    }
+
+    #[tokio::test]
+    async fn body_under_the_limit_is_accepted() {
+        let client = TestClient::new(make_route());
+
+        let response = client
+            .post("/small")
+            .header("content-length", 10)
+            .body("a".repeat(10))
+            .send()
+            .await;
+
+        response.assert_status_is_ok();
+        response.assert_text("10").await;
+    }
+
+    #[tokio::test]
+    async fn body_over_the_limit_is_rejected() {
+        let client = TestClient::new(make_route());
+
+        let response = client
+            .post("/small")
+            .header("content-length", 11)
+            .body("a".repeat(11))
+            .send()
+            .await;
+
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn body_without_content_length_over_the_limit_is_rejected() {
+        let client = TestClient::new(make_route());
+
+        let response = client.post("/small").body("a".repeat(11)).send().await;
+
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn route_override_is_applied() {
+        let client = TestClient::new(make_route());
+
+        let response = client
+            .post("/large/upload")
+            .header("content-length", 100)
+            .body("a".repeat(100))
+            .send()
+            .await;
+
+        response.assert_status_is_ok();
+
+        let response = client
+            .post("/large/upload")
+            .header("content-length", 101)
+            .body("a".repeat(101))
+            .send()
+            .await;
+
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::app_config::{ResponseBodyLimitAction, ResponseBodyLimitConfig};
+use crate::metrics;
+use bytes::BytesMut;
+use futures_util::StreamExt;
+use http::header::CONTENT_LENGTH;
+use http::StatusCode;
+use poem::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use std::sync::Arc;
+use tracing::warn;
+
+// Limits the size of the response bodies of the custom request (gateway) server, which are
+// built from the responses of the workers. The body is read up to the limit, so an oversized
+// response of a misbehaving worker is never forwarded (or buffered) as a whole.
+// Depending on the config, an oversized response is replaced with 502, or truncated.
+// When it's replaced, the result of the worker is also checked before it's rendered
+// (see `CustomHttpRequestApi::with_response_body_limit`), so it's not rendered as a whole either.
+pub struct ResponseBodyLimit {
+    config: Arc<ResponseBodyLimitConfig>,
+}
+
+impl ResponseBodyLimit {
+    pub fn new(config: ResponseBodyLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ResponseBodyLimit {
+    type Output = ResponseBodyLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ResponseBodyLimitEndpoint {
+            inner: ep,
+            config: self.config.clone(),
+        }
+    }
+}
+
+pub struct ResponseBodyLimitEndpoint<E> {
+    inner: E,
+    config: Arc<ResponseBodyLimitConfig>,
+}
+
+impl<E: Endpoint> Endpoint for ResponseBodyLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let path = req.uri().path().to_string();
+        let mut response = self.inner.call(req).await?.into_response();
+        let limit = self.config.max_bytes;
+
+        let mut stream = response.take_body().into_bytes_stream();
+        let mut buffer = BytesMut::new();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|err| {
+                poem::Error::from_string(err.to_string(), StatusCode::BAD_GATEWAY)
+            })?;
+
+            if (buffer.len() + chunk.len()) as u64 > limit {
+                warn!(
+                    "Response body of {} exceeds the limit of {} bytes",
+                    path, limit
+                );
+                metrics::record_gateway_oversized_response(self.config.on_exceeded);
+
+                match self.config.on_exceeded {
+                    ResponseBodyLimitAction::Reject => return Ok(bad_gateway(limit)),
+                    ResponseBodyLimitAction::Truncate => {
+                        let remaining = limit as usize - buffer.len();
+                        buffer.extend_from_slice(&chunk[..remaining]);
+                        break;
+                    }
+                }
+            }
+
+            buffer.extend_from_slice(&chunk);
+        }
+
+        // The content length of the truncated body is the one of the kept part
+        response.headers_mut().remove(CONTENT_LENGTH);
+        response.set_body(Body::from_bytes(buffer.freeze()));
+
+        Ok(response)
+    }
+}
+
+fn bad_gateway(limit: u64) -> Response {
+    Response::builder()
+        .status(StatusCode::BAD_GATEWAY)
+        .body(Body::from_string(format!(
+            "Response body exceeds the limit of {} bytes",
+            limit
+        )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app_config::{ResponseBodyLimitAction, ResponseBodyLimitConfig};
+    use crate::http::response_body_limit::ResponseBodyLimit;
+    use crate::metrics;
+    use http::StatusCode;
+    use poem::test::TestClient;
+    use poem::{handler, Endpoint, EndpointExt, Route};
+
+    #[handler]
+    fn small() -> String {
+        "a".repeat(10)
+    }
+
+    #[handler]
+    fn large() -> String {
+        "a".repeat(11)
+    }
+
+    fn make_route(on_exceeded: ResponseBodyLimitAction) -> impl Endpoint {
+        Route::new()
+            .at("/small", poem::get(small))
+            .at("/large", poem::get(large))
+            .with(ResponseBodyLimit::new(ResponseBodyLimitConfig {
+                max_bytes: 10,
+                on_exceeded,
+            }))
+    }
+
+    #[tokio::test]
+    async fn body_under_the_limit_is_forwarded() {
+        let client = TestClient::new(make_route(ResponseBodyLimitAction::Reject));
+
+        let response = client.get("/small").send().await;
+
+        response.assert_status_is_ok();
+        response.assert_text("a".repeat(10)).await;
+    }
+
+    #[tokio::test]
+    async fn body_over_the_limit_is_rejected() {
+        let client = TestClient::new(make_route(ResponseBodyLimitAction::Reject));
+        let rejected_before = metrics::gateway_oversized_responses(ResponseBodyLimitAction::Reject);
+
+        let response = client.get("/large").send().await;
+
+        response.assert_status(StatusCode::BAD_GATEWAY);
+        response
+            .assert_text("Response body exceeds the limit of 10 bytes")
+            .await;
+        assert!(
+            metrics::gateway_oversized_responses(ResponseBodyLimitAction::Reject) > rejected_before
+        );
+    }
+
+    #[tokio::test]
+    async fn body_over_the_limit_is_truncated() {
+        let client = TestClient::new(make_route(ResponseBodyLimitAction::Truncate));
+        let truncated_before =
+            metrics::gateway_oversized_responses(ResponseBodyLimitAction::Truncate);
+
+        let response = client.get("/large").send().await;
+
+        response.assert_status_is_ok();
+        response.assert_text("a".repeat(10)).await;
+        assert!(
+            metrics::gateway_oversized_responses(ResponseBodyLimitAction::Truncate)
+                > truncated_before
+        );
+    }
+}
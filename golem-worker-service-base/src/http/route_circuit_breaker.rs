@@ -0,0 +1,344 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::app_config::RouteCircuitBreakerConfig;
+use crate::metrics;
+use http::header::RETRY_AFTER;
+use http::StatusCode;
+use poem::{Body, Response};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+// Identifies the circuit of a route. The same route (Ex: `GET /users/{id}`) can be defined by
+// more API definitions, deployed on different hosts, each with workers of their own
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RouteKey {
+    pub host: String,
+    // Ex: `users-api@0.0.1`
+    pub api_definition: String,
+    pub route: String,
+}
+
+impl Display for RouteKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.host, self.api_definition, self.route)
+    }
+}
+
+// A request allowed to invoke the worker of a route, whose outcome is recorded with `record`
+pub struct RouteCall {
+    key: RouteKey,
+    probe: Option<u64>,
+}
+
+// Stops invoking the workers of a route while most of its recent invocations fail (5xx),
+// so a misbehaving worker is not called again and again, while the other routes are unaffected.
+// An open route is rejected with 503 until the cool-down ends. Then a single probe request
+// is let through: its success closes the route again, and its failure opens it for another cool-down.
+// Only the outcome of the probe changes the state of a half-open route, the requests allowed
+// before the route opened can't close it
+pub struct RouteCircuitBreaker {
+    config: RouteCircuitBreakerConfig,
+    routes: Mutex<HashMap<RouteKey, RouteState>>,
+    next_probe: AtomicU64,
+}
+
+enum RouteState {
+    // The outcomes of the latest requests, `true` for a failure
+    Closed(VecDeque<bool>),
+    Open { until: Instant },
+    // A probe started at the given time. If it doesn't finish (Ex: the request is cancelled),
+    // another probe is let through after the cool-down
+    HalfOpen { probe: Option<(u64, Instant)> },
+}
+
+impl RouteCircuitBreaker {
+    pub fn new(config: RouteCircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            routes: Mutex::new(HashMap::new()),
+            next_probe: AtomicU64::new(0),
+        }
+    }
+
+    pub fn state(&self, key: &RouteKey) -> CircuitState {
+        let mut routes = self.routes.lock().unwrap();
+
+        match routes.get_mut(key) {
+            Some(state) => {
+                Self::end_cool_down(state, Instant::now());
+                match state {
+                    RouteState::Closed(_) => CircuitState::Closed,
+                    RouteState::Open { .. } => CircuitState::Open,
+                    RouteState::HalfOpen { .. } => CircuitState::HalfOpen,
+                }
+            }
+            None => CircuitState::Closed,
+        }
+    }
+
+    // Whether a request to the route can invoke the worker. The outcome of an allowed
+    // request has to be recorded with `record`
+    pub fn allow(&self, key: &RouteKey) -> Option<RouteCall> {
+        let now = Instant::now();
+        let mut routes = self.routes.lock().unwrap();
+        let state = routes
+            .entry(key.clone())
+            .or_insert_with(|| RouteState::Closed(VecDeque::new()));
+
+        Self::end_cool_down(state, now);
+
+        let probe = match state {
+            RouteState::Closed(_) => None,
+            RouteState::Open { .. } => return None,
+            RouteState::HalfOpen { probe } => match *probe {
+                Some((_, started)) if now.duration_since(started) < self.config.cool_down => {
+                    return None
+                }
+                _ => {
+                    let id = self.next_probe.fetch_add(1, Ordering::Relaxed);
+                    *probe = Some((id, now));
+                    Some(id)
+                }
+            },
+        };
+
+        Some(RouteCall {
+            key: key.clone(),
+            probe,
+        })
+    }
+
+    pub fn record(&self, call: RouteCall, status: StatusCode) {
+        let failed = status.is_server_error();
+        let now = Instant::now();
+        let key = &call.key;
+        let mut routes = self.routes.lock().unwrap();
+        let state = routes
+            .entry(key.clone())
+            .or_insert_with(|| RouteState::Closed(VecDeque::new()));
+
+        let open = match state {
+            RouteState::Closed(outcomes) => {
+                outcomes.push_back(failed);
+                if outcomes.len() > self.config.window_size {
+                    outcomes.pop_front();
+                }
+
+                let failures = outcomes.iter().filter(|failed| **failed).count();
+                let error_rate = failures as f64 / outcomes.len() as f64;
+                let open = outcomes.len() >= self.config.min_requests
+                    && error_rate > self.config.error_rate_threshold;
+
+                if open {
+                    warn!(
+                        "Opening the circuit of route {}, {} of the last {} requests failed",
+                        key,
+                        failures,
+                        outcomes.len()
+                    );
+                }
+                open
+            }
+            // A request allowed before the route was opened
+            RouteState::Open { .. } => false,
+            RouteState::HalfOpen { probe } => {
+                let probe = (*probe).map(|(id, _)| id);
+
+                // A request allowed before the route was opened, or a probe given up on
+                if call.probe.is_none() || call.probe != probe {
+                    false
+                } else if failed {
+                    true
+                } else {
+                    *state = RouteState::Closed(VecDeque::new());
+                    false
+                }
+            }
+        };
+
+        if open {
+            metrics::record_gateway_route_circuit_opened(&key.api_definition, &key.route);
+            *state = RouteState::Open {
+                until: now + self.config.cool_down,
+            };
+        }
+    }
+
+    pub fn service_unavailable(&self, key: &RouteKey) -> Response {
+        metrics::record_gateway_route_circuit_rejection(&key.api_definition, &key.route);
+        service_unavailable(self.config.cool_down)
+    }
+
+    fn end_cool_down(state: &mut RouteState, now: Instant) {
+        if let RouteState::Open { until } = state {
+            if now >= *until {
+                *state = RouteState::HalfOpen { probe: None };
+            }
+        }
+    }
+}
+
+fn service_unavailable(retry_after: Duration) -> Response {
+    // Retry-After is in whole seconds, a sub-second delay is rounded up
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header(RETRY_AFTER, seconds.to_string())
+        .body(Body::from_string(
+            "The route is temporarily unavailable".to_string(),
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app_config::RouteCircuitBreakerConfig;
+    use crate::http::route_circuit_breaker::{CircuitState, RouteCircuitBreaker, RouteKey};
+    use crate::metrics;
+    use http::StatusCode;
+    use std::time::Duration;
+
+    const API_DEFINITION: &str = "carts-api@0.0.1";
+    const ROUTE: &str = "GET /carts/{cart-id}";
+
+    fn key(host: &str, api_definition: &str, route: &str) -> RouteKey {
+        RouteKey {
+            host: host.to_string(),
+            api_definition: api_definition.to_string(),
+            route: route.to_string(),
+        }
+    }
+
+    fn breaker() -> RouteCircuitBreaker {
+        RouteCircuitBreaker::new(RouteCircuitBreakerConfig {
+            window_size: 4,
+            min_requests: 4,
+            error_rate_threshold: 0.5,
+            cool_down: Duration::from_millis(100),
+        })
+    }
+
+    fn call(breaker: &RouteCircuitBreaker, key: &RouteKey, status: StatusCode) -> bool {
+        match breaker.allow(key) {
+            Some(route_call) => {
+                breaker.record(route_call, status);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn open(breaker: &RouteCircuitBreaker, key: &RouteKey) {
+        for _ in 0..4 {
+            call(breaker, key, StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        assert_eq!(breaker.state(key), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn route_circuit_opens_and_half_opens_after_the_cool_down() {
+        let breaker = breaker();
+        let route = key("localhost", API_DEFINITION, ROUTE);
+        let opened_before = metrics::gateway_route_circuit_openings(API_DEFINITION, ROUTE);
+
+        // 2 of 4 failed is not above the threshold
+        assert!(call(&breaker, &route, StatusCode::OK));
+        assert!(call(&breaker, &route, StatusCode::OK));
+        assert!(call(&breaker, &route, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(call(&breaker, &route, StatusCode::BAD_GATEWAY));
+        assert_eq!(breaker.state(&route), CircuitState::Closed);
+
+        // 3 of the last 4 failed
+        assert!(call(&breaker, &route, StatusCode::INTERNAL_SERVER_ERROR));
+        assert_eq!(breaker.state(&route), CircuitState::Open);
+        assert!(!call(&breaker, &route, StatusCode::OK));
+        assert_eq!(
+            metrics::gateway_route_circuit_openings(API_DEFINITION, ROUTE),
+            opened_before + 1
+        );
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(breaker.state(&route), CircuitState::HalfOpen);
+
+        // Only a single probe is let through, and its failure opens the route again
+        let probe = breaker.allow(&route).unwrap();
+        assert!(breaker.allow(&route).is_none());
+        breaker.record(probe, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(breaker.state(&route), CircuitState::Open);
+
+        // A successful probe closes it
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(call(&breaker, &route, StatusCode::OK));
+        assert_eq!(breaker.state(&route), CircuitState::Closed);
+        assert!(call(&breaker, &route, StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn only_the_probe_changes_the_state_of_a_half_open_route() {
+        let breaker = breaker();
+        let route = key("localhost", API_DEFINITION, ROUTE);
+
+        // Allowed before the route opens, finishing after the cool-down
+        let slow_call = breaker.allow(&route).unwrap();
+        open(&breaker, &route);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        let probe = breaker.allow(&route).unwrap();
+        breaker.record(slow_call, StatusCode::OK);
+        assert_eq!(breaker.state(&route), CircuitState::HalfOpen);
+
+        breaker.record(probe, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(breaker.state(&route), CircuitState::Open);
+    }
+
+    #[test]
+    fn the_same_route_of_other_definitions_and_hosts_is_not_affected() {
+        let breaker = breaker();
+        let route = key("localhost", API_DEFINITION, ROUTE);
+        let other_route = key("localhost", API_DEFINITION, "GET /users/{user-id}");
+        let other_definition = key("localhost", "shop-api@0.0.1", ROUTE);
+        let other_host = key("example.com", API_DEFINITION, ROUTE);
+
+        open(&breaker, &route);
+
+        for other in [other_route, other_definition, other_host] {
+            assert!(call(&breaker, &other, StatusCode::OK), "{}", other);
+            assert_eq!(breaker.state(&other), CircuitState::Closed, "{}", other);
+        }
+    }
+
+    #[test]
+    fn client_errors_do_not_open_the_circuit() {
+        let breaker = breaker();
+        let route = key("localhost", API_DEFINITION, ROUTE);
+
+        for _ in 0..8 {
+            assert!(call(&breaker, &route, StatusCode::NOT_FOUND));
+        }
+
+        assert_eq!(breaker.state(&route), CircuitState::Closed);
+    }
+}
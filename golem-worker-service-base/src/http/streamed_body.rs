@@ -0,0 +1,446 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures_util::StreamExt;
+use poem::Body;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadError {
+    // The body couldn't be read
+    Read(String),
+    // The body is not valid UTF-8, so it has no JSON value
+    NotUtf8,
+    // The body is not valid JSON
+    Invalid(String),
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Read(err) => write!(f, "Failed to read the request body: {}", err),
+            ReadError::NotUtf8 => write!(f, "The request body is not valid UTF-8"),
+            ReadError::Invalid(err) => write!(f, "Invalid JSON request body: {}", err),
+        }
+    }
+}
+
+// Reads the whole body, and parses it as JSON
+pub async fn read_json(body: Body) -> Result<Value, ReadError> {
+    let bytes = body
+        .into_vec()
+        .await
+        .map_err(|err| ReadError::Read(err.to_string()))?;
+
+    parse_json(&bytes)
+}
+
+// Reads the given top level fields of a JSON object body, while it is streamed. Only the values
+// of these fields are kept, and the body is read only until all of them are found,
+// so a small field at the start of a large body (Ex: a type discriminator) is resolved
+// without reading (or buffering) the rest. As a consequence, the part of the body
+// after the fields isn't validated (not even as UTF-8), and if a field is repeated after that,
+// the repetition isn't seen (a repetition that is read replaces the value, as in serde_json).
+// A body that is not a JSON object is read and parsed as a whole
+pub async fn read_json_fields(body: Body, fields: &HashSet<String>) -> Result<Value, ReadError> {
+    let mut stream = body.into_bytes_stream();
+    let mut reader = internal::FieldReader::new(fields);
+    let mut utf8 = internal::Utf8Validator::default();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| ReadError::Read(err.to_string()))?;
+
+        utf8.feed(&chunk)?;
+
+        match reader.feed(&chunk).map_err(ReadError::Invalid)? {
+            internal::Progress::NeedMore => {}
+            internal::Progress::Done => return Ok(reader.into_value()),
+            internal::Progress::NotAnObject => {
+                // The previous chunks were only whitespace
+                let mut buffer = chunk.to_vec();
+                while let Some(chunk) = stream.next().await {
+                    buffer
+                        .extend_from_slice(&chunk.map_err(|err| ReadError::Read(err.to_string()))?);
+                }
+                return parse_json(&buffer);
+            }
+        }
+    }
+
+    utf8.finish()?;
+
+    Err(ReadError::Invalid(
+        "Unexpected end of the request body".to_string(),
+    ))
+}
+
+fn parse_json(bytes: &[u8]) -> Result<Value, ReadError> {
+    let text = std::str::from_utf8(bytes).map_err(|_| ReadError::NotUtf8)?;
+    serde_json::from_str(text).map_err(|err| ReadError::Invalid(err.to_string()))
+}
+
+mod internal {
+    use serde_json::{Map, Value};
+    use std::collections::HashSet;
+
+    use crate::http::streamed_body::ReadError;
+
+    // Validates UTF-8 across the chunks, which can split a character
+    #[derive(Default)]
+    pub(crate) struct Utf8Validator {
+        // The start of a character that continues in the next chunk
+        incomplete: Vec<u8>,
+    }
+
+    impl Utf8Validator {
+        pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<(), ReadError> {
+            let joined;
+            let bytes = if self.incomplete.is_empty() {
+                chunk
+            } else {
+                joined = [self.incomplete.as_slice(), chunk].concat();
+                joined.as_slice()
+            };
+
+            match std::str::from_utf8(bytes) {
+                Ok(_) => {
+                    self.incomplete.clear();
+                    Ok(())
+                }
+                // Only the end of the bytes is incomplete
+                Err(err) if err.error_len().is_none() => {
+                    self.incomplete = bytes[err.valid_up_to()..].to_vec();
+                    Ok(())
+                }
+                Err(_) => Err(ReadError::NotUtf8),
+            }
+        }
+
+        pub(crate) fn finish(&self) -> Result<(), ReadError> {
+            if self.incomplete.is_empty() {
+                Ok(())
+            } else {
+                Err(ReadError::NotUtf8)
+            }
+        }
+    }
+
+    pub(crate) enum Progress {
+        NeedMore,
+        Done,
+        NotAnObject,
+    }
+
+    enum State {
+        Start,
+        BeforeKey { first: bool },
+        Key { escaped: bool },
+        AfterKey,
+        BeforeValue,
+        Value(ValueState),
+        AfterValue,
+    }
+
+    struct ValueState {
+        depth: usize,
+        in_string: bool,
+        escaped: bool,
+    }
+
+    // A state machine over the bytes of the object, so the chunks can be split anywhere
+    pub(crate) struct FieldReader<'a> {
+        fields: &'a HashSet<String>,
+        values: Map<String, Value>,
+        state: State,
+        key: Vec<u8>,
+        // The bytes of the value of a field that is read, None while skipping a value
+        value: Option<Vec<u8>>,
+    }
+
+    impl<'a> FieldReader<'a> {
+        pub(crate) fn new(fields: &'a HashSet<String>) -> Self {
+            FieldReader {
+                fields,
+                values: Map::new(),
+                state: State::Start,
+                key: Vec::new(),
+                value: None,
+            }
+        }
+
+        pub(crate) fn into_value(self) -> Value {
+            Value::Object(self.values)
+        }
+
+        pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<Progress, String> {
+            let mut index = 0;
+
+            while index < chunk.len() {
+                let byte = chunk[index];
+
+                match &mut self.state {
+                    State::Start => {
+                        if !byte.is_ascii_whitespace() {
+                            if byte != b'{' {
+                                return Ok(Progress::NotAnObject);
+                            }
+                            if self.fields.is_empty() {
+                                return Ok(Progress::Done);
+                            }
+                            self.state = State::BeforeKey { first: true };
+                        }
+                    }
+                    State::BeforeKey { first } => match byte {
+                        b'"' => {
+                            self.key.clear();
+                            self.key.push(byte);
+                            self.state = State::Key { escaped: false };
+                        }
+                        b'}' if *first => return Ok(Progress::Done),
+                        _ if byte.is_ascii_whitespace() => {}
+                        _ => return Err(unexpected(byte)),
+                    },
+                    State::Key { escaped } => {
+                        self.key.push(byte);
+                        if *escaped {
+                            *escaped = false;
+                        } else if byte == b'\\' {
+                            *escaped = true;
+                        } else if byte == b'"' {
+                            self.state = State::AfterKey;
+                        }
+                    }
+                    State::AfterKey => match byte {
+                        b':' => self.state = State::BeforeValue,
+                        _ if byte.is_ascii_whitespace() => {}
+                        _ => return Err(unexpected(byte)),
+                    },
+                    State::BeforeValue => {
+                        if !byte.is_ascii_whitespace() {
+                            let key: String =
+                                serde_json::from_slice(&self.key).map_err(|err| err.to_string())?;
+
+                            self.value = if self.fields.contains(&key) {
+                                Some(Vec::new())
+                            } else {
+                                None
+                            };
+                            self.state = State::Value(ValueState {
+                                depth: 0,
+                                in_string: false,
+                                escaped: false,
+                            });
+                            // The first byte of the value
+                            continue;
+                        }
+                    }
+                    State::Value(value_state) => {
+                        let (consumed, ended) = value_state.next(byte);
+
+                        if consumed {
+                            if let Some(value) = &mut self.value {
+                                value.push(byte);
+                            }
+                        }
+
+                        if ended {
+                            self.state = State::AfterValue;
+
+                            if let Some(value) = self.value.take() {
+                                let key: String = serde_json::from_slice(&self.key)
+                                    .map_err(|err| err.to_string())?;
+                                let value: Value = serde_json::from_slice(&value)
+                                    .map_err(|err| err.to_string())?;
+
+                                // The last one of a repeated field, as in serde_json
+                                self.values.insert(key, value);
+
+                                if self.values.len() == self.fields.len() {
+                                    return Ok(Progress::Done);
+                                }
+                            }
+                        }
+
+                        if !consumed {
+                            // The byte after a number, `true`, `false` or `null`
+                            continue;
+                        }
+                    }
+                    State::AfterValue => match byte {
+                        b',' => self.state = State::BeforeKey { first: false },
+                        b'}' => return Ok(Progress::Done),
+                        _ if byte.is_ascii_whitespace() => {}
+                        _ => return Err(unexpected(byte)),
+                    },
+                }
+
+                index += 1;
+            }
+
+            Ok(Progress::NeedMore)
+        }
+    }
+
+    impl ValueState {
+        // Whether the byte is part of the value, and whether the value ended
+        fn next(&mut self, byte: u8) -> (bool, bool) {
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    return (true, self.depth == 0);
+                }
+                return (true, false);
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    (true, false)
+                }
+                b'{' | b'[' => {
+                    self.depth += 1;
+                    (true, false)
+                }
+                b'}' | b']' if self.depth > 0 => {
+                    self.depth -= 1;
+                    (true, self.depth == 0)
+                }
+                b',' | b'}' | b']' => (false, true),
+                _ if byte.is_ascii_whitespace() && self.depth == 0 => (false, true),
+                _ => (true, false),
+            }
+        }
+    }
+
+    fn unexpected(byte: u8) -> String {
+        format!(
+            "Unexpected character '{}' in the request body",
+            char::from(byte)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::streamed_body::{read_json_fields, ReadError};
+    use bytes::Bytes;
+    use poem::Body;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn fields(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    // The body split into chunks of a single byte, to split the tokens everywhere
+    fn body_in_bytes(body: &str) -> Body {
+        let chunks = body
+            .bytes()
+            .map(|byte| Ok::<_, std::io::Error>(Bytes::from(vec![byte])))
+            .collect::<Vec<_>>();
+
+        Body::from_bytes_stream(futures_util::stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn test_read_json_fields() {
+        let body = r#" {"skipped": {"a": [1, "}", {"b": "\""}]}, "kind" : "cart", "count":12,
+            "k\"ey": null, "active": true, "rest": "x"}"#;
+
+        let value = read_json_fields(
+            body_in_bytes(body),
+            &fields(&["kind", "count", "k\"ey", "active", "missing"]),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            value,
+            json!({"kind": "cart", "count": 12, "k\"ey": null, "active": true})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_stops_after_the_fields() {
+        // The rest of the body is not read, so it isn't validated either
+        let value = read_json_fields(body_in_bytes(r#"{"kind": 1, "rest": "#), &fields(&["kind"]))
+            .await
+            .unwrap();
+
+        assert_eq!(value, json!({"kind": 1}));
+    }
+
+    #[tokio::test]
+    async fn test_read_json_fields_of_invalid_body() {
+        let body = read_json_fields(body_in_bytes(r#"{"kind" 1}"#), &fields(&["kind"])).await;
+        assert!(body.is_err());
+
+        let body = read_json_fields(body_in_bytes(r#"{"kind": "#), &fields(&["kind"])).await;
+        assert!(body.is_err());
+
+        // Not an object, so it's parsed as a whole
+        let body = read_json_fields(body_in_bytes("  [1, 2]"), &fields(&["kind"])).await;
+        assert_eq!(body, Ok(json!([1, 2])));
+    }
+
+    #[tokio::test]
+    async fn test_read_json_fields_of_non_utf8_body() {
+        let body = |bytes: Vec<u8>| {
+            Body::from_bytes_stream(futures_util::stream::iter(
+                bytes
+                    .into_iter()
+                    .map(|byte| Ok::<_, std::io::Error>(Bytes::from(vec![byte]))),
+            ))
+        };
+
+        // 0xff is never a part of a UTF-8 character
+        let mut bytes = b"{\"kind\": \"".to_vec();
+        bytes.extend_from_slice(&[0xff, b'"', b'}']);
+        let value = read_json_fields(body(bytes), &fields(&["kind"])).await;
+        assert_eq!(value, Err(ReadError::NotUtf8));
+
+        // A character that is split between the chunks is valid
+        let value = read_json_fields(
+            body("{\"kind\": \"é\"}".as_bytes().to_vec()),
+            &fields(&["kind"]),
+        )
+        .await;
+        assert_eq!(value, Ok(json!({"kind": "é"})));
+
+        // A character that never ends is not
+        let mut bytes = b"{\"kind\": \"".to_vec();
+        bytes.push(0xc3);
+        let value = read_json_fields(body(bytes), &fields(&["kind"])).await;
+        assert_eq!(value, Err(ReadError::NotUtf8));
+    }
+
+    #[tokio::test]
+    async fn test_read_json_fields_keeps_the_last_of_repeated_fields() {
+        let body = r#"{"kind": "first", "count": 1, "kind": "last", "name": "foo"}"#;
+
+        let value = read_json_fields(body_in_bytes(body), &fields(&["kind", "name"]))
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(body).unwrap();
+
+        assert_eq!(value, json!({"kind": "last", "name": "foo"}));
+        assert_eq!(value["kind"], parsed["kind"]);
+    }
+}
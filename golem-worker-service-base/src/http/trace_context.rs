@@ -0,0 +1,243 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::{FutureExt, TraceContextExt};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use poem::http::HeaderMap;
+use poem::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use tracing::{info_span, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+// Continues the trace of the caller, read from the W3C `traceparent` and `tracestate` headers.
+// The `gateway_request` span is created with the caller's span as its parent, and the request
+// is handled with the caller's context as the current OpenTelemetry context, so the spans started
+// while handling the request (Ex: poem's request span, invoking the worker) belong to the caller's
+// trace. Without the headers (or if they are invalid), a new trace starts.
+// Must be the outermost tracing middleware (applied after poem's `Tracing`), otherwise the spans
+// of the outer middlewares are created before the parent is known
+pub struct TraceContext;
+
+impl<E: Endpoint> Middleware<E> for TraceContext {
+    type Output = TraceContextEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        TraceContextEndpoint { inner: ep }
+    }
+}
+
+pub struct TraceContextEndpoint<E> {
+    inner: E,
+}
+
+impl<E: Endpoint> Endpoint for TraceContextEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let parent = TraceContextPropagator::new().extract(&HeaderExtractor(req.headers()));
+
+        let span = info_span!("gateway_request");
+        if parent.span().span_context().is_valid() {
+            span.set_parent(parent.clone());
+        }
+
+        self.inner
+            .call(req)
+            .with_context(parent)
+            .instrument(span)
+            .await
+            .map(IntoResponse::into_response)
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl<'a> Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::trace_context::TraceContext;
+    use futures_util::future::BoxFuture;
+    use opentelemetry::trace::{SpanId, TraceContextExt, TraceId, TracerProvider as _};
+    use opentelemetry::Context;
+    use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+    use opentelemetry_sdk::trace::TracerProvider;
+    use poem::middleware::Tracing;
+    use poem::test::TestClient;
+    use poem::{handler, Endpoint, EndpointExt, Route};
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    // Collects the exported spans
+    #[derive(Debug, Clone, Default)]
+    struct TestExporter(Arc<Mutex<Vec<SpanData>>>);
+
+    impl SpanExporter for TestExporter {
+        fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            self.0.lock().unwrap().extend(batch);
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    // The span the spans started by the handler would be the children of
+    #[handler]
+    fn parent_span() -> String {
+        let context = Context::current();
+        let span_context = context.span().span_context().clone();
+
+        if span_context.is_valid() {
+            format!("{}-{}", span_context.trace_id(), span_context.span_id())
+        } else {
+            "root".to_string()
+        }
+    }
+
+    fn make_route() -> impl Endpoint {
+        Route::new()
+            .at("/", poem::get(parent_span))
+            .with(TraceContext)
+    }
+
+    #[tokio::test]
+    async fn inbound_trace_context_is_the_parent() {
+        let client = TestClient::new(make_route());
+
+        let response = client
+            .get("/")
+            .header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            )
+            .send()
+            .await;
+
+        response.assert_status_is_ok();
+        response
+            .assert_text("4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn request_without_trace_context_starts_a_new_trace() {
+        let client = TestClient::new(make_route());
+
+        let response = client.get("/").send().await;
+
+        response.assert_status_is_ok();
+        response.assert_text("root").await;
+    }
+
+    #[tokio::test]
+    async fn invalid_trace_context_starts_a_new_trace() {
+        let client = TestClient::new(make_route());
+
+        let response = client
+            .get("/")
+            .header("traceparent", "00-not-a-trace-01")
+            .send()
+            .await;
+
+        response.assert_status_is_ok();
+        response.assert_text("root").await;
+    }
+
+    #[tokio::test]
+    async fn request_span_is_exported_with_the_inbound_parent() {
+        let exporter = TestExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let guard = tracing::subscriber::set_default(subscriber);
+
+        // As in the worker service, poem's request span is created inside the trace context
+        let client = TestClient::new(
+            Route::new()
+                .at("/", poem::get(parent_span))
+                .with(Tracing)
+                .with(TraceContext),
+        );
+
+        client
+            .get("/")
+            .header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            )
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        drop(guard);
+
+        let trace_id = TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap();
+        let spans = exporter.0.lock().unwrap();
+
+        let request_span = spans
+            .iter()
+            .find(|span| span.name == "gateway_request")
+            .expect("gateway_request span is exported");
+
+        assert_eq!(request_span.span_context.trace_id(), trace_id);
+        assert_eq!(
+            request_span.parent_span_id,
+            SpanId::from_hex("00f067aa0ba902b7").unwrap()
+        );
+
+        let inner_spans = spans
+            .iter()
+            .filter(|span| span.name != "gateway_request")
+            .collect::<Vec<_>>();
+
+        assert!(!inner_spans.is_empty());
+        for span in inner_spans {
+            assert_eq!(span.span_context.trace_id(), trace_id);
+            assert_eq!(span.parent_span_id, request_span.span_context.span_id());
+        }
+    }
+
+    #[tokio::test]
+    async fn request_span_without_trace_context_is_a_root() {
+        let exporter = TestExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let guard = tracing::subscriber::set_default(subscriber);
+
+        let client = TestClient::new(make_route());
+
+        client.get("/").send().await.assert_status_is_ok();
+
+        drop(guard);
+
+        let spans = exporter.0.lock().unwrap();
+
+        let request_span = spans
+            .iter()
+            .find(|span| span.name == "gateway_request")
+            .expect("gateway_request span is exported");
+
+        assert_eq!(request_span.parent_span_id, SpanId::INVALID);
+    }
+}
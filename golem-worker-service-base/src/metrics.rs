@@ -12,18 +12,131 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::app_config::ResponseBodyLimitAction;
 use crate::VERSION;
+use golem_common::metrics::DEFAULT_TIME_BUCKETS;
 use lazy_static::lazy_static;
 use prometheus::*;
+use std::time::Duration;
 
 lazy_static! {
     static ref VERSION_INFO: IntCounterVec =
         register_int_counter_vec!("version_info", "Version info of the server", &["version"])
             .unwrap();
+    static ref GATEWAY_IN_FLIGHT_REQUESTS: IntGauge = register_int_gauge!(
+        "gateway_in_flight_requests",
+        "Number of requests currently handled by the custom request server"
+    )
+    .unwrap();
+    static ref GATEWAY_REJECTED_REQUESTS_TOTAL: IntCounter = register_int_counter!(
+        "gateway_rejected_requests_total",
+        "Number of requests rejected because the concurrency limit was reached"
+    )
+    .unwrap();
+    static ref GATEWAY_OVERSIZED_RESPONSES_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "gateway_oversized_responses_total",
+        "Number of responses exceeding the response body limit, by the action taken",
+        &["action"]
+    )
+    .unwrap();
+    static ref GATEWAY_EVALUATION_SECONDS: HistogramVec = register_histogram_vec!(
+        "gateway_evaluation_seconds",
+        "Duration of the evaluation of the response mapping of the routes, including the worker invocations",
+        &["route"],
+        DEFAULT_TIME_BUCKETS.to_vec()
+    )
+    .unwrap();
+    static ref GATEWAY_ROUTE_CIRCUIT_OPENED_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "gateway_route_circuit_opened_total",
+        "Number of times the circuit breaker of a route opened, because of the error rate of its requests",
+        &["api_definition", "route"]
+    )
+    .unwrap();
+    static ref GATEWAY_ROUTE_CIRCUIT_REJECTIONS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "gateway_route_circuit_rejections_total",
+        "Number of requests rejected because the circuit breaker of their route was open",
+        &["api_definition", "route"]
+    )
+    .unwrap();
+    static ref HANDLER_PANICS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "handler_panics_total",
+        "Number of requests whose handler panicked, and got a 500 response",
+        &["server"]
+    )
+    .unwrap();
 }
 
 pub fn register_all() -> Registry {
     VERSION_INFO.with_label_values(&[VERSION]).inc();
+    // Exposed from the start, not only after the first request
+    lazy_static::initialize(&GATEWAY_IN_FLIGHT_REQUESTS);
+    lazy_static::initialize(&GATEWAY_REJECTED_REQUESTS_TOTAL);
 
     default_registry().clone()
 }
+
+pub fn inc_gateway_in_flight_requests() {
+    GATEWAY_IN_FLIGHT_REQUESTS.inc();
+}
+
+pub fn dec_gateway_in_flight_requests() {
+    GATEWAY_IN_FLIGHT_REQUESTS.dec();
+}
+
+pub fn record_gateway_rejected_request() {
+    GATEWAY_REJECTED_REQUESTS_TOTAL.inc();
+}
+
+pub fn gateway_rejected_requests() -> u64 {
+    GATEWAY_REJECTED_REQUESTS_TOTAL.get()
+}
+
+pub fn record_gateway_oversized_response(action: ResponseBodyLimitAction) {
+    GATEWAY_OVERSIZED_RESPONSES_TOTAL
+        .with_label_values(&[action.as_str()])
+        .inc();
+}
+
+pub fn gateway_oversized_responses(action: ResponseBodyLimitAction) -> u64 {
+    GATEWAY_OVERSIZED_RESPONSES_TOTAL
+        .with_label_values(&[action.as_str()])
+        .get()
+}
+
+pub fn record_gateway_route_circuit_opened(api_definition: &str, route: &str) {
+    GATEWAY_ROUTE_CIRCUIT_OPENED_TOTAL
+        .with_label_values(&[api_definition, route])
+        .inc();
+}
+
+pub fn gateway_route_circuit_openings(api_definition: &str, route: &str) -> u64 {
+    GATEWAY_ROUTE_CIRCUIT_OPENED_TOTAL
+        .with_label_values(&[api_definition, route])
+        .get()
+}
+
+pub fn record_gateway_route_circuit_rejection(api_definition: &str, route: &str) {
+    GATEWAY_ROUTE_CIRCUIT_REJECTIONS_TOTAL
+        .with_label_values(&[api_definition, route])
+        .inc();
+}
+
+pub fn record_handler_panic(server: &str) {
+    HANDLER_PANICS_TOTAL.with_label_values(&[server]).inc();
+}
+
+pub fn handler_panics(server: &str) -> u64 {
+    HANDLER_PANICS_TOTAL.with_label_values(&[server]).get()
+}
+
+pub fn record_gateway_evaluation(route: &str, duration: Duration) {
+    GATEWAY_EVALUATION_SECONDS
+        .with_label_values(&[route])
+        .observe(duration.as_secs_f64());
+}
+
+pub fn gateway_evaluations(route: &str) -> u64 {
+    GATEWAY_EVALUATION_SECONDS
+        .with_label_values(&[route])
+        .get_sample_count()
+}
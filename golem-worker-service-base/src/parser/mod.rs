@@ -2,9 +2,62 @@ use std::fmt;
 
 pub(crate) mod path_pattern_parser;
 pub(crate) mod place_holder_parser;
+pub(crate) mod rib_parser;
 
 pub trait GolemParser<T> {
     fn parse(&self, str: &str) -> Result<T, ParseError>;
+
+    // Parses each of the inputs (Ex: all the templates of an api definition) with the same parser.
+    // Unlike stopping at the first failure, every input gets a result, and the errors
+    // tell which input they belong to
+    fn parse_all<S: AsRef<str>>(&self, inputs: &[S]) -> Vec<Result<T, IndexedParseError>>
+    where
+        Self: Sized,
+    {
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                self.parse(input.as_ref())
+                    .map_err(|error| IndexedParseError { index, error })
+            })
+            .collect()
+    }
+
+    // Like `parse_all`, but either all the inputs are parsed, or all the errors are returned
+    fn try_parse_all<S: AsRef<str>>(&self, inputs: &[S]) -> Result<Vec<T>, Vec<IndexedParseError>>
+    where
+        Self: Sized,
+    {
+        let mut parsed = vec![];
+        let mut errors = vec![];
+
+        for result in self.parse_all(inputs) {
+            match result {
+                Ok(value) => parsed.push(value),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(parsed)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// The error of one of the inputs parsed by `GolemParser::parse_all`
+#[derive(Debug, PartialEq)]
+pub struct IndexedParseError {
+    pub index: usize,
+    pub error: ParseError,
+}
+
+impl fmt::Display for IndexedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Input {}: {}", self.index, self.error)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rib::Expr;
+
+use crate::parser::{GolemParser, ParseError};
+
+// Parses the Rib templates (Ex: the response mapping of a route).
+// The parsed templates are cached, so the templates repeated across the routes
+// (Ex: the same worker name) are parsed only once per parser
+#[derive(Default)]
+pub struct RibParser {
+    cache: Mutex<HashMap<String, Expr>>,
+}
+
+impl RibParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GolemParser<Expr> for RibParser {
+    fn parse(&self, input: &str) -> Result<Expr, ParseError> {
+        if let Some(expr) = self.cache.lock().unwrap().get(input) {
+            return Ok(expr.clone());
+        }
+
+        let expr = rib::from_string(input).map_err(ParseError::Message)?;
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(input.to_string(), expr.clone());
+
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rib::Expr;
+
+    use crate::parser::rib_parser::RibParser;
+    use crate::parser::GolemParser;
+
+    #[test]
+    fn test_parse_all_templates() {
+        let templates = vec![
+            "${request.path.user-id}".to_string(),
+            "${if request.path.user-id > 1 then".to_string(),
+            r#"${"user-${request.path.user-id}"}"#.to_string(),
+        ];
+
+        let results = RibParser::new().parse_all(&templates);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0],
+            Ok(Expr::select_field(
+                Expr::select_field(Expr::identifier("request"), "path"),
+                "user-id"
+            ))
+        );
+        assert_eq!(
+            results[2],
+            Ok(Expr::concat(vec![
+                Expr::literal("user-"),
+                Expr::select_field(
+                    Expr::select_field(Expr::identifier("request"), "path"),
+                    "user-id"
+                )
+            ]))
+        );
+
+        let error = results[1].as_ref().unwrap_err();
+        assert_eq!(error.index, 1);
+        assert!(error.to_string().starts_with("Input 1: "));
+    }
+
+    #[test]
+    fn test_try_parse_all_templates() {
+        let parser = RibParser::new();
+
+        let parsed = parser.try_parse_all(&["${foo}", "${foo}", "${bar}"]);
+        assert_eq!(
+            parsed,
+            Ok(vec![
+                Expr::identifier("foo"),
+                Expr::identifier("foo"),
+                Expr::identifier("bar")
+            ])
+        );
+
+        let errors = parser.try_parse_all(&["${foo}", "${foo(}", "${bar}", "${}}"]);
+        assert_eq!(
+            errors
+                .unwrap_err()
+                .into_iter()
+                .map(|error| error.index)
+                .collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+    }
+}
@@ -308,11 +308,35 @@ impl ApiDefinitionRepo for DbApiDefinitionRepo<sqlx::Postgres> {
     }
 }
 
+// Decodes all the stored API definitions of the namespace, and returns an error for each
+// of them (or each of their routes) that can't be decoded. Used to detect broken
+// definitions at startup, instead of failing the requests routed to them
+pub async fn invalid_api_definitions(
+    repo: &(dyn ApiDefinitionRepo + Sync + Send),
+    namespace: &str,
+) -> Result<Vec<String>, RepoError> {
+    let records = repo.get_all(namespace).await?;
+
+    Ok(records
+        .iter()
+        .flat_map(|record| {
+            record_data_serde::invalid_routes(&record.data)
+                .into_iter()
+                .map(|error| {
+                    format!(
+                        "API definition {} (version {}): {}",
+                        record.id, record.version, error
+                    )
+                })
+        })
+        .collect())
+}
+
 pub mod record_data_serde {
     use crate::api_definition::http::CompiledRoute;
     use bytes::{BufMut, Bytes, BytesMut};
     use golem_api_grpc::proto::golem::apidefinition::{
-        CompiledHttpApiDefinition, CompiledHttpRoute,
+        CompiledHttpApiDefinition, CompiledHttpRoute, HttpMethod,
     };
     use prost::Message;
 
@@ -352,4 +376,33 @@ pub mod record_data_serde {
             _ => Err("Unsupported serialization version".to_string()),
         }
     }
+
+    // Like `deserialize`, but decodes every route instead of stopping at the first error.
+    // The errors of the routes are identified by the method and the path of the route
+    pub fn invalid_routes(bytes: &[u8]) -> Vec<String> {
+        let Some((version, data)) = bytes.split_first() else {
+            return vec!["Missing serialization version".to_string()];
+        };
+
+        match *version {
+            SERIALIZATION_VERSION_V1 => match CompiledHttpApiDefinition::decode(data) {
+                Ok(proto_value) => proto_value
+                    .routes
+                    .into_iter()
+                    .filter_map(|route| {
+                        let method = HttpMethod::try_from(route.method)
+                            .map(|method| method.as_str_name().to_string())
+                            .unwrap_or_else(|_| route.method.to_string());
+                        let name = format!("{} {}", method, route.path);
+
+                        CompiledRoute::try_from(route)
+                            .err()
+                            .map(|error| format!("route {}: {}", name, error))
+                    })
+                    .collect(),
+                Err(e) => vec![format!("Failed to deserialize value: {e}")],
+            },
+            _ => vec!["Unsupported serialization version".to_string()],
+        }
+    }
 }
@@ -29,6 +29,7 @@ use chrono::Utc;
 use golem_common::SafeDisplay;
 use golem_service_base::model::{Component, VersionedComponentId};
 use golem_service_base::repo::RepoError;
+use rib::PredicateMode;
 use tracing::{error, info};
 
 use super::api_definition_validator::{ApiDefinitionValidatorService, ValidationErrors};
@@ -161,6 +162,8 @@ pub struct ApiDefinitionServiceDefault<AuthCtx, ValidationError> {
     pub deployment_repo: Arc<dyn ApiDeploymentRepo + Sync + Send>,
     pub api_definition_validator:
         Arc<dyn ApiDefinitionValidatorService<HttpApiDefinition, ValidationError> + Sync + Send>,
+    // Whether the conditions of the Rib scripts must be booleans when they are compiled
+    pub predicate_mode: PredicateMode,
 }
 
 impl<AuthCtx, ValidationError> ApiDefinitionServiceDefault<AuthCtx, ValidationError> {
@@ -177,6 +180,14 @@ impl<AuthCtx, ValidationError> ApiDefinitionServiceDefault<AuthCtx, ValidationEr
             definition_repo,
             deployment_repo,
             api_definition_validator,
+            predicate_mode: PredicateMode::Strict,
+        }
+    }
+
+    pub fn with_predicate_mode(self, predicate_mode: PredicateMode) -> Self {
+        Self {
+            predicate_mode,
+            ..self
         }
     }
 
@@ -266,10 +277,12 @@ where
         let component_metadata_dictionary =
             ComponentMetadataDictionary::from_components(&components);
 
-        let compiled_http_api_definition = CompiledHttpApiDefinition::from_http_api_definition(
-            &definition,
-            &component_metadata_dictionary,
-        )?;
+        let compiled_http_api_definition =
+            CompiledHttpApiDefinition::from_http_api_definition_with_predicate_mode(
+                &definition,
+                &component_metadata_dictionary,
+                self.predicate_mode,
+            )?;
 
         let record = ApiDefinitionRecord::new(
             namespace.clone(),
@@ -321,10 +334,12 @@ where
         let component_metadata_dictionary =
             ComponentMetadataDictionary::from_components(&components);
 
-        let compiled_http_api_definition = CompiledHttpApiDefinition::from_http_api_definition(
-            &definition,
-            &component_metadata_dictionary,
-        )?;
+        let compiled_http_api_definition =
+            CompiledHttpApiDefinition::from_http_api_definition_with_predicate_mode(
+                &definition,
+                &component_metadata_dictionary,
+                self.predicate_mode,
+            )?;
 
         let record = ApiDefinitionRecord::new(
             namespace.clone(),
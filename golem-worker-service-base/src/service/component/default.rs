@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
 use http::Uri;
 use tonic::codec::CompressionEncoding;
@@ -34,6 +36,12 @@ pub trait ComponentService<AuthCtx> {
         component_id: &ComponentId,
         auth_ctx: &AuthCtx,
     ) -> ComponentResult<Component>;
+
+    // Waits for the in-flight calls to finish (on shutdown), up to the given timeout.
+    // Returns the number of calls still in flight
+    async fn drain(&self, _timeout: Duration) -> usize {
+        0
+    }
 }
 
 #[derive(Clone)]
@@ -119,7 +127,7 @@ where
             |(client, id, metadata)| {
                 Box::pin(async move {
                     let response = client
-                        .call(move |client| {
+                        .call("get_component_metadata", move |client| {
                             let request = GetVersionedComponentRequest {
                                 component_id: Some(id.clone().into()),
                                 version,
@@ -156,7 +164,7 @@ where
             |(client, id, metadata)| {
                 Box::pin(async move {
                     let response = client
-                        .call(move |client| {
+                        .call("get_latest_component_metadata", move |client| {
                             let request = GetLatestComponentRequest {
                                 component_id: Some(id.clone().into()),
                             };
@@ -176,4 +184,8 @@ where
 
         Ok(value)
     }
+
+    async fn drain(&self, timeout: Duration) -> usize {
+        self.client.drain(timeout).await
+    }
 }
@@ -4,6 +4,7 @@ use golem_api_grpc::proto::golem::worker::v1::{
     worker_error, worker_execution_error, UnknownError, WorkerError as GrpcWorkerError,
     WorkerExecutionError,
 };
+use golem_common::client::GrpcCallError;
 use golem_common::SafeDisplay;
 
 // The dependents of golem-worker-service-base is expected
@@ -49,6 +50,12 @@ impl From<Status> for ComponentServiceError {
     }
 }
 
+impl From<GrpcCallError> for ComponentServiceError {
+    fn from(error: GrpcCallError) -> Self {
+        ComponentServiceError::FailedGrpcStatus(error.into())
+    }
+}
+
 impl From<tonic::transport::Error> for ComponentServiceError {
     fn from(error: tonic::transport::Error) -> Self {
         ComponentServiceError::FailedTransport(error)
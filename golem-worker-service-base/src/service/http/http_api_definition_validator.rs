@@ -57,7 +57,8 @@ impl ApiDefinitionValidatorService<HttpApiDefinition, RouteValidationError>
         api: &HttpApiDefinition,
         _components: &[Component],
     ) -> Result<(), ValidationErrors<RouteValidationError>> {
-        let errors = unique_routes(api.routes.as_slice());
+        let mut errors = unique_routes(api.routes.as_slice());
+        errors.extend(lint_routes(api.routes.as_slice()));
 
         if errors.is_empty() {
             Ok(())
@@ -73,7 +74,6 @@ fn unique_routes(routes: &[Route]) -> Vec<RouteValidationError> {
     let mut errors = vec![];
 
     for route in routes {
-        let method: hyper::Method = route.method.clone().into();
         let path: Vec<RouterPattern> = route
             .path
             .path_patterns
@@ -82,17 +82,67 @@ fn unique_routes(routes: &[Route]) -> Vec<RouteValidationError> {
             .map(|p| p.into())
             .collect();
 
-        if !router.add_route(method.clone(), path.clone(), route) {
-            let current_route = router.get_route(&method, &path).unwrap();
+        let mut route_methods: Vec<MethodPattern> = vec![];
 
-            let detail = format!("Duplicate route with path: {}", current_route.path);
+        // A route with more methods is checked for conflicts with each of them
+        for method_pattern in route.all_methods() {
+            if route_methods.contains(&method_pattern) {
+                errors.push(RouteValidationError::from_route(
+                    route.clone(),
+                    format!(
+                        "Method {} is listed more than once in the route",
+                        method_pattern
+                    ),
+                ));
+                continue;
+            }
+            route_methods.push(method_pattern.clone());
+
+            let method: hyper::Method = method_pattern.clone().into();
+
+            if !router.add_route(method.clone(), path.clone(), route) {
+                let current_route = router.get_route(&method, &path).unwrap();
+
+                let detail = format!("Duplicate route with path: {}", current_route.path);
+
+                errors.push(RouteValidationError {
+                    method: method_pattern,
+                    path: route.path.to_string(),
+                    component: route.binding.component_id.clone(),
+                    detail,
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+// The problems the lints find in the templates (Ex: a typo like `request.pat`) are rejected
+// like the conflicting routes, as otherwise they would only fail once a request is handled
+fn lint_routes(routes: &[Route]) -> Vec<RouteValidationError> {
+    let mut errors = vec![];
+
+    for route in routes {
+        let binding = &route.binding;
+        let mut templates = vec![
+            ("response", &binding.response.0),
+            ("worker name", &binding.worker_name),
+        ];
+        templates.extend(
+            binding
+                .idempotency_key
+                .as_ref()
+                .map(|idempotency_key| ("idempotency key", idempotency_key)),
+        );
 
-            errors.push(RouteValidationError {
-                method: route.method.clone(),
-                path: route.path.to_string(),
-                component: route.binding.component_id.clone(),
-                detail,
-            });
+        for (template, expr) in templates {
+            for message in rib::lint(expr) {
+                errors.push(RouteValidationError::from_route(
+                    route.clone(),
+                    format!("Invalid {}: {}", template, message),
+                ));
+            }
         }
     }
 
@@ -102,30 +152,31 @@ fn unique_routes(routes: &[Route]) -> Vec<RouteValidationError> {
 #[cfg(test)]
 mod tests {
     use crate::api_definition::http::{MethodPattern, Route};
-    use crate::service::http::http_api_definition_validator::unique_routes;
+    use crate::service::http::http_api_definition_validator::{lint_routes, unique_routes};
     use crate::worker_binding::ResponseMapping;
     use golem_common::model::ComponentId;
     use golem_service_base::model::VersionedComponentId;
     use rib::Expr;
 
-    #[test]
-    fn test_unique_routes() {
-        fn make_route(method: MethodPattern, path: &str) -> Route {
-            Route {
-                method,
-                path: crate::api_definition::http::AllPathPatterns::parse(path).unwrap(),
-                binding: crate::worker_binding::GolemWorkerBinding {
-                    component_id: VersionedComponentId {
-                        component_id: ComponentId::new_v4(),
-                        version: 1,
-                    },
-                    worker_name: Expr::identifier("request"),
-                    idempotency_key: None,
-                    response: ResponseMapping(Expr::literal("sample")),
+    fn make_route(method: MethodPattern, path: &str) -> Route {
+        Route {
+            method,
+            methods: vec![],
+            path: crate::api_definition::http::AllPathPatterns::parse(path).unwrap(),
+            binding: crate::worker_binding::GolemWorkerBinding {
+                component_id: VersionedComponentId {
+                    component_id: ComponentId::new_v4(),
+                    version: 1,
                 },
-            }
+                worker_name: Expr::identifier("request"),
+                idempotency_key: None,
+                response: ResponseMapping(Expr::literal("sample")),
+            },
         }
+    }
 
+    #[test]
+    fn test_unique_routes() {
         let paths = &[
             "/users/{id}/posts/{post_id}",
             "/users/{id}/posts/{post_id}/comments/{comment_id}",
@@ -155,4 +206,56 @@ mod tests {
         assert!(errors.len() == 1);
         assert!(errors[0].detail.contains(paths[0]), "Received: {errors:?}");
     }
+
+    #[test]
+    fn test_unique_routes_with_more_methods() {
+        let get_or_head = Route {
+            methods: vec![MethodPattern::Head],
+            ..make_route(MethodPattern::Get, "/carts/{cart-id}")
+        };
+        let listed_twice = Route {
+            methods: vec![MethodPattern::Put, MethodPattern::Put],
+            ..make_route(MethodPattern::Post, "/carts")
+        };
+        let head = make_route(MethodPattern::Head, "/carts/{id}");
+
+        let errors = unique_routes(&[get_or_head.clone(), listed_twice]);
+        assert_eq!(errors.len(), 1, "Received: {errors:?}");
+        assert_eq!(
+            errors[0].detail,
+            "Method Put is listed more than once in the route"
+        );
+
+        let errors = unique_routes(&[get_or_head, head]);
+        assert_eq!(errors.len(), 1, "Received: {errors:?}");
+        assert_eq!(errors[0].method, MethodPattern::Head);
+        assert!(errors[0].detail.contains("/carts/{cart-id}"));
+    }
+
+    #[test]
+    fn test_lint_routes() {
+        let valid = Route {
+            binding: crate::worker_binding::GolemWorkerBinding {
+                response: ResponseMapping(
+                    Expr::from_text("{body: worker.response, status: 200}").unwrap(),
+                ),
+                ..make_route(MethodPattern::Get, "/carts/{cart-id}").binding
+            },
+            ..make_route(MethodPattern::Get, "/carts/{cart-id}")
+        };
+        let typo = Route {
+            binding: crate::worker_binding::GolemWorkerBinding {
+                worker_name: Expr::from_text("request.pat.cart-id").unwrap(),
+                ..make_route(MethodPattern::Post, "/carts/{cart-id}").binding
+            },
+            ..make_route(MethodPattern::Post, "/carts/{cart-id}")
+        };
+
+        let errors = lint_routes(&[valid, typo]);
+        assert_eq!(errors.len(), 1, "Received: {errors:?}");
+        assert_eq!(errors[0].method, MethodPattern::Post);
+        assert!(errors[0]
+            .detail
+            .starts_with("Invalid worker name: `request.pat` is not a field of the request"));
+    }
 }
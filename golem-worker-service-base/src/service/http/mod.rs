@@ -1 +1,2 @@
 pub mod http_api_definition_validator;
+pub mod route_validation;
@@ -0,0 +1,187 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::api::HttpApiDefinitionRequest;
+use crate::api_definition::http::{HttpApiDefinition, Route};
+use crate::service::api_definition_validator::ApiDefinitionValidatorService;
+use crate::service::http::http_api_definition_validator::HttpApiDefinitionValidator;
+
+// Validates the routes of an API definition the same way registering it does, without the
+// components (and so without type checking the templates against them). Unlike registering,
+// every route is checked, so a single run reports all the invalid ones.
+// Returns the number of the (valid) routes, or the report of the invalid ones
+pub fn validate_routes(definition: HttpApiDefinitionRequest) -> Result<usize, Vec<String>> {
+    let mut routes: Vec<Route> = Vec::new();
+    let mut report = Vec::new();
+
+    for route in definition.routes {
+        let method = route.method.clone();
+        let path = route.path.clone();
+
+        match route.try_into() {
+            Ok(route) => routes.push(route),
+            Err(error) => report.push(format!("{} {}: {}", method, path, error)),
+        }
+    }
+
+    let route_count = routes.len() + report.len();
+
+    let definition = HttpApiDefinition::new(
+        crate::api_definition::http::HttpApiDefinitionRequest {
+            id: definition.id,
+            version: definition.version,
+            routes,
+            draft: definition.draft,
+        },
+        chrono::Utc::now(),
+    );
+
+    let validator = HttpApiDefinitionValidator {};
+
+    if let Err(errors) = validator.validate(&definition, &[]) {
+        report.extend(
+            errors
+                .errors
+                .into_iter()
+                .map(|error| format!("{} {}: {}", error.method, error.path, error.detail)),
+        );
+    }
+
+    if report.is_empty() {
+        Ok(route_count)
+    } else {
+        Err(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{GolemWorkerBinding, HttpApiDefinitionRequest, Route};
+    use crate::api_definition::http::MethodPattern;
+    use crate::api_definition::{ApiDefinitionId, ApiVersion};
+    use crate::service::http::route_validation::validate_routes;
+    use golem_common::model::ComponentId;
+    use golem_service_base::model::VersionedComponentId;
+
+    fn route(method: MethodPattern, path: &str, response: &str) -> Route {
+        Route {
+            method,
+            methods: vec![],
+            path: path.to_string(),
+            binding: GolemWorkerBinding {
+                component_id: VersionedComponentId {
+                    component_id: ComponentId::new_v4(),
+                    version: 0,
+                },
+                worker_name: "\"shopping-cart\"".to_string(),
+                idempotency_key: None,
+                response: response.to_string(),
+            },
+        }
+    }
+
+    fn definition(routes: Vec<Route>) -> HttpApiDefinitionRequest {
+        HttpApiDefinitionRequest {
+            id: ApiDefinitionId("shopping-cart".to_string()),
+            version: ApiVersion("0.0.1".to_string()),
+            routes,
+            draft: true,
+        }
+    }
+
+    #[test]
+    fn test_valid_routes() {
+        let result = validate_routes(definition(vec![
+            route(
+                MethodPattern::Get,
+                "/carts/{cart-id}",
+                "${request.path.cart-id}",
+            ),
+            route(MethodPattern::Post, "/carts/{cart-id}", "${request.body}"),
+        ]));
+
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn test_routes_with_more_methods() {
+        let get_or_head = Route {
+            methods: vec![MethodPattern::Head],
+            ..route(
+                MethodPattern::Get,
+                "/carts/{cart-id}",
+                "${request.path.cart-id}",
+            )
+        };
+        let listed_twice = Route {
+            methods: vec![MethodPattern::Put, MethodPattern::Put],
+            ..route(MethodPattern::Post, "/carts", "${request.body}")
+        };
+        let conflicting = route(MethodPattern::Head, "/carts/{id}", "${request.path.id}");
+
+        let result =
+            validate_routes(definition(vec![get_or_head, listed_twice, conflicting])).unwrap_err();
+
+        assert_eq!(
+            result,
+            vec![
+                "Post /carts: Method Put is listed more than once in the route".to_string(),
+                "Head /carts/{id}: Duplicate route with path: /carts/{cart-id}".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_all_invalid_routes_are_reported() {
+        let result = validate_routes(definition(vec![
+            route(
+                MethodPattern::Get,
+                "/carts/{cart-id}",
+                "${request.path.cart-id}",
+            ),
+            route(MethodPattern::Put, "/carts/{cart-id}", "${if then}"),
+            route(MethodPattern::Get, "/carts/{id}", "${request.path.id}"),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(result.len(), 2);
+        assert!(result[0].starts_with("Put /carts/{cart-id}: Invalid response"));
+        assert!(result[1].starts_with("Get /carts/{id}: Duplicate route"));
+    }
+
+    #[test]
+    fn test_lints_are_reported() {
+        let result = validate_routes(definition(vec![
+            route(
+                MethodPattern::Get,
+                "/carts/{cart-id}",
+                "${request.pat.cart-id}",
+            ),
+            route(
+                MethodPattern::Post,
+                "/carts/{cart-id}",
+                "${{body: worker.respone}}",
+            ),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(result.len(), 2);
+        assert!(result[0].starts_with(
+            "Get /carts/{cart-id}: Invalid response: `request.pat` is not a field of the request"
+        ));
+        assert!(result[1].starts_with(
+            "Post /carts/{cart-id}: Invalid response: `worker.respone` is not a field of the worker"
+        ));
+    }
+}
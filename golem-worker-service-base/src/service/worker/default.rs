@@ -309,6 +309,7 @@ where
         let worker_id_clone = worker_id.clone();
         self.call_worker_executor(
             worker_id.clone(),
+            "create_worker",
             move |worker_executor_client| {
                 info!("Create worker");
                 let worker_id = worker_id_clone.clone();
@@ -348,6 +349,7 @@ where
         let stream = self
             .call_worker_executor(
                 worker_id.clone(),
+                "connect_worker",
                 move |worker_executor_client| {
                     info!("Connect worker");
                     Box::pin(worker_executor_client.connect_worker(ConnectWorkerRequest {
@@ -381,6 +383,7 @@ where
         let worker_id = worker_id.clone();
         self.call_worker_executor(
             worker_id.clone(),
+            "delete_worker",
             move |worker_executor_client| {
                 info!("Delete worker");
                 let worker_id = worker_id.clone();
@@ -437,6 +440,7 @@ where
 
         let invoke_response = self.call_worker_executor(
             worker_id.clone(),
+            "invoke_and_await_worker_typed",
             move |worker_executor_client| {
                 info!("Invoking function on {}: {}", worker_id_clone, function_name);
                 Box::pin(worker_executor_client.invoke_and_await_worker_typed(
@@ -498,6 +502,7 @@ where
 
         let invoke_response = self.call_worker_executor(
             worker_id.clone(),
+            "invoke_and_await_worker",
             move |worker_executor_client| {
                 info!("Invoke and await function");
                 Box::pin(worker_executor_client.invoke_and_await_worker(
@@ -556,6 +561,7 @@ where
         let worker_id = worker_id.clone();
         self.call_worker_executor(
             worker_id.clone(),
+            "invoke_worker",
             move |worker_executor_client| {
                 info!("Invoke function");
                 let worker_id = worker_id.clone();
@@ -605,6 +611,7 @@ where
         let result = self
             .call_worker_executor(
                 worker_id.clone(),
+                "complete_promise",
                 move |worker_executor_client| {
                     info!("Complete promise");
                     let promise_id = promise_id.clone();
@@ -653,6 +660,7 @@ where
         let worker_id = worker_id.clone();
         self.call_worker_executor(
             worker_id.clone(),
+            "interrupt_worker",
             move |worker_executor_client| {
                 info!("Interrupt");
                 let worker_id = worker_id.clone();
@@ -690,6 +698,7 @@ where
         let worker_id = worker_id.clone();
         let metadata = self.call_worker_executor(
             worker_id.clone(),
+            "get_worker_metadata",
             move |worker_executor_client| {
                 let worker_id = worker_id.clone();
                 info!("Get metadata");
@@ -766,6 +775,7 @@ where
         let worker_id = worker_id.clone();
         self.call_worker_executor(
             worker_id.clone(),
+            "resume_worker",
             move |worker_executor_client| {
                 let worker_id = worker_id.clone();
                 Box::pin(worker_executor_client.resume_worker(ResumeWorkerRequest {
@@ -799,6 +809,7 @@ where
         let worker_id = worker_id.clone();
         self.call_worker_executor(
             worker_id.clone(),
+            "update_worker",
             move |worker_executor_client| {
                 info!("Update worker");
                 let worker_id = worker_id.clone();
@@ -846,6 +857,7 @@ where
         let worker_id = worker_id.clone();
         self.call_worker_executor(
             worker_id.clone(),
+            "get_oplog",
             move |worker_executor_client| {
                 info!("Get oplog");
                 let worker_id = worker_id.clone();
@@ -939,6 +951,7 @@ where
         let component_id = component_id.clone();
         let result = self.call_worker_executor(
             AllExecutors,
+            "get_running_workers_metadata",
             move |worker_executor_client| {
                 let component_id: golem_api_grpc::proto::golem::component::ComponentId =
                     component_id.clone().into();
@@ -996,6 +1009,7 @@ where
         let result = self
             .call_worker_executor(
                 RandomExecutor,
+                "get_workers_metadata",
                 move |worker_executor_client| {
                     let component_id: golem_api_grpc::proto::golem::component::ComponentId =
                         component_id.clone().into();
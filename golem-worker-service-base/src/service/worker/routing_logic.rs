@@ -26,7 +26,7 @@ use tracing::{debug, error, info, warn, Instrument};
 
 use golem_api_grpc::proto::golem::worker::v1::WorkerExecutionError;
 use golem_api_grpc::proto::golem::workerexecutor::v1::worker_executor_client::WorkerExecutorClient;
-use golem_common::client::MultiTargetGrpcClient;
+use golem_common::client::{GrpcCallError, MultiTargetGrpcClient};
 use golem_common::config::RetryConfig;
 use golem_common::model::{Pod, ShardId, TargetWorkerId, WorkerId};
 use golem_common::retriable_error::IsRetriableError;
@@ -42,6 +42,7 @@ pub trait RoutingLogic {
     async fn call_worker_executor<Target, F, G, H, Out, R>(
         &self,
         target: Target,
+        description: &'static str,
         remote_call: F,
         response_map: G,
         error_map: H,
@@ -69,6 +70,7 @@ pub trait CallOnExecutor<Out: Send + 'static> {
     async fn call_on_worker_executor<F>(
         &self,
         context: &(impl HasRoutingTableService + HasWorkerExecutorClients + Send + Sync),
+        description: &'static str,
         f: F,
     ) -> Result<(Option<Self::ResultOut>, Option<Pod>), CallWorkerExecutorErrorWithContext>
     where
@@ -91,6 +93,7 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for WorkerId {
     async fn call_on_worker_executor<F>(
         &self,
         context: &(impl HasRoutingTableService + HasWorkerExecutorClients + Send + Sync),
+        description: &'static str,
         f: F,
     ) -> Result<(Option<Self::ResultOut>, Option<Pod>), CallWorkerExecutorErrorWithContext>
     where
@@ -115,7 +118,7 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for WorkerId {
                 Some(
                     context
                         .worker_executor_clients()
-                        .call(pod.uri_02(), f)
+                        .call(pod.uri_02(), description, f)
                         .await
                         .map_err(|err| {
                             CallWorkerExecutorErrorWithContext::failed_to_connect_to_pod(
@@ -141,6 +144,7 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for TargetWorkerId {
     async fn call_on_worker_executor<F>(
         &self,
         context: &(impl HasRoutingTableService + HasWorkerExecutorClients + Send + Sync),
+        description: &'static str,
         f: F,
     ) -> Result<(Option<Self::ResultOut>, Option<Pod>), CallWorkerExecutorErrorWithContext>
     where
@@ -155,11 +159,15 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for TargetWorkerId {
     {
         if let Some(worker_id) = self.clone().try_into_worker_id() {
             // The TargetWorkerId had a worker name so we know which shard we need to call it on
-            worker_id.call_on_worker_executor(context, f).await
+            worker_id
+                .call_on_worker_executor(context, description, f)
+                .await
         } else {
             // The TargetWorkerId did not have a worker name specified so we can forward the call to a random
             // executor
-            RandomExecutor.call_on_worker_executor(context, f).await
+            RandomExecutor
+                .call_on_worker_executor(context, description, f)
+                .await
         }
     }
 
@@ -181,6 +189,7 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for RandomExecutor {
     async fn call_on_worker_executor<F>(
         &self,
         context: &(impl HasRoutingTableService + HasWorkerExecutorClients + Send + Sync),
+        description: &'static str,
         f: F,
     ) -> Result<(Option<Self::ResultOut>, Option<Pod>), CallWorkerExecutorErrorWithContext>
     where
@@ -205,11 +214,11 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for RandomExecutor {
                 Some(
                     context
                         .worker_executor_clients()
-                        .call(pod.uri_02(), f)
+                        .call(pod.uri_02(), description, f)
                         .await
-                        .map_err(|status| {
+                        .map_err(|err| {
                             CallWorkerExecutorErrorWithContext::failed_to_connect_to_pod(
-                                status,
+                                err,
                                 pod.clone(),
                             )
                         })?,
@@ -233,6 +242,7 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for AllExecutors {
     async fn call_on_worker_executor<F>(
         &self,
         context: &(impl HasRoutingTableService + HasWorkerExecutorClients + Send + Sync),
+        description: &'static str,
         f: F,
     ) -> Result<(Option<Self::ResultOut>, Option<Pod>), CallWorkerExecutorErrorWithContext>
     where
@@ -263,7 +273,7 @@ impl<Out: Send + 'static> CallOnExecutor<Out> for AllExecutors {
                     let f = f.clone();
                     async move {
                         worker_executor_clients
-                            .call(pod.uri_02(), f)
+                            .call(pod.uri_02(), description, f)
                             .await
                             .map_err(|err| (err, pod))
                     }
@@ -349,6 +359,7 @@ impl<T: HasRoutingTableService + HasWorkerExecutorClients + Send + Sync> Routing
     async fn call_worker_executor<Target, F, G, H, Out, R>(
         &self,
         target: Target,
+        description: &'static str,
         remote_call: F,
         response_map: G,
         error_map: H,
@@ -373,7 +384,7 @@ impl<T: HasRoutingTableService + HasWorkerExecutorClients + Send + Sync> Routing
             let span = retry.start_attempt(Target::tracing_kind(&target));
 
             let worker_result = target
-                .call_on_worker_executor(self, remote_call.clone())
+                .call_on_worker_executor(self, description, remote_call.clone())
                 .await;
 
             let result = async {
@@ -427,6 +438,8 @@ pub enum CallWorkerExecutorError {
     FailedToGetRoutingTable(RoutingTableError),
     #[error("Failed to connect to pod: {} {}", .0.code(), .0.message())]
     FailedToConnectToPod(Status),
+    #[error("The response of the worker executor exceeds the limit of {limit} bytes. The invoked function has to return less data")]
+    ResponseTooLarge { limit: usize },
 }
 
 impl SafeDisplay for CallWorkerExecutorError {
@@ -434,6 +447,7 @@ impl SafeDisplay for CallWorkerExecutorError {
         match self {
             CallWorkerExecutorError::FailedToGetRoutingTable(_) => self.to_string(),
             CallWorkerExecutorError::FailedToConnectToPod(_) => self.to_string(),
+            CallWorkerExecutorError::ResponseTooLarge { .. } => self.to_string(),
         }
     }
 }
@@ -451,9 +465,16 @@ impl CallWorkerExecutorErrorWithContext {
         }
     }
 
-    fn failed_to_connect_to_pod(status: Status, pod: Pod) -> Self {
+    fn failed_to_connect_to_pod(error: GrpcCallError, pod: Pod) -> Self {
+        let error = match error {
+            GrpcCallError::ResponseTooLarge { limit } => {
+                CallWorkerExecutorError::ResponseTooLarge { limit }
+            }
+            GrpcCallError::Status(status) => CallWorkerExecutorError::FailedToConnectToPod(status),
+        };
+
         CallWorkerExecutorErrorWithContext {
-            error: CallWorkerExecutorError::FailedToConnectToPod(status),
+            error,
             pod: Some(pod),
         }
     }
@@ -464,6 +485,7 @@ impl IsRetriableError for CallWorkerExecutorError {
         match self {
             CallWorkerExecutorError::FailedToGetRoutingTable(error) => error.is_retriable(),
             CallWorkerExecutorError::FailedToConnectToPod(status) => status.is_retriable(),
+            CallWorkerExecutorError::ResponseTooLarge { .. } => false,
         }
     }
 
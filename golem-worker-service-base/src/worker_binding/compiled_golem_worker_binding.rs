@@ -2,8 +2,9 @@ use crate::worker_binding::{GolemWorkerBinding, ResponseMapping};
 use crate::worker_service_rib_compiler::{DefaultRibCompiler, WorkerServiceRibCompiler};
 use bincode::{Decode, Encode};
 use golem_service_base::model::VersionedComponentId;
-use golem_wasm_ast::analysis::AnalysedExport;
-use rib::{Expr, RibByteCode, RibInputTypeInfo};
+use golem_wasm_ast::analysis::{AnalysedExport, AnalysedType};
+use rib::{Expr, PredicateMode, RibByteCode, RibInputTypeInfo};
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
 pub struct CompiledGolemWorkerBinding {
@@ -17,21 +18,25 @@ impl CompiledGolemWorkerBinding {
     pub fn from_golem_worker_binding(
         golem_worker_binding: &GolemWorkerBinding,
         export_metadata: &[AnalysedExport],
+        predicate_mode: PredicateMode,
     ) -> Result<Self, String> {
         let worker_name_compiled = WorkerNameCompiled::from_worker_name(
             &golem_worker_binding.worker_name,
             export_metadata,
+            predicate_mode,
         )?;
         let idempotency_key_compiled = match &golem_worker_binding.idempotency_key {
             Some(idempotency_key) => Some(IdempotencyKeyCompiled::from_idempotency_key(
                 idempotency_key,
                 export_metadata,
+                predicate_mode,
             )?),
             None => None,
         };
         let response_compiled = ResponseMappingCompiled::from_response_mapping(
             &golem_worker_binding.response,
             export_metadata,
+            predicate_mode,
         )?;
 
         Ok(CompiledGolemWorkerBinding {
@@ -41,6 +46,75 @@ impl CompiledGolemWorkerBinding {
             response_compiled,
         })
     }
+
+    // Whether any of the Rib scripts of the binding reads the request body.
+    // If none does, the body of the request doesn't have to be read
+    pub fn references_request_body(&self) -> bool {
+        self.references(&["request", "body"])
+    }
+
+    // Whether any of the Rib scripts of the binding reads the body as it was sent
+    // (`request.rawBody`), instead of (or besides) its JSON value
+    pub fn references_raw_request_body(&self) -> bool {
+        self.references(&["request", "rawBody"])
+    }
+
+    fn references(&self, path: &[&str]) -> bool {
+        self.worker_name_compiled
+            .rib_input_type_info
+            .is_referenced(path)
+            || self
+                .idempotency_key_compiled
+                .as_ref()
+                .is_some_and(|compiled| compiled.rib_input.is_referenced(path))
+            || self.response_compiled.rib_input.is_referenced(path)
+    }
+
+    // The fields of the request body read by the Rib scripts of the binding, if they only read
+    // its fields directly (Ex: `request.body.kind`). Only these fields have to be read from the body,
+    // which can then be streamed instead of buffered. None if the body is read deeper
+    // (Ex: `request.body.item.kind`, `request.body.items[0]`) or as a whole (Ex: a list)
+    pub fn request_body_fields(&self) -> Option<HashSet<String>> {
+        let mut rib_inputs = vec![
+            &self.worker_name_compiled.rib_input_type_info,
+            &self.response_compiled.rib_input,
+        ];
+        rib_inputs.extend(
+            self.idempotency_key_compiled
+                .as_ref()
+                .map(|compiled| &compiled.rib_input),
+        );
+
+        let mut fields = HashSet::new();
+
+        for rib_input in rib_inputs {
+            let body = match rib_input.types.get("request") {
+                Some(AnalysedType::Record(request)) => request
+                    .fields
+                    .iter()
+                    .find(|field| field.name == "body")
+                    .map(|field| &field.typ),
+                _ => None,
+            };
+
+            match body {
+                Some(AnalysedType::Record(body)) => {
+                    for field in &body.fields {
+                        match field.typ {
+                            AnalysedType::Record(_) | AnalysedType::List(_) => return None,
+                            _ => {
+                                fields.insert(field.name.clone());
+                            }
+                        }
+                    }
+                }
+                Some(_) => return None,
+                None => {}
+            }
+        }
+
+        Some(fields)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
@@ -54,8 +128,10 @@ impl WorkerNameCompiled {
     pub fn from_worker_name(
         worker_name: &Expr,
         exports: &[AnalysedExport],
+        predicate_mode: PredicateMode,
     ) -> Result<Self, String> {
-        let worker_name_compiled = DefaultRibCompiler::compile(worker_name, exports)?;
+        let worker_name_compiled =
+            DefaultRibCompiler::compile(worker_name, exports, predicate_mode)?;
 
         Ok(WorkerNameCompiled {
             worker_name: worker_name.clone(),
@@ -76,8 +152,10 @@ impl IdempotencyKeyCompiled {
     pub fn from_idempotency_key(
         idempotency_key: &Expr,
         exports: &[AnalysedExport],
+        predicate_mode: PredicateMode,
     ) -> Result<Self, String> {
-        let idempotency_key_compiled = DefaultRibCompiler::compile(idempotency_key, exports)?;
+        let idempotency_key_compiled =
+            DefaultRibCompiler::compile(idempotency_key, exports, predicate_mode)?;
 
         Ok(IdempotencyKeyCompiled {
             idempotency_key: idempotency_key.clone(),
@@ -98,8 +176,10 @@ impl ResponseMappingCompiled {
     pub fn from_response_mapping(
         response_mapping: &ResponseMapping,
         exports: &[AnalysedExport],
+        predicate_mode: PredicateMode,
     ) -> Result<Self, String> {
-        let response_compiled = DefaultRibCompiler::compile(&response_mapping.0, exports)?;
+        let response_compiled =
+            DefaultRibCompiler::compile(&response_mapping.0, exports, predicate_mode)?;
 
         Ok(ResponseMappingCompiled {
             response_rib_expr: response_mapping.0.clone(),
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::worker_binding::CompiledGolemWorkerBinding;
 use golem_service_base::model::VersionedComponentId;
-use rib::Expr;
+use rib::{Expr, Pipeline};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode)]
 #[serde(rename_all = "camelCase")]
@@ -18,6 +18,15 @@ pub struct GolemWorkerBinding {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub struct ResponseMapping(pub Expr);
 
+// The steps of a pipeline are evaluated in order, as a single expression
+impl TryFrom<Pipeline> for ResponseMapping {
+    type Error = String;
+
+    fn try_from(pipeline: Pipeline) -> Result<Self, Self::Error> {
+        pipeline.to_expr().map(ResponseMapping)
+    }
+}
+
 impl From<CompiledGolemWorkerBinding> for GolemWorkerBinding {
     fn from(value: CompiledGolemWorkerBinding) -> Self {
         let worker_binding = value.clone();
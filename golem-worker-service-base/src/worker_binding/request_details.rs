@@ -1,5 +1,6 @@
 use crate::api_definition::http::{QueryInfo, VarInfo};
 
+use http::header::COOKIE;
 use http::HeaderMap;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -11,7 +12,7 @@ pub enum RequestDetails {
 impl RequestDetails {
     pub fn from(
         path_params: &HashMap<VarInfo, &str>,
-        query_variable_values: &HashMap<String, String>,
+        query_variable_values: &HashMap<String, Vec<String>>,
         query_variable_names: &[QueryInfo],
         request_body: &Value,
         headers: &HeaderMap,
@@ -25,6 +26,18 @@ impl RequestDetails {
         )?))
     }
 
+    // The body as it was sent, readable as `request.rawBody`
+    pub fn with_raw_body(self, raw_body: Option<String>) -> Self {
+        match self {
+            RequestDetails::Http(http_request_details) => {
+                RequestDetails::Http(HttpRequestDetails {
+                    request_raw_body: raw_body,
+                    ..http_request_details
+                })
+            }
+        }
+    }
+
     pub fn as_json(&self) -> Value {
         match self {
             RequestDetails::Http(http_request_details) => {
@@ -51,14 +64,29 @@ impl RequestDetails {
 
                 let header_value = Value::Object(header_records);
 
-                Value::Object(serde_json::Map::from_iter(vec![
+                let mut cookie_records = serde_json::Map::new();
+
+                for field in http_request_details.request_cookie_values.0.fields.iter() {
+                    cookie_records.insert(field.name.clone(), field.value.clone());
+                }
+
+                let cookie_value = Value::Object(cookie_records);
+
+                let mut request = serde_json::Map::from_iter(vec![
                     ("path".to_string(), merged_request_path_and_query),
                     (
                         "body".to_string(),
                         http_request_details.request_body.0.clone(),
                     ),
                     ("headers".to_string(), header_value),
-                ]))
+                    ("cookies".to_string(), cookie_value),
+                ]);
+
+                if let Some(raw_body) = &http_request_details.request_raw_body {
+                    request.insert("rawBody".to_string(), Value::String(raw_body.clone()));
+                }
+
+                Value::Object(request)
             }
         }
     }
@@ -70,6 +98,8 @@ pub struct HttpRequestDetails {
     pub request_body: RequestBody,
     pub request_query_values: RequestQueryValues,
     pub request_header_values: RequestHeaderValues,
+    pub request_cookie_values: RequestCookieValues,
+    pub request_raw_body: Option<String>,
 }
 
 impl HttpRequestDetails {
@@ -79,6 +109,8 @@ impl HttpRequestDetails {
             request_body: RequestBody(Value::Null),
             request_query_values: RequestQueryValues(JsonKeyValues::default()),
             request_header_values: RequestHeaderValues(JsonKeyValues::default()),
+            request_cookie_values: RequestCookieValues(JsonKeyValues::default()),
+            request_raw_body: None,
         }
     }
 
@@ -93,7 +125,7 @@ impl HttpRequestDetails {
 
     fn from_input_http_request(
         path_params: &HashMap<VarInfo, &str>,
-        query_variable_values: &HashMap<String, String>,
+        query_variable_values: &HashMap<String, Vec<String>>,
         query_variable_names: &[QueryInfo],
         request_body: &Value,
         headers: &HeaderMap,
@@ -102,12 +134,15 @@ impl HttpRequestDetails {
         let path_params = RequestPathValues::from(path_params);
         let query_params = RequestQueryValues::from(query_variable_values, query_variable_names)?;
         let header_params = RequestHeaderValues::from(headers)?;
+        let cookie_params = RequestCookieValues::from(headers);
 
         Ok(Self {
             request_path_values: path_params,
             request_body,
             request_query_values: query_params,
             request_header_values: header_params,
+            request_cookie_values: cookie_params,
+            request_raw_body: None,
         })
     }
 }
@@ -135,8 +170,12 @@ impl RequestPathValues {
 pub struct RequestQueryValues(pub JsonKeyValues);
 
 impl RequestQueryValues {
+    // A query key that occurs once is a single value, and a repeated one (Ex: `?tag=a&tag=b`)
+    // is a list of the values in the order they appear. The value is adapted to the type the
+    // expression reads it as (a single occurrence to a list, or a list to its last value)
+    // when it is resolved as a Rib input
     fn from(
-        query_key_values: &HashMap<String, String>,
+        query_key_values: &HashMap<String, Vec<String>>,
         query_keys: &[QueryInfo],
     ) -> Result<RequestQueryValues, Vec<String>> {
         let mut unavailable_query_variables: Vec<String> = vec![];
@@ -144,8 +183,16 @@ impl RequestQueryValues {
 
         for spec_query_variable in query_keys.iter() {
             let key = &spec_query_variable.key_name;
-            if let Some(query_value) = query_key_values.get(key) {
-                let typed_value = internal::refine_json_str_value(query_value);
+            if let Some(query_values) = query_key_values.get(key) {
+                let typed_value = match query_values.as_slice() {
+                    [query_value] => internal::refine_json_str_value(query_value),
+                    query_values => Value::Array(
+                        query_values
+                            .iter()
+                            .map(internal::refine_json_str_value)
+                            .collect(),
+                    ),
+                };
                 query_variable_map.push(key.clone(), typed_value);
             } else {
                 unavailable_query_variables.push(spec_query_variable.to_string());
@@ -178,6 +225,31 @@ impl RequestHeaderValues {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct RequestCookieValues(JsonKeyValues);
+
+impl RequestCookieValues {
+    // The cookies of the `Cookie` headers (RFC 6265), Ex: `Cookie: session=abc; theme="dark"`.
+    // The values are unquoted and percent-decoded. A cookie sent more than once has its first value
+    fn from(headers: &HeaderMap) -> RequestCookieValues {
+        let mut cookies_map: JsonKeyValues = JsonKeyValues::default();
+
+        for header_value in headers.get_all(COOKIE) {
+            let Ok(header_value_str) = header_value.to_str() else {
+                continue;
+            };
+
+            for (name, value) in internal::parse_cookies(header_value_str) {
+                if !cookies_map.fields.iter().any(|field| field.name == name) {
+                    cookies_map.push(name, internal::refine_json_str_value(value));
+                }
+            }
+        }
+
+        RequestCookieValues(cookies_map)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RequestBody(Value);
 
@@ -226,4 +298,112 @@ mod internal {
             LiteralValue::Bool(value) => Value::Bool(value),
         }
     }
+
+    // The `name=value` pairs of a `Cookie` header, separated by `;`.
+    // Pairs without a name are skipped, like the user agents do
+    pub(crate) fn parse_cookies(header_value: &str) -> Vec<(String, String)> {
+        header_value
+            .split(';')
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                let name = name.trim();
+
+                if name.is_empty() {
+                    return None;
+                }
+
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+
+                Some((name.to_string(), percent_decode(value)))
+            })
+            .collect()
+    }
+
+    // Decodes the `%XX` escapes, keeping the malformed ones as they are
+    fn percent_decode(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let escaped = match bytes.get(index + 1..index + 3) {
+                Some([high, low]) if bytes[index] == b'%' => {
+                    match ((*high as char).to_digit(16), (*low as char).to_digit(16)) {
+                        (Some(high), Some(low)) => Some((high * 16 + low) as u8),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+
+            match escaped {
+                Some(byte) => {
+                    decoded.push(byte);
+                    index += 3;
+                }
+                None => {
+                    decoded.push(bytes[index]);
+                    index += 1;
+                }
+            }
+        }
+
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::worker_binding::RequestDetails;
+    use http::{HeaderMap, HeaderValue};
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    fn cookies(cookie_headers: &[&str]) -> Value {
+        let mut headers = HeaderMap::new();
+        for cookie_header in cookie_headers {
+            headers.append("cookie", HeaderValue::from_str(cookie_header).unwrap());
+        }
+
+        let request_details = RequestDetails::from(
+            &HashMap::new(),
+            &HashMap::new(),
+            &[],
+            &Value::Null,
+            &headers,
+        )
+        .unwrap();
+
+        request_details.as_json()["cookies"].clone()
+    }
+
+    #[test]
+    fn test_multiple_cookies() {
+        let cookies = cookies(&["session=abc; theme=dark", "lang=en;count=2"]);
+
+        assert_eq!(cookies["session"], Value::String("abc".to_string()));
+        assert_eq!(cookies["theme"], Value::String("dark".to_string()));
+        assert_eq!(cookies["lang"], Value::String("en".to_string()));
+        assert_eq!(cookies["count"], Value::from(2));
+    }
+
+    #[test]
+    fn test_quoted_and_encoded_cookie_values() {
+        let cookies =
+            cookies(&[r#"name="John%20Doe"; path=%2Fhome%2; session=first; session=second"#]);
+
+        assert_eq!(cookies["name"], Value::String("John Doe".to_string()));
+        assert_eq!(cookies["path"], Value::String("/home%2".to_string()));
+        assert_eq!(cookies["session"], Value::String("first".to_string()));
+    }
+
+    #[test]
+    fn test_absent_cookie() {
+        assert_eq!(cookies(&["session=abc"]).get("theme"), None);
+        assert_eq!(cookies(&[]), Value::Object(serde_json::Map::new()));
+    }
 }
@@ -1,7 +1,9 @@
+use crate::http::http_request::RequestVariables;
 use crate::worker_binding::{RequestDetails, WorkerDetail};
 use golem_wasm_rpc::json::TypeAnnotatedValueJsonExtensions;
 use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
 use rib::RibInputTypeInfo;
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt::Display;
 
@@ -51,10 +53,12 @@ impl RibInputValueResolver for RequestDetails {
     ) -> Result<RibInputValue, RibInputTypeMismatch> {
         let request_type_info = required_types.types.get("request");
 
-        let rib_input_with_request_content = &self.as_json();
-
         match request_type_info {
             Some(request_type) => {
+                let mut request_json = self.as_json();
+                internal::adapt_query_values(&mut request_json, request_type);
+                let rib_input_with_request_content = &request_json;
+
                 let input = TypeAnnotatedValue::parse_with_type(rib_input_with_request_content, request_type)
                         .map_err(|err| RibInputTypeMismatch(format!("Input request details don't match the requirements for rib expression to execute: {}. Requirements. {:?}", err.join(", "), request_type)))?;
 
@@ -93,3 +97,64 @@ impl RibInputValueResolver for WorkerDetail {
         }
     }
 }
+
+// The variables are a read-only record under `vars`. A variable the script reads
+// but which isn't provided is a type mismatch, like a missing field of the request
+impl RibInputValueResolver for RequestVariables {
+    fn resolve_rib_input_value(
+        &self,
+        required_types: &RibInputTypeInfo,
+    ) -> Result<RibInputValue, RibInputTypeMismatch> {
+        let variables_type_info = required_types.types.get("vars");
+
+        match variables_type_info {
+            Some(variables_type) => {
+                let variables_json = Value::Object(serde_json::Map::from_iter(self.0.clone()));
+                let variables_value =
+                    TypeAnnotatedValue::parse_with_type(&variables_json, variables_type)
+                        .map_err(|err| RibInputTypeMismatch(format!("Variables don't match the requirements for rib expression to execute: {}. Requirements. {:?}", err.join(", "), variables_type)))?;
+
+                let mut rib_input_map = HashMap::new();
+                rib_input_map.insert("vars".to_string(), variables_value);
+                Ok(RibInputValue {
+                    value: rib_input_map,
+                })
+            }
+            None => Ok(RibInputValue::empty()),
+        }
+    }
+}
+
+mod internal {
+    use golem_wasm_ast::analysis::{AnalysedType, TypeRecord};
+    use serde_json::Value;
+
+    // The path and query variables (`request.path`) are single values, except for the
+    // repeated query keys which are lists. Each of them is adapted to the type the
+    // expression reads it as: a single value read as a list is a list of one element,
+    // and a list read as a single value is its last element
+    pub(crate) fn adapt_query_values(request_json: &mut Value, request_type: &AnalysedType) {
+        let path_type = match request_type {
+            AnalysedType::Record(TypeRecord { fields }) => fields
+                .iter()
+                .find(|field| field.name == "path")
+                .map(|field| &field.typ),
+            _ => None,
+        };
+
+        if let (Some(AnalysedType::Record(TypeRecord { fields })), Some(Value::Object(values))) =
+            (path_type, request_json.get_mut("path"))
+        {
+            for field in fields {
+                if let Some(value) = values.get_mut(&field.name) {
+                    *value = match (&field.typ, value.take()) {
+                        (AnalysedType::List(_), Value::Array(elements)) => Value::Array(elements),
+                        (AnalysedType::List(_), single) => Value::Array(vec![single]),
+                        (_, Value::Array(mut elements)) => elements.pop().unwrap_or(Value::Null),
+                        (_, single) => single,
+                    };
+                }
+            }
+        }
+    }
+}
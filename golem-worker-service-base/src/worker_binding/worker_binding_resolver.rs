@@ -1,17 +1,22 @@
 use crate::api_definition::http::{CompiledHttpApiDefinition, VarInfo};
-use crate::http::http_request::router;
+use crate::app_config::ResponseBodyLimitAction;
+use crate::http::http_request::{router, RequestVariables};
 use crate::http::router::RouterPattern;
 use crate::http::InputHttpRequest;
+use crate::metrics;
 use crate::worker_service_rib_interpreter::EvaluationError;
 use crate::worker_service_rib_interpreter::WorkerServiceRibInterpreter;
 use async_trait::async_trait;
 use golem_common::model::IdempotencyKey;
 use golem_service_base::model::VersionedComponentId;
+use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
 use rib::RibInterpreterResult;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::sync::Arc;
+use std::time::Instant;
+use tracing::warn;
 
 use crate::worker_binding::rib_input_value_resolver::RibInputValueResolver;
 use crate::worker_binding::{RequestDetails, ResponseMappingCompiled, RibInputTypeMismatch};
@@ -47,7 +52,12 @@ impl Display for WorkerBindingResolutionError {
 pub struct ResolvedWorkerBindingFromRequest {
     pub worker_detail: WorkerDetail,
     pub request_details: RequestDetails,
+    pub variables: RequestVariables,
     pub compiled_response_mapping: ResponseMappingCompiled,
+    // The matched route (Ex: `GET /users/{id}`)
+    pub route: String,
+    // The API definition of the matched route (Ex: `users-api@0.0.1`)
+    pub api_definition: String,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -78,27 +88,51 @@ impl WorkerDetail {
     }
 }
 
+// The result of the response mapping (Ex: the response of the worker) is larger than the limit
+// of the response bodies, so it's rejected before it's rendered as the body
+#[derive(Debug, Clone, PartialEq)]
+pub struct RibResultTooLarge {
+    pub limit: u64,
+}
+
+impl Display for RibResultTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Response body exceeds the limit of {} bytes", self.limit)
+    }
+}
+
 impl ResolvedWorkerBindingFromRequest {
+    // With a limit, a result which is larger (in its encoded form, as received from the worker)
+    // is rejected without rendering it, instead of buffering the whole rendered body first
     pub async fn interpret_response_mapping<R>(
         &self,
         evaluator: &Arc<dyn WorkerServiceRibInterpreter + Sync + Send>,
+        response_body_limit: Option<u64>,
     ) -> R
     where
         RibInterpreterResult: ToResponse<R>,
         EvaluationError: ToResponse<R>,
         RibInputTypeMismatch: ToResponse<R>,
+        RibResultTooLarge: ToResponse<R>,
     {
-        let request_rib_input = self
+        let required_types = &self.compiled_response_mapping.rib_input;
+
+        let rib_input = self
             .request_details
-            .resolve_rib_input_value(&self.compiled_response_mapping.rib_input);
+            .resolve_rib_input_value(required_types)
+            .and_then(|request_rib_input| {
+                let worker_rib_input =
+                    self.worker_detail.resolve_rib_input_value(required_types)?;
+                let variables_rib_input = self.variables.resolve_rib_input_value(required_types)?;
 
-        let worker_rib_input = self
-            .worker_detail
-            .resolve_rib_input_value(&self.compiled_response_mapping.rib_input);
+                Ok(request_rib_input
+                    .merge(worker_rib_input)
+                    .merge(variables_rib_input))
+            });
 
-        match (request_rib_input, worker_rib_input) {
-            (Ok(request_rib_input), Ok(worker_rib_input)) => {
-                let rib_input = request_rib_input.merge(worker_rib_input);
+        match rib_input {
+            Ok(rib_input) => {
+                let start = Instant::now();
                 let result = evaluator
                     .evaluate(
                         &self.worker_detail.worker_name,
@@ -108,33 +142,143 @@ impl ResolvedWorkerBindingFromRequest {
                         &rib_input,
                     )
                     .await;
+                metrics::record_gateway_evaluation(&self.route, start.elapsed());
 
                 match result {
-                    Ok(worker_response) => worker_response.to_response(&self.request_details),
+                    Ok(worker_response) => match response_body_limit {
+                        Some(limit) if encoded_len(&worker_response) as u64 > limit => {
+                            warn!(
+                                "Response of {} exceeds the limit of {} bytes",
+                                self.route, limit
+                            );
+                            metrics::record_gateway_oversized_response(
+                                ResponseBodyLimitAction::Reject,
+                            );
+                            RibResultTooLarge { limit }.to_response(&self.request_details)
+                        }
+                        _ => worker_response.to_response(&self.request_details),
+                    },
                     Err(err) => err.to_response(&self.request_details),
                 }
             }
-            (Err(err), _) => err.to_response(&self.request_details),
-            (_, Err(err)) => err.to_response(&self.request_details),
+            Err(err) => err.to_response(&self.request_details),
         }
     }
 }
 
-#[async_trait]
-impl RequestToWorkerBindingResolver<CompiledHttpApiDefinition> for InputHttpRequest {
-    async fn resolve_worker_binding(
+// The size of the result as it's received from the worker, which is about the size of
+// the rendered body, without rendering it
+fn encoded_len(result: &RibInterpreterResult) -> usize {
+    match result {
+        RibInterpreterResult::Unit => 0,
+        RibInterpreterResult::Val(value) => value.encoded_len(),
+        RibInterpreterResult::Reject { message, .. } => message.len(),
+    }
+}
+
+impl InputHttpRequest {
+    // Whether the request resolves to any of the routes. Conflicting routes are
+    // reported when resolving the worker binding, so they count as a match here
+    pub fn matches_route(&self, compiled_api_definitions: &[CompiledHttpApiDefinition]) -> bool {
+        let compiled_routes = compiled_api_definitions
+            .iter()
+            .flat_map(|x| x.routes.clone())
+            .collect::<Vec<_>>();
+
+        let path: Vec<&str> = RouterPattern::split(&self.input_path.base_path).collect();
+
+        router::build(compiled_routes)
+            .ok()
+            .map(|router| router.check_path(&self.req_method, &path).is_some())
+            .unwrap_or(true)
+    }
+
+    // Whether the route the request resolves to reads the request body.
+    // A request that doesn't resolve to a route fails regardless of the body
+    pub fn route_references_body(
         &self,
-        compiled_api_definitions: Vec<CompiledHttpApiDefinition>,
-    ) -> Result<ResolvedWorkerBindingFromRequest, WorkerBindingResolutionError> {
+        compiled_api_definitions: &[CompiledHttpApiDefinition],
+    ) -> bool {
+        let compiled_routes = compiled_api_definitions
+            .iter()
+            .flat_map(|x| x.routes.clone())
+            .collect::<Vec<_>>();
+
+        let path: Vec<&str> = RouterPattern::split(&self.input_path.base_path).collect();
+
+        router::build(compiled_routes)
+            .ok()
+            .and_then(|router| {
+                router
+                    .check_path(&self.req_method, &path)
+                    .map(|entry| entry.binding.references_request_body())
+            })
+            .unwrap_or(false)
+    }
+
+    // Whether the route the request resolves to reads the body as it was sent
+    pub fn route_references_raw_body(
+        &self,
+        compiled_api_definitions: &[CompiledHttpApiDefinition],
+    ) -> bool {
         let compiled_routes = compiled_api_definitions
             .iter()
             .flat_map(|x| x.routes.clone())
             .collect::<Vec<_>>();
 
+        let path: Vec<&str> = RouterPattern::split(&self.input_path.base_path).collect();
+
+        router::build(compiled_routes)
+            .ok()
+            .and_then(|router| {
+                router
+                    .check_path(&self.req_method, &path)
+                    .map(|entry| entry.binding.references_raw_request_body())
+            })
+            .unwrap_or(false)
+    }
+
+    // The fields of the request body the route the request resolves to reads,
+    // if it only reads the fields directly (see `request_body_fields`)
+    pub fn route_request_body_fields(
+        &self,
+        compiled_api_definitions: &[CompiledHttpApiDefinition],
+    ) -> Option<HashSet<String>> {
+        let compiled_routes = compiled_api_definitions
+            .iter()
+            .flat_map(|x| x.routes.clone())
+            .collect::<Vec<_>>();
+
+        let path: Vec<&str> = RouterPattern::split(&self.input_path.base_path).collect();
+
+        router::build(compiled_routes).ok().and_then(|router| {
+            router
+                .check_path(&self.req_method, &path)
+                .and_then(|entry| entry.binding.request_body_fields())
+        })
+    }
+}
+
+#[async_trait]
+impl RequestToWorkerBindingResolver<CompiledHttpApiDefinition> for InputHttpRequest {
+    async fn resolve_worker_binding(
+        &self,
+        compiled_api_definitions: Vec<CompiledHttpApiDefinition>,
+    ) -> Result<ResolvedWorkerBindingFromRequest, WorkerBindingResolutionError> {
         let api_request = self;
-        let router = router::build(compiled_routes);
+        let router =
+            router::build_for_definitions(&compiled_api_definitions).map_err(|conflicts| {
+                format!(
+                    "Conflicting routes: {}",
+                    conflicts
+                        .iter()
+                        .map(|conflict| conflict.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
         let path: Vec<&str> = RouterPattern::split(&api_request.input_path.base_path).collect();
-        let request_query_variables = self.input_path.query_components().unwrap_or_default();
+        let request_query_variables = self.input_path.query_values().unwrap_or_default();
         let request_body = &self.req_body;
         let headers = &self.headers;
 
@@ -142,6 +286,8 @@ impl RequestToWorkerBindingResolver<CompiledHttpApiDefinition> for InputHttpRequ
             path_params,
             query_params,
             binding,
+            route,
+            api_definition,
         } = router
             .check_path(&api_request.req_method, &path)
             .ok_or("Failed to resolve route")?;
@@ -160,7 +306,8 @@ impl RequestToWorkerBindingResolver<CompiledHttpApiDefinition> for InputHttpRequ
             request_body,
             headers,
         )
-        .map_err(|err| format!("Failed to fetch input request details {}", err.join(", ")))?;
+        .map_err(|err| format!("Failed to fetch input request details {}", err.join(", ")))?
+        .with_raw_body(self.req_raw_body.clone());
 
         let resolve_rib_input = http_request_details
             .resolve_rib_input_value(&binding.worker_name_compiled.rib_input_type_info)
@@ -169,7 +316,14 @@ impl RequestToWorkerBindingResolver<CompiledHttpApiDefinition> for InputHttpRequ
                     "Failed to resolve rib input value from http request details {}",
                     err
                 )
-            })?;
+            })?
+            .merge(
+                self.variables
+                    .resolve_rib_input_value(&binding.worker_name_compiled.rib_input_type_info)
+                    .map_err(|err| {
+                        format!("Failed to resolve rib input value of variables {}", err)
+                    })?,
+            );
 
         // To evaluate worker-name, most probably
         let worker_name: String = rib::interpret_pure(
@@ -184,27 +338,50 @@ impl RequestToWorkerBindingResolver<CompiledHttpApiDefinition> for InputHttpRequ
 
         let component_id = &binding.component_id;
 
-        let idempotency_key =
-            if let Some(idempotency_key_compiled) = &binding.idempotency_key_compiled {
-                let idempotency_key_value = rib::interpret_pure(
-                    &idempotency_key_compiled.compiled_idempotency_key,
-                    &resolve_rib_input.value,
-                )
-                .await
-                .map_err(|err| err.to_string())?;
-
-                let idempotency_key = idempotency_key_value
-                    .get_literal()
-                    .ok_or("Idempotency Key is not a string")?
-                    .as_string();
-
-                Some(IdempotencyKey::new(idempotency_key))
-            } else {
-                headers
-                    .get("idempotency-key")
-                    .and_then(|h| h.to_str().ok())
-                    .map(|value| IdempotencyKey::new(value.to_string()))
-            };
+        // The key computed by the route takes precedence over the `Idempotency-Key` header,
+        // so that retries of the same request are deduplicated by the worker executor
+        let idempotency_key = if let Some(idempotency_key_compiled) =
+            &binding.idempotency_key_compiled
+        {
+            let idempotency_key_rib_input = http_request_details
+                .resolve_rib_input_value(&idempotency_key_compiled.rib_input)
+                .map_err(|err| {
+                    format!(
+                        "Failed to resolve rib input value of idempotency key {}",
+                        err
+                    )
+                })?
+                .merge(
+                    self.variables
+                        .resolve_rib_input_value(&idempotency_key_compiled.rib_input)
+                        .map_err(|err| {
+                            format!("Failed to resolve rib input value of variables {}", err)
+                        })?,
+                );
+
+            let idempotency_key_value = rib::interpret_pure(
+                &idempotency_key_compiled.compiled_idempotency_key,
+                &idempotency_key_rib_input.value,
+            )
+            .await
+            .map_err(|err| format!("Failed to evaluate idempotency key rib expression. {}", err))?;
+
+            match idempotency_key_value.get_val() {
+                Some(TypeAnnotatedValue::Str(idempotency_key)) => {
+                    Some(IdempotencyKey::new(idempotency_key))
+                }
+                _ => {
+                    return Err(
+                        "Idempotency key is not a Rib expression that resolves to String".into(),
+                    )
+                }
+            }
+        } else {
+            headers
+                .get("idempotency-key")
+                .and_then(|h| h.to_str().ok())
+                .map(|value| IdempotencyKey::new(value.to_string()))
+        };
 
         let worker_detail = WorkerDetail {
             component_id: component_id.clone(),
@@ -215,7 +392,10 @@ impl RequestToWorkerBindingResolver<CompiledHttpApiDefinition> for InputHttpRequ
         let resolved_binding = ResolvedWorkerBindingFromRequest {
             worker_detail,
             request_details: http_request_details,
+            variables: self.variables.clone(),
             compiled_response_mapping: binding.response_compiled.clone(),
+            route: route.clone(),
+            api_definition: api_definition.clone().unwrap_or_default(),
         };
 
         Ok(resolved_binding)
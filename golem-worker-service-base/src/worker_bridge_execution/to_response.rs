@@ -1,4 +1,4 @@
-use crate::worker_binding::{RequestDetails, RibInputTypeMismatch};
+use crate::worker_binding::{RequestDetails, RibInputTypeMismatch, RibResultTooLarge};
 use crate::worker_service_rib_interpreter::EvaluationError;
 
 use http::StatusCode;
@@ -39,6 +39,14 @@ impl ToResponse<poem::Response> for EvaluationError {
     }
 }
 
+impl ToResponse<poem::Response> for RibResultTooLarge {
+    fn to_response(&self, _request_details: &RequestDetails) -> poem::Response {
+        poem::Response::builder()
+            .status(StatusCode::BAD_GATEWAY)
+            .body(Body::from_string(self.to_string()))
+    }
+}
+
 impl ToResponse<poem::Response> for String {
     fn to_response(&self, _request_details: &RequestDetails) -> poem::Response {
         poem::Response::builder()
@@ -103,6 +111,17 @@ mod internal {
                     status: StatusCode::default(),
                     headers: ResolvedResponseHeaders::default(),
                 }),
+                RibInterpreterResult::Reject { status, message } => {
+                    let status = StatusCode::from_u16(*status).map_err(|e| {
+                        EvaluationError(format!("Invalid status code in reject: {}", e))
+                    })?;
+
+                    Ok(IntermediateHttpResponse {
+                        body: Some(TypeAnnotatedValue::Str(message.clone())),
+                        status,
+                        headers: ResolvedResponseHeaders::default(),
+                    })
+                }
             }
         }
 
@@ -248,6 +267,7 @@ mod test {
     use super::*;
     use crate::worker_binding::HttpRequestDetails;
     use crate::worker_bridge_execution::to_response::internal::ResolvedResponseHeaders;
+    use golem_wasm_ast::analysis::analysed_type::{bool, field, record, str};
     use golem_wasm_rpc::protobuf::type_annotated_value::TypeAnnotatedValue;
     use golem_wasm_rpc::protobuf::Type;
     use golem_wasm_rpc::protobuf::{NameTypePair, NameValuePair, TypedRecord};
@@ -319,6 +339,50 @@ mod test {
         assert_eq!(status, expected_status);
     }
 
+    #[tokio::test]
+    async fn test_evaluation_result_to_response_with_expression_driven_status_and_headers() {
+        let expr = rib::Expr::from_text(
+            r#"
+              let status: u16 = if request.path.permanent then 301 else 302;
+              { status: status, headers: { Location: request.path.target }, body: "redirecting" }
+            "#,
+        )
+        .unwrap();
+
+        let compiled = rib::compile(&expr, &vec![]).unwrap();
+
+        let request_type = record(vec![field(
+            "path",
+            record(vec![field("permanent", bool()), field("target", str())]),
+        )]);
+
+        let request = golem_wasm_rpc::type_annotated_value_from_str(
+            &request_type,
+            r#"{path: {permanent: false, target: "/new-location"}}"#,
+        )
+        .unwrap();
+
+        let evaluation_result = rib::interpret_pure(
+            &compiled.byte_code,
+            &HashMap::from([("request".to_string(), request)]),
+        )
+        .await
+        .unwrap();
+
+        let http_response: poem::Response =
+            evaluation_result.to_response(&RequestDetails::Http(HttpRequestDetails::empty()));
+
+        let (response_parts, body) = http_response.into_parts();
+        let body = body.into_string().await.unwrap();
+
+        assert_eq!(response_parts.status, StatusCode::FOUND);
+        assert_eq!(
+            response_parts.headers.get(http::header::LOCATION).unwrap(),
+            "/new-location"
+        );
+        assert_eq!(body, "redirecting");
+    }
+
     #[tokio::test]
     async fn test_evaluation_result_to_response_with_no_http_specifics() {
         let evaluation_result: RibInterpreterResult =
@@ -346,6 +410,23 @@ mod test {
         assert_eq!(status, expected_status);
     }
 
+    #[tokio::test]
+    async fn test_evaluation_result_to_response_with_reject() {
+        let evaluation_result = RibInterpreterResult::Reject {
+            status: 401,
+            message: "no auth".to_string(),
+        };
+
+        let http_response: poem::Response =
+            evaluation_result.to_response(&RequestDetails::Http(HttpRequestDetails::empty()));
+
+        let (response_parts, body) = http_response.into_parts();
+        let body = body.into_string().await.unwrap();
+
+        assert_eq!(response_parts.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body, "no auth");
+    }
+
     #[test]
     fn test_get_response_headers_from_typed_value() {
         let header_map = create_record(vec![
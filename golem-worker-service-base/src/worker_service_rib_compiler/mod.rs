@@ -1,20 +1,29 @@
 use golem_wasm_ast::analysis::AnalysedExport;
-use rib::{CompilerOutput, Expr};
+use rib::{CompilerOutput, Expr, PredicateMode};
 
 // A wrapper service over original Rib Compiler concerning
 // the details of the worker bridge.
 pub trait WorkerServiceRibCompiler {
-    fn compile(rib: &Expr, export_metadata: &[AnalysedExport]) -> Result<CompilerOutput, String>;
+    fn compile(
+        rib: &Expr,
+        export_metadata: &[AnalysedExport],
+        predicate_mode: PredicateMode,
+    ) -> Result<CompilerOutput, String>;
 }
 
 pub struct DefaultRibCompiler;
 
 impl WorkerServiceRibCompiler for DefaultRibCompiler {
-    fn compile(rib: &Expr, export_metadata: &[AnalysedExport]) -> Result<CompilerOutput, String> {
-        rib::compile_with_limited_globals(
+    fn compile(
+        rib: &Expr,
+        export_metadata: &[AnalysedExport],
+        predicate_mode: PredicateMode,
+    ) -> Result<CompilerOutput, String> {
+        rib::compile_with_predicate_mode(
             rib,
             &export_metadata.to_vec(),
-            Some(vec!["request".to_string()]),
+            Some(vec!["request".to_string(), "vars".to_string()]),
+            predicate_mode,
         )
     }
 }
@@ -215,6 +215,7 @@ mod tests {
 
         test_definition_crud(definition_service.clone()).await;
         test_delete_non_existing(definition_service.clone()).await;
+        test_overlapping_ranges_rejected(definition_service.clone()).await;
         test_deployment(definition_service.clone(), deployment_service.clone()).await;
         test_deployment_conflict(definition_service.clone(), deployment_service.clone()).await;
     }
@@ -730,6 +731,54 @@ mod tests {
         }
     }
 
+    async fn test_overlapping_ranges_rejected(
+        definition_service: Arc<
+            dyn ApiDefinitionService<EmptyAuthCtx, DefaultNamespace, RouteValidationError>
+                + Sync
+                + Send,
+        >,
+    ) {
+        let definition = get_api_definition(
+            &Uuid::new_v4().to_string(),
+            "0.0.1",
+            "/api/scores/{score}",
+            "${\"shopping-cart\"}",
+            "${let score: u64 = request.path.score; match score { 0..50 => \"low\", 40..80 => \"mid\", _ => \"high\" }}",
+            true,
+        );
+
+        let create_result = definition_service
+            .create(
+                &definition,
+                &DefaultNamespace::default(),
+                &EmptyAuthCtx::default(),
+            )
+            .await;
+
+        match create_result {
+            Err(ApiDefinitionError::ValidationError(errors)) => {
+                assert_eq!(errors.errors.len(), 1, "Received: {errors:?}");
+                assert_eq!(
+                    errors.errors[0].detail,
+                    "Invalid response: Range patterns `0..50` and `40..80` in match on `score` overlap, the values in both are matched by the first one"
+                );
+            }
+            other => panic!("Expected a validation error, received: {other:?}"),
+        }
+
+        let get_result = definition_service
+            .get(
+                &definition.id,
+                &definition.version,
+                &DefaultNamespace::default(),
+                &EmptyAuthCtx::default(),
+            )
+            .await
+            .unwrap();
+
+        assert!(get_result.is_none(), "definition should not be created");
+    }
+
     fn get_api_definition(
         id: &str,
         version: &str,
@@ -1,5 +1,6 @@
 pub mod api_definition;
 pub mod api_deployment;
+pub mod route_match;
 pub mod worker;
 pub mod worker_connect;
 
@@ -7,9 +8,12 @@ use crate::api::worker::WorkerApi;
 use crate::service::Services;
 use golem_worker_service_base::api::CustomHttpRequestApi;
 use golem_worker_service_base::api::HealthcheckApi;
-use poem::endpoint::PrometheusExporter;
+use golem_worker_service_base::app_config::{ApiDocsUi, NonUtf8RequestBodyPolicy};
+use golem_worker_service_base::http::default_route::DefaultRoute;
+use golem_worker_service_base::http::metrics_endpoint::MetricsEndpoint;
+use golem_worker_service_base::http::route_circuit_breaker::RouteCircuitBreaker;
 use poem::{get, EndpointExt, Route};
-use poem_openapi::OpenApiService;
+use poem_openapi::{OpenApi, OpenApiService, Webhook};
 use prometheus::Registry;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -18,21 +22,26 @@ type ApiServices = (
     WorkerApi,
     api_definition::RegisterApiDefinitionApi,
     api_deployment::ApiDeploymentApi,
+    route_match::RouteMatchApi,
     HealthcheckApi,
 );
 
-pub fn combined_routes(prometheus_registry: Arc<Registry>, services: &Services) -> Route {
+pub fn combined_routes(
+    prometheus_registry: Arc<Registry>,
+    services: &Services,
+    api_docs_ui: Option<ApiDocsUi>,
+) -> Route {
     let api_service = make_open_api_service(services);
 
-    let ui = api_service.swagger_ui();
+    let docs = api_docs_route(&api_service, api_docs_ui);
     let spec = api_service.spec_endpoint_yaml();
-    let metrics = PrometheusExporter::new(prometheus_registry.deref().clone());
+    let metrics = MetricsEndpoint::new(prometheus_registry.deref().clone());
 
     let connect_services = worker_connect::ConnectService::new(services.worker_service.clone());
 
     Route::new()
         .nest("/", api_service)
-        .nest("/docs", ui)
+        .nest("/docs", docs)
         .nest("/specs", spec)
         .nest("/metrics", metrics)
         .at(
@@ -41,12 +50,48 @@ pub fn combined_routes(prometheus_registry: Arc<Registry>, services: &Services)
         )
 }
 
-pub fn custom_request_route(services: Services) -> Route {
-    let custom_request_executor = CustomHttpRequestApi::new(
+// The UI of the API docs, pointed at the spec of the service. If it's disabled,
+// the route has no endpoints, so the docs are not found
+fn api_docs_route<T: OpenApi, W: Webhook>(
+    api_service: &OpenApiService<T, W>,
+    api_docs_ui: Option<ApiDocsUi>,
+) -> Route {
+    match api_docs_ui {
+        Some(ApiDocsUi::SwaggerUi) => Route::new().nest("/", api_service.swagger_ui()),
+        Some(ApiDocsUi::Rapidoc) => Route::new().nest("/", api_service.rapidoc()),
+        None => Route::new(),
+    }
+}
+
+pub fn custom_request_route(
+    services: Services,
+    default_route: Option<DefaultRoute>,
+    route_circuit_breaker: Option<RouteCircuitBreaker>,
+    non_utf8_request_body: NonUtf8RequestBodyPolicy,
+    response_body_limit: Option<u64>,
+) -> Route {
+    let mut custom_request_executor = CustomHttpRequestApi::new(
         services.worker_to_http_service,
         services.http_definition_lookup_service,
     );
 
+    if let Some(default_route) = default_route {
+        custom_request_executor = custom_request_executor.with_default_route(default_route);
+    }
+
+    if let Some(route_circuit_breaker) = route_circuit_breaker {
+        custom_request_executor =
+            custom_request_executor.with_route_circuit_breaker(route_circuit_breaker);
+    }
+
+    custom_request_executor =
+        custom_request_executor.with_non_utf8_request_body(non_utf8_request_body);
+
+    if let Some(response_body_limit) = response_body_limit {
+        custom_request_executor =
+            custom_request_executor.with_response_body_limit(response_body_limit);
+    }
+
     Route::new().nest("/", custom_request_executor)
 }
 
@@ -59,9 +104,43 @@ pub fn make_open_api_service(services: &Services) -> OpenApiService<ApiServices,
             },
             api_definition::RegisterApiDefinitionApi::new(services.definition_service.clone()),
             api_deployment::ApiDeploymentApi::new(services.deployment_service.clone()),
+            route_match::RouteMatchApi::new(services.deployment_service.clone()),
             HealthcheckApi,
         ),
         "Golem API",
         "1.0",
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::api_docs_route;
+    use golem_worker_service_base::api::HealthcheckApi;
+    use golem_worker_service_base::app_config::ApiDocsUi;
+    use poem::http::StatusCode;
+    use poem::test::TestClient;
+    use poem::Route;
+    use poem_openapi::OpenApiService;
+
+    fn docs(api_docs_ui: Option<ApiDocsUi>) -> TestClient<Route> {
+        let api_service = OpenApiService::new(HealthcheckApi, "Golem API", "1.0");
+        TestClient::new(Route::new().nest("/docs", api_docs_route(&api_service, api_docs_ui)))
+    }
+
+    #[tokio::test]
+    async fn api_docs_are_served_if_enabled() {
+        for api_docs_ui in [ApiDocsUi::SwaggerUi, ApiDocsUi::Rapidoc] {
+            let response = docs(Some(api_docs_ui)).get("/docs").send().await;
+
+            response.assert_status_is_ok();
+            response.assert_content_type("text/html; charset=utf-8");
+        }
+    }
+
+    #[tokio::test]
+    async fn api_docs_are_not_found_if_disabled() {
+        let response = docs(None).get("/docs").send().await;
+
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use golem_common::{recorded_http_api_request, safe};
+use golem_service_base::api_tags::ApiTags;
+use golem_service_base::auth::DefaultNamespace;
+use golem_worker_service_base::api::ApiEndpointError;
+use golem_worker_service_base::api_definition::http::MethodPattern;
+use golem_worker_service_base::api_definition::ApiSiteString;
+use golem_worker_service_base::http::http_request::router;
+use golem_worker_service_base::service::api_deployment::ApiDeploymentService;
+use poem_openapi::payload::Json;
+use poem_openapi::*;
+use serde::{Deserialize, Serialize};
+use tracing::Instrument;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+#[serde(rename_all = "camelCase")]
+#[oai(rename_all = "camelCase")]
+pub struct RouteMatchRequest {
+    pub site: String,
+    pub method: MethodPattern,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+#[serde(rename_all = "camelCase")]
+#[oai(rename_all = "camelCase")]
+pub struct RouteMatchResponse {
+    pub matched_route: Option<String>,
+    pub rejected_routes: Vec<RouteRejection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Object)]
+#[serde(rename_all = "camelCase")]
+#[oai(rename_all = "camelCase")]
+pub struct RouteRejection {
+    pub route: String,
+    pub reason: String,
+}
+
+impl From<router::RouteMatchReport> for RouteMatchResponse {
+    fn from(value: router::RouteMatchReport) -> Self {
+        RouteMatchResponse {
+            matched_route: value.matched_route,
+            rejected_routes: value
+                .rejected_routes
+                .into_iter()
+                .map(|rejection| RouteRejection {
+                    route: rejection.route,
+                    reason: rejection.reason,
+                })
+                .collect(),
+        }
+    }
+}
+
+pub struct RouteMatchApi {
+    deployment_service: Arc<dyn ApiDeploymentService<DefaultNamespace> + Sync + Send>,
+}
+
+#[OpenApi(prefix_path = "/v1/routes", tag = ApiTags::ApiDeployment)]
+impl RouteMatchApi {
+    pub fn new(
+        deployment_service: Arc<dyn ApiDeploymentService<DefaultNamespace> + Sync + Send>,
+    ) -> Self {
+        Self { deployment_service }
+    }
+
+    /// Match a request against the deployed routes
+    ///
+    /// Resolves a sample method and path against the routes deployed to a site, and returns
+    /// the matched route along with the reason each of the other routes doesn't match.
+    #[oai(path = "/match", method = "post", operation_id = "match_route")]
+    async fn match_route(
+        &self,
+        payload: Json<RouteMatchRequest>,
+    ) -> Result<Json<RouteMatchResponse>, ApiEndpointError> {
+        let record = recorded_http_api_request!(
+            "match_route",
+            site = payload.0.site.clone(),
+            path = payload.0.path.clone()
+        );
+        let response = {
+            let definitions = self
+                .deployment_service
+                .get_definitions_by_site(&ApiSiteString(payload.0.site.clone()))
+                .instrument(record.span.clone())
+                .await?;
+
+            let routes = definitions
+                .into_iter()
+                .flat_map(|definition| definition.routes)
+                .collect::<Vec<_>>();
+
+            let method = payload.0.method.clone().into();
+
+            let report =
+                router::explain(routes, &method, &payload.0.path).map_err(|conflicts| {
+                    ApiEndpointError::bad_request(safe(format!(
+                        "Conflicting routes: {}",
+                        conflicts
+                            .iter()
+                            .map(|conflict| conflict.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )))
+                })?;
+
+            Ok(Json(report.into()))
+        };
+
+        record.result(response)
+    }
+}
@@ -1,18 +1,27 @@
 use golem_api_grpc::proto;
 use golem_api_grpc::proto::golem::apidefinition::v1::api_definition_service_server::ApiDefinitionServiceServer;
 use golem_api_grpc::proto::golem::worker::v1::worker_service_server::WorkerServiceServer;
+use std::future::Future;
 use std::net::SocketAddr;
 use tonic::codec::CompressionEncoding;
 use tonic::transport::{Error, Server};
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
 
 use crate::grpcapi::api_definition::GrpcApiDefinitionService;
 use crate::grpcapi::worker::WorkerGrpcApi;
 use crate::service::Services;
+use golem_worker_service_base::app_config::GrpcServerConfig;
 
 mod api_definition;
 mod worker;
 
-pub async fn start_grpc_server(addr: SocketAddr, services: &Services) -> Result<(), Error> {
+pub async fn start_grpc_server(
+    addr: SocketAddr,
+    config: &GrpcServerConfig,
+    services: &Services,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), Error> {
     let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
 
     health_reporter
@@ -23,12 +32,14 @@ pub async fn start_grpc_server(addr: SocketAddr, services: &Services) -> Result<
         .set_serving::<ApiDefinitionServiceServer<GrpcApiDefinitionService>>()
         .await;
 
+    let shutdown = not_serving_on_shutdown(health_reporter, shutdown);
+
     let reflection_service = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
         .build()
         .unwrap();
 
-    Server::builder()
+    server_builder(config)
         .add_service(reflection_service)
         .add_service(health_service)
         .add_service(
@@ -46,6 +57,161 @@ pub async fn start_grpc_server(addr: SocketAddr, services: &Services) -> Result<
             .accept_compressed(CompressionEncoding::Gzip)
             .send_compressed(CompressionEncoding::Gzip),
         )
-        .serve(addr)
+        .serve_with_shutdown(addr, shutdown)
         .await
 }
+
+fn server_builder(config: &GrpcServerConfig) -> Server {
+    Server::builder()
+        .max_concurrent_streams(config.max_concurrent_streams)
+        .initial_connection_window_size(config.initial_connection_window_size)
+        .initial_stream_window_size(config.initial_stream_window_size)
+}
+
+// Once the shutdown starts, the services are reported as NOT_SERVING
+// while the in-flight calls are drained, so no new calls are routed to them
+async fn not_serving_on_shutdown(
+    mut health_reporter: HealthReporter,
+    shutdown: impl Future<Output = ()>,
+) {
+    shutdown.await;
+
+    health_reporter
+        .set_not_serving::<WorkerServiceServer<WorkerGrpcApi>>()
+        .await;
+
+    health_reporter
+        .set_not_serving::<ApiDefinitionServiceServer<GrpcApiDefinitionService>>()
+        .await;
+
+    health_reporter
+        .set_service_status("", ServingStatus::NotServing)
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grpcapi::{not_serving_on_shutdown, server_builder};
+    use golem_worker_service_base::app_config::GrpcServerConfig;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tonic::transport::{Channel, Server};
+    use tonic_health::pb::health_check_response::ServingStatus;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    async fn connect(addr: SocketAddr) -> HealthClient<Channel> {
+        for _ in 0..50 {
+            if let Ok(client) = HealthClient::connect(format!("http://{}", addr)).await {
+                return client;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("Failed to connect to the health service")
+    }
+
+    #[tokio::test]
+    async fn health_is_not_serving_after_shutdown() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let (health_reporter, health_service) = tonic_health::server::health_reporter();
+        let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(
+            Server::builder()
+                .add_service(health_service)
+                .serve_with_shutdown(
+                    addr,
+                    not_serving_on_shutdown(health_reporter, async {
+                        let _ = shutdown_receiver.await;
+                    }),
+                ),
+        );
+
+        let mut client = connect(addr).await;
+
+        let status = client
+            .check(HealthCheckRequest {
+                service: "".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .status();
+
+        assert_eq!(status, ServingStatus::Serving);
+
+        // A watch stream is in flight, so it sees the status change while draining
+        let mut statuses = client
+            .watch(HealthCheckRequest {
+                service: "".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        let status = statuses.message().await.unwrap().unwrap().status();
+        assert_eq!(status, ServingStatus::Serving);
+
+        shutdown_sender.send(()).unwrap();
+
+        let status = statuses.message().await.unwrap().unwrap().status();
+        assert_eq!(status, ServingStatus::NotServing);
+
+        drop(statuses);
+        drop(client);
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn server_advertises_the_configured_max_concurrent_streams() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let config = GrpcServerConfig {
+            max_concurrent_streams: Some(3),
+            initial_connection_window_size: Some(1024 * 1024),
+            initial_stream_window_size: Some(256 * 1024),
+        };
+
+        let (_, health_service) = tonic_health::server::health_reporter();
+        let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(
+            server_builder(&config)
+                .add_service(health_service)
+                .serve_with_shutdown(addr, async {
+                    let _ = shutdown_receiver.await;
+                }),
+        );
+
+        // Connected to the health service, so the server is listening
+        drop(connect(addr).await);
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (send_request, connection) = h2::client::handshake(stream).await.unwrap();
+        let connection = tokio::spawn(connection);
+
+        // The limit is advertised in the SETTINGS frame of the server, once it's received
+        let mut max_streams = send_request.current_max_send_streams();
+        for _ in 0..50 {
+            if max_streams == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            max_streams = send_request.current_max_send_streams();
+        }
+
+        assert_eq!(max_streams, 3);
+
+        drop(send_request);
+        connection.abort();
+        shutdown_sender.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
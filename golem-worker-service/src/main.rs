@@ -1,16 +1,21 @@
 use std::net::{Ipv4Addr, SocketAddrV4};
 use std::sync::Arc;
 
+use futures::future::join_all;
 use opentelemetry::global;
-use opentelemetry_sdk::metrics::MeterProviderBuilder;
+use opentelemetry::metrics::MetricsError;
+use opentelemetry_prometheus::PrometheusExporter;
+use opentelemetry_sdk::metrics::{MeterProviderBuilder, SdkMeterProvider};
 use poem::listener::TcpListener;
 use poem::middleware::{OpenTelemetryMetrics, Tracing};
-use poem::EndpointExt;
+use poem::{Endpoint, EndpointExt};
 use prometheus::Registry;
 use tokio::select;
-use tracing::error;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 
 use golem_common::config::DbConfig;
+use golem_common::shutdown::{InFlight, Shutdown};
 use golem_common::tracing::init_tracing_with_default_env_filter;
 use golem_service_base::db;
 use golem_worker_service::api;
@@ -18,18 +23,94 @@ use golem_worker_service::api::make_open_api_service;
 use golem_worker_service::config::make_config_loader;
 use golem_worker_service::grpcapi;
 use golem_worker_service::service::Services;
-use golem_worker_service_base::app_config::WorkerServiceBaseConfig;
+use golem_worker_service_base::api::HttpApiDefinitionRequest;
+use golem_worker_service_base::app_config::{
+    ResponseBodyLimitAction, RuntimeConfig, WorkerServiceBaseConfig,
+};
+use golem_worker_service_base::http::access_log::AccessLog;
+use golem_worker_service_base::http::catch_panic::CatchPanic;
+use golem_worker_service_base::http::concurrency_limit::ConcurrencyLimit;
+use golem_worker_service_base::http::default_route::DefaultRoute;
+use golem_worker_service_base::http::request_body_limit::RequestBodyLimit;
+use golem_worker_service_base::http::response_body_limit::ResponseBodyLimit;
+use golem_worker_service_base::http::route_circuit_breaker::RouteCircuitBreaker;
+use golem_worker_service_base::http::trace_context::TraceContext;
 use golem_worker_service_base::metrics;
+use golem_worker_service_base::service::http::route_validation;
 
 fn main() -> std::io::Result<()> {
-    tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()?
-        .block_on(async_main())
+    let args = std::env::args().collect::<Vec<_>>();
+
+    // The commands don't load the config, so they run on the default runtime
+    if args
+        .iter()
+        .any(|arg| arg == "--validate-routes" || arg == "--dump-openapi-yaml")
+    {
+        runtime_builder(&RuntimeConfig::default())
+            .build()?
+            .block_on(run_command(args))
+    } else if let Some(config) = make_config_loader().load_or_dump_config() {
+        let stack_size = config.runtime.thread_stack_size;
+        let runtime = runtime_builder(&config.runtime).build()?;
+        let run = move || {
+            runtime.block_on(async {
+                let prometheus = metrics::register_all();
+                app(&config, prometheus).await
+            })
+        };
+
+        // The stack size applies to the threads started by the runtime, while the tasks run
+        // on the thread calling `block_on`, which is therefore started with the same stack
+        match stack_size {
+            Some(stack_size) => std::thread::Builder::new()
+                .stack_size(stack_size)
+                .spawn(run)?
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+            None => run(),
+        }
+    } else {
+        Ok(())
+    }
+}
+
+fn runtime_builder(config: &RuntimeConfig) -> tokio::runtime::Builder {
+    let mut builder = tokio::runtime::Builder::new_current_thread();
+    builder.enable_all();
+
+    if let Some(thread_stack_size) = config.thread_stack_size {
+        builder.thread_stack_size(thread_stack_size);
+    }
+    if let Some(max_blocking_threads) = config.max_blocking_threads {
+        builder.max_blocking_threads(max_blocking_threads);
+    }
+
+    builder
 }
 
-async fn async_main() -> std::io::Result<()> {
-    if std::env::args().any(|arg| arg == "--dump-openapi-yaml") {
+async fn run_command(args: Vec<String>) -> std::io::Result<()> {
+    if let Some(position) = args.iter().position(|arg| arg == "--validate-routes") {
+        let path = args.get(position + 1).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--validate-routes requires the path of an API definition",
+            )
+        })?;
+
+        match validate_routes(path) {
+            Ok(route_count) => {
+                println!("All {} routes of {} are valid", route_count, path);
+                Ok(())
+            }
+            Err(report) => {
+                eprintln!("Invalid routes in {}:", path);
+                for error in report {
+                    eprintln!("  {}", error);
+                }
+                std::process::exit(1)
+            }
+        }
+    } else {
         let config = WorkerServiceBaseConfig::default();
         let services = Services::new(&config)
             .await
@@ -37,21 +118,36 @@ async fn async_main() -> std::io::Result<()> {
         let api_service = make_open_api_service(&services);
         println!("{}", api_service.spec_yaml());
         Ok(())
-    } else if let Some(config) = make_config_loader().load_or_dump_config() {
-        let prometheus = metrics::register_all();
-        app(&config, prometheus).await
-    } else {
-        Ok(())
     }
 }
 
+// Validates the routes of the API definition (YAML or JSON) in the file,
+// without starting the servers or connecting to the DB
+fn validate_routes(path: &str) -> Result<usize, Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| vec![format!("Failed to read {}: {}", path, err)])?;
+    let definition: HttpApiDefinitionRequest = serde_yaml::from_str(&content)
+        .map_err(|err| vec![format!("Failed to parse {}: {}", path, err)])?;
+
+    route_validation::validate_routes(definition)
+}
+
 pub async fn app(
     config: &WorkerServiceBaseConfig,
     prometheus_registry: Registry,
 ) -> std::io::Result<()> {
     let config = config.clone();
 
-    init_tracing(&config, prometheus_registry.clone());
+    init_tracing_with_default_env_filter(&config.tracing);
+
+    let metrics_registry = prometheus_registry.clone();
+    if let Some(meter_provider) = meter_provider(move || {
+        opentelemetry_prometheus::exporter()
+            .with_registry(metrics_registry)
+            .build()
+    }) {
+        global::set_meter_provider(meter_provider);
+    }
 
     match config.db.clone() {
         DbConfig::Postgres(c) => {
@@ -76,62 +172,432 @@ pub async fn app(
         .await
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
 
+    // Compiled before the servers start, so that an invalid body fails the startup
+    let default_route = config
+        .default_route
+        .as_ref()
+        .map(DefaultRoute::from_config)
+        .transpose()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+
+    let route_circuit_breaker = config
+        .route_circuit_breaker
+        .clone()
+        .map(RouteCircuitBreaker::new);
+
+    let access_log = AccessLog::new(config.access_log.as_ref())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+
     let http_service1 = services.clone();
     let http_service2 = services.clone();
     let grpc_services = services.clone();
 
-    let custom_request_server = tokio::spawn(async move {
-        let route = api::custom_request_route(http_service1)
+    let custom_request_body_limit = config.custom_request_body_limit.clone();
+    let custom_request_concurrency_limit = config.custom_request_concurrency_limit.clone();
+    let custom_response_body_limit = config.custom_response_body_limit.clone();
+    // The results of the workers are rejected before rendering them only if the oversized
+    // responses are rejected, as they are truncated after rendering otherwise
+    let worker_result_limit = (custom_response_body_limit.on_exceeded
+        == ResponseBodyLimitAction::Reject)
+        .then_some(custom_response_body_limit.max_bytes);
+    let non_utf8_request_body = config.non_utf8_request_body;
+    let api_docs_ui = config.api_docs_ui();
+    let in_flight_requests = InFlight::new();
+    let gateway_in_flight_requests = in_flight_requests.clone();
+    let worker_in_flight_requests = in_flight_requests.clone();
+
+    let shutdown = Shutdown::new(config.shutdown_drain_timeout);
+    shutdown.request_on_signal();
+
+    let custom_request_server = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            let route = api::custom_request_route(
+                http_service1,
+                default_route,
+                route_circuit_breaker,
+                non_utf8_request_body,
+                worker_result_limit,
+            )
+            .with(CatchPanic::new("gateway"))
+            .with(RequestBodyLimit::new(custom_request_body_limit))
+            .with(ResponseBodyLimit::new(custom_response_body_limit))
+            .with(ConcurrencyLimit::new(custom_request_concurrency_limit))
+            .with(access_log)
             .with(OpenTelemetryMetrics::new())
-            .with(Tracing);
+            .with(Tracing)
+            .with(TraceContext)
+            .around(move |ep, req| {
+                let in_flight_requests = gateway_in_flight_requests.clone();
+                async move {
+                    let _in_flight = in_flight_requests.enter();
+                    ep.call(req).await
+                }
+            });
 
-        poem::Server::new(TcpListener::bind(("0.0.0.0", config.custom_request_port)))
-            .name("gateway")
-            .run(route)
-            .await
-            .expect("Custom Request server failed")
+            poem::Server::new(TcpListener::bind(("0.0.0.0", config.custom_request_port)))
+                .name("gateway")
+                .run_with_graceful_shutdown(
+                    route,
+                    shutdown.requested(),
+                    Some(shutdown.drain_timeout()),
+                )
+                .await
+        }
     });
 
-    let worker_server = tokio::spawn(async move {
-        let prometheus_registry = Arc::new(prometheus_registry);
-        let app = api::combined_routes(prometheus_registry, &http_service2)
-            .with(OpenTelemetryMetrics::new())
-            .with(Tracing);
+    let worker_server = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            let prometheus_registry = Arc::new(prometheus_registry);
+            let app = api::combined_routes(prometheus_registry, &http_service2, api_docs_ui)
+                .with(CatchPanic::new("worker-api"))
+                .with(OpenTelemetryMetrics::new())
+                .with(Tracing)
+                .around(move |ep, req| {
+                    let in_flight_requests = worker_in_flight_requests.clone();
+                    async move {
+                        let _in_flight = in_flight_requests.enter();
+                        ep.call(req).await
+                    }
+                });
 
-        poem::Server::new(TcpListener::bind(format!("0.0.0.0:{}", config.port)))
-            .run(app)
-            .await
-            .expect("HTTP server failed");
+            poem::Server::new(TcpListener::bind(format!("0.0.0.0:{}", config.port)))
+                .run_with_graceful_shutdown(
+                    app,
+                    shutdown.requested(),
+                    Some(shutdown.drain_timeout()),
+                )
+                .await
+        }
     });
 
-    let grpc_server = tokio::spawn(async move {
-        grpcapi::start_grpc_server(
-            SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), config.worker_grpc_port).into(),
-            &grpc_services,
-        )
-        .await
-        .expect("gRPC server failed");
+    let grpc_server = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            grpcapi::start_grpc_server(
+                SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), config.worker_grpc_port).into(),
+                &config.worker_grpc_server,
+                &grpc_services,
+                shutdown.requested(),
+            )
+            .await
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+        }
     });
 
+    let result = wait_for_servers(
+        vec![
+            ("Custom Request server", custom_request_server),
+            ("HTTP server", worker_server),
+            ("gRPC server", grpc_server),
+        ],
+        &shutdown,
+    )
+    .await;
+
+    let remaining_requests = in_flight_requests.count();
+    if remaining_requests > 0 {
+        warn!("{remaining_requests} requests still in flight at the shutdown drain deadline");
+    }
+
+    // The clients get what is left of the deadline the servers were drained within
+    let remaining = shutdown.remaining();
+    tokio::join!(
+        services.worker_executor_grpc_clients.drain(remaining),
+        services.component_service.drain(remaining),
+    );
+
+    info!("Shutdown complete");
+    result
+}
+
+// Waits for all the servers to stop. A server stopping on its own (Ex: failing) requests the
+// shutdown of the others. The servers still running at the drain deadline are aborted, as the
+// gRPC server waits for its connections without a timeout.
+// Returns the error of the first server that failed
+async fn wait_for_servers(
+    servers: Vec<(&'static str, JoinHandle<std::io::Result<()>>)>,
+    shutdown: &Shutdown,
+) -> std::io::Result<()> {
+    let abort_handles = servers
+        .iter()
+        .map(|(_, server)| server.abort_handle())
+        .collect::<Vec<_>>();
+
+    let stopped = join_all(servers.into_iter().map(|(name, server)| async move {
+        let result = match server.await {
+            Ok(result) => result,
+            Err(err) => Err(std::io::Error::new(std::io::ErrorKind::Other, err)),
+        };
+        shutdown.request();
+        result.map_err(|err| {
+            error!("{name} failed: {err}");
+            err
+        })
+    }));
+
     select! {
-        _ = worker_server => {},
-        _ = custom_request_server => {},
-        _ = grpc_server => {},
+        results = stopped => results.into_iter().collect(),
+        _ = shutdown.deadline_elapsed() => {
+            warn!(
+                "Shutdown drain timeout of {:?} elapsed, aborting the servers still running",
+                shutdown.drain_timeout()
+            );
+            for abort_handle in abort_handles {
+                abort_handle.abort();
+            }
+            Ok(())
+        }
     }
-    Ok(())
 }
 
-fn init_tracing(config: &WorkerServiceBaseConfig, prometheus_registry: Registry) {
-    init_tracing_with_default_env_filter(&config.tracing);
+// The OpenTelemetry meter provider, exporting to the Prometheus registry.
+// Without it, only the metrics registered directly in the registry are scraped, so if the
+// exporter fails to initialize, the service continues without it
+fn meter_provider(
+    build_exporter: impl FnOnce() -> Result<PrometheusExporter, MetricsError>,
+) -> Option<SdkMeterProvider> {
+    match build_exporter() {
+        Ok(exporter) => Some(
+            MeterProviderBuilder::default()
+                .with_reader(exporter)
+                .build(),
+        ),
+        Err(err) => {
+            error!(
+                "Failed to initialize the OpenTelemetry metrics, continuing without them: {}",
+                err
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{meter_provider, runtime_builder, wait_for_servers};
+    use golem_common::shutdown::{InFlight, Shutdown};
+    use golem_worker_service_base::app_config::RuntimeConfig;
+    use opentelemetry::metrics::{MeterProvider, MetricsError};
+    use poem::listener::{Acceptor, Listener, TcpListener};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn failing_exporter_does_not_abort() {
+        let meter_provider =
+            meter_provider(|| Err(MetricsError::Other("duplicate registration".to_string())));
+
+        assert!(meter_provider.is_none());
+    }
+
+    #[test]
+    fn exporter_is_installed() {
+        let registry = prometheus::Registry::new();
 
-    let exporter = opentelemetry_prometheus::exporter()
-        .with_registry(prometheus_registry.clone())
-        .build()
+        let meter_provider = meter_provider(|| {
+            opentelemetry_prometheus::exporter()
+                .with_registry(registry.clone())
+                .build()
+        })
         .unwrap();
 
-    global::set_meter_provider(
-        MeterProviderBuilder::default()
-            .with_reader(exporter)
-            .build(),
-    );
+        // Used directly, without setting the global meter provider
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("exported_requests")
+            .init();
+        counter.add(1, &[]);
+
+        let families = registry.gather();
+        assert!(
+            families
+                .iter()
+                .any(|family| family.get_name().starts_with("exported_requests")),
+            "{:?}",
+            families
+                .iter()
+                .map(|family| family.get_name())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn runtime_builder_applies_the_config() {
+        let config = RuntimeConfig {
+            thread_stack_size: Some(8 * 1024 * 1024),
+            max_blocking_threads: Some(1),
+        };
+
+        let builder = runtime_builder(&config);
+        let description = format!("{:?}", builder);
+        assert!(
+            description.contains("thread_stack_size: Some(8388608)"),
+            "{description}"
+        );
+
+        // With a single blocking thread, the blocking tasks run one after the other
+        let runtime = runtime_builder(&config).build().unwrap();
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        runtime.block_on(async {
+            let tasks = (0..3)
+                .map(|_| {
+                    let running = running.clone();
+                    let max_running = max_running.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_running.fetch_max(now_running, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for task in tasks {
+                task.await.unwrap();
+            }
+        });
+
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn long_running_request_is_cut_off_at_the_drain_deadline() {
+        let drain_timeout = Duration::from_millis(200);
+        let shutdown = Shutdown::new(drain_timeout);
+        let in_flight_requests = InFlight::new();
+        let in_flight_calls = InFlight::new();
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+
+        let server = tokio::spawn({
+            let shutdown = shutdown.clone();
+            let in_flight_requests = in_flight_requests.clone();
+            async move {
+                let route = poem::endpoint::make(move |_| {
+                    let in_flight_requests = in_flight_requests.clone();
+                    async move {
+                        let _request = in_flight_requests.enter();
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        "done"
+                    }
+                });
+
+                poem::Server::new_with_acceptor(acceptor)
+                    .run_with_graceful_shutdown(
+                        route,
+                        shutdown.requested(),
+                        Some(shutdown.drain_timeout()),
+                    )
+                    .await
+            }
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        stream.writable().await.unwrap();
+        stream
+            .try_write(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        while in_flight_requests.count() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // A client call outliving the request
+        let _call = in_flight_calls.enter();
+
+        let start = Instant::now();
+        shutdown.request();
+        let result = wait_for_servers(vec![("test server", server)], &shutdown).await;
+        // The client calls get what is left of the same deadline, not another drain timeout
+        let remaining_calls = in_flight_calls
+            .drain("test client", shutdown.remaining())
+            .await;
+        let elapsed = start.elapsed();
+
+        assert!(result.is_ok());
+        assert!(elapsed >= drain_timeout, "{elapsed:?}");
+        assert!(elapsed < drain_timeout * 2, "{elapsed:?}");
+        assert_eq!(remaining_calls, 1);
+
+        // The connection is closed without a response
+        let response = tokio::time::timeout(Duration::from_secs(1), async {
+            let mut buffer = [0u8; 1024];
+            loop {
+                stream.readable().await.unwrap();
+                match stream.try_read(&mut buffer) {
+                    Ok(0) => break None,
+                    Ok(n) => break Some(String::from_utf8_lossy(&buffer[..n]).to_string()),
+                    Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
+                    Err(_) => break None,
+                }
+            }
+        })
+        .await
+        .expect("the connection was not closed");
+        assert_eq!(response, None);
+    }
+
+    #[tokio::test]
+    async fn stuck_server_is_aborted_at_the_drain_deadline() {
+        let drain_timeout = Duration::from_millis(100);
+        let shutdown = Shutdown::new(drain_timeout);
+
+        // Ignores the shutdown, as the gRPC server waiting for its connections
+        let server = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok::<_, std::io::Error>(())
+        });
+        let abort_handle = server.abort_handle();
+
+        let start = Instant::now();
+        shutdown.request();
+        let result = wait_for_servers(vec![("stuck server", server)], &shutdown).await;
+
+        assert!(result.is_ok());
+        assert!(start.elapsed() < drain_timeout * 2);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !abort_handle.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("the server was not aborted");
+    }
+
+    #[tokio::test]
+    async fn failing_server_stops_the_others() {
+        let shutdown = Shutdown::new(Duration::from_secs(60));
+
+        let failing = tokio::spawn(async {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                "address in use",
+            ))
+        });
+        let waiting = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move {
+                shutdown.requested().await;
+                Ok::<_, std::io::Error>(())
+            }
+        });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            wait_for_servers(vec![("failing", failing), ("waiting", waiting)], &shutdown),
+        )
+        .await
+        .expect("the servers did not stop");
+
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::AddrInUse);
+    }
 }
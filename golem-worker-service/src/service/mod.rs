@@ -39,9 +39,11 @@ use golem_worker_service_base::service::api_deployment::{
 use std::sync::Arc;
 use std::time::Duration;
 use tonic::codec::CompressionEncoding;
+use tonic::transport::Channel;
 
 #[derive(Clone)]
 pub struct Services {
+    pub worker_executor_grpc_clients: MultiTargetGrpcClient<WorkerExecutorClient<Channel>>,
     pub worker_service: worker::WorkerService,
     pub component_service: component::ComponentService,
     pub definition_service: Arc<
@@ -68,11 +70,19 @@ impl Services {
             ),
         );
 
-        let worker_executor_grpc_clients = MultiTargetGrpcClient::new(
-            |channel| {
-                WorkerExecutorClient::new(channel)
-                    .send_compressed(CompressionEncoding::Gzip)
-                    .accept_compressed(CompressionEncoding::Gzip)
+        let worker_executor_grpc_clients = MultiTargetGrpcClient::new_with_codec(
+            |channel, config| {
+                let mut client = WorkerExecutorClient::new(channel);
+                if let Some(encoding) = config.send_compression {
+                    client = client.send_compressed(encoding);
+                }
+                if let Some(encoding) = config.accept_compression {
+                    client = client.accept_compressed(encoding);
+                }
+                if let Some(limit) = config.max_decoding_message_size {
+                    client = client.max_decoding_message_size(limit);
+                }
+                client
             },
             GrpcClientConfig {
                 retries_on_unavailable: RetryConfig {
@@ -83,8 +93,13 @@ impl Services {
                     max_jitter_factor: Some(0.15),
                 },
                 connect_timeout: Duration::from_secs(10),
+                send_compression: Some(CompressionEncoding::Gzip),
+                accept_compression: Some(CompressionEncoding::Gzip),
+                ..Default::default()
             },
         );
+        worker_executor_grpc_clients
+            .spawn_endpoint_refresh(config.worker_executor_refresh_interval);
 
         let component_service: component::ComponentService = {
             let config = &config.component_service;
@@ -136,18 +151,37 @@ impl Services {
             }
         };
 
+        if config.validate_api_definitions_on_startup {
+            let invalid_api_definitions = api_definition::invalid_api_definitions(
+                api_definition_repo.as_ref(),
+                &DefaultNamespace::default().to_string(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+            if !invalid_api_definitions.is_empty() {
+                return Err(format!(
+                    "Invalid API definitions:\n{}",
+                    invalid_api_definitions.join("\n")
+                ));
+            }
+        }
+
         let api_definition_validator_service = Arc::new(HttpApiDefinitionValidator {});
 
         let definition_service: Arc<
             dyn ApiDefinitionService<EmptyAuthCtx, DefaultNamespace, RouteValidationError>
                 + Sync
                 + Send,
-        > = Arc::new(ApiDefinitionServiceDefault::new(
-            component_service.clone(),
-            api_definition_repo.clone(),
-            api_deployment_repo.clone(),
-            api_definition_validator_service.clone(),
-        ));
+        > = Arc::new(
+            ApiDefinitionServiceDefault::new(
+                component_service.clone(),
+                api_definition_repo.clone(),
+                api_deployment_repo.clone(),
+                api_definition_validator_service.clone(),
+            )
+            .with_predicate_mode(config.rib_predicate_mode),
+        );
 
         let deployment_service: Arc<dyn ApiDeploymentService<DefaultNamespace> + Sync + Send> =
             Arc::new(ApiDeploymentServiceDefault::new(
@@ -159,6 +193,7 @@ impl Services {
             Arc::new(HttpApiDefinitionLookup::new(deployment_service.clone()));
 
         Ok(Services {
+            worker_executor_grpc_clients,
             worker_service,
             definition_service,
             deployment_service,
@@ -169,3 +204,89 @@ impl Services {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::service::Services;
+    use bytes::{BufMut, BytesMut};
+    use golem_api_grpc::proto::golem::apidefinition::{
+        CompiledHttpApiDefinition, CompiledHttpRoute, HttpMethod,
+    };
+    use golem_common::config::{DbConfig, DbSqliteConfig};
+    use golem_service_base::db;
+    use golem_worker_service_base::app_config::WorkerServiceBaseConfig;
+    use golem_worker_service_base::repo::api_definition::{
+        record_data_serde, ApiDefinitionRecord, ApiDefinitionRepo, DbApiDefinitionRepo,
+    };
+    use prost::Message;
+
+    struct SqliteDb {
+        db_path: String,
+    }
+
+    impl Default for SqliteDb {
+        fn default() -> Self {
+            Self {
+                db_path: format!("/tmp/golem-worker-{}.db", uuid::Uuid::new_v4()),
+            }
+        }
+    }
+
+    impl Drop for SqliteDb {
+        fn drop(&mut self) {
+            std::fs::remove_file(&self.db_path).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn services_fail_to_start_with_invalid_api_definition() {
+        let db = SqliteDb::default();
+        let db_config = DbSqliteConfig {
+            database: db.db_path.to_string(),
+            max_connections: 10,
+        };
+
+        db::sqlite_migrate(&db_config, "db/migration/sqlite")
+            .await
+            .unwrap();
+
+        // A route stored without its binding can't be decoded
+        let routes = CompiledHttpApiDefinition {
+            routes: vec![CompiledHttpRoute {
+                method: HttpMethod::Get as i32,
+                path: "/users/{user-id}".to_string(),
+                binding: None,
+                methods: vec![],
+            }],
+        };
+        let mut data = BytesMut::new();
+        data.put_u8(record_data_serde::SERIALIZATION_VERSION_V1);
+        data.extend_from_slice(&routes.encode_to_vec());
+
+        let db_pool = db::create_sqlite_pool(&db_config).await.unwrap();
+        DbApiDefinitionRepo::new(db_pool.into())
+            .create(&ApiDefinitionRecord {
+                namespace: "default".to_string(),
+                id: "users-api".to_string(),
+                version: "0.0.1".to_string(),
+                draft: true,
+                data: data.to_vec(),
+                created_at: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let config = WorkerServiceBaseConfig {
+            db: DbConfig::Sqlite(db_config),
+            validate_api_definitions_on_startup: true,
+            ..Default::default()
+        };
+
+        let error = Services::new(&config).await.err().unwrap();
+
+        assert_eq!(
+            error,
+            "Invalid API definitions:\nAPI definition users-api (version 0.0.1): route GET /users/{user-id}: binding is missing"
+        );
+    }
+}
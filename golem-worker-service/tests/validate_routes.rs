@@ -0,0 +1,88 @@
+// Copyright 2024 Golem Cloud
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+use std::process::{Command, Output};
+
+fn validate_routes(definition: &str) -> Output {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(definition.as_bytes()).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_golem-worker-service"))
+        .arg("--validate-routes")
+        .arg(file.path())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn valid_routes_are_accepted() {
+    let output = validate_routes(
+        r#"
+id: shopping-cart
+version: 0.0.1
+routes:
+  - method: Get
+    path: /carts/{cart-id}
+    binding:
+      componentId:
+        componentId: 0b6d9cd8-f373-4e29-8a5a-548e61b868a5
+        version: 0
+      workerName: '"shopping-cart"'
+      response: '${request.path.cart-id}'
+"#,
+    );
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("All 1 routes"));
+}
+
+#[test]
+fn invalid_template_is_reported() {
+    let output = validate_routes(
+        r#"
+id: shopping-cart
+version: 0.0.1
+routes:
+  - method: Get
+    path: /carts/{cart-id}
+    binding:
+      componentId:
+        componentId: 0b6d9cd8-f373-4e29-8a5a-548e61b868a5
+        version: 0
+      workerName: '"shopping-cart"'
+      response: '${request.path.cart-id}'
+  - method: Put
+    path: /carts/{cart-id}
+    binding:
+      componentId:
+        componentId: 0b6d9cd8-f373-4e29-8a5a-548e61b868a5
+        version: 0
+      workerName: '"shopping-cart"'
+      response: '${if then}'
+"#,
+    );
+
+    let report = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        report.contains("Put /carts/{cart-id}: Invalid response"),
+        "Received: {report}"
+    );
+    assert!(
+        !report.contains("Get /carts/{cart-id}"),
+        "Received: {report}"
+    );
+}